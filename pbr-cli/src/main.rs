@@ -2,18 +2,29 @@
 
 use clap::{Parser, Subcommand};
 use pbr_core::{
-    batch_export_with_preset, estimate_vram, export_with_lod, export_with_preset,
-    fix_tileability_with_report, record_analysis, run_advanced_analysis,
+    estimate_vram, export_with_quality_floor, ExportQualityReport, QualityFloor,
+    resolve_preset, verify_export, ExportManifest, OptimizationPreset, EXPORT_MANIFEST_FILE,
+    export_is_current, material_output_names, remove_orphaned_materials, remove_orphaned_outputs,
+    export_folder_tiled, load_folder, needs_tiling, TILED_THRESHOLD,
+    record_analysis, run_advanced_analysis,
     run_advanced_analysis_and_write,
     export_html_batch, export_html_single, export_pdf_batch, export_pdf_single,
     export_audit_log_text, load_audit_log, record_optimization as audit_record_optimization,
     save_audit_log_text,
     record_report as audit_record_report, record_validation as audit_record_validation,
-    ai_analyze_json, ExportPreset, MaterialReport, MaterialSet, PluginInfo, PluginLoader, Validator,
+    ai_analyze_json, MaterialReport, MaterialSet, PluginInfo, PluginLoader, TextureSlot,
+    AnalysisCache, CachedAnalysis, ScoreBreakdown, ScoringModel, Validator,
+    analyze_texel_density, DensityStatus, MeshUvStats, TexelDensityRule, TexelDensityTarget,
+    fix_material_folder, make_seamless_with_report, SeamlessMethod, SeamlessOptions, FIX_REPORT_FILE,
+    apply_dedup, find_exact_duplicates, ExactMatch, SearchIndex, SEARCH_INDEX_FILE,
+    export_atlas, AtlasOptions, PackingAlgorithm,
+    evaluate_scene_budget, Platform, SceneManifest,
+    export_budget_fit, plan_budget_fit, FitOptions,
 };
-use pbr_core::optimization::{save_texture, TargetResolution};
-use pbr_core::validation::{Issue, Severity};
+use pbr_core::optimization::save_texture;
+use pbr_core::validation::{Issue, Severity, TextureResolutionRule};
 use serde::Serialize;
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 
@@ -39,6 +50,7 @@ struct CiMaterialResult {
     issues: Vec<CiIssue>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     optimization_suggestions: Vec<CiOptimizationSuggestion>,
+    score_breakdown: ScoreBreakdown,
 }
 
 #[derive(Debug, Serialize)]
@@ -73,14 +85,27 @@ struct Cli {
     #[arg(long, global = true)]
     plugins_dir: Option<PathBuf>,
 
-    /// Config file (TOML). Can set plugins_dir.
+    /// Config file (TOML). Can set plugins_dir and scoring.
     #[arg(long, global = true)]
     config: Option<PathBuf>,
+
+    /// Scoring model file (TOML/JSON). Default: .pbr-studio/scoring.toml in the project
+    #[arg(long, global = true)]
+    scoring: Option<PathBuf>,
+
+    /// Worker threads for batch loading and analysis (default: one per CPU)
+    #[arg(short = 'j', long, global = true)]
+    jobs: Option<usize>,
+
+    /// Ignore and do not update the analysis cache (.pbr-studio/cache)
+    #[arg(long, global = true)]
+    no_cache: bool,
 }
 
 #[derive(Debug, serde::Deserialize)]
 struct CliConfig {
     plugins_dir: Option<String>,
+    scoring: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -98,6 +123,12 @@ enum Commands {
         /// Load custom rules from plugins
         #[arg(long)]
         plugins: bool,
+        /// Check texel density against this mesh (OBJ/glTF/GLB)
+        #[arg(long)]
+        mesh: Option<PathBuf>,
+        /// Target texel density in px/cm (enables the check; mesh auto-detected in the folder)
+        #[arg(long, value_name = "PX_PER_CM")]
+        texel_density: Option<f64>,
     },
     /// Recursively scan for material folders and print validation summary
     BatchCheck {
@@ -116,6 +147,9 @@ enum Commands {
         /// Load custom rules from plugins
         #[arg(long)]
         plugins: bool,
+        /// Target texel density in px/cm, checked against a mesh in each material folder
+        #[arg(long, value_name = "PX_PER_CM")]
+        texel_density: Option<f64>,
     },
     /// Validate materials affected by staged files (for Git pre-commit hooks)
     PreCommit {
@@ -131,6 +165,9 @@ enum Commands {
         /// Load custom rules from plugins
         #[arg(long)]
         plugins: bool,
+        /// Target texel density in px/cm, checked against a mesh in each material folder
+        #[arg(long, value_name = "PX_PER_CM")]
+        texel_density: Option<f64>,
     },
     /// Export optimized textures for target engine
     Optimize {
@@ -139,12 +176,33 @@ enum Commands {
         /// Output folder for optimized textures
         #[arg(short, long)]
         output: PathBuf,
-        /// Target: 4k, unreal, unity, or mobile
+        /// Target: 4k, unreal, unity, mobile, or a plugin preset id
         #[arg(long, default_value = "unreal")]
         target: String,
         /// Generate LOD chain (LOD0, LOD1, LOD2 subdirs)
         #[arg(long)]
         lod: bool,
+        /// Fail when any exported texture drops below this PSNR (dB)
+        #[arg(long)]
+        min_psnr: Option<f64>,
+        /// Fail when any exported texture drops below this SSIM (0-1)
+        #[arg(long)]
+        min_ssim: Option<f64>,
+        /// Fail when any exported texture drops below this MS-SSIM (0-1)
+        #[arg(long)]
+        min_ms_ssim: Option<f64>,
+        /// Fail when the mean normal-map angle error exceeds this (degrees)
+        #[arg(long)]
+        max_normal_angle: Option<f64>,
+        /// File name template, e.g. "T_{material}_{slot:short}.{ext}" (tokens: material, slot, slot:short, lod, res, ext; default: the preset's, "{slot}.{ext}")
+        #[arg(long)]
+        naming: Option<String>,
+        /// LOD folder template (tokens: material, lod; default: the preset's, "LOD{lod}"); empty puts all levels in one folder
+        #[arg(long)]
+        lod_dir: Option<String>,
+        /// Dither 16-bit/float sources to 8 bits: none, ordered or blue_noise, for all slots and/or per slot (e.g. "height=blue_noise,albedo=ordered"); default: the preset's
+        #[arg(long)]
+        dither: Option<String>,
     },
     /// Batch export all materials under root with preset
     BatchOptimize {
@@ -153,12 +211,98 @@ enum Commands {
         /// Output root folder
         #[arg(short, long)]
         output: PathBuf,
-        /// Target: 4k, unreal, unity, or mobile
+        /// Target: 4k, unreal, unity, mobile, or a plugin preset id
         #[arg(long, default_value = "unreal")]
         target: String,
         /// Generate LOD chain for each material
         #[arg(long)]
         lod: bool,
+        /// Skip materials whose sources and preset are unchanged since the last export, and delete orphaned outputs
+        #[arg(long)]
+        incremental: bool,
+        /// Fail when any exported texture drops below this PSNR (dB)
+        #[arg(long)]
+        min_psnr: Option<f64>,
+        /// Fail when any exported texture drops below this SSIM (0-1)
+        #[arg(long)]
+        min_ssim: Option<f64>,
+        /// Fail when any exported texture drops below this MS-SSIM (0-1)
+        #[arg(long)]
+        min_ms_ssim: Option<f64>,
+        /// Fail when the mean normal-map angle error exceeds this (degrees)
+        #[arg(long)]
+        max_normal_angle: Option<f64>,
+        /// File name template, e.g. "T_{material}_{slot:short}.{ext}" (tokens: material, slot, slot:short, lod, res, ext; default: the preset's, "{slot}.{ext}")
+        #[arg(long)]
+        naming: Option<String>,
+        /// LOD folder template (tokens: material, lod; default: the preset's, "LOD{lod}"); empty puts all levels in one folder
+        #[arg(long)]
+        lod_dir: Option<String>,
+        /// Dither 16-bit/float sources to 8 bits: none, ordered or blue_noise, for all slots and/or per slot (e.g. "height=blue_noise,albedo=ordered"); default: the preset's
+        #[arg(long)]
+        dither: Option<String>,
+    },
+    /// Check exported textures against the export's manifest.json checksums
+    VerifyExport {
+        /// Export folder containing manifest.json
+        folder: PathBuf,
+    },
+    /// Pack small materials into shared atlas pages with a UV remap table
+    Atlas {
+        /// Root folder containing material subfolders
+        root_folder: PathBuf,
+        /// Output folder for atlas pages, atlas.json and atlas.csv
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Packing algorithm: maxrects or skyline
+        #[arg(long, default_value = "maxrects")]
+        algorithm: String,
+        /// Largest atlas page edge (power of two)
+        #[arg(long, default_value = "2048")]
+        max_size: u32,
+        /// Edge-extended gutter around each material in pixels
+        #[arg(long, default_value = "4")]
+        padding: u32,
+        /// Number of mips that must not bleed between materials (cells align to 2^n)
+        #[arg(long, default_value = "4")]
+        mip_levels: u32,
+        /// Only pack materials up to this size (longest edge)
+        #[arg(long, default_value = "512")]
+        max_source_size: u32,
+    },
+    /// Check a level's texture memory against per-platform budgets
+    Budget {
+        /// Scene manifest (TOML or JSON) listing materials, instances, priorities and budgets
+        manifest: PathBuf,
+        /// Only report this platform (pc, console, mobile)
+        #[arg(long)]
+        platform: Option<String>,
+        /// Number of offenders to list per platform
+        #[arg(long, default_value = "10")]
+        top: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Choose per-slot resolutions that fit a memory budget, optionally exporting them
+    FitBudget {
+        /// Root folder containing material subfolders
+        root_folder: PathBuf,
+        /// Platform whose formats are used (pc, console, mobile)
+        #[arg(long, default_value = "console")]
+        platform: String,
+        /// Memory budget in MiB (full mip chains)
+        #[arg(long)]
+        budget: f64,
+        /// Smallest allowed longest edge
+        #[arg(long, default_value = "256")]
+        min_size: u32,
+        /// Export the fitted materials to this folder
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Output the plan as JSON
+        #[arg(long)]
+        json: bool,
     },
     /// Generate a report (text or JSON)
     Report {
@@ -209,6 +353,37 @@ enum Commands {
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
+    /// Find byte- and pixel-identical textures and report disk/VRAM savings
+    Dedup {
+        /// Root folder containing material subfolders
+        root_folder: PathBuf,
+        /// Keep one texture per duplicate group and delete the duplicates
+        #[arg(long)]
+        apply: bool,
+        /// Folder for the full dedup manifest (with --apply). Default: <root>
+        #[arg(long)]
+        manifest_dir: Option<PathBuf>,
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Find library materials similar to an example image or material
+    Search {
+        /// Query image file or material folder
+        query: PathBuf,
+        /// Library root containing material subfolders (index stored in <root>/.pbr-studio)
+        #[arg(long)]
+        library: PathBuf,
+        /// Number of results. Default 10
+        #[arg(short = 'k', long, default_value = "10")]
+        top: usize,
+        /// Only compare an image query against this slot (e.g. albedo). Default: all slots
+        #[arg(long)]
+        slot: Option<String>,
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Show audit log (validation, optimization, report actions)
     AuditLog {
         /// Maximum number of entries to show. Default 50
@@ -238,16 +413,42 @@ enum Commands {
         #[arg(long)]
         model: Option<PathBuf>,
     },
-    /// Apply tileability fix to albedo texture and save
+    /// Texel density of a material on a mesh (UV area vs surface area)
+    TexelDensity {
+        /// Path to the material folder
+        folder: PathBuf,
+        /// Mesh file (OBJ/glTF/GLB). Default: first mesh in the material folder
+        #[arg(long)]
+        mesh: Option<PathBuf>,
+        /// Target texel density in px/cm
+        #[arg(long, default_value = "10.24")]
+        target: f64,
+        /// Allowed relative deviation from the target (0.25 = ±25%)
+        #[arg(long, default_value = "0.25")]
+        tolerance: f64,
+        /// Centimeters per mesh unit (100 for meters)
+        #[arg(long, default_value = "100")]
+        cm_per_unit: f64,
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Make a texture, or every slot of a material folder, tile seamlessly and save it
     FixTileability {
-        /// Path to material folder or texture file
+        /// Path to material folder (fixes every slot) or texture file
         path: PathBuf,
-        /// Output path (file or folder)
+        /// Output path: a folder for a material folder; a file or folder for a texture
         #[arg(short, long)]
         output: PathBuf,
-        /// Blend width in pixels. Default 4
+        /// Blend width in pixels (edge blend) or feather width across seam cuts. Default 4
         #[arg(long, default_value = "4")]
         blend_width: u32,
+        /// Fix method: hybrid (seam patch + Poisson), quilt, poisson, or blend (legacy edge averaging)
+        #[arg(long, default_value = "hybrid")]
+        method: String,
+        /// Slot the seam cuts are computed from for a material folder (default: albedo)
+        #[arg(long)]
+        reference: Option<String>,
     },
 }
 
@@ -260,24 +461,116 @@ fn main() {
 
 fn run() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+    pbr_core::parallel::configure_threads(cli.jobs);
+    let scoring = load_scoring_override(cli.scoring.as_ref(), cli.config.as_ref())?;
+    let scoring = scoring.as_ref();
 
     match cli.command {
-        Commands::Check { folder, min_score, ci, plugins } => {
-            let validator = build_validator(cli.plugins_dir.as_ref(), cli.config.as_ref(), plugins);
-            cmd_check(&folder, min_score, ci, validator)
+        Commands::Check { folder, min_score, ci, plugins, mesh, texel_density } => {
+            let mut validator = build_validator(cli.plugins_dir.as_ref(), cli.config.as_ref(), plugins);
+            if texel_density.is_some() || mesh.is_some() {
+                add_texel_density_rules(&mut validator, texel_density, mesh.as_deref())?;
+            }
+            cmd_check(&folder, min_score, ci, validator, scoring)
+        }
+        Commands::BatchCheck { root_folder, min_score, ci, plugins, output, texel_density } => {
+            let mut validator = build_validator(cli.plugins_dir.as_ref(), cli.config.as_ref(), plugins);
+            let target = match texel_density {
+                Some(_) => add_texel_density_rules(&mut validator, texel_density, None)?,
+                None => TexelDensityTarget::default(),
+            };
+            let output = output.as_ref().map(|p| p.as_path());
+            cmd_batch_check(&root_folder, min_score, ci, output, validator, &target, scoring, !cli.no_cache)
+        }
+        Commands::PreCommit { min_score, root, ci, plugins, texel_density } => {
+            let mut validator = build_validator(cli.plugins_dir.as_ref(), cli.config.as_ref(), plugins);
+            let target = match texel_density {
+                Some(_) => add_texel_density_rules(&mut validator, texel_density, None)?,
+                None => TexelDensityTarget::default(),
+            };
+            cmd_pre_commit(min_score, root.as_deref(), ci, validator, &target, scoring, !cli.no_cache)
         }
-        Commands::BatchCheck { root_folder, min_score, ci, plugins, output } => {
-            let validator = build_validator(cli.plugins_dir.as_ref(), cli.config.as_ref(), plugins);
-            cmd_batch_check(&root_folder, min_score, ci, output.as_ref().map(|p| p.as_path()), validator)
+        Commands::Optimize {
+            folder,
+            output,
+            target,
+            lod,
+            min_psnr,
+            min_ssim,
+            min_ms_ssim,
+            max_normal_angle,
+            naming,
+            lod_dir,
+            dither,
+        } => {
+            let floor = QualityFloor {
+                min_psnr,
+                min_ssim,
+                min_ms_ssim,
+                max_normal_angle,
+            };
+            let loader = build_plugin_loader(cli.plugins_dir.as_ref(), cli.config.as_ref());
+            let preset = optimization_preset(
+                &loader,
+                &target,
+                naming.as_deref(),
+                lod_dir.as_deref(),
+                dither.as_deref(),
+            )?;
+            cmd_optimize(&folder, &output, &target, &preset, lod, &floor)
         }
-        Commands::PreCommit { min_score, root, ci, plugins } => {
-            let validator = build_validator(cli.plugins_dir.as_ref(), cli.config.as_ref(), plugins);
-            cmd_pre_commit(min_score, root.as_deref(), ci, validator)
+        Commands::BatchOptimize {
+            root_folder,
+            output,
+            target,
+            lod,
+            min_psnr,
+            min_ssim,
+            min_ms_ssim,
+            max_normal_angle,
+            naming,
+            lod_dir,
+            dither,
+            incremental,
+        } => {
+            let floor = QualityFloor {
+                min_psnr,
+                min_ssim,
+                min_ms_ssim,
+                max_normal_angle,
+            };
+            let loader = build_plugin_loader(cli.plugins_dir.as_ref(), cli.config.as_ref());
+            let preset = optimization_preset(
+                &loader,
+                &target,
+                naming.as_deref(),
+                lod_dir.as_deref(),
+                dither.as_deref(),
+            )?;
+            cmd_batch_optimize(&root_folder, &output, &preset, lod, &floor, incremental)
         }
-        Commands::Optimize { folder, output, target, lod } => cmd_optimize(&folder, &output, &target, lod),
-        Commands::BatchOptimize { root_folder, output, target, lod } => cmd_batch_optimize(&root_folder, &output, &target, lod),
-        Commands::Report { folder, json, vram, export, output } => cmd_report(&folder, json, vram, export.as_deref(), output.as_ref()),
-        Commands::ExportReport { folders, format, output, track } => cmd_export_report(&folders, &format, &output, track),
+        Commands::VerifyExport { folder } => cmd_verify_export(&folder),
+        Commands::Budget { manifest, platform, top, json } => cmd_budget(&manifest, platform.as_deref(), top, json),
+        Commands::FitBudget { root_folder, platform, budget, min_size, output, json } => {
+            cmd_fit_budget(&root_folder, &platform, budget, min_size, output.as_deref(), json)
+        }
+        Commands::Atlas {
+            root_folder,
+            output,
+            algorithm,
+            max_size,
+            padding,
+            mip_levels,
+            max_source_size,
+        } => cmd_atlas(&root_folder, &output, &algorithm, AtlasOptions {
+            max_size,
+            padding,
+            mip_levels,
+            max_source_size,
+            ..Default::default()
+        }),
+        Commands::Report { folder, json, vram, export, output } => cmd_report(&folder, json, vram, export.as_deref(), output.as_ref(), scoring),
+        Commands::ExportReport { folders, format, output, track } => cmd_export_report(&folders, &format, &output, track, scoring),
         Commands::Analyze {
             root_folder,
             tileability,
@@ -285,14 +578,33 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
             similar_threshold,
             output,
         } => cmd_analyze(&root_folder, tileability, duplicate_threshold, similar_threshold, output.as_deref()),
+        Commands::Dedup {
+            root_folder,
+            apply,
+            manifest_dir,
+            json,
+        } => cmd_dedup(&root_folder, apply, manifest_dir.as_deref(), json),
+        Commands::Search {
+            query,
+            library,
+            top,
+            slot,
+            json,
+        } => cmd_search(&query, &library, top, slot.as_deref(), json),
         Commands::FixTileability {
             path,
             output,
             blend_width,
-        } => cmd_fix_tileability(&path, &output, blend_width),
+            method,
+            reference,
+        } => cmd_fix_tileability(&path, &output, blend_width, &method, reference.as_deref()),
         Commands::AuditLog { limit, json, output, format } => cmd_audit_log(limit, json, output.as_deref(), &format),
         Commands::PluginList { json } => cmd_plugin_list(&cli, json),
         Commands::AiAnalyze { folder, model } => cmd_ai_analyze(&folder, model.as_deref()),
+        Commands::TexelDensity { folder, mesh, target, tolerance, cm_per_unit, json } => {
+            let target = TexelDensityTarget { px_per_cm: target, tolerance, cm_per_unit };
+            cmd_texel_density(&folder, mesh.as_deref(), &target, json)
+        }
     }
 }
 
@@ -308,10 +620,80 @@ fn build_validator(
     Validator::default().with_plugins(&loader)
 }
 
-fn cmd_check(folder: &PathBuf, min_score: i32, ci: bool, validator: Validator) -> Result<(), Box<dyn std::error::Error>> {
-    let set = MaterialSet::load_from_folder(folder)?;
+/// Analysis cache under `root`, or a disabled one for `--no-cache`.
+fn open_cache(root: &Path, validator: &Validator, use_cache: bool) -> AnalysisCache {
+    if use_cache {
+        AnalysisCache::open(root, validator)
+    } else {
+        AnalysisCache::disabled()
+    }
+}
+
+/// Validate and report one folder, served from the cache when nothing changed.
+/// The texel density target is part of the validator's fingerprint, so cached
+/// suggestions always match it.
+fn evaluate_cached(
+    cache: &AnalysisCache,
+    folder: &Path,
+    validator: &Validator,
+    texel_density: &TexelDensityTarget,
+    scoring_model: &ScoringModel,
+) -> pbr_core::Result<CachedAnalysis> {
+    cache.evaluate(folder, scoring_model, |set| {
+        let issues = validator.check(set);
+        let report =
+            MaterialReport::from_material_set_with_target(set, issues.clone(), None, scoring_model, texel_density);
+        (issues, report)
+    })
+}
+
+/// Load the scoring model given by `--scoring` or the config file, if any.
+fn load_scoring_override(
+    scoring_path: Option<&PathBuf>,
+    config_path: Option<&PathBuf>,
+) -> Result<Option<ScoringModel>, Box<dyn std::error::Error>> {
+    if let Some(path) = scoring_path {
+        return Ok(Some(ScoringModel::load(path)?));
+    }
+    if let Some(config) = config_path {
+        let s = std::fs::read_to_string(config)
+            .map_err(|e| format!("Could not read config {}: {}", config.display(), e))?;
+        let cfg = toml::from_str::<CliConfig>(&s)
+            .map_err(|e| format!("Invalid config {}: {}", config.display(), e))?;
+        if let Some(path) = cfg.scoring {
+            return Ok(Some(ScoringModel::load(path)?));
+        }
+    }
+    Ok(None)
+}
+
+/// Scoring model for a material folder: explicit override, else the project model, else default.
+/// A project model that fails to load is an error rather than a silent fallback.
+fn scoring_for(scoring: Option<&ScoringModel>, folder: &Path) -> pbr_core::Result<ScoringModel> {
+    if let Some(model) = scoring {
+        return Ok(model.clone());
+    }
+    match ScoringModel::for_project(folder) {
+        Ok(model) => Ok(model.unwrap_or_default()),
+        Err(e) => Err(pbr_core::Error::Other(format!(
+            "Invalid project scoring model for {}: {}",
+            folder.display(),
+            e
+        ))),
+    }
+}
+
+fn cmd_check(
+    folder: &PathBuf,
+    min_score: i32,
+    ci: bool,
+    validator: Validator,
+    scoring: Option<&ScoringModel>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let set = load_folder(folder)?;
     let issues = validator.check(&set);
-    let score = pbr_core::validation::compute_score(&issues);
+    let breakdown = scoring_for(scoring, folder)?.evaluate(&issues);
+    let score = breakdown.score;
     let passed = score >= min_score;
 
     if ci {
@@ -321,7 +703,7 @@ fn cmd_check(folder: &PathBuf, min_score: i32, ci: bool, validator: Validator) -
             total_materials: 1,
             passed: if passed { 1 } else { 0 },
             failed: if passed { 0 } else { 1 },
-            results: vec![to_ci_result(folder, &issues, score, min_score)],
+            results: vec![to_ci_result(folder, &issues, &breakdown, min_score)],
         };
         println!("{}", serde_json::to_string(&output)?);
     } else {
@@ -338,6 +720,12 @@ fn cmd_check(folder: &PathBuf, min_score: i32, ci: bool, validator: Validator) -
         let major = issues.iter().filter(|i| i.severity == Severity::Major).count();
         let minor = issues.iter().filter(|i| i.severity == Severity::Minor).count();
         println!("\nScore: {} (min: {})", score, min_score);
+        let categories: Vec<String> = breakdown
+            .categories
+            .iter()
+            .map(|c| format!("{} {}", c.category.as_str(), c.score))
+            .collect();
+        println!("Categories: {}", categories.join(", "));
         println!("{} issue(s) ({} critical, {} major, {} minor)", issues.len(), critical, major, minor);
     }
 
@@ -360,17 +748,18 @@ fn cmd_check(folder: &PathBuf, min_score: i32, ci: bool, validator: Validator) -
     Ok(())
 }
 
-fn to_ci_result(path: &Path, issues: &[Issue], score: i32, min_score: i32) -> CiMaterialResult {
-    to_ci_result_with_suggestions(path, issues, score, min_score, &[])
+fn to_ci_result(path: &Path, issues: &[Issue], breakdown: &ScoreBreakdown, min_score: i32) -> CiMaterialResult {
+    to_ci_result_with_suggestions(path, issues, breakdown, min_score, &[])
 }
 
 fn to_ci_result_with_suggestions(
     path: &Path,
     issues: &[Issue],
-    score: i32,
+    breakdown: &ScoreBreakdown,
     min_score: i32,
     optimization_suggestions: &[pbr_core::OptimizationSuggestion],
 ) -> CiMaterialResult {
+    let score = breakdown.score;
     let critical = issues.iter().filter(|i| i.severity == Severity::Critical).count();
     let major = issues.iter().filter(|i| i.severity == Severity::Major).count();
     let minor = issues.iter().filter(|i| i.severity == Severity::Minor).count();
@@ -390,6 +779,59 @@ fn to_ci_result_with_suggestions(
             category: s.category.clone(),
             message: s.message.clone(),
         }).collect(),
+        score_breakdown: breakdown.clone(),
+    }
+}
+
+/// Built-in or plugin preset for `--target`, with `--naming` / `--lod-dir` / `--dither` overrides.
+fn optimization_preset(
+    loader: &PluginLoader,
+    target: &str,
+    naming: Option<&str>,
+    lod_dir: Option<&str>,
+    dither: Option<&str>,
+) -> Result<OptimizationPreset, Box<dyn std::error::Error>> {
+    let (_, presets) = loader.load();
+    let mut preset = resolve_preset(target, &presets)?;
+    if let Some(file) = naming {
+        preset.naming.file = file.to_string();
+    }
+    if let Some(dir) = lod_dir {
+        preset.naming.lod_dir = dir.to_string();
+    }
+    preset.naming.validate()?;
+    if let Some(dither) = dither {
+        preset.dither = dither.parse()?;
+    }
+    Ok(preset)
+}
+
+fn print_quality_report(report: &ExportQualityReport) {
+    for t in &report.textures {
+        let normal = t
+            .metrics
+            .normal_angle
+            .map(|a| format!("  angle {:.2}° (p95 {:.2}°)", a.mean_degrees, a.p95_degrees))
+            .unwrap_or_default();
+        println!(
+            "  {:<10} {:>5}x{:<5} -> {:>5}x{:<5} PSNR {:>6.2} dB  SSIM {:.4}  MS-SSIM {:.4}  banding {:.2}{}",
+            t.slot,
+            t.original_width,
+            t.original_height,
+            t.width,
+            t.height,
+            t.metrics.psnr,
+            t.metrics.ssim,
+            t.metrics.ms_ssim,
+            t.banding_score,
+            normal
+        );
+        for v in &t.violations {
+            println!("    FAIL {}", v);
+        }
+    }
+    if let Some(note) = &report.note {
+        println!("  Note: {}", note);
     }
 }
 
@@ -397,41 +839,53 @@ fn cmd_optimize(
     folder: &PathBuf,
     output: &PathBuf,
     target: &str,
+    preset: &OptimizationPreset,
     lod: bool,
+    floor: &QualityFloor,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let preset = match target.to_lowercase().as_str() {
-        "4k" | "4k_high" => ExportPreset::Res4K,
-        "unreal" | "unreal_engine" => ExportPreset::UnrealEngine,
-        "unity" => ExportPreset::Unity,
-        "mobile" | "mobile_optimized" => ExportPreset::MobileOptimized,
-        _ => return Err(format!("Unknown target: {}. Use 4k, unreal, unity, or mobile.", target).into()),
-    };
-
-    let material = MaterialSet::load_from_folder(folder)?;
-    let written = if lod {
-        let levels = TargetResolution::default_lod_levels();
-        export_with_lod(&material, output, preset, levels)?
+    let lod = lod || preset.include_lod;
+    let written = if needs_tiling(folder)? {
+        export_tiled(folder, output, preset, lod, floor)?
     } else {
-        export_with_preset(&material, output, preset)?
+        let material = MaterialSet::load_from_folder(folder)?;
+        let report = export_with_quality_floor(&material, output, preset, lod, floor)?;
+        print_quality_report(&report);
+        report.outputs.len()
     };
-    let _ = audit_record_optimization(folder, output, &target, written.len(), None);
-    println!("Exported {} texture(s) to {}", written.len(), output.display());
+    let _ = audit_record_optimization(folder, output, &target, written, None);
+    println!("Exported {} texture(s) to {}", written, output.display());
     Ok(())
 }
 
+/// Stream-export a material too large to load; returns the files written.
+fn export_tiled(
+    folder: &Path,
+    out_dir: &Path,
+    preset: &OptimizationPreset,
+    lod: bool,
+    floor: &QualityFloor,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    if !floor.is_empty() {
+        return Err(format!(
+            "{}: quality floors can't be checked for sources above {}px; export without --min-psnr/--min-ssim/--min-ms-ssim/--max-normal-angle",
+            folder.display(),
+            TILED_THRESHOLD
+        )
+        .into());
+    }
+    println!("  Sources above {}px: exporting tiled", TILED_THRESHOLD);
+    Ok(export_folder_tiled(folder, out_dir, preset, lod)?.len())
+}
+
 fn cmd_batch_optimize(
     root_folder: &PathBuf,
     output: &PathBuf,
-    target: &str,
+    preset: &OptimizationPreset,
     lod: bool,
+    floor: &QualityFloor,
+    incremental: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let preset = match target.to_lowercase().as_str() {
-        "4k" | "4k_high" => ExportPreset::Res4K,
-        "unreal" | "unreal_engine" => ExportPreset::UnrealEngine,
-        "unity" => ExportPreset::Unity,
-        "mobile" | "mobile_optimized" => ExportPreset::MobileOptimized,
-        _ => return Err(format!("Unknown target: {}. Use 4k, unreal, unity, or mobile.", target).into()),
-    };
+    let lod = lod || preset.include_lod;
 
     let root = root_folder.canonicalize().unwrap_or_else(|_| root_folder.clone());
     if !root.is_dir() {
@@ -440,52 +894,111 @@ fn cmd_batch_optimize(
 
     let mut material_folders = Vec::new();
     find_material_folders(&root, &root, &mut material_folders)?;
+    material_folders.sort();
 
     if material_folders.is_empty() {
         return Err(format!("No material folders found under \"{}\"", root.display()).into());
     }
 
-    let mut materials: Vec<(std::path::PathBuf, MaterialSet)> = Vec::new();
+    // One output folder per material, suffixed when folder names clash
+    let output_names: HashMap<PathBuf, String> = material_folders
+        .iter()
+        .cloned()
+        .zip(material_output_names(&material_folders))
+        .collect();
+
+    let mut changed = Vec::new();
+    let mut up_to_date = 0;
+    let mut failed = Vec::new();
     for folder in &material_folders {
-        match MaterialSet::load_from_folder(folder) {
-            Ok(set) => materials.push((folder.clone(), set)),
-            Err(e) => eprintln!("⚠ Skipping {}: {}", folder.display(), e),
+        let name = &output_names[folder];
+        if !incremental {
+            changed.push(folder.clone());
+            continue;
+        }
+        match export_is_current(folder, &output.join(name), preset, lod, floor) {
+            Ok(true) => {
+                println!("{}: up to date", name);
+                up_to_date += 1;
+            }
+            Ok(false) => changed.push(folder.clone()),
+            Err(e) => {
+                eprintln!("{}: {}", name, e);
+                failed.push(name.clone());
+            }
         }
     }
-
-    let written = if lod {
-        let mut all = Vec::new();
-        for (folder, material) in &materials {
-            let name = material
-                .name
-                .clone()
-                .or_else(|| folder.file_name().map(|n| n.to_string_lossy().into_owned()))
-                .unwrap_or_else(|| "material".to_string());
-            let out_dir = output.join(&name);
-            let levels = TargetResolution::default_lod_levels();
-            all.extend(export_with_lod(material, &out_dir, preset, levels)?);
+    let (tiled, changed): (Vec<PathBuf>, Vec<PathBuf>) =
+        changed.into_iter().partition(|folder| needs_tiling(folder).unwrap_or(false));
+    let materials = load_materials_or_skip(&changed);
+
+    let mut written = 0;
+    let mut exported = 0;
+    let mut removed = Vec::new();
+    let exports = tiled.iter().map(|folder| (folder, None)).chain(materials.iter().map(|(f, m)| (f, Some(m))));
+    for (folder, material) in exports {
+        let name = &output_names[folder];
+        let out_dir = output.join(name);
+        println!("{}", name);
+        let previous = if incremental {
+            ExportManifest::load(&out_dir.join(EXPORT_MANIFEST_FILE)).ok()
+        } else {
+            None
+        };
+        let result = match material {
+            None => export_tiled(folder, &out_dir, preset, lod, floor),
+            Some(material) => export_with_quality_floor(material, &out_dir, preset, lod, floor)
+                .map(|report| {
+                    print_quality_report(&report);
+                    report.outputs.len()
+                })
+                .map_err(|e| e.into()),
+        };
+        let result = result.and_then(|count| {
+            written += count;
+            exported += 1;
+            let _ = audit_record_optimization(folder, &out_dir, preset.label(), count, None);
+            match previous {
+                Some(previous) => Ok(remove_orphaned_outputs(&out_dir, &previous)?),
+                None => Ok(Vec::new()),
+            }
+        });
+        match result {
+            Ok(orphans) => removed.extend(orphans),
+            Err(e) => {
+                eprintln!("  {}", e);
+                failed.push(name.clone());
+            }
+        }
+    }
+    if incremental {
+        let names: Vec<String> = material_folders.iter().map(|f| output_names[f].clone()).collect();
+        removed.extend(remove_orphaned_materials(output, &root, &names)?);
+        for path in &removed {
+            println!("Removed orphaned {}", path.display());
         }
-        all
-    } else {
-        batch_export_with_preset(&materials, output, preset)?
-    };
-
-    let per_material = written.len() / materials.len().max(1);
-    for (folder, material) in &materials {
-        let name = material
-            .name
-            .clone()
-            .or_else(|| folder.file_name().map(|n| n.to_string_lossy().into_owned()))
-            .unwrap_or_else(|| "material".to_string());
-        let out_dir = output.join(&name);
-        let _ = audit_record_optimization(folder, &out_dir, &target, per_material, None);
     }
 
-    println!("Exported {} texture(s) from {} material(s) to {}", written.len(), materials.len(), output.display());
+    println!("Exported {} texture(s) from {} material(s) to {}", written, exported, output.display());
+    if incremental {
+        println!("{} material(s) up to date, {} orphaned file(s) removed", up_to_date, removed.len());
+    }
+    if !failed.is_empty() {
+        return Err(format!("{} material(s) failed to export: {}", failed.len(), failed.join(", ")).into());
+    }
     Ok(())
 }
 
-fn cmd_batch_check(root: &PathBuf, min_score: i32, ci: bool, output_path: Option<&Path>, validator: Validator) -> Result<(), Box<dyn std::error::Error>> {
+fn cmd_batch_check(
+    root: &PathBuf,
+    min_score: i32,
+    ci: bool,
+    output_path: Option<&Path>,
+    validator: Validator,
+    texel_density: &TexelDensityTarget,
+    scoring: Option<&ScoringModel>,
+    use_cache: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     let root = root.canonicalize().unwrap_or_else(|_| root.clone());
     if !root.is_dir() {
         return Err(format!("Not a directory: {}", root.display()).into());
@@ -493,6 +1006,7 @@ fn cmd_batch_check(root: &PathBuf, min_score: i32, ci: bool, output_path: Option
 
     let mut material_folders = Vec::new();
     find_material_folders(&root, &root, &mut material_folders)?;
+    material_folders.sort();
 
     if material_folders.is_empty() {
         let output = CiOutput {
@@ -517,9 +1031,19 @@ fn cmd_batch_check(root: &PathBuf, min_score: i32, ci: bool, output_path: Option
     let mut results: Vec<CiMaterialResult> = Vec::new();
     let mut failed_count = 0;
 
-    for folder in &material_folders {
-        let set = match MaterialSet::load_from_folder(folder) {
-            Ok(s) => s,
+    // Load, validate and score in parallel (unchanged folders come from the cache);
+    // audit logging and output stay sequential
+    let cache = open_cache(&root, &validator, use_cache);
+    let evaluated = pbr_core::parallel::par_map(&material_folders, |folder| {
+        let scoring_model = scoring_for(scoring, folder)?;
+        let analysis = evaluate_cached(&cache, folder, &validator, texel_density, &scoring_model)?;
+        let breakdown = scoring_model.evaluate(&analysis.issues);
+        Ok::<_, pbr_core::Error>((analysis.issues, breakdown, analysis.report))
+    });
+
+    for (folder, evaluation) in material_folders.iter().zip(evaluated) {
+        let (issues, breakdown, report) = match evaluation {
+            Ok(e) => e,
             Err(e) => {
                 if !ci {
                     eprintln!("⚠ Skipping {}: {}", folder.display(), e);
@@ -528,8 +1052,7 @@ fn cmd_batch_check(root: &PathBuf, min_score: i32, ci: bool, output_path: Option
             }
         };
 
-        let issues = validator.check(&set);
-        let score = pbr_core::validation::compute_score(&issues);
+        let score = breakdown.score;
         let passed = score >= min_score;
         if !passed {
             failed_count += 1;
@@ -549,11 +1072,10 @@ fn cmd_batch_check(root: &PathBuf, min_score: i32, ci: bool, output_path: Option
         );
 
         let rel = folder.strip_prefix(&root).unwrap_or(folder);
-        let report = MaterialReport::from_material_set(&set, issues.clone());
         let result = to_ci_result_with_suggestions(
             rel,
             &issues,
-            score,
+            &breakdown,
             min_score,
             &report.optimization_suggestions,
         );
@@ -605,7 +1127,15 @@ fn cmd_batch_check(root: &PathBuf, min_score: i32, ci: bool, output_path: Option
     Ok(())
 }
 
-fn cmd_pre_commit(min_score: i32, root: Option<&Path>, ci: bool, validator: Validator) -> Result<(), Box<dyn std::error::Error>> {
+fn cmd_pre_commit(
+    min_score: i32,
+    root: Option<&Path>,
+    ci: bool,
+    validator: Validator,
+    texel_density: &TexelDensityTarget,
+    scoring: Option<&ScoringModel>,
+    use_cache: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     let root = match root {
         Some(p) => p.canonicalize().unwrap_or_else(|_| p.to_path_buf()),
         None => {
@@ -669,9 +1199,17 @@ fn cmd_pre_commit(min_score: i32, root: Option<&Path>, ci: bool, validator: Vali
     let mut results: Vec<CiMaterialResult> = Vec::new();
     let mut failed_count = 0;
 
-    for folder in &material_folders {
-        let set = match MaterialSet::load_from_folder(folder) {
-            Ok(s) => s,
+    let cache = open_cache(&root, &validator, use_cache);
+    let evaluated = pbr_core::parallel::par_map(&material_folders, |folder| {
+        let scoring_model = scoring_for(scoring, folder)?;
+        let analysis = evaluate_cached(&cache, folder, &validator, texel_density, &scoring_model)?;
+        let breakdown = scoring_model.evaluate(&analysis.issues);
+        Ok::<_, pbr_core::Error>((analysis.issues, breakdown))
+    });
+
+    for (folder, evaluation) in material_folders.iter().zip(evaluated) {
+        let (issues, breakdown) = match evaluation {
+            Ok(e) => e,
             Err(e) => {
                 if !ci {
                     eprintln!("⚠ Skipping {}: {}", folder.display(), e);
@@ -680,8 +1218,7 @@ fn cmd_pre_commit(min_score: i32, root: Option<&Path>, ci: bool, validator: Vali
             }
         };
 
-        let issues = validator.check(&set);
-        let score = pbr_core::validation::compute_score(&issues);
+        let score = breakdown.score;
         let passed = score >= min_score;
         if !passed {
             failed_count += 1;
@@ -701,7 +1238,7 @@ fn cmd_pre_commit(min_score: i32, root: Option<&Path>, ci: bool, validator: Vali
         );
 
         let rel = folder.strip_prefix(&root).unwrap_or(folder);
-        let result = to_ci_result(rel, &issues, score, min_score);
+        let result = to_ci_result(rel, &issues, &breakdown, min_score);
         results.push(result);
 
         if !ci {
@@ -755,18 +1292,16 @@ fn cmd_analyze(
 
     let mut material_folders = Vec::new();
     find_material_folders(&root, &root, &mut material_folders)?;
+    material_folders.sort();
 
     if material_folders.is_empty() {
         return Err(format!("No material folders found under \"{}\"", root.display()).into());
     }
 
-    let mut materials: Vec<(PathBuf, MaterialSet)> = Vec::new();
-    for folder in &material_folders {
-        match MaterialSet::load_from_folder(folder) {
-            Ok(set) => materials.push((folder.clone(), set)),
-            Err(e) => eprintln!("⚠ Skipping {}: {}", folder.display(), e),
-        }
-    }
+    // Materials above the tiled threshold are analyzed from streamed statistics
+    let materials = skip_failed_loads(pbr_core::parallel::par_map(&material_folders, |folder| {
+        (folder.clone(), load_folder(folder))
+    }));
 
     if let Some(out) = output {
         run_advanced_analysis_and_write(&materials, out, duplicate_threshold, similar_threshold, None, tileability)?;
@@ -778,44 +1313,314 @@ fn cmd_analyze(
     Ok(())
 }
 
+fn cmd_verify_export(folder: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let manifest = ExportManifest::load(&folder.join(EXPORT_MANIFEST_FILE))?;
+    let problems = verify_export(folder)?;
+    println!(
+        "{} ({}, pbr-core {}): {} file(s)",
+        manifest.material.as_deref().unwrap_or("material"),
+        manifest.preset,
+        manifest.generator_version,
+        manifest.files.len()
+    );
+    for p in &problems {
+        println!("  FAIL {}", p);
+    }
+    if !problems.is_empty() {
+        return Err(format!("{} of {} file(s) do not match the manifest", problems.len(), manifest.files.len()).into());
+    }
+    println!("All files match the manifest");
+    Ok(())
+}
+
+fn cmd_budget(manifest_path: &Path, platform: Option<&str>, top: usize, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let platform: Option<Platform> = platform.map(str::parse).transpose()?;
+    let manifest = SceneManifest::load(manifest_path)?;
+    let base_dir = manifest_path.parent().unwrap_or(Path::new("."));
+    let mut report = evaluate_scene_budget(&manifest, base_dir);
+    if let Some(p) = platform {
+        report.platforms.retain(|b| b.platform == p);
+    }
+    for m in &report.missing {
+        eprintln!("Warning: skipping {}", m);
+    }
+
+    if json {
+        println!("{}", report.to_json()?);
+    } else {
+        println!("Scene: {} ({} materials)", report.name.as_deref().unwrap_or("unnamed"), report.material_count);
+        for p in &report.platforms {
+            let budget = p.budget_formatted.as_deref().unwrap_or("no budget");
+            let status = if p.within_budget { "OK" } else { "OVER" };
+            println!("\n[{}] {} resident / {} ({} full mip chains) — {}", p.platform, p.resident_formatted, budget, p.full_formatted, status);
+            for o in p.offenders.iter().take(top) {
+                println!("  {:>10}  {:>4.1}%  {}x{}  x{:<4} {:?}  {}",
+                    o.resident_formatted, o.share * 100.0, o.width, o.height, o.instances, o.priority, o.material);
+            }
+            if !p.within_budget && p.downgrades.is_empty() {
+                println!("  No downgrade available: materials are pinned or at {} px", pbr_core::budget::MIN_DOWNGRADE_DIMENSION);
+            } else if !p.downgrades.is_empty() {
+                println!("  Suggested downgrades (projected {}{}):", p.projected_formatted,
+                    if p.fits_after_downgrades { "" } else { ", still over budget" });
+                for d in &p.downgrades {
+                    println!("    {} {} -> {}  saves {}", d.material, d.from_max_dimension, d.target, d.saved_formatted);
+                }
+            }
+        }
+    }
+
+    if !report.within_budget() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn cmd_fit_budget(
+    root: &Path,
+    platform: &str,
+    budget_mb: f64,
+    min_size: u32,
+    output: Option<&Path>,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let platform: Platform = platform.parse()?;
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.display()).into());
+    }
+    let mut material_folders = Vec::new();
+    find_material_folders(&root, &root, &mut material_folders)?;
+    material_folders.sort();
+    let materials = load_materials_or_skip(&material_folders);
+
+    let mut options = FitOptions::new(platform, (budget_mb * 1024.0 * 1024.0).round() as u64);
+    options.min_dimension = min_size;
+    let plan = plan_budget_fit(&materials, &options)?;
+
+    if json {
+        println!("{}", plan.to_json()?);
+    } else {
+        println!("[{}] {} -> {} (budget {}){}", plan.platform, plan.original_formatted, plan.fitted_formatted,
+            plan.budget_formatted, if plan.fits { "" } else { " — still over budget at minimum size" });
+        for m in &plan.materials {
+            let changes: Vec<String> = m.slots.iter()
+                .filter(|s| s.downgraded())
+                .map(|s| format!("{} {}x{} -> {} (SSIM {:.3})", s.slot, s.original_width, s.original_height, s.target, s.ssim))
+                .collect();
+            if !changes.is_empty() {
+                println!("  {}: {}", m.material, changes.join(", "));
+            }
+        }
+    }
+
+    if let Some(out) = output {
+        let written = export_budget_fit(&materials, &plan, out)?;
+        if !json {
+            println!("Exported {} file(s) to {}", written.len(), out.display());
+        }
+    }
+    if !plan.fits {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn cmd_atlas(root: &Path, output: &Path, algorithm: &str, mut options: AtlasOptions) -> Result<(), Box<dyn std::error::Error>> {
+    options.algorithm = match algorithm.to_lowercase().as_str() {
+        "maxrects" => PackingAlgorithm::MaxRects,
+        "skyline" => PackingAlgorithm::Skyline,
+        other => return Err(format!("Unknown packing algorithm '{}'. Use maxrects or skyline.", other).into()),
+    };
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.display()).into());
+    }
+
+    let mut material_folders = Vec::new();
+    find_material_folders(&root, &root, &mut material_folders)?;
+    material_folders.sort();
+    let materials = load_materials_or_skip(&material_folders);
+    let report = export_atlas(&materials, output, &options)?;
+
+    let layout = &report.layout;
+    println!("Packed {} materials into {} page(s) of {}x{} ({:.0}% occupied)",
+        layout.entries.len(), layout.pages, layout.page_width, layout.page_height, layout.occupancy * 100.0);
+    for skipped in &layout.skipped {
+        println!("  skipped {}: {}", skipped.material, skipped.reason);
+    }
+    for path in &report.written {
+        println!("  wrote {}", path);
+    }
+    Ok(())
+}
+
+fn cmd_dedup(root: &Path, apply: bool, manifest_dir: Option<&Path>, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.display()).into());
+    }
+
+    let mut material_folders = Vec::new();
+    find_material_folders(&root, &root, &mut material_folders)?;
+    material_folders.sort();
+    let materials = load_materials_or_skip(&material_folders);
+    let report = find_exact_duplicates(&materials);
+
+    if json {
+        println!("{}", report.to_json()?);
+    } else {
+        println!("Exact duplicates: {} of {} textures ({} byte-identical, {} pixel-identical)",
+            report.duplicate_count, report.texture_count, report.byte_identical_count, report.pixel_identical_count);
+        for group in &report.groups {
+            println!("  {} ({}x{})", group.shared_path, group.width, group.height);
+            for dup in &group.duplicates {
+                let kind = match dup.match_kind {
+                    ExactMatch::Bytes => "bytes",
+                    ExactMatch::Pixels => "pixels",
+                };
+                println!("    = {} [{}]", dup.path, kind);
+            }
+        }
+        println!("Savings: {} disk, {} VRAM", report.disk_saved, report.vram_saved);
+    }
+
+    if apply && !report.groups.is_empty() {
+        let manifest_dir = manifest_dir.map(Path::to_path_buf).unwrap_or_else(|| root.clone());
+        let manifest = apply_dedup(&report, &manifest_dir)?;
+        eprintln!("Deduplicated {} groups (manifest: {})",
+            manifest.entries.len(), manifest_dir.join(pbr_core::DEDUP_MANIFEST_FILE).display());
+    }
+    Ok(())
+}
+
+fn cmd_search(query: &Path, library: &Path, top: usize, slot: Option<&str>, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let library = library.canonicalize().unwrap_or_else(|_| library.to_path_buf());
+    if !library.is_dir() {
+        return Err(format!("Not a directory: {}", library.display()).into());
+    }
+    let slot = match slot {
+        Some(name) => Some(TextureSlot::from_name(name).ok_or_else(|| format!("Unknown slot '{}'", name))?),
+        None => None,
+    };
+
+    let mut material_folders = Vec::new();
+    find_material_folders(&library, &library, &mut material_folders)?;
+    material_folders.sort();
+    let (index, update) = SearchIndex::open(&library, &material_folders)?;
+    eprintln!("Index: {} materials ({} indexed, {} unchanged, {} removed) in {}",
+        index.materials.len(), update.indexed, update.reused, update.removed, library.join(SEARCH_INDEX_FILE).display());
+
+    let query = query.canonicalize().unwrap_or_else(|_| query.to_path_buf());
+    let hits = if query.is_dir() {
+        let set = MaterialSet::load_from_folder(&query)?;
+        index.search_material(&set, top)
+    } else {
+        let img = pbr_core::ImageLoader::load(&query)?;
+        let texture = pbr_core::material::TextureMap::from_loaded(img, Some(query.clone()));
+        index.search_texture(&texture, slot, top)
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&hits)?);
+        return Ok(());
+    }
+    if hits.is_empty() {
+        println!("No similar materials found.");
+    }
+    for (rank, hit) in hits.iter().enumerate() {
+        let name = hit.material_name.clone().unwrap_or_else(|| hit.folder.clone());
+        let slot = hit.slot.as_deref().map(|s| format!(" [{}]", s)).unwrap_or_default();
+        println!("{:>2}. {:>5.1}%  {}{}  (hash {:.2}, features {:.2})",
+            rank + 1, hit.similarity * 100.0, name, slot, hit.hash_similarity, hit.feature_similarity);
+        println!("      {}", hit.folder);
+    }
+    Ok(())
+}
+
 fn cmd_fix_tileability(
     path: &PathBuf,
     output: &PathBuf,
     blend_width: u32,
+    method: &str,
+    reference: Option<&str>,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let method = match method.to_lowercase().as_str() {
+        "blend" => SeamlessMethod::Blend,
+        "quilt" => SeamlessMethod::Quilt,
+        "poisson" => SeamlessMethod::Poisson,
+        "hybrid" => SeamlessMethod::Hybrid,
+        other => return Err(format!("Unknown fix method '{}'. Use hybrid, quilt, poisson or blend.", other).into()),
+    };
+    let reference_slot = match reference {
+        Some(name) => Some(TextureSlot::from_name(name).ok_or_else(|| format!("Unknown reference slot '{}'", name))?),
+        None => None,
+    };
+    let options = SeamlessOptions {
+        method,
+        reference_slot,
+        feather: blend_width,
+        ..Default::default()
+    };
     let path = path.canonicalize().unwrap_or_else(|_| path.clone());
 
-    let (texture, output_path) = if path.is_dir() {
-        let set = MaterialSet::load_from_folder(&path)?;
-        let albedo = set.albedo.ok_or("No albedo texture found in material folder")?;
-        let out = if output.is_dir() {
-            output.join("albedo.png")
-        } else {
-            output.clone()
-        };
-        (albedo, out)
+    if path.is_dir() {
+        if output.is_file() || has_image_extension(output) {
+            return Err(format!(
+                "Output for a material folder must be a folder, not a file: {}",
+                output.display()
+            )
+            .into());
+        }
+        let report = fix_material_folder(&path, output, &options)?;
+        println!("Fixed tileability: {} -> {} (reference: {:?})", report.source, report.output, report.reference_slot);
+        for slot in &report.slots {
+            println!("  {:<10} seam {:.2} -> {:.2}  edge diff {:.1} -> {:.1}",
+                slot.slot, slot.result.original_seam_score, slot.result.fixed_seam_score,
+                slot.result.original_edge_difference, slot.result.fixed_edge_difference);
+        }
+        println!("Report: {}", output.join(FIX_REPORT_FILE).display());
+        return Ok(());
+    }
+
+    let img = pbr_core::ImageLoader::load(&path)?;
+    let texture = pbr_core::material::TextureMap::from_loaded(img, Some(path.clone()));
+    let output_path = if output.is_dir() {
+        output.join(path.file_name().unwrap_or(OsStr::new("albedo.png")))
     } else {
-        let img = pbr_core::ImageLoader::load(&path)?;
-        let texture = pbr_core::material::TextureMap::from_loaded(img, Some(path.clone()));
-        let out = if output.is_dir() {
-            output.join(path.file_name().unwrap_or(OsStr::new("albedo.png")))
-        } else {
-            output.clone()
-        };
-        (texture, out)
+        output.clone()
     };
 
-    let (fixed, result) = fix_tileability_with_report(&texture, blend_width)?;
+    let (fixed, result) = make_seamless_with_report(&texture, &options)?;
     if let Some(parent) = output_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
     save_texture(&fixed, &output_path)?;
     println!("Fixed tileability: {} -> {}", result.path, output_path.display());
-    println!("  Edge difference: {:.1} -> {:.1} (improved: {})",
-        result.original_edge_difference, result.fixed_edge_difference, result.improved);
+    println!("  Seam score: {:.2} -> {:.2} (improved: {})",
+        result.original_seam_score, result.fixed_seam_score, result.improved);
+    println!("  Edge difference: {:.1} -> {:.1}",
+        result.original_edge_difference, result.fixed_edge_difference);
     Ok(())
 }
 
+/// Load material folders in parallel, skipping (with a warning) those that fail.
+fn load_materials_or_skip(folders: &[PathBuf]) -> Vec<(PathBuf, MaterialSet)> {
+    skip_failed_loads(pbr_core::parallel::load_materials(folders))
+}
+
+fn skip_failed_loads(loaded: Vec<(PathBuf, pbr_core::Result<MaterialSet>)>) -> Vec<(PathBuf, MaterialSet)> {
+    loaded
+        .into_iter()
+        .filter_map(|(folder, result)| match result {
+            Ok(set) => Some((folder, set)),
+            Err(e) => {
+                eprintln!("⚠ Skipping {}: {}", folder.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
 fn find_material_folders(
     root: &Path,
     dir: &Path,
@@ -838,11 +1643,17 @@ fn find_material_folders(
     Ok(())
 }
 
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tga", "exr"];
+
+fn has_image_extension(path: &Path) -> bool {
+    let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
+    IMAGE_EXTENSIONS.contains(&ext.as_str())
+}
+
 fn is_material_folder(path: &Path) -> bool {
     let Ok(entries) = std::fs::read_dir(path) else {
         return false;
     };
-    const EXTS: &[&str] = &["png", "jpg", "jpeg", "tga", "exr"];
     const SLOTS: &[&str] = &[
         "albedo", "basecolor", "diffuse", "color",
         "normal", "norm",
@@ -858,8 +1669,7 @@ fn is_material_folder(path: &Path) -> bool {
             continue;
         }
         let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
-        let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
-        if EXTS.contains(&ext.as_str()) && SLOTS.iter().any(|s| stem.contains(s)) {
+        if has_image_extension(&path) && SLOTS.iter().any(|s| stem.contains(s)) {
             return true;
         }
     }
@@ -872,13 +1682,20 @@ fn cmd_report(
     vram: bool,
     export: Option<&str>,
     output: Option<&PathBuf>,
+    scoring: Option<&ScoringModel>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let set = MaterialSet::load_from_folder(folder)?;
+    let set = load_folder(folder)?;
     let validator = Validator::default();
     let issues = validator.check(&set);
+    let scoring = scoring_for(scoring, folder)?;
+    let report = MaterialReport::from_material_set_with_scoring(&set, issues.clone(), None, &scoring);
 
     if let (Some(format), Some(out)) = (export, output) {
-        let report = MaterialReport::from_material_set(&set, issues);
+        let report = if format.eq_ignore_ascii_case("html") {
+            report.with_color_distributions(&set)
+        } else {
+            report
+        };
         match format.to_lowercase().as_str() {
             "html" => export_html_single(&report, out)?,
             "pdf" => export_pdf_single(&report, out)?,
@@ -901,15 +1718,27 @@ fn cmd_report(
     }
 
     if json {
-        let report = MaterialReport::from_material_set(&set, issues);
         println!("{}", report.to_json()?);
     } else {
         let text_report = pbr_core::Report::from_material_set(&set, issues);
         println!("{}", text_report.to_text());
+        if let Some(breakdown) = &report.score_breakdown {
+            let categories: Vec<String> = breakdown
+                .categories
+                .iter()
+                .map(|c| format!("{} {}", c.category.as_str(), c.score))
+                .collect();
+            println!("Score: {} ({})", report.score, breakdown.model);
+            println!("Categories: {}", categories.join(", "));
+        }
         if vram {
             let can_pack = set.roughness.is_some() && set.metallic.is_some() && set.ao.is_some();
             let est = estimate_vram(&set, true, can_pack);
-            println!("\nVRAM estimate (mipmaps): {}", est.formatted);
+            println!("\nVRAM estimate (mipmaps): {} uncompressed RGBA8", est.formatted);
+            for p in &est.platforms {
+                let formats: Vec<String> = p.textures.iter().map(|t| format!("{} {}", t.slot, t.format.name())).collect();
+                println!("  {:<8} {:>10}  {:>4.1}x  ({})", p.platform, p.formatted, p.compression_ratio, formats.join(", "));
+            }
         }
     }
 
@@ -921,6 +1750,7 @@ fn cmd_export_report(
     format: &str,
     output: &PathBuf,
     track: bool,
+    scoring: Option<&ScoringModel>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if folders.is_empty() {
         return Err("At least one folder required".into());
@@ -928,10 +1758,11 @@ fn cmd_export_report(
 
     let validator = Validator::default();
     let mut reports: Vec<(String, MaterialReport)> = Vec::new();
+    let html = format.eq_ignore_ascii_case("html");
 
     for folder in folders {
         let path_str = folder.display().to_string();
-        let set = match MaterialSet::load_from_folder(folder) {
+        let set = match load_folder(folder) {
             Ok(s) => s,
             Err(e) => {
                 eprintln!("Warning: skipping {}: {}", path_str, e);
@@ -939,7 +1770,8 @@ fn cmd_export_report(
             }
         };
         let issues = validator.check(&set);
-        let report = MaterialReport::from_material_set(&set, issues);
+        let report = MaterialReport::from_material_set_with_scoring(&set, issues, None, &scoring_for(scoring, folder)?);
+        let report = if html { report.with_color_distributions(&set) } else { report };
         if track {
             if let Err(e) = record_analysis(folder, report.score, report.passed, report.error_count, report.warning_count, report.issues.len()) {
                 eprintln!("Warning: could not record version for {}: {}", path_str, e);
@@ -1035,6 +1867,70 @@ fn cmd_ai_analyze(folder: &PathBuf, model: Option<&Path>) -> Result<(), Box<dyn
     Ok(())
 }
 
+/// Texel density rule for `--texel-density` / `--mesh` (mesh auto-detected per folder when omitted);
+/// the 4K resolution rule uses the same target, which is returned for report suggestions.
+fn add_texel_density_rules(
+    validator: &mut Validator,
+    px_per_cm: Option<f64>,
+    mesh: Option<&Path>,
+) -> Result<TexelDensityTarget, Box<dyn std::error::Error>> {
+    let mut target = TexelDensityTarget::default();
+    if let Some(px) = px_per_cm {
+        target.px_per_cm = px;
+    }
+    let mesh = mesh.map(MeshUvStats::load).transpose()?;
+    validator.replace_rule(TextureResolutionRule::new(target.clone(), mesh.clone()));
+    validator.add_rule(TexelDensityRule::new(target.clone(), mesh));
+    Ok(target)
+}
+
+fn cmd_texel_density(
+    folder: &Path,
+    mesh: Option<&Path>,
+    target: &TexelDensityTarget,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mesh_path = match mesh {
+        Some(p) => p.to_path_buf(),
+        None => MeshUvStats::find_in_folder(folder)
+            .ok_or_else(|| format!("No mesh (.obj/.gltf/.glb) in {}; pass --mesh", folder.display()))?,
+    };
+    let mesh = MeshUvStats::load(&mesh_path)?;
+    let set = MaterialSet::load_from_folder(folder)?;
+    let report = analyze_texel_density(&set, &mesh, target)
+        .ok_or("No textures in folder or no UV-mapped triangles in mesh")?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    let status = match report.status {
+        DensityStatus::UnderDense => "✗ under-dense",
+        DensityStatus::WithinRange => "✓ within range",
+        DensityStatus::OverDense => "⚠ over-dense",
+    };
+    println!("Mesh: {} ({} triangles)", mesh_path.display(), report.mesh.triangle_count);
+    println!("Texture: {}x{}", report.texture_width, report.texture_height);
+    println!(
+        "Texel density: {:.2} px/cm (5th–95th percentile {:.2}–{:.2})",
+        report.px_per_cm, report.px_per_cm_p05, report.px_per_cm_p95
+    );
+    println!(
+        "Target: {:.2} px/cm ({:.2}–{:.2}) → {}",
+        target.px_per_cm,
+        target.min_px_per_cm(),
+        target.max_px_per_cm(),
+        status
+    );
+    if report.status != DensityStatus::WithinRange {
+        println!(
+            "Recommended resolution: {} (ideal longest edge {} px)",
+            report.recommended_resolution, report.ideal_max_dimension
+        );
+    }
+    Ok(())
+}
+
 fn build_plugin_loader(
     plugins_dir: Option<&PathBuf>,
     config_path: Option<&PathBuf>,
@@ -1109,7 +2005,7 @@ mod tests {
 
         let out = tmp.path().join("batch-report.json");
         let folders = vec![mat1.clone(), mat2_path];
-        let result = cmd_export_report(&folders, "json", &out, false);
+        let result = cmd_export_report(&folders, "json", &out, false, None);
 
         assert!(result.is_ok(), "export-report json failed: {:?}", result.err());
         assert!(out.exists(), "JSON file was not created");
@@ -11,6 +11,7 @@
 //! model path for ML-based classification.
 
 use crate::material::{MaterialSet, TextureMap};
+use crate::texel_density::{material_texel_density, DensityStatus, TexelDensityTarget};
 use serde::{Deserialize, Serialize};
 
 /// True when built with `--features ai` (ONNX support)
@@ -91,9 +92,8 @@ pub struct AiInsights {
 
 /// Extract features from a texture for analysis
 pub fn extract_features(tex: &TextureMap) -> TextureFeatures {
-    let (w, h) = (tex.width as usize, tex.height as usize);
-    let n = w * h;
-    if n == 0 {
+    let stats = tex.stats();
+    if stats.pixel_count == 0 {
         return TextureFeatures {
             mean_r: 0.0, mean_g: 0.0, mean_b: 0.0,
             std_r: 0.0, std_g: 0.0, std_b: 0.0,
@@ -102,81 +102,22 @@ pub fn extract_features(tex: &TextureMap) -> TextureFeatures {
         };
     }
 
-    let mut sum_r: f64 = 0.0;
-    let mut sum_g: f64 = 0.0;
-    let mut sum_b: f64 = 0.0;
-    let mut sum_r2: f64 = 0.0;
-    let mut sum_g2: f64 = 0.0;
-    let mut sum_b2: f64 = 0.0;
-    let mut sum_sat: f64 = 0.0;
-    let mut sum_warm: f64 = 0.0;
-    let mut edge_count: u32 = 0;
-
-    let data = &tex.data;
-    for y in 0..h {
-        for x in 0..w {
-            let i = (y * w + x) * 4;
-            if i + 4 > data.len() {
-                break;
-            }
-            let r = data[i] as f64;
-            let g = data[i + 1] as f64;
-            let b = data[i + 2] as f64;
-            sum_r += r;
-            sum_g += g;
-            sum_b += b;
-            sum_r2 += r * r;
-            sum_g2 += g * g;
-            sum_b2 += b * b;
-            let maxc = r.max(g).max(b);
-            let minc = r.min(g).min(b);
-            let sat = if maxc > 0.0 { (maxc - minc) / maxc } else { 0.0 };
-            sum_sat += sat;
-            let total = r + g + b;
-            sum_warm += if total > 0.0 { r / total } else { 0.33 };
-        }
-    }
-
-    let nf = n as f64;
-    let mean_r = (sum_r / nf) as f32;
-    let mean_g = (sum_g / nf) as f32;
-    let mean_b = (sum_b / nf) as f32;
-    let std_r = ((sum_r2 / nf - (sum_r / nf).powi(2)).max(0.0).sqrt()) as f32;
-    let std_g = ((sum_g2 / nf - (sum_g / nf).powi(2)).max(0.0).sqrt()) as f32;
-    let std_b = ((sum_b2 / nf - (sum_b / nf).powi(2)).max(0.0).sqrt()) as f32;
-    let variance = (std_r * std_r + std_g * std_g + std_b * std_b) / 3.0;
-    let saturation_mean = (sum_sat / nf) as f32;
-    let warm_ratio = (sum_warm / nf) as f32;
-
-    // Simple edge detection: count pixels where neighbor difference > threshold
-    let threshold = 30.0;
-    for y in 1..h.saturating_sub(1) {
-        for x in 1..w.saturating_sub(1) {
-            let i = (y * w + x) * 4;
-            let c = data[i] as f32 + data[i + 1] as f32 + data[i + 2] as f32;
-            let right = (data[i + 4] as f32 + data[i + 5] as f32 + data[i + 6] as f32).abs();
-            let down = (data[(y + 1) * w * 4 + x * 4] as f32
-                + data[(y + 1) * w * 4 + x * 4 + 1] as f32
-                + data[(y + 1) * w * 4 + x * 4 + 2] as f32)
-                .abs();
-            if (c - right).abs() > threshold || (c - down).abs() > threshold {
-                edge_count += 1;
-            }
-        }
-    }
-    let edge_density = edge_count as f32 / n as f32;
+    let [r, g, b, _] = &stats.channels;
+    let std_r = r.population_stddev as f32;
+    let std_g = g.population_stddev as f32;
+    let std_b = b.population_stddev as f32;
 
     TextureFeatures {
-        mean_r,
-        mean_g,
-        mean_b,
+        mean_r: r.mean as f32,
+        mean_g: g.mean as f32,
+        mean_b: b.mean as f32,
         std_r,
         std_g,
         std_b,
-        variance,
-        edge_density,
-        saturation_mean,
-        warm_ratio,
+        variance: (std_r * std_r + std_g * std_g + std_b * std_b) / 3.0,
+        edge_density: stats.edge_density() as f32,
+        saturation_mean: stats.saturation_mean as f32,
+        warm_ratio: stats.warm_ratio as f32,
     }
 }
 
@@ -305,10 +246,40 @@ fn classify_with_onnx(tex: &TextureMap, path: &std::path::Path) -> Result<(Mater
     Ok((class, confidence))
 }
 
-/// Generate smart optimization suggestions based on texture analysis
+/// Generate smart optimization suggestions based on texture analysis.
+/// With a mesh in the material folder, resolution advice comes from its texel
+/// density (default target) instead of texture complexity.
 pub fn suggest_optimizations(set: &MaterialSet) -> Vec<AiSuggestion> {
+    suggest_optimizations_with_target(set, &TexelDensityTarget::default())
+}
+
+/// [`suggest_optimizations`] with resolution advice against the given texel density target.
+pub fn suggest_optimizations_with_target(set: &MaterialSet, target: &TexelDensityTarget) -> Vec<AiSuggestion> {
     let mut suggestions = Vec::new();
 
+    let density = material_texel_density(set, target);
+    if let Some(d) = &density {
+        let message = match d.status {
+            DensityStatus::OverDense => Some(format!(
+                "Texel density {:.2} px/cm is above the {:.2} px/cm target on the mesh. Downscale to {} to save memory.",
+                d.px_per_cm, target.px_per_cm, d.recommended_resolution
+            )),
+            DensityStatus::UnderDense => Some(format!(
+                "Texel density {:.2} px/cm is below the {:.2} px/cm target on the mesh. Use a {} texture or reduce UV tiling.",
+                d.px_per_cm, target.px_per_cm, d.recommended_resolution
+            )),
+            DensityStatus::WithinRange => None,
+        };
+        if let Some(message) = message {
+            suggestions.push(AiSuggestion {
+                category: "resolution".to_string(),
+                message,
+                confidence: 0.85,
+                target_resolution: Some(d.recommended_resolution.clone()),
+            });
+        }
+    }
+
     let albedo = match &set.albedo {
         Some(a) => a,
         None => return suggestions,
@@ -319,7 +290,7 @@ pub fn suggest_optimizations(set: &MaterialSet) -> Vec<AiSuggestion> {
     let max_dim = w.max(h) as f32;
 
     // Low complexity → safe to downscale
-    if f.variance < 300.0 && f.edge_density < 0.02 {
+    if density.is_none() && f.variance < 300.0 && f.edge_density < 0.02 {
         let target = if max_dim > 2048.0 { "2K" } else { "1K" };
         suggestions.push(AiSuggestion {
             category: "resolution".to_string(),
@@ -333,7 +304,7 @@ pub fn suggest_optimizations(set: &MaterialSet) -> Vec<AiSuggestion> {
     }
 
     // High res + low complexity
-    if max_dim > 4096.0 && f.variance < 800.0 {
+    if density.is_none() && max_dim > 4096.0 && f.variance < 800.0 {
         suggestions.push(AiSuggestion {
             category: "resolution".to_string(),
             message: "Texture exceeds 4K with moderate complexity. 4K or 2K may suffice for most use cases.".to_string(),
@@ -422,8 +393,17 @@ pub fn detect_anomalies(set: &MaterialSet) -> Vec<Anomaly> {
 
 /// Run full AI analysis and return insights for report integration
 pub fn analyze_material(set: &MaterialSet, onnx_path: Option<&std::path::Path>) -> AiInsights {
+    analyze_material_with_target(set, onnx_path, &TexelDensityTarget::default())
+}
+
+/// [`analyze_material`] with resolution suggestions against the given texel density target.
+pub fn analyze_material_with_target(
+    set: &MaterialSet,
+    onnx_path: Option<&std::path::Path>,
+    target: &TexelDensityTarget,
+) -> AiInsights {
     let (classification, conf) = classify_material(set, onnx_path);
-    let suggestions = suggest_optimizations(set);
+    let suggestions = suggest_optimizations_with_target(set, target);
     let anomalies = detect_anomalies(set);
 
     AiInsights {
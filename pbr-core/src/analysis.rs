@@ -1,101 +1,47 @@
 //! Advanced analysis modules.
 //!
-//! Provides duplicate/similar texture detection, cross-material consistency
-//! analysis, and automatic tileability fixes. All analyses are fully offline
-//! and output structured JSON results.
+//! Provides exact and perceptual duplicate texture detection, cross-material consistency
+//! analysis, tileability and repetition analysis, per-texture color distributions, and
+//! automatic tileability fixes. All analyses are fully offline and output structured JSON results.
 
+use crate::ai::{classify_material, MaterialClass};
+use crate::banding::{analyze_banding, TextureBanding};
+use crate::color_distribution::{analyze_color_distributions, TextureColorDistribution};
+use crate::dedup::{file_digest, find_exact_duplicates, precision_digest, ExactDuplicateReport, ExactMatch};
+use crate::image_hash::{max_phash_distance, HashIndex, HashTransform};
 use crate::material::{MaterialSet, TextureMap};
+use crate::tileability::{fft2, luminance_grid, SeamAnalysis};
 use crate::Result;
+use rayon::prelude::*;
+use rustfft::num_complex::Complex;
+use rustfft::FftDirection;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
-/// Perceptual hash size for similarity comparison (8x8 = 64 values)
-const PHASH_SIZE: u32 = 8;
-
-/// Compute a simple perceptual hash: downsample to PHASH_SIZE x PHASH_SIZE grayscale,
-/// return mean per block. Used for duplicate/similar detection.
-fn perceptual_hash(map: &TextureMap) -> Vec<f32> {
-    let w = map.width as usize;
-    let h = map.height as usize;
-    if w == 0 || h == 0 {
-        return vec![];
-    }
-
-    let block_w = (w as f32 / PHASH_SIZE as f32).max(1.0);
-    let block_h = (h as f32 / PHASH_SIZE as f32).max(1.0);
-    let mut hash = Vec::with_capacity((PHASH_SIZE * PHASH_SIZE) as usize);
-
-    for by in 0..PHASH_SIZE {
-        for bx in 0..PHASH_SIZE {
-            let x0 = (bx as f32 * block_w) as usize;
-            let y0 = (by as f32 * block_h) as usize;
-            let x1 = ((bx as f32 + 1.0) * block_w).min(w as f32) as usize;
-            let y1 = ((by as f32 + 1.0) * block_h).min(h as f32) as usize;
-
-            let mut sum = 0.0f64;
-            let mut count = 0usize;
-            for y in y0..y1 {
-                for x in x0..x1 {
-                    let i = (y * w + x) * 4;
-                    if i + 2 < map.data.len() {
-                        let g = 0.299 * map.data[i] as f64
-                            + 0.587 * map.data[i + 1] as f64
-                            + 0.114 * map.data[i + 2] as f64;
-                        sum += g;
-                        count += 1;
-                    }
-                }
-            }
-            let mean = if count > 0 { sum / count as f64 } else { 0.0 };
-            hash.push(mean as f32);
-        }
-    }
-    hash
-}
-
-/// Compute similarity (0.0 = different, 1.0 = identical) from perceptual hashes.
-fn hash_similarity(a: &[f32], b: &[f32]) -> f32 {
-    if a.len() != b.len() || a.is_empty() {
-        return 0.0;
-    }
-    let sq_diff: f32 = a.iter().zip(b.iter()).map(|(x, y)| (x - y).powi(2)).sum();
-    let max_diff = a.len() as f32 * 255.0 * 255.0;
-    (1.0 - (sq_diff / max_diff).min(1.0)).max(0.0)
-}
-
-/// Texture descriptor for duplicate detection
-#[derive(Debug, Clone)]
-struct TextureRef {
-    path: Option<PathBuf>,
-    slot: String,
-    material_name: Option<String>,
-    hash: Vec<f32>,
-}
-
-fn collect_texture_refs(materials: &[(PathBuf, MaterialSet)]) -> Vec<TextureRef> {
-    let mut refs = Vec::new();
+/// All textures of all materials as (material name, slot, texture), in material then slot order.
+/// Textures without pixel data (streamed 16K+ sources) are left out.
+pub(crate) fn material_textures(materials: &[(PathBuf, MaterialSet)]) -> Vec<(Option<String>, &'static str, &TextureMap)> {
+    let mut out = Vec::new();
     for (folder, set) in materials {
         let name = set.name.clone().or_else(|| folder.file_name().map(|n| n.to_string_lossy().into_owned()));
-        for (opt, slot) in [
-            (set.albedo.as_ref(), "albedo"),
-            (set.normal.as_ref(), "normal"),
-            (set.roughness.as_ref(), "roughness"),
-            (set.metallic.as_ref(), "metallic"),
-            (set.ao.as_ref(), "ao"),
-            (set.height.as_ref(), "height"),
-        ] {
-            if let Some(t) = opt {
-                refs.push(TextureRef {
-                    path: t.path.clone(),
-                    slot: slot.to_string(),
-                    material_name: name.clone(),
-                    hash: perceptual_hash(t),
-                });
-            }
-        }
+        out.extend(slot_textures(set).filter(|(_, t)| t.has_pixels()).map(|(slot, t)| (name.clone(), slot, t)));
     }
-    refs
+    out
+}
+
+/// Present textures of one material as (slot, texture), in slot order.
+pub(crate) fn slot_textures(set: &MaterialSet) -> impl Iterator<Item = (&'static str, &TextureMap)> {
+    [
+        (set.albedo.as_ref(), "albedo"),
+        (set.normal.as_ref(), "normal"),
+        (set.roughness.as_ref(), "roughness"),
+        (set.metallic.as_ref(), "metallic"),
+        (set.ao.as_ref(), "ao"),
+        (set.height.as_ref(), "height"),
+    ]
+    .into_iter()
+    .filter_map(|(t, slot)| t.map(|t| (slot, t)))
 }
 
 // --- JSON output types ---
@@ -108,6 +54,18 @@ pub struct DuplicatePair {
     pub material_a: Option<String>,
     pub material_b: Option<String>,
     pub similarity: f32,
+    /// How texture b relates to texture a
+    pub transform: HashTransform,
+    /// Fraction of each side of a that b shows, when b is a center crop of a
+    pub crop: Option<f32>,
+    /// Linear size of b relative to a (2.0 = twice the resolution)
+    pub scale: f32,
+    /// pHash Hamming distance (0-64) after applying the transform
+    pub phash_distance: u32,
+    /// Color histogram intersection (0-1)
+    pub color_similarity: f32,
+    /// Set when the two textures have identical pixels (and whether the files are identical too)
+    pub exact: Option<ExactMatch>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -135,12 +93,111 @@ pub struct MapCoverage {
     pub missing_in: Vec<String>,
 }
 
+/// Per-material value compared across the library (all on a 0-1 scale).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueMetric {
+    /// Mean Rec.601 luminance of the albedo
+    AlbedoLuminance,
+    /// Mean saturation of the albedo
+    AlbedoSaturation,
+    /// Mean roughness
+    RoughnessMean,
+    /// 5th to 95th percentile roughness spread
+    RoughnessRange,
+    /// Mean tilt of the normal map away from flat (per-channel spread of X and Y)
+    NormalIntensity,
+}
+
+impl ValueMetric {
+    const ALL: [ValueMetric; 5] = [
+        ValueMetric::AlbedoLuminance,
+        ValueMetric::AlbedoSaturation,
+        ValueMetric::RoughnessMean,
+        ValueMetric::RoughnessRange,
+        ValueMetric::NormalIntensity,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            ValueMetric::AlbedoLuminance => "albedo luminance",
+            ValueMetric::AlbedoSaturation => "albedo saturation",
+            ValueMetric::RoughnessMean => "roughness",
+            ValueMetric::RoughnessRange => "roughness range",
+            ValueMetric::NormalIntensity => "normal intensity",
+        }
+    }
+}
+
+/// Value statistics of one material; `None` when the slot is missing.
+#[derive(Debug, Clone, Serialize)]
+pub struct MaterialValueStats {
+    pub material: String,
+    pub class: MaterialClass,
+    pub albedo_luminance: Option<f32>,
+    pub albedo_saturation: Option<f32>,
+    pub roughness_mean: Option<f32>,
+    /// 5th and 95th percentile roughness
+    pub roughness_low: Option<f32>,
+    pub roughness_high: Option<f32>,
+    pub normal_intensity: Option<f32>,
+}
+
+impl MaterialValueStats {
+    pub fn value(&self, metric: ValueMetric) -> Option<f32> {
+        match metric {
+            ValueMetric::AlbedoLuminance => self.albedo_luminance,
+            ValueMetric::AlbedoSaturation => self.albedo_saturation,
+            ValueMetric::RoughnessMean => self.roughness_mean,
+            ValueMetric::RoughnessRange => Some(self.roughness_high? - self.roughness_low?),
+            ValueMetric::NormalIntensity => self.normal_intensity,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricDistribution {
+    pub metric: ValueMetric,
+    pub count: usize,
+    pub mean: f32,
+    pub stddev: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Value distributions of the materials of one class.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClassValueDistribution {
+    pub class: MaterialClass,
+    pub materials: Vec<String>,
+    pub metrics: Vec<MetricDistribution>,
+}
+
+/// A material whose value is far from the rest of its class.
+#[derive(Debug, Clone, Serialize)]
+pub struct ValueOutlier {
+    pub material: String,
+    pub class: MaterialClass,
+    pub metric: ValueMetric,
+    pub value: f32,
+    /// Mean of the other materials of the class
+    pub class_mean: f32,
+    /// Distance from `class_mean` in standard deviations of the other materials
+    pub z_score: f32,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct CrossMaterialResult {
     pub material_count: usize,
     pub resolution_distributions: Vec<ResolutionDistribution>,
     pub resolution_inconsistent: bool,
     pub map_coverage: Vec<MapCoverage>,
+    /// Albedo, roughness and normal statistics per material
+    pub value_stats: Vec<MaterialValueStats>,
+    /// Distribution of each metric per material class
+    pub class_distributions: Vec<ClassValueDistribution>,
+    /// Materials inconsistent with their class, largest |z| first
+    pub value_outliers: Vec<ValueOutlier>,
     pub recommendations: Vec<String>,
 }
 
@@ -149,59 +206,175 @@ pub struct TileabilityFixResult {
     pub path: String,
     pub original_edge_difference: f32,
     pub fixed_edge_difference: f32,
+    /// Seam score (see [`SeamAnalysis`]) before and after the fix
+    pub original_seam_score: f32,
+    pub fixed_seam_score: f32,
+    /// Whether the seam score went down
     pub improved: bool,
 }
 
-/// Per-texture tileability analysis (which textures would benefit from edge blending).
+/// Per-texture tileability analysis (which textures would benefit from a seam fix).
 #[derive(Debug, Clone, Serialize)]
 pub struct TileabilityAnalysisEntry {
     pub path: String,
     pub slot: String,
     pub material_name: Option<String>,
+    /// Mean outermost row/column difference (legacy metric)
     pub edge_difference: f32,
     pub needs_fix: bool,
+    /// Seam scores, spectral cross scores and per-position seam profiles
+    #[serde(flatten)]
+    pub seam: SeamAnalysis,
+}
+
+/// Default seam score (0-1, see [`SeamAnalysis`]) above which a texture is considered
+/// non-tileable, by advanced analysis and by the `tileability` validation rule.
+pub const TILEABILITY_THRESHOLD: f32 = 0.4;
+
+/// Repetition score (0-1) above which tiling repetition is considered visible.
+pub const REPETITION_THRESHOLD: f32 = 0.35;
+
+/// Longest edge of the luminance grid used for repetition analysis
+const REPETITION_MAX_DIM: usize = 256;
+
+/// Local deviation (see [`RepetitionHotspot::strength`]) above which a spot is a hotspot
+const HOTSPOT_STRENGTH: f64 = 1.0;
+
+/// Maximum number of hotspots reported per texture
+const MAX_HOTSPOTS: usize = 8;
+
+/// A distinctive spot that makes tiling repetition easy to notice.
+#[derive(Debug, Clone, Serialize)]
+pub struct RepetitionHotspot {
+    /// Center in texture pixels
+    pub x: u32,
+    pub y: u32,
+    /// Approximate radius in texture pixels
+    pub radius: u32,
+    /// Large-scale luminance deviation relative to the texture's fine-detail contrast
+    pub strength: f32,
+}
+
+/// How visible repetition is when a texture is tiled. Scores are 0-1.
+#[derive(Debug, Clone, Serialize)]
+pub struct RepetitionAnalysis {
+    /// 0 (repetition invisible) to 1 (obvious repeating pattern)
+    pub repetition_score: f32,
+    /// Strongest autocorrelation of the large-scale structure away from the tile
+    /// period. High values mean the texture already repeats within the tile
+    /// (bricks, planks), which hides the tile grid.
+    pub internal_correlation: f32,
+    /// Large-scale luminance variation relative to fine-detail contrast
+    pub low_frequency_contrast: f32,
+    /// Strength of the most distinctive local feature
+    pub outlier_score: f32,
+    /// Distinctive spots, strongest first
+    pub hotspots: Vec<RepetitionHotspot>,
 }
 
-/// Default edge-difference threshold above which a texture is considered non-tileable.
-pub const TILEABILITY_THRESHOLD: f32 = 10.0;
+/// Per-texture repetition analysis.
+#[derive(Debug, Clone, Serialize)]
+pub struct RepetitionAnalysisEntry {
+    pub path: String,
+    pub slot: String,
+    pub material_name: Option<String>,
+    /// Repetition score above threshold
+    pub visible: bool,
+    #[serde(flatten)]
+    pub analysis: RepetitionAnalysis,
+}
+
+/// Ranking key for a candidate duplicate: (similarity, variant index, index of texture a).
+type MatchRank = (f32, usize, usize);
 
 /// Detect duplicate or highly similar textures within a set of materials.
-/// Compares textures of the same slot (albedo to albedo, etc.) across materials.
+/// Compares textures of the same slot (albedo to albedo, etc.) across materials
+/// using pHash/dHash/color signatures and a BK-tree lookup, so rotated, mirrored,
+/// rescaled and center-cropped variants are found without comparing every pair.
 pub fn detect_duplicates(
     materials: &[(PathBuf, MaterialSet)],
     duplicate_threshold: f32,
     similar_threshold: f32,
 ) -> DuplicateAnalysisResult {
-    let refs = collect_texture_refs(materials);
-    let mut duplicate_pairs = Vec::new();
-    let mut similar_pairs = Vec::new();
+    let textures = material_textures(materials);
+    textures.par_iter().for_each(|(_, _, t)| {
+        t.hashes();
+    });
 
-    for i in 0..refs.len() {
-        for j in (i + 1)..refs.len() {
-            if refs[i].slot != refs[j].slot {
-                continue;
-            }
-            let sim = hash_similarity(&refs[i].hash, &refs[j].hash);
-            let path_a = refs[i].path.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "unknown".into());
-            let path_b = refs[j].path.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "unknown".into());
-
-            let pair = DuplicatePair {
-                path_a: path_a.clone(),
-                path_b: path_b.clone(),
-                slot: refs[i].slot.clone(),
-                material_a: refs[i].material_name.clone(),
-                material_b: refs[j].material_name.clone(),
-                similarity: sim,
-            };
+    // One BK-tree per slot; textures are only compared within a slot
+    let mut indexes: HashMap<&str, HashIndex> = HashMap::new();
+    for (i, (_, slot, t)) in textures.iter().enumerate() {
+        indexes.entry(slot).or_default().insert(i, t.hashes());
+    }
 
-            if sim >= duplicate_threshold {
-                duplicate_pairs.push(pair);
-            } else if sim >= similar_threshold {
-                similar_pairs.push(pair);
-            }
+    let min_similarity = duplicate_threshold.min(similar_threshold);
+    let radius = max_phash_distance(min_similarity);
+    // Best match per unordered pair, ranked by (similarity, variant, a): on ties
+    // lower variants (identity before rotations before crops) win, then the
+    // orientation with the earlier texture as a
+    let matches: Vec<((usize, usize), MatchRank, DuplicatePair)> = (0..textures.len())
+        .into_par_iter()
+        .flat_map_iter(|q| {
+            let (_, slot, query) = textures[q];
+            let query_hashes = query.hashes();
+            let textures = &textures;
+            indexes[slot]
+                .query(query_hashes.identity().phash, radius)
+                .into_iter()
+                .filter(move |m| m.item != q)
+                .filter_map(move |m| {
+                    let (ref material_a, _, a) = textures[m.item];
+                    let a_hashes = a.hashes();
+                    let similarity = a_hashes.similarity(m.variant, query_hashes);
+                    if similarity < min_similarity {
+                        return None;
+                    }
+                    let variant = &a_hashes.variants[m.variant];
+                    let pair = DuplicatePair {
+                        path_a: display_path(a),
+                        path_b: display_path(query),
+                        slot: slot.to_string(),
+                        material_a: material_a.clone(),
+                        material_b: textures[q].0.clone(),
+                        similarity,
+                        transform: variant.transform,
+                        crop: variant.crop,
+                        scale: ((query.width as f32 * query.height as f32) / (a.width as f32 * a.height as f32).max(1.0)).sqrt(),
+                        phash_distance: m.phash_distance,
+                        color_similarity: a_hashes.color_similarity(query_hashes),
+                        exact: None,
+                    };
+                    Some(((m.item.min(q), m.item.max(q)), (similarity, m.variant, m.item), pair))
+                })
+        })
+        .collect();
+
+    let mut best: BTreeMap<(usize, usize), (MatchRank, DuplicatePair)> = BTreeMap::new();
+    for (key, rank, pair) in matches {
+        let better = best
+            .get(&key)
+            .is_none_or(|(r, _)| rank.0 > r.0 || (rank.0 == r.0 && (rank.1, rank.2) < (r.1, r.2)));
+        if better {
+            best.insert(key, (rank, pair));
         }
     }
 
+    // Pixel-identical pairs hash identically, so every exact duplicate is among
+    // the matches; tell apart same-bytes from same-pixels-different-file
+    let (duplicate_pairs, similar_pairs): (Vec<_>, Vec<_>) = best
+        .into_iter()
+        .map(|((a, b), (_, mut pair))| {
+            let (ta, tb) = (textures[a].2, textures[b].2);
+            if pair.similarity >= duplicate_threshold && precision_digest(ta) == precision_digest(tb) {
+                let same_file = file_digest(ta).is_some_and(|d| file_digest(tb) == Some(d));
+                pair.exact = Some(if same_file { ExactMatch::Bytes } else { ExactMatch::Pixels });
+                pair.similarity = 1.0;
+            }
+            pair
+        })
+        .filter(|p| p.similarity >= similar_threshold || p.similarity >= duplicate_threshold)
+        .partition(|p| p.similarity >= duplicate_threshold);
+
     DuplicateAnalysisResult {
         duplicate_pairs,
         similar_pairs,
@@ -210,6 +383,10 @@ pub fn detect_duplicates(
     }
 }
 
+fn display_path(t: &TextureMap) -> String {
+    t.path.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "unknown".into())
+}
+
 /// Analyze consistency across multiple materials.
 pub fn analyze_cross_material(materials: &[(PathBuf, MaterialSet)]) -> CrossMaterialResult {
     let mut resolution_groups: HashMap<(u32, u32), Vec<String>> = HashMap::new();
@@ -285,15 +462,149 @@ pub fn analyze_cross_material(materials: &[(PathBuf, MaterialSet)]) -> CrossMate
         }
     }
 
+    let value_stats: Vec<MaterialValueStats> = materials
+        .par_iter()
+        .map(|(folder, set)| material_value_stats(folder, set))
+        .collect();
+    let (class_distributions, value_outliers) = value_consistency(&value_stats);
+    for o in &value_outliers {
+        recommendations.push(format!(
+            "'{}' {} {:.2} is {:+.1}σ from other {} materials (mean {:.2}). Check it stays visually coherent.",
+            o.material,
+            o.metric.label(),
+            o.value,
+            o.z_score,
+            o.class.as_str(),
+            o.class_mean
+        ));
+    }
+
     CrossMaterialResult {
         material_count: total,
         resolution_distributions,
         resolution_inconsistent,
         map_coverage,
+        value_stats,
+        class_distributions,
+        value_outliers,
         recommendations,
     }
 }
 
+/// |z| at which a material is flagged against the rest of its class
+pub const VALUE_OUTLIER_Z: f32 = 2.5;
+
+/// Smallest spread assumed for a class, so near-identical materials don't turn
+/// tiny differences into huge z-scores
+const MIN_VALUE_SPREAD: f32 = 0.03;
+
+/// Materials with a metric a class needs before its outliers are flagged
+const MIN_CLASS_SIZE: usize = 3;
+
+fn material_value_stats(folder: &std::path::Path, set: &MaterialSet) -> MaterialValueStats {
+    let material = set.name.clone().or_else(|| folder.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| folder.display().to_string());
+    let albedo = set.albedo.as_ref().map(|t| t.stats());
+    let roughness = set.roughness.as_ref().map(|t| &t.stats().channels[0]);
+    let normal_intensity = set.normal.as_ref().map(|t| {
+        let [x, y, ..] = &t.stats().channels;
+        (x.population_stddev.powi(2) + y.population_stddev.powi(2)).sqrt() as f32 / 127.5
+    });
+    MaterialValueStats {
+        material,
+        class: classify_material(set, None).0,
+        albedo_luminance: albedo.map(|s| s.luminance_mean as f32 / 255.0),
+        albedo_saturation: albedo.map(|s| s.saturation_mean as f32),
+        roughness_mean: roughness.map(|c| c.mean as f32 / 255.0),
+        roughness_low: roughness.map(|c| histogram_percentile(&c.histogram, 0.05)),
+        roughness_high: roughness.map(|c| histogram_percentile(&c.histogram, 0.95)),
+        normal_intensity,
+    }
+}
+
+/// Value (0-1) below which fraction `p` of a 256-bin histogram lies.
+fn histogram_percentile(histogram: &[u32], p: f64) -> f32 {
+    let total: u64 = histogram.iter().map(|&c| c as u64).sum();
+    let target = (total as f64 * p).ceil().max(1.0) as u64;
+    let mut seen = 0u64;
+    for (v, &c) in histogram.iter().enumerate() {
+        seen += c as u64;
+        if seen >= target {
+            return v as f32 / 255.0;
+        }
+    }
+    1.0
+}
+
+/// Mean and population standard deviation.
+fn mean_stddev(values: &[f32]) -> (f32, f32) {
+    let n = values.len().max(1) as f32;
+    let mean = values.iter().sum::<f32>() / n;
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n;
+    (mean, var.sqrt())
+}
+
+/// Per-class distributions and leave-one-out z-score outliers: each material is
+/// compared with the mean and spread of the *other* materials of its class, so
+/// a single outlier cannot hide by inflating the spread it is measured against.
+fn value_consistency(stats: &[MaterialValueStats]) -> (Vec<ClassValueDistribution>, Vec<ValueOutlier>) {
+    let mut classes: Vec<(MaterialClass, Vec<&MaterialValueStats>)> = Vec::new();
+    for s in stats {
+        match classes.iter_mut().find(|(c, _)| *c == s.class) {
+            Some((_, members)) => members.push(s),
+            None => classes.push((s.class, vec![s])),
+        }
+    }
+
+    let mut distributions = Vec::new();
+    let mut outliers = Vec::new();
+    for (class, members) in classes {
+        let mut metrics = Vec::new();
+        for metric in ValueMetric::ALL {
+            let values: Vec<(&MaterialValueStats, f32)> =
+                members.iter().filter_map(|s| s.value(metric).map(|v| (*s, v))).collect();
+            if values.is_empty() {
+                continue;
+            }
+            let all: Vec<f32> = values.iter().map(|(_, v)| *v).collect();
+            let (mean, stddev) = mean_stddev(&all);
+            metrics.push(MetricDistribution {
+                metric,
+                count: all.len(),
+                mean,
+                stddev,
+                min: all.iter().copied().fold(f32::INFINITY, f32::min),
+                max: all.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+            });
+            if values.len() < MIN_CLASS_SIZE {
+                continue;
+            }
+            for (i, (s, value)) in values.iter().enumerate() {
+                let others: Vec<f32> = all.iter().enumerate().filter(|(j, _)| *j != i).map(|(_, v)| *v).collect();
+                let (class_mean, spread) = mean_stddev(&others);
+                let z_score = (value - class_mean) / spread.max(MIN_VALUE_SPREAD);
+                if z_score.abs() >= VALUE_OUTLIER_Z {
+                    outliers.push(ValueOutlier {
+                        material: s.material.clone(),
+                        class,
+                        metric,
+                        value: *value,
+                        class_mean,
+                        z_score,
+                    });
+                }
+            }
+        }
+        distributions.push(ClassValueDistribution {
+            class,
+            materials: members.iter().map(|s| s.material.clone()).collect(),
+            metrics,
+        });
+    }
+    outliers.sort_by(|a, b| b.z_score.abs().total_cmp(&a.z_score.abs()));
+    (distributions, outliers)
+}
+
 /// Compute mean edge difference (top↔bottom, left↔right). Higher = less tileable.
 pub fn edge_difference(map: &TextureMap) -> f64 {
     let w = map.width as usize;
@@ -331,39 +642,202 @@ pub fn edge_difference(map: &TextureMap) -> f64 {
     }
 }
 
-/// Analyze which textures have high edge difference (would benefit from tileability fix).
+/// Analyze which textures have visible wrap-around seams (would benefit from a tileability fix).
+/// `threshold` applies to the seam score (0 = seamless, 1 = hard seam).
 pub fn analyze_tileability(
     materials: &[(PathBuf, MaterialSet)],
     threshold: f32,
 ) -> Vec<TileabilityAnalysisEntry> {
-    let mut entries = Vec::new();
-    for (folder, set) in materials {
-        let name = set.name.clone().or_else(|| folder.file_name().map(|n| n.to_string_lossy().into_owned()));
-        for (opt, slot) in [
-            (set.albedo.as_ref(), "albedo"),
-            (set.normal.as_ref(), "normal"),
-            (set.roughness.as_ref(), "roughness"),
-            (set.metallic.as_ref(), "metallic"),
-            (set.ao.as_ref(), "ao"),
-            (set.height.as_ref(), "height"),
-        ] {
-            if let Some(t) = opt {
-                let ed = edge_difference(t);
-                let path = t.path.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "unknown".into());
-                entries.push(TileabilityAnalysisEntry {
-                    path,
-                    slot: slot.to_string(),
-                    material_name: name.clone(),
-                    edge_difference: ed as f32,
-                    needs_fix: ed > threshold as f64,
-                });
+    material_textures(materials)
+        .into_par_iter()
+        .map(|(material_name, slot, t)| {
+            let seam = t.tileability().clone();
+            let path = t.path.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "unknown".into());
+            TileabilityAnalysisEntry {
+                path,
+                slot: slot.to_string(),
+                material_name,
+                edge_difference: t.stats().edge_difference as f32,
+                needs_fix: seam.score > threshold,
+                seam,
+            }
+        })
+        .collect()
+}
+
+/// Analyze how visible tiling repetition is for each material's albedo.
+/// `threshold` applies to the repetition score.
+pub fn analyze_repetition(materials: &[(PathBuf, MaterialSet)], threshold: f32) -> Vec<RepetitionAnalysisEntry> {
+    material_textures(materials)
+        .into_par_iter()
+        .filter(|(_, slot, _)| *slot == "albedo")
+        .map(|(material_name, slot, t)| {
+            let analysis = repetition_analysis(t);
+            RepetitionAnalysisEntry {
+                path: t.path.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "unknown".into()),
+                slot: slot.to_string(),
+                material_name,
+                visible: analysis.repetition_score > threshold,
+                analysis,
+            }
+        })
+        .collect()
+}
+
+/// Measure how visible repetition is in a virtual tiled grid of the texture.
+///
+/// Works on a low-passed luminance grid filtered with wrap-around, so features
+/// crossing the tile edge look as they do in the tiled grid. The circular
+/// autocorrelation of that grid is the correlation over the tiled grid: a
+/// texture whose large-scale structure also repeats inside the tile hides the
+/// tile period, while a unique layout of blotches makes it stand out. Local
+/// outliers (a bright spot, a distinctive stain) are reported as hotspots.
+pub fn repetition_analysis(map: &TextureMap) -> RepetitionAnalysis {
+    let (lum, w, h, step) = luminance_grid(map, REPETITION_MAX_DIM);
+    if w < 8 || h < 8 {
+        return RepetitionAnalysis {
+            repetition_score: 0.0,
+            internal_correlation: 0.0,
+            low_frequency_contrast: 0.0,
+            outlier_score: 0.0,
+            hotspots: Vec::new(),
+        };
+    }
+    let radius = (w.max(h) / 32).max(1);
+    let low = box_blur_wrapped(&box_blur_wrapped(&lum, w, h, radius), w, h, radius);
+
+    // Fine-detail contrast masks large-scale variation: robust spread of the unfiltered grid
+    let lum_median = median(&lum);
+    let spread = 1.4826 * median(&lum.iter().map(|v| (v - lum_median).abs()).collect::<Vec<_>>());
+    let masking = 0.5 * spread + 2.0;
+
+    let low_median = median(&low);
+    let low_mean = low.iter().sum::<f64>() / low.len() as f64;
+    let low_std = (low.iter().map(|v| (v - low_mean).powi(2)).sum::<f64>() / low.len() as f64).sqrt();
+    let low_frequency_contrast = (low_std / masking).min(1.0) as f32;
+
+    let internal_correlation = internal_correlation(&low, w, h, low_mean);
+
+    let strength: Vec<f64> = low.iter().map(|v| (v - low_median).abs() / masking).collect();
+    let hotspots = find_hotspots(&strength, w, h, step, radius);
+    let max_strength = strength.iter().copied().fold(0.0, f64::max);
+    let outlier_score = ((max_strength - 0.5) / 2.0).clamp(0.0, 1.0) as f32;
+
+    let distinct = 1.0 - internal_correlation;
+    RepetitionAnalysis {
+        repetition_score: (distinct * 0.5 * (outlier_score + low_frequency_contrast)).clamp(0.0, 1.0),
+        internal_correlation,
+        low_frequency_contrast,
+        outlier_score,
+        hotspots,
+    }
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted[sorted.len() / 2]
+}
+
+/// Box blur with wrap-around (separable, radius in grid pixels).
+fn box_blur_wrapped(img: &[f64], w: usize, h: usize, radius: usize) -> Vec<f64> {
+    let n = (2 * radius + 1) as f64;
+    let mut horizontal = vec![0.0; w * h];
+    for y in 0..h {
+        for x in 0..w {
+            let sum: f64 = (0..=2 * radius).map(|k| img[y * w + (x + w * radius + k - radius) % w]).sum();
+            horizontal[y * w + x] = sum / n;
+        }
+    }
+    let mut out = vec![0.0; w * h];
+    for y in 0..h {
+        for x in 0..w {
+            let sum: f64 = (0..=2 * radius).map(|k| horizontal[((y + h * radius + k - radius) % h) * w + x]).sum();
+            out[y * w + x] = sum / n;
+        }
+    }
+    out
+}
+
+/// Strongest normalized circular autocorrelation at lags outside the central
+/// eighth of the tile in both directions (0-1).
+fn internal_correlation(low: &[f64], w: usize, h: usize, mean: f64) -> f32 {
+    let mut buf: Vec<Complex<f64>> = low.iter().map(|&v| Complex::new(v - mean, 0.0)).collect();
+    fft2(&mut buf, w, h, FftDirection::Forward);
+    for c in buf.iter_mut() {
+        *c = Complex::new(c.norm_sqr(), 0.0);
+    }
+    fft2(&mut buf, w, h, FftDirection::Inverse);
+    let zero_lag = buf[0].re;
+    if zero_lag <= 1e-9 {
+        return 0.0;
+    }
+    let (min_dx, min_dy) = ((w / 8).max(1), (h / 8).max(1));
+    let mut best = 0.0f64;
+    for dy in 0..h {
+        for dx in 0..w {
+            if dx.min(w - dx) < min_dx && dy.min(h - dy) < min_dy {
+                continue;
             }
+            best = best.max(buf[dy * w + dx].re / zero_lag);
+        }
+    }
+    best.clamp(0.0, 1.0) as f32
+}
+
+/// Local maxima of `strength` above [`HOTSPOT_STRENGTH`], at least a few blur
+/// radii apart (measured with wrap-around), strongest first.
+fn find_hotspots(strength: &[f64], w: usize, h: usize, step: usize, radius: usize) -> Vec<RepetitionHotspot> {
+    let mut candidates: Vec<usize> = (0..strength.len()).filter(|&i| strength[i] > HOTSPOT_STRENGTH).collect();
+    candidates.sort_by(|&a, &b| strength[b].total_cmp(&strength[a]));
+    let wrapped = |a: usize, b: usize, n: usize| {
+        let d = a.abs_diff(b);
+        d.min(n - d)
+    };
+    let separation = 4 * radius;
+    let mut picked: Vec<usize> = Vec::new();
+    for i in candidates {
+        if picked.len() >= MAX_HOTSPOTS {
+            break;
+        }
+        let (x, y) = (i % w, i / w);
+        let far = picked
+            .iter()
+            .all(|&p| wrapped(x, p % w, w) > separation || wrapped(y, p / w, h) > separation);
+        if far {
+            picked.push(i);
         }
     }
-    entries
+    picked
+        .into_iter()
+        .map(|i| {
+            let (x, y) = (i % w, i / w);
+            // Walk out until the deviation falls below half of the peak
+            let half = strength[i] / 2.0;
+            let extent = |dx: isize, dy: isize| {
+                let limit = w.max(h) / 4;
+                (1..limit)
+                    .find(|&k| {
+                        let px = (x as isize + dx * k as isize).rem_euclid(w as isize) as usize;
+                        let py = (y as isize + dy * k as isize).rem_euclid(h as isize) as usize;
+                        strength[py * w + px] < half
+                    })
+                    .unwrap_or(limit)
+            };
+            let r = (extent(1, 0) + extent(-1, 0) + extent(0, 1) + extent(0, -1)) as f32 / 4.0;
+            RepetitionHotspot {
+                x: (x * step + step / 2) as u32,
+                y: (y * step + step / 2) as u32,
+                radius: (r * step as f32).round() as u32,
+                strength: strength[i] as f32,
+            }
+        })
+        .collect()
 }
 
 /// Apply automatic tileability fix by blending opposite edges.
+/// Fast but only hides the outermost pixels; see [`crate::seamless`] for the
+/// seam-patching fixer.
 /// Blends top↔bottom and left↔right so opposite edges match for seamless tiling.
 /// `blend_width` controls how many pixel rows/columns from each edge are blended.
 pub fn fix_tileability(texture: &TextureMap, blend_width: u32) -> Result<TextureMap> {
@@ -406,12 +880,7 @@ pub fn fix_tileability(texture: &TextureMap, blend_width: u32) -> Result<Texture
         }
     }
 
-    Ok(TextureMap {
-        width: texture.width,
-        height: texture.height,
-        data,
-        path: texture.path.clone(),
-    })
+    Ok(TextureMap::new(texture.width, texture.height, data, texture.path.clone()))
 }
 
 /// Run tileability fix and return before/after metrics.
@@ -419,28 +888,26 @@ pub fn fix_tileability_with_report(
     texture: &TextureMap,
     blend_width: u32,
 ) -> Result<(TextureMap, TileabilityFixResult)> {
-    let path = texture.path.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "unknown".into());
-    let original_ed = edge_difference(texture);
     let fixed = fix_tileability(texture, blend_width)?;
-    let fixed_ed = edge_difference(&fixed);
-
-    let result = TileabilityFixResult {
-        path,
-        original_edge_difference: original_ed as f32,
-        fixed_edge_difference: fixed_ed as f32,
-        improved: fixed_ed < original_ed,
-    };
-
+    let result = crate::seamless::fix_report(texture, &fixed);
     Ok((fixed, result))
 }
 
 /// Combined advanced analysis output for JSON export.
 #[derive(Debug, Clone, Serialize)]
 pub struct AdvancedAnalysisReport {
+    /// Byte- and pixel-identical textures with disk/VRAM savings from deduplication.
+    pub exact_duplicates: ExactDuplicateReport,
     pub duplicates: DuplicateAnalysisResult,
     pub cross_material: CrossMaterialResult,
-    /// Textures that would benefit from tileability fix (edge difference above threshold).
+    /// Textures that would benefit from tileability fix (seam score above threshold).
     pub tileability_analysis: Vec<TileabilityAnalysisEntry>,
+    /// How visible tiling repetition is for each albedo, with hotspot locations.
+    pub repetition_analysis: Vec<RepetitionAnalysisEntry>,
+    /// Per-texture histograms, percentiles, dominant colors and gamut summary.
+    pub color_distributions: Vec<TextureColorDistribution>,
+    /// Stair-stepping in smooth gradients (height maps, skin albedo) per texture.
+    pub banding_analysis: Vec<TextureBanding>,
     /// Results from applying tileability fix (when run with fix_tileability_maps=true).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tileability_fixes: Option<Vec<TileabilityFixResult>>,
@@ -472,21 +939,12 @@ impl AdvancedAnalysisReport {
 mod tests {
     use super::*;
     use crate::material::MaterialSet;
+    use crate::test_util::{gray, noise, uniform};
     use std::path::PathBuf;
 
-    fn make_texture(w: u32, h: u32, value: u8) -> TextureMap {
-        let len = (w as usize) * (h as usize) * 4;
-        TextureMap {
-            width: w,
-            height: h,
-            data: vec![value; len],
-            path: None,
-        }
-    }
-
     #[test]
     fn detect_duplicate_identical() {
-        let tex = make_texture(8, 8, 128);
+        let tex = uniform(8, 8, 128);
         let mut set1 = MaterialSet::new();
         set1.albedo = Some(tex.clone());
         let mut set2 = MaterialSet::new();
@@ -499,6 +957,30 @@ mod tests {
         let result = detect_duplicates(&materials, 0.99, 0.8);
         assert_eq!(result.duplicate_pairs.len(), 1);
         assert!(result.duplicate_pairs[0].similarity >= 0.99);
+        // In-memory textures have no file to compare, so they only match by pixels
+        assert_eq!(result.duplicate_pairs[0].exact, Some(ExactMatch::Pixels));
+    }
+
+    #[test]
+    fn detect_duplicates_reports_mirrored_variant() {
+        let tex = gray(64, 64, |x, y| ((x * 3 + y * y / 8) % 256) as u8);
+        let mirrored = gray(64, 64, |x, y| ((((63 - x) * 3) + y * y / 8) % 256) as u8);
+        let other = gray(64, 64, noise);
+        let materials: Vec<(PathBuf, MaterialSet)> = [tex, mirrored, other]
+            .into_iter()
+            .enumerate()
+            .map(|(i, t)| {
+                let mut set = MaterialSet::new();
+                set.albedo = Some(t);
+                (PathBuf::from(format!("mat{}", i)), set)
+            })
+            .collect();
+        let result = detect_duplicates(&materials, 0.99, 0.8);
+        assert_eq!(result.duplicate_pairs.len(), 1);
+        assert!(result.similar_pairs.is_empty());
+        let pair = &result.duplicate_pairs[0];
+        assert_eq!(pair.transform, HashTransform::FlipHorizontal);
+        assert_eq!((pair.material_a.as_deref(), pair.material_b.as_deref()), (Some("mat0"), Some("mat1")));
     }
 
     #[test]
@@ -513,23 +995,74 @@ mod tests {
                 data[i + 3] = 255;
             }
         }
-        let tex = TextureMap { width: 16, height: 16, data: data.clone(), path: None };
+        let tex = TextureMap::new(16, 16, data.clone(), None);
         let ed_before = edge_difference(&tex);
         let fixed = fix_tileability(&tex, 4).unwrap();
         let ed_after = edge_difference(&fixed);
         assert!(ed_after < ed_before || ed_before < 1.0);
     }
 
+    #[test]
+    fn cross_material_flags_bright_outlier_in_class() {
+        // Five noisy gray "bricks"; one is twice as bright as the others
+        let materials: Vec<(PathBuf, MaterialSet)> = [80u8, 86, 76, 82, 170]
+            .into_iter()
+            .enumerate()
+            .map(|(i, base)| {
+                let mut set = MaterialSet::new();
+                set.albedo = Some(gray(32, 32, |x, y| base + noise(x + i as u32 * 7, y) / 4));
+                (PathBuf::from(format!("brick_{}", i)), set)
+            })
+            .collect();
+        let result = analyze_cross_material(&materials);
+        assert_eq!(result.class_distributions.len(), 1);
+        let lum: Vec<_> = result
+            .value_outliers
+            .iter()
+            .filter(|o| o.metric == ValueMetric::AlbedoLuminance)
+            .collect();
+        assert_eq!(lum.len(), 1);
+        assert_eq!(lum[0].material, "brick_4");
+        assert!(lum[0].z_score > VALUE_OUTLIER_Z);
+        assert!(result.recommendations.iter().any(|r| r.contains("brick_4")));
+    }
+
+    #[test]
+    fn repetition_flags_distinctive_blotch() {
+        let tex = gray(128, 128, |x, y| {
+            let d2 = (x as f32 - 40.0).powi(2) + (y as f32 - 90.0).powi(2);
+            let blob = 110.0 * (-d2 / 60.0).exp();
+            (60.0 + noise(x, y) as f32 * 0.3 + blob) as u8
+        });
+        let a = repetition_analysis(&tex);
+        assert!(a.repetition_score > REPETITION_THRESHOLD, "{:?}", a);
+        let top = &a.hotspots[0];
+        assert!(top.x.abs_diff(40) <= 4 && top.y.abs_diff(90) <= 4, "{:?}", top);
+    }
+
+    #[test]
+    fn repetition_ignores_noise_and_regular_patterns() {
+        let noisy = repetition_analysis(&gray(128, 128, noise));
+        assert!(noisy.repetition_score < 0.15, "{:?}", noisy.repetition_score);
+        assert!(noisy.hotspots.is_empty());
+
+        let bricks = repetition_analysis(&gray(128, 128, |x, y| if (x / 16 + y / 16) % 2 == 0 { 60 } else { 180 }));
+        assert!(bricks.internal_correlation > 0.9);
+        assert!(bricks.repetition_score < 0.1, "{:?}", bricks.repetition_score);
+    }
+
     #[test]
     fn run_advanced_analysis_produces_json() {
         let mut set = MaterialSet::new();
-        set.albedo = Some(make_texture(64, 64, 128));
-        set.normal = Some(make_texture(64, 64, 128));
+        set.albedo = Some(uniform(64, 64, 128));
+        set.normal = Some(uniform(64, 64, 128));
         let materials = vec![(PathBuf::from("test"), set)];
         let report = run_advanced_analysis(&materials, 0.99, 0.8, false).unwrap();
         let json = report.to_json().unwrap();
         assert!(json.contains("duplicates"));
         assert!(json.contains("cross_material"));
+        assert!(json.contains("repetition_analysis"));
+        assert!(json.contains("banding_analysis"));
     }
 }
 
@@ -557,9 +1090,13 @@ pub fn run_advanced_analysis_with_tileability_threshold(
     tileability_threshold: f32,
     fix_tileability_maps: bool,
 ) -> Result<AdvancedAnalysisReport> {
+    let exact_duplicates = find_exact_duplicates(materials);
     let duplicates = detect_duplicates(materials, duplicate_threshold, similar_threshold);
     let cross_material = analyze_cross_material(materials);
     let tileability_analysis = analyze_tileability(materials, tileability_threshold);
+    let repetition_analysis = analyze_repetition(materials, REPETITION_THRESHOLD);
+    let color_distributions = analyze_color_distributions(materials);
+    let banding_analysis = analyze_banding(materials);
 
     let mut tileability_fixes: Vec<TileabilityFixResult> = Vec::new();
     if fix_tileability_maps {
@@ -575,9 +1112,13 @@ pub fn run_advanced_analysis_with_tileability_threshold(
     }
 
     Ok(AdvancedAnalysisReport {
+        exact_duplicates,
         duplicates,
         cross_material,
         tileability_analysis,
+        repetition_analysis,
+        color_distributions,
+        banding_analysis,
         tileability_fixes: if tileability_fixes.is_empty() {
             None
         } else {
@@ -0,0 +1,418 @@
+//! Persistent on-disk analysis cache.
+//!
+//! Stores rule results and decoded texture statistics in `.pbr-studio/cache/`
+//! under a project root. Results are keyed by the content hash of every image
+//! and mesh in the material folder (including shared copies of maps removed by
+//! dedup), the rule-set fingerprint and the scoring model, so unchanged
+//! materials are served without decoding a single image.
+//!
+//! Layout:
+//! - `results/<key>.json` – issues and report for one material folder
+//! - `stats/<file hash>.json` – [`TextureStats`] for one image file, reused when
+//!   a material must be re-validated (e.g. after a rule change) but some of its
+//!   images did not change
+
+use crate::dedup::{DedupManifest, DEDUP_MANIFEST_FILE};
+use crate::json_report::MaterialReport;
+use crate::material::{MaterialSet, TextureMap, IMAGE_EXTENSIONS};
+use crate::scoring::ScoringModel;
+use crate::texel_density::{MeshUvStats, MESH_EXTENSIONS};
+use crate::texture_stats::TextureStats;
+use crate::validation::{Issue, Validator};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Cache directory relative to the project root
+pub const CACHE_DIR: &str = ".pbr-studio/cache";
+
+/// Bumped whenever the cached entry layout, rule semantics or scoring change
+/// (v2: spectral seam analysis for tileability, weighted report scores)
+const CACHE_FORMAT: &str = "pbr-cache-v2";
+
+/// Unique suffix for temp files so concurrent writers never share one
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Cached (or freshly computed) analysis of one material folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedAnalysis {
+    /// Cache key (content hash + rule set + config)
+    pub key: String,
+    pub issues: Vec<Issue>,
+    pub report: MaterialReport,
+    /// True when served from disk without loading the images
+    #[serde(skip)]
+    pub from_cache: bool,
+}
+
+/// On-disk cache rooted at `<root>/.pbr-studio/cache`.
+#[derive(Debug, Clone)]
+pub struct AnalysisCache {
+    /// `None` when caching is disabled
+    dir: Option<PathBuf>,
+    /// Hash of the validator's rule fingerprints
+    ruleset: String,
+}
+
+impl AnalysisCache {
+    /// Open the cache for `root`. Caching is disabled when a rule cannot be
+    /// fingerprinted (e.g. script plugins whose output may change at any time).
+    pub fn open(root: &Path, validator: &Validator) -> Self {
+        match validator.fingerprint() {
+            Some(fp) => Self {
+                dir: Some(root.join(CACHE_DIR)),
+                ruleset: sha256_hex(fp.as_bytes()),
+            },
+            None => Self::disabled(),
+        }
+    }
+
+    /// A cache that never stores or serves anything.
+    pub fn disabled() -> Self {
+        Self {
+            dir: None,
+            ruleset: String::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    /// Cache directory, when enabled.
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    /// Serve `folder` from the cache, or load it, run `analyze` and store the result.
+    /// Cache read/write failures fall back to a fresh analysis and never fail the call.
+    pub fn evaluate<F>(&self, folder: &Path, scoring: &ScoringModel, analyze: F) -> Result<CachedAnalysis>
+    where
+        F: FnOnce(&MaterialSet) -> (Vec<Issue>, MaterialReport),
+    {
+        let Some(dir) = &self.dir else {
+            let set = crate::tiled::load_folder(folder)?;
+            let (issues, report) = analyze(&set);
+            return Ok(CachedAnalysis {
+                key: String::new(),
+                issues,
+                report,
+                from_cache: false,
+            });
+        };
+
+        let files = hash_material_files(folder)?;
+        let key = self.material_key(folder, scoring, &files);
+        let result_path = dir.join("results").join(format!("{}.json", key));
+        if let Some(mut hit) = read_json::<CachedAnalysis>(&result_path) {
+            hit.from_cache = true;
+            return Ok(hit);
+        }
+
+        let mut set = crate::tiled::load_folder(folder)?;
+        for tex in textures_mut(&mut set) {
+            if let Some(hash) = file_hash(&files, tex) {
+                if let Some(stats) = read_json::<TextureStats>(&stats_path(dir, hash)) {
+                    tex.set_stats(stats);
+                }
+            }
+        }
+
+        let (issues, report) = analyze(&set);
+
+        for tex in textures(&set) {
+            if let (Some(hash), Some(stats)) = (file_hash(&files, tex), tex.cached_stats()) {
+                let path = stats_path(dir, hash);
+                if !path.exists() {
+                    let _ = write_json(&path, stats);
+                }
+            }
+        }
+        let entry = CachedAnalysis {
+            key,
+            issues,
+            report,
+            from_cache: false,
+        };
+        let _ = write_json(&result_path, &entry);
+        Ok(entry)
+    }
+
+    /// Remove all cached entries under `root`.
+    pub fn clear(root: &Path) -> Result<()> {
+        let dir = root.join(CACHE_DIR);
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+        Ok(())
+    }
+
+    fn material_key(&self, folder: &Path, scoring: &ScoringModel, files: &[(PathBuf, String)]) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            CACHE_FORMAT,
+            env!("CARGO_PKG_VERSION"),
+            &self.ruleset,
+            &canonical_json(scoring),
+            &folder.to_string_lossy(),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0u8]);
+        }
+        for (path, hash) in files {
+            let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+            hasher.update(name.as_bytes());
+            hasher.update([0u8]);
+            hasher.update(hash.as_bytes());
+            hasher.update([0u8]);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// SHA-256 of every image and mesh file directly inside `folder`, plus the
+/// external buffers its glTF meshes reference and, after dedup, the dedup
+/// manifest and the shared copies it points to, sorted by path. A missing
+/// buffer hashes as "missing".
+pub fn hash_material_files(folder: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(folder)? {
+        let path = entry?.path();
+        let ext = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).unwrap_or_default();
+        let is_mesh = MESH_EXTENSIONS.contains(&ext.as_str());
+        if !path.is_file() || !(is_mesh || IMAGE_EXTENSIONS.contains(&ext.as_str())) {
+            continue;
+        }
+        let hash = sha256_hex(&fs::read(&path)?);
+        if is_mesh {
+            // A broken glTF fails the texel density check itself; hash what is there
+            for dependency in MeshUvStats::dependencies(&path).unwrap_or_default() {
+                let hash = fs::read(&dependency).map(|b| sha256_hex(&b)).unwrap_or_else(|_| "missing".into());
+                files.push((dependency, hash));
+            }
+        }
+        files.push((path, hash));
+    }
+    // Maps removed by dedup load from a shared copy elsewhere
+    let manifest_path = folder.join(DEDUP_MANIFEST_FILE);
+    if manifest_path.is_file() {
+        let manifest = DedupManifest::load(&manifest_path)?;
+        files.push((manifest_path.clone(), sha256_hex(&fs::read(&manifest_path)?)));
+        for (_, shared) in manifest.replacements_in(folder).unwrap_or_default() {
+            let hash = fs::read(&shared).map(|b| sha256_hex(&b)).unwrap_or_else(|_| "missing".into());
+            files.push((shared, hash));
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn stats_path(dir: &Path, file_hash: &str) -> PathBuf {
+    dir.join("stats").join(format!("{}.json", file_hash))
+}
+
+fn file_hash<'a>(files: &'a [(PathBuf, String)], tex: &TextureMap) -> Option<&'a str> {
+    let path = tex.path.as_ref()?;
+    files.iter().find(|(p, _)| p == path).map(|(_, h)| h.as_str())
+}
+
+fn textures(set: &MaterialSet) -> impl Iterator<Item = &TextureMap> {
+    [&set.albedo, &set.normal, &set.roughness, &set.metallic, &set.ao, &set.height]
+        .into_iter()
+        .flatten()
+}
+
+fn textures_mut(set: &mut MaterialSet) -> impl Iterator<Item = &mut TextureMap> {
+    [
+        &mut set.albedo,
+        &mut set.normal,
+        &mut set.roughness,
+        &mut set.metallic,
+        &mut set.ao,
+        &mut set.height,
+    ]
+    .into_iter()
+    .flatten()
+}
+
+/// JSON with object keys sorted, so hash-map backed configs hash the same every run.
+fn canonical_json<T: Serialize>(value: &T) -> String {
+    fn write(v: &serde_json::Value, out: &mut String) {
+        match v {
+            serde_json::Value::Object(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                out.push('{');
+                for (i, k) in keys.into_iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    out.push_str(&serde_json::Value::String(k.clone()).to_string());
+                    out.push(':');
+                    write(&map[k], out);
+                }
+                out.push('}');
+            }
+            serde_json::Value::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    write(item, out);
+                }
+                out.push(']');
+            }
+            other => out.push_str(&other.to_string()),
+        }
+    }
+    let mut out = String::new();
+    if let Ok(v) = serde_json::to_value(value) {
+        write(&v, &mut out);
+    }
+    out
+}
+
+pub(crate) fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Option<T> {
+    let data = fs::read(path).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+/// Write via a temp file and rename so concurrent readers never see partial entries.
+pub(crate) fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension(format!(
+        "{}.{}.tmp",
+        std::process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&tmp, serde_json::to_vec(value)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_material(dir: &Path, value: u8) {
+        fs::create_dir_all(dir).unwrap();
+        let img = image::RgbaImage::from_raw(4, 4, vec![value; 4 * 4 * 4]).unwrap();
+        img.save(dir.join("albedo.png")).unwrap();
+    }
+
+    fn analyze(set: &MaterialSet) -> (Vec<Issue>, MaterialReport) {
+        let issues = Validator::default().check(set);
+        let report = MaterialReport::from_material_set(set, issues.clone());
+        (issues, report)
+    }
+
+    #[test]
+    fn second_run_is_served_from_cache_until_content_changes() {
+        let root = std::env::temp_dir().join("pbr_analysis_cache_test");
+        let _ = fs::remove_dir_all(&root);
+        let folder = root.join("mat");
+        write_material(&folder, 128);
+
+        let cache = AnalysisCache::open(&root, &Validator::default());
+        let scoring = ScoringModel::default();
+        let first = cache.evaluate(&folder, &scoring, analyze).unwrap();
+        assert!(!first.from_cache);
+        let second = cache.evaluate(&folder, &scoring, analyze).unwrap();
+        assert!(second.from_cache);
+        assert_eq!(first.key, second.key);
+        assert_eq!(first.report.score, second.report.score);
+
+        write_material(&folder, 0);
+        let third = cache.evaluate(&folder, &scoring, analyze).unwrap();
+        assert!(!third.from_cache);
+        assert_ne!(third.key, first.key);
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn scoring_model_changes_the_key() {
+        let root = std::env::temp_dir().join("pbr_analysis_cache_key_test");
+        let _ = fs::remove_dir_all(&root);
+        let folder = root.join("mat");
+        write_material(&folder, 128);
+
+        let cache = AnalysisCache::open(&root, &Validator::default());
+        let files = hash_material_files(&folder).unwrap();
+        let default_key = cache.material_key(&folder, &ScoringModel::default(), &files);
+        let mut model = ScoringModel::default();
+        model.rule_weights.insert("tileability".into(), 0.5);
+        assert_ne!(default_key, cache.material_key(&folder, &model, &files));
+        assert_eq!(default_key, cache.material_key(&folder, &ScoringModel::default(), &files));
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn disabled_cache_never_serves_hits() {
+        let root = std::env::temp_dir().join("pbr_analysis_cache_disabled_test");
+        let _ = fs::remove_dir_all(&root);
+        let folder = root.join("mat");
+        write_material(&folder, 128);
+
+        let cache = AnalysisCache::disabled();
+        cache.evaluate(&folder, &ScoringModel::default(), analyze).unwrap();
+        let again = cache.evaluate(&folder, &ScoringModel::default(), analyze).unwrap();
+        assert!(!again.from_cache);
+        assert!(!root.join(CACHE_DIR).exists());
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn external_gltf_buffer_is_part_of_the_key() {
+        let root = std::env::temp_dir().join("pbr_analysis_cache_gltf_test");
+        let _ = fs::remove_dir_all(&root);
+        let folder = root.join("mat");
+        write_material(&folder, 128);
+        let gltf = serde_json::json!({ "buffers": [{ "uri": "../mesh.bin", "byteLength": 4 }] });
+        fs::write(folder.join("mesh.gltf"), gltf.to_string()).unwrap();
+        fs::write(root.join("mesh.bin"), [0u8; 4]).unwrap();
+
+        let files = hash_material_files(&folder).unwrap();
+        assert!(files.iter().any(|(p, _)| p.ends_with("mesh.bin")));
+        fs::write(root.join("mesh.bin"), [1u8; 4]).unwrap();
+        assert_ne!(files, hash_material_files(&folder).unwrap());
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn shared_copy_from_dedup_is_part_of_the_key() {
+        let root = std::env::temp_dir().join("pbr_analysis_cache_dedup_test");
+        let _ = fs::remove_dir_all(&root);
+        let (a, b) = (root.join("A"), root.join("B"));
+        write_material(&a, 128);
+        write_material(&b, 128);
+        let materials: Vec<_> = [&a, &b]
+            .iter()
+            .map(|f| (f.to_path_buf(), MaterialSet::load_from_folder(f).unwrap()))
+            .collect();
+        crate::dedup::apply_dedup(&crate::dedup::find_exact_duplicates(&materials), &root).unwrap();
+
+        let (removed, shared) = if a.join("albedo.png").exists() { (&b, &a) } else { (&a, &b) };
+        let files = hash_material_files(removed).unwrap();
+        assert!(files.iter().any(|(p, _)| p.ends_with(DEDUP_MANIFEST_FILE)));
+        assert!(files.iter().any(|(p, _)| p == &shared.join("albedo.png")));
+        write_material(shared, 0);
+        assert_ne!(files, hash_material_files(removed).unwrap());
+
+        fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,619 @@
+//! Texture atlas / trim-sheet packing.
+//!
+//! Packs many small materials into shared atlas pages, one texture per slot,
+//! so a whole set of props can be drawn with one material and one sampler per
+//! slot. Every material gets the same rectangle on every slot page, and a UV
+//! remap table (JSON and CSV) maps each source material's 0-1 UVs into it.
+//!
+//! Rectangles are placed with MaxRects (best short side fit) or skyline
+//! (bottom-left) bin packing. Each cell is expanded by `padding` pixels of
+//! edge-extended gutter and aligned to `2^mip_levels` pixels, so no texel of
+//! the first `mip_levels` mips mixes two materials and bilinear filtering at
+//! the cell border only sees the material's own edge colors.
+
+use crate::image_loading::TextureSlot;
+use crate::material::{MaterialSet, TextureMap};
+use crate::optimization::{resize_to_exact, save_texture};
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// UV remap table written by [`export_atlas`]
+pub const ATLAS_JSON_FILE: &str = "atlas.json";
+pub const ATLAS_CSV_FILE: &str = "atlas.csv";
+
+/// Bin packing algorithm.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackingAlgorithm {
+    /// MaxRects with best short side fit (tightest, slower)
+    #[default]
+    MaxRects,
+    /// Skyline bottom-left (fast, good for similar sizes)
+    Skyline,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AtlasOptions {
+    pub algorithm: PackingAlgorithm,
+    /// Largest page edge; pages are powers of two up to this size
+    pub max_size: u32,
+    /// Edge-extended gutter around each material, in pixels
+    pub padding: u32,
+    /// Cells are aligned to `2^mip_levels` pixels so these mips never mix materials
+    pub mip_levels: u32,
+    /// Only pack materials whose longest edge is at most this (larger ones are skipped)
+    pub max_source_size: u32,
+}
+
+impl Default for AtlasOptions {
+    fn default() -> Self {
+        Self {
+            algorithm: PackingAlgorithm::MaxRects,
+            max_size: 2048,
+            padding: 4,
+            mip_levels: 4,
+            max_source_size: 512,
+        }
+    }
+}
+
+/// Where one material ended up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AtlasEntry {
+    pub material: String,
+    pub page: usize,
+    /// Content rectangle in pixels (without gutter)
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// `uv_atlas = uv * scale + offset` (top-left origin, V down)
+    pub u_scale: f32,
+    pub v_scale: f32,
+    pub u_offset: f32,
+    pub v_offset: f32,
+}
+
+/// A material that was not packed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedMaterial {
+    pub material: String,
+    pub reason: String,
+}
+
+/// Placement of all materials on the atlas pages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AtlasLayout {
+    pub algorithm: PackingAlgorithm,
+    pub page_width: u32,
+    pub page_height: u32,
+    pub pages: usize,
+    pub padding: u32,
+    pub mip_levels: u32,
+    /// Content pixels / page pixels over all pages
+    pub occupancy: f32,
+    pub entries: Vec<AtlasEntry>,
+    pub skipped: Vec<SkippedMaterial>,
+    /// Cell rectangle (x, y, w, h) including gutter and alignment, per entry
+    #[serde(skip)]
+    cells: Vec<(u32, u32, u32, u32)>,
+    /// Index into the input materials, per entry
+    #[serde(skip)]
+    sources: Vec<usize>,
+}
+
+impl AtlasLayout {
+    pub fn to_json(&self) -> std::result::Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// UV remap table as CSV (one row per material).
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("material,page,x,y,width,height,u_scale,v_scale,u_offset,v_offset\n");
+        for e in &self.entries {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{:.8},{:.8},{:.8},{:.8}\n",
+                csv_field(&e.material),
+                e.page,
+                e.x,
+                e.y,
+                e.width,
+                e.height,
+                e.u_scale,
+                e.v_scale,
+                e.u_offset,
+                e.v_offset
+            ));
+        }
+        out
+    }
+}
+
+/// One atlas page: a texture per slot present in any packed material.
+#[derive(Debug, Clone)]
+pub struct AtlasPage {
+    pub index: usize,
+    pub textures: Vec<(TextureSlot, TextureMap)>,
+}
+
+/// Result of [`export_atlas`].
+#[derive(Debug, Clone, Serialize)]
+pub struct AtlasReport {
+    #[serde(flatten)]
+    pub layout: AtlasLayout,
+    pub written: Vec<String>,
+}
+
+const SLOTS: [(TextureSlot, &str); 6] = [
+    (TextureSlot::Albedo, "BaseColor"),
+    (TextureSlot::Normal, "Normal"),
+    (TextureSlot::Roughness, "Roughness"),
+    (TextureSlot::Metallic, "Metallic"),
+    (TextureSlot::AmbientOcclusion, "AmbientOcclusion"),
+    (TextureSlot::Height, "Height"),
+];
+
+/// Fill for a material that lacks a slot present in others.
+fn slot_default(slot: TextureSlot) -> [u8; 4] {
+    match slot {
+        TextureSlot::Normal => [128, 128, 255, 255],
+        TextureSlot::Roughness | TextureSlot::AmbientOcclusion => [255, 255, 255, 255],
+        TextureSlot::Height => [128, 128, 128, 255],
+        _ => [0, 0, 0, 255],
+    }
+}
+
+/// Compute where each material goes. Materials are sized by their first slot;
+/// all slots are resized to that size when the atlas is built.
+pub fn pack_atlas(materials: &[(PathBuf, MaterialSet)], options: &AtlasOptions) -> Result<AtlasLayout> {
+    if options.max_size == 0 || !options.max_size.is_power_of_two() {
+        return Err(Error::Other(format!("Atlas max size must be a power of two, got {}", options.max_size)));
+    }
+    let align = 1u32 << options.mip_levels.min(12);
+    let cell = |v: u32| (v + 2 * options.padding).div_ceil(align) * align;
+
+    let mut items = Vec::new();
+    let mut skipped = Vec::new();
+    for (i, (folder, set)) in materials.iter().enumerate() {
+        let name = material_name(folder, set);
+        let reason = match set.dimensions() {
+            None => Some("no textures".to_string()),
+            Some((w, h)) if w.max(h) > options.max_source_size => {
+                Some(format!("{}x{} exceeds the {} px source limit", w, h, options.max_source_size))
+            }
+            Some((w, h)) if cell(w) > options.max_size || cell(h) > options.max_size => {
+                Some(format!("{}x{} does not fit a {} px page", w, h, options.max_size))
+            }
+            Some((w, h)) => {
+                items.push((i, name.clone(), w, h, cell(w), cell(h)));
+                None
+            }
+        };
+        if let Some(reason) = reason {
+            skipped.push(SkippedMaterial { material: name, reason });
+        }
+    }
+    // Largest first packs tighter for both algorithms
+    items.sort_by_key(|&(i, _, _, _, cw, ch)| (std::cmp::Reverse(cw.max(ch)), std::cmp::Reverse(cw.min(ch)), i));
+    let sizes: Vec<(u32, u32)> = items.iter().map(|&(_, _, _, _, cw, ch)| (cw, ch)).collect();
+
+    // Smallest power-of-two page that holds everything, else as many max-size pages as needed
+    let area: u64 = sizes.iter().map(|&(w, h)| w as u64 * h as u64).sum();
+    let mut page = (options.max_size, options.max_size);
+    let mut placements = None;
+    let mut h = align.max(1);
+    'search: while h <= options.max_size {
+        for w in [h, h * 2] {
+            if w > options.max_size || (w as u64 * h as u64) < area {
+                continue;
+            }
+            let placed = pack_page(options.algorithm, &sizes, w, h);
+            if placed.iter().all(Option::is_some) {
+                page = (w, h);
+                placements = Some(placed.into_iter().map(|p| p.map(|(x, y)| (0, x, y))).collect::<Vec<_>>());
+                break 'search;
+            }
+        }
+        h *= 2;
+    }
+    let placements = placements.unwrap_or_else(|| pack_pages(options.algorithm, &sizes, page.0, page.1));
+
+    let mut entries = Vec::new();
+    let mut cells = Vec::new();
+    let mut sources = Vec::new();
+    let mut pages = 0;
+    let mut content = 0u64;
+    for (&(source, ref name, w, h, cw, ch), placement) in items.iter().zip(placements) {
+        let (page_index, cx, cy) = placement.expect("every item is placed on some page");
+        pages = pages.max(page_index + 1);
+        content += w as u64 * h as u64;
+        let (x, y) = (cx + options.padding, cy + options.padding);
+        entries.push(AtlasEntry {
+            material: name.clone(),
+            page: page_index,
+            x,
+            y,
+            width: w,
+            height: h,
+            u_scale: w as f32 / page.0 as f32,
+            v_scale: h as f32 / page.1 as f32,
+            u_offset: x as f32 / page.0 as f32,
+            v_offset: y as f32 / page.1 as f32,
+        });
+        cells.push((cx, cy, cw, ch));
+        sources.push(source);
+    }
+    let page_pixels = pages as u64 * page.0 as u64 * page.1 as u64;
+
+    Ok(AtlasLayout {
+        algorithm: options.algorithm,
+        page_width: page.0,
+        page_height: page.1,
+        pages,
+        padding: options.padding,
+        mip_levels: options.mip_levels,
+        occupancy: if page_pixels > 0 { content as f32 / page_pixels as f32 } else { 0.0 },
+        entries,
+        skipped,
+        cells,
+        sources,
+    })
+}
+
+/// Render the atlas pages for `layout` (from [`pack_atlas`] on the same materials).
+pub fn build_atlas(materials: &[(PathBuf, MaterialSet)], layout: &AtlasLayout) -> Result<Vec<AtlasPage>> {
+    let slots: Vec<TextureSlot> = SLOTS
+        .iter()
+        .map(|&(slot, _)| slot)
+        .filter(|&slot| layout.sources.iter().any(|&i| materials[i].1.get(slot).is_some()))
+        .collect();
+    let (pw, ph) = (layout.page_width, layout.page_height);
+
+    let mut pages = Vec::new();
+    for index in 0..layout.pages {
+        let mut textures = Vec::new();
+        for &slot in &slots {
+            let mut data = vec![0u8; pw as usize * ph as usize * 4];
+            for (k, entry) in layout.entries.iter().enumerate().filter(|(_, e)| e.page == index) {
+                let set = &materials[layout.sources[k]].1;
+                let source = match set.get(slot) {
+                    Some(t) => Some(resize_to_exact(t, entry.width, entry.height)?),
+                    None => None,
+                };
+                blit_cell(&mut data, pw, layout.cells[k], layout.padding, entry, source.as_ref(), slot_default(slot));
+            }
+            textures.push((slot, TextureMap::new(pw, ph, data, None)));
+        }
+        pages.push(AtlasPage { index, textures });
+    }
+    Ok(pages)
+}
+
+/// Pack, render and write `atlas_<page>_<Slot>.png` plus [`ATLAS_JSON_FILE`]
+/// and [`ATLAS_CSV_FILE`] to `output_dir`.
+pub fn export_atlas(
+    materials: &[(PathBuf, MaterialSet)],
+    output_dir: &Path,
+    options: &AtlasOptions,
+) -> Result<AtlasReport> {
+    let layout = pack_atlas(materials, options)?;
+    if layout.entries.is_empty() {
+        return Err(Error::Other("No materials small enough to pack into an atlas".into()));
+    }
+    let pages = build_atlas(materials, &layout)?;
+    fs::create_dir_all(output_dir)?;
+
+    let mut written = Vec::new();
+    for page in &pages {
+        for (slot, texture) in &page.textures {
+            let name = SLOTS.iter().find(|(s, _)| s == slot).map(|(_, n)| *n).unwrap_or("Texture");
+            let path = output_dir.join(format!("atlas_{}_{}.png", page.index, name));
+            save_texture(texture, &path)?;
+            written.push(path.display().to_string());
+        }
+    }
+    fs::write(output_dir.join(ATLAS_JSON_FILE), layout.to_json()?)?;
+    fs::write(output_dir.join(ATLAS_CSV_FILE), layout.to_csv())?;
+    written.push(output_dir.join(ATLAS_JSON_FILE).display().to_string());
+    written.push(output_dir.join(ATLAS_CSV_FILE).display().to_string());
+    Ok(AtlasReport { layout, written })
+}
+
+fn material_name(folder: &Path, set: &MaterialSet) -> String {
+    set.name
+        .clone()
+        .or_else(|| folder.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| folder.display().to_string())
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Copy `source` into its cell; every cell pixel outside the content repeats
+/// the nearest content pixel (edge-extended gutter).
+fn blit_cell(
+    data: &mut [u8],
+    page_width: u32,
+    (cx, cy, cw, ch): (u32, u32, u32, u32),
+    padding: u32,
+    entry: &AtlasEntry,
+    source: Option<&TextureMap>,
+    fill: [u8; 4],
+) {
+    for y in 0..ch {
+        let sy = y.saturating_sub(padding).min(entry.height - 1);
+        for x in 0..cw {
+            let sx = x.saturating_sub(padding).min(entry.width - 1);
+            let px = source.and_then(|t| t.pixel(sx, sy)).unwrap_or(fill);
+            let i = (((cy + y) * page_width + cx + x) * 4) as usize;
+            data[i..i + 4].copy_from_slice(&px);
+        }
+    }
+}
+
+/// Place as many `sizes` as fit on one `w` x `h` page, in order.
+fn pack_page(algorithm: PackingAlgorithm, sizes: &[(u32, u32)], w: u32, h: u32) -> Vec<Option<(u32, u32)>> {
+    match algorithm {
+        PackingAlgorithm::MaxRects => {
+            let mut bin = MaxRects::new(w, h);
+            sizes.iter().map(|&(rw, rh)| bin.insert(rw, rh)).collect()
+        }
+        PackingAlgorithm::Skyline => {
+            let mut bin = Skyline::new(w, h);
+            sizes.iter().map(|&(rw, rh)| bin.insert(rw, rh)).collect()
+        }
+    }
+}
+
+/// Fill pages one after another with whatever did not fit on the previous ones.
+fn pack_pages(algorithm: PackingAlgorithm, sizes: &[(u32, u32)], w: u32, h: u32) -> Vec<Option<(usize, u32, u32)>> {
+    let mut out = vec![None; sizes.len()];
+    let mut remaining: Vec<usize> = (0..sizes.len()).collect();
+    let mut page = 0;
+    while !remaining.is_empty() {
+        let page_sizes: Vec<(u32, u32)> = remaining.iter().map(|&i| sizes[i]).collect();
+        let placed = pack_page(algorithm, &page_sizes, w, h);
+        let mut next = Vec::new();
+        for (&i, p) in remaining.iter().zip(placed) {
+            match p {
+                Some((x, y)) => out[i] = Some((page, x, y)),
+                None => next.push(i),
+            }
+        }
+        // Every item fits an empty page (checked by the caller), so each page places at least one
+        remaining = next;
+        page += 1;
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+impl Rect {
+    fn contains(&self, o: &Rect) -> bool {
+        o.x >= self.x && o.y >= self.y && o.x + o.w <= self.x + self.w && o.y + o.h <= self.y + self.h
+    }
+
+    fn intersects(&self, o: &Rect) -> bool {
+        o.x < self.x + self.w && o.x + o.w > self.x && o.y < self.y + self.h && o.y + o.h > self.y
+    }
+}
+
+/// MaxRects bin with best short side fit.
+struct MaxRects {
+    free: Vec<Rect>,
+}
+
+impl MaxRects {
+    fn new(w: u32, h: u32) -> Self {
+        Self {
+            free: vec![Rect { x: 0, y: 0, w, h }],
+        }
+    }
+
+    fn insert(&mut self, w: u32, h: u32) -> Option<(u32, u32)> {
+        let best = self
+            .free
+            .iter()
+            .filter(|f| f.w >= w && f.h >= h)
+            .min_by_key(|f| ((f.w - w).min(f.h - h), (f.w - w).max(f.h - h), f.y, f.x))?;
+        let placed = Rect { x: best.x, y: best.y, w, h };
+
+        let mut next = Vec::with_capacity(self.free.len() + 4);
+        for f in &self.free {
+            if !f.intersects(&placed) {
+                next.push(*f);
+                continue;
+            }
+            if placed.x > f.x {
+                next.push(Rect { w: placed.x - f.x, ..*f });
+            }
+            if placed.x + placed.w < f.x + f.w {
+                next.push(Rect { x: placed.x + placed.w, w: f.x + f.w - placed.x - placed.w, ..*f });
+            }
+            if placed.y > f.y {
+                next.push(Rect { h: placed.y - f.y, ..*f });
+            }
+            if placed.y + placed.h < f.y + f.h {
+                next.push(Rect { y: placed.y + placed.h, h: f.y + f.h - placed.y - placed.h, ..*f });
+            }
+        }
+        // Drop free rectangles contained in another one
+        let mut pruned: Vec<Rect> = Vec::with_capacity(next.len());
+        for (i, r) in next.iter().enumerate() {
+            let covered = next
+                .iter()
+                .enumerate()
+                .any(|(j, o)| j != i && o.contains(r) && (o != r || j < i));
+            if !covered {
+                pruned.push(*r);
+            }
+        }
+        self.free = pruned;
+        Some((placed.x, placed.y))
+    }
+}
+
+/// Skyline bin with bottom-left placement.
+struct Skyline {
+    width: u32,
+    height: u32,
+    /// (x, y, width) segments, left to right
+    segments: Vec<(u32, u32, u32)>,
+}
+
+impl Skyline {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            segments: vec![(0, 0, width)],
+        }
+    }
+
+    fn insert(&mut self, w: u32, h: u32) -> Option<(u32, u32)> {
+        let mut best: Option<(u32, u32)> = None;
+        for start in 0..self.segments.len() {
+            let x = self.segments[start].0;
+            if x + w > self.width {
+                break;
+            }
+            // Resting height: highest segment under [x, x + w)
+            let y = self
+                .segments
+                .iter()
+                .skip(start)
+                .take_while(|s| s.0 < x + w)
+                .map(|s| s.1)
+                .max()
+                .unwrap_or(0);
+            if y + h <= self.height && best.is_none_or(|b| (y, x) < b) {
+                best = Some((y, x));
+            }
+        }
+        let (y, x) = best?;
+
+        // Replace the covered span [x, x + w) with the new top edge; x is always a segment start
+        let mut next = Vec::with_capacity(self.segments.len() + 2);
+        for &(sx, sy, sw) in &self.segments {
+            let end = sx + sw;
+            if sx == x {
+                next.push((x, y + h, w));
+            }
+            if end <= x || sx >= x + w {
+                next.push((sx, sy, sw));
+            } else if end > x + w {
+                next.push((x + w, sy, end - x - w));
+            }
+        }
+        // Merge neighbors at the same height
+        self.segments.clear();
+        for s in next {
+            match self.segments.last_mut() {
+                Some(last) if last.1 == s.1 && last.0 + last.2 == s.0 => last.2 += s.2,
+                _ => self.segments.push(s),
+            }
+        }
+        Some((x, y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn material(name: &str, size: u32, value: u8, normal: bool) -> (PathBuf, MaterialSet) {
+        let mut set = MaterialSet::new();
+        let len = (size * size * 4) as usize;
+        set.albedo = Some(TextureMap::new(size, size, vec![value; len], None));
+        if normal {
+            set.normal = Some(TextureMap::new(size, size, vec![200; len], None));
+        }
+        (PathBuf::from(name), set)
+    }
+
+    fn assert_no_overlap(layout: &AtlasLayout) {
+        for (i, a) in layout.cells.iter().enumerate() {
+            let ra = Rect { x: a.0, y: a.1, w: a.2, h: a.3 };
+            assert!(a.0 + a.2 <= layout.page_width && a.1 + a.3 <= layout.page_height);
+            for (j, b) in layout.cells.iter().enumerate().skip(i + 1) {
+                let rb = Rect { x: b.0, y: b.1, w: b.2, h: b.3 };
+                let same_page = layout.entries[i].page == layout.entries[j].page;
+                assert!(!(same_page && ra.intersects(&rb)), "cells {} and {} overlap", i, j);
+            }
+        }
+    }
+
+    #[test]
+    fn packs_without_overlap_with_aligned_cells() {
+        let materials: Vec<_> = [64, 32, 32, 48, 16, 100, 64, 8]
+            .iter()
+            .enumerate()
+            .map(|(i, &s)| material(&format!("m{}", i), s, i as u8 * 20, false))
+            .collect();
+        for algorithm in [PackingAlgorithm::MaxRects, PackingAlgorithm::Skyline] {
+            let options = AtlasOptions {
+                algorithm,
+                ..Default::default()
+            };
+            let layout = pack_atlas(&materials, &options).unwrap();
+            assert_eq!(layout.entries.len(), materials.len());
+            assert_eq!(layout.pages, 1);
+            assert!(layout.page_width.is_power_of_two() && layout.page_height.is_power_of_two());
+            assert!(layout.cells.iter().all(|c| c.0 % 16 == 0 && c.1 % 16 == 0 && c.2 % 16 == 0));
+            assert_no_overlap(&layout);
+        }
+    }
+
+    #[test]
+    fn spills_to_more_pages_and_skips_large_materials() {
+        let mut materials: Vec<_> = (0..6).map(|i| material(&format!("m{}", i), 120, 10, false)).collect();
+        materials.push(material("big", 600, 10, false));
+        let options = AtlasOptions {
+            max_size: 256,
+            ..Default::default()
+        };
+        let layout = pack_atlas(&materials, &options).unwrap();
+        assert_eq!(layout.entries.len(), 6);
+        assert_eq!(layout.pages, 2);
+        assert_eq!(layout.skipped.len(), 1);
+        assert_eq!(layout.skipped[0].material, "big");
+        assert_no_overlap(&layout);
+    }
+
+    #[test]
+    fn build_fills_content_gutter_and_missing_slots() {
+        let materials = vec![material("a", 16, 50, true), material("b", 16, 150, false)];
+        let layout = pack_atlas(&materials, &AtlasOptions::default()).unwrap();
+        let pages = build_atlas(&materials, &layout).unwrap();
+        assert_eq!(pages.len(), 1);
+        let slots: Vec<_> = pages[0].textures.iter().map(|(s, _)| *s).collect();
+        assert_eq!(slots, vec![TextureSlot::Albedo, TextureSlot::Normal]);
+
+        let (albedo, normal) = (&pages[0].textures[0].1, &pages[0].textures[1].1);
+        let b = layout.entries.iter().position(|e| e.material == "b").unwrap();
+        let (e, cell) = (&layout.entries[b], layout.cells[b]);
+        assert_eq!(albedo.pixel(e.x + 3, e.y + 3), Some([150; 4]));
+        // Gutter repeats the edge color
+        assert_eq!(albedo.pixel(cell.0, cell.1), Some([150; 4]));
+        // b has no normal map: its cell gets a flat normal
+        assert_eq!(normal.pixel(e.x, e.y), Some([128, 128, 255, 255]));
+
+        let csv = layout.to_csv();
+        assert_eq!(csv.lines().count(), 3);
+        assert!((e.u_offset - e.x as f32 / layout.page_width as f32).abs() < 1e-6);
+    }
+}
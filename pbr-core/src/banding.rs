@@ -0,0 +1,216 @@
+//! Banding detection in smooth gradients.
+//!
+//! A gradient stored with too few levels shows as a staircase: flat runs of
+//! one value separated by steps of a single level, all going the same way.
+//! Each row and column is split into runs of equal value; runs whose
+//! neighbors are at most [`MAX_STEP`] levels away belong to a smooth
+//! gradient, and those that are at least [`MIN_BAND_WIDTH`] pixels wide and
+//! sit on a monotonic staircase of runs wider than a pixel are bands.
+//! Dithered or noisy gradients alternate up and down in short runs and score
+//! low; flat areas and hard edges are not gradients and are ignored. RGB
+//! channels are scanned separately and the worst one is reported.
+
+use crate::analysis::{material_textures, slot_textures};
+use crate::material::{MaterialSet, TextureMap};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Largest step (in 8-bit levels) between runs of a smooth gradient
+pub const MAX_STEP: u8 = 2;
+
+/// Narrowest run (pixels) counted as a visible band
+pub const MIN_BAND_WIDTH: usize = 3;
+
+/// Narrowest neighbor run that counts as the next step of a staircase
+/// (single-pixel excursions are dither or noise, not a band edge)
+const MIN_STEP_WIDTH: usize = 2;
+
+/// Banding score (0-1) above which stair-stepping is considered visible
+pub const BANDING_THRESHOLD: f32 = 0.3;
+
+/// Smooth gradients must cover at least this fraction of the scanned pixels
+/// for the score to count (a few smooth pixels in a noisy map say nothing)
+const MIN_SMOOTH_FRACTION: f64 = 0.02;
+
+/// Rows and columns scanned per direction (larger textures are strided)
+const MAX_LINES: usize = 1024;
+
+/// How much a texture's smooth gradients stair-step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BandingAnalysis {
+    /// 0 (smooth or dithered) to 1 (every gradient is a staircase)
+    pub banding_score: f32,
+    /// Fraction of scanned pixels that lie in smooth gradients
+    pub smooth_fraction: f32,
+    /// Mean width in pixels of the bands found
+    pub mean_band_width: f32,
+    /// Channel with the highest score: "r", "g" or "b"
+    pub channel: String,
+}
+
+/// Banding analysis of one texture.
+#[derive(Debug, Clone, Serialize)]
+pub struct TextureBanding {
+    pub path: String,
+    pub slot: String,
+    pub material_name: Option<String>,
+    /// Banding score above [`BANDING_THRESHOLD`]
+    pub banded: bool,
+    #[serde(flatten)]
+    pub analysis: BandingAnalysis,
+}
+
+/// Run counts along scanned lines of one channel.
+#[derive(Default, Clone, Copy)]
+struct LineCounts {
+    scanned: usize,
+    smooth: usize,
+    banded: usize,
+    bands: usize,
+}
+
+impl LineCounts {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            scanned: self.scanned + other.scanned,
+            smooth: self.smooth + other.smooth,
+            banded: self.banded + other.banded,
+            bands: self.bands + other.bands,
+        }
+    }
+
+    /// Count the runs of one line of 8-bit values.
+    fn scan(values: impl Iterator<Item = u8>) -> Self {
+        let mut runs: Vec<(u8, usize)> = Vec::new();
+        for v in values {
+            match runs.last_mut() {
+                Some((value, len)) if *value == v => *len += 1,
+                _ => runs.push((v, 1)),
+            }
+        }
+        let mut counts = LineCounts { scanned: runs.iter().map(|r| r.1).sum(), ..Default::default() };
+        let step = |a: usize, b: usize| runs[b].0 as i16 - runs[a].0 as i16;
+        let small = |s: i16| s != 0 && s.unsigned_abs() <= MAX_STEP as u16;
+        for (i, &(_, len)) in runs.iter().enumerate() {
+            let before = (i > 0).then(|| step(i - 1, i));
+            let after = (i + 1 < runs.len()).then(|| step(i, i + 1));
+            let smooth = match (before, after) {
+                (Some(b), Some(a)) => small(b) && small(a),
+                (Some(s), None) | (None, Some(s)) => small(s),
+                (None, None) => false,
+            };
+            if !smooth {
+                continue;
+            }
+            counts.smooth += len;
+            if let (Some(b), Some(a)) = (before, after) {
+                let steps_wide = runs[i - 1].1 >= MIN_STEP_WIDTH && runs[i + 1].1 >= MIN_STEP_WIDTH;
+                if len >= MIN_BAND_WIDTH && steps_wide && b.signum() == a.signum() {
+                    counts.banded += len;
+                    counts.bands += 1;
+                }
+            }
+        }
+        counts
+    }
+}
+
+/// Scan `texture` for stair-stepped gradients.
+pub fn banding_analysis(texture: &TextureMap) -> BandingAnalysis {
+    let (w, h) = (texture.width as usize, texture.height as usize);
+    let data = &texture.data;
+    let mut best: Option<(f32, LineCounts, usize)> = None;
+    if w * h * 4 <= data.len() && w > 0 && h > 0 {
+        for c in 0..3 {
+            let rows: Vec<usize> = (0..h).step_by(h.div_ceil(MAX_LINES)).collect();
+            let cols: Vec<usize> = (0..w).step_by(w.div_ceil(MAX_LINES)).collect();
+            let row_counts = rows
+                .par_iter()
+                .map(|&y| LineCounts::scan((0..w).map(|x| data[(y * w + x) * 4 + c])))
+                .reduce(LineCounts::default, LineCounts::merge);
+            let col_counts = cols
+                .par_iter()
+                .map(|&x| LineCounts::scan((0..h).map(|y| data[(y * w + x) * 4 + c])))
+                .reduce(LineCounts::default, LineCounts::merge);
+            let counts = row_counts.merge(col_counts);
+            let smooth_enough = counts.smooth as f64 >= MIN_SMOOTH_FRACTION * counts.scanned as f64;
+            let score = if smooth_enough && counts.smooth > 0 {
+                counts.banded as f32 / counts.smooth as f32
+            } else {
+                0.0
+            };
+            if best.is_none_or(|(s, _, _)| score > s) {
+                best = Some((score, counts, c));
+            }
+        }
+    }
+    let (score, counts, channel) = best.unwrap_or((0.0, LineCounts::default(), 0));
+    BandingAnalysis {
+        banding_score: score,
+        smooth_fraction: if counts.scanned > 0 { counts.smooth as f32 / counts.scanned as f32 } else { 0.0 },
+        mean_band_width: if counts.bands > 0 { counts.banded as f32 / counts.bands as f32 } else { 0.0 },
+        channel: ["r", "g", "b"][channel].to_string(),
+    }
+}
+
+fn texture_banding(material_name: Option<String>, slot: &str, texture: &TextureMap) -> TextureBanding {
+    let analysis = banding_analysis(texture);
+    TextureBanding {
+        path: texture.path.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "unknown".into()),
+        slot: slot.to_string(),
+        material_name,
+        banded: analysis.banding_score > BANDING_THRESHOLD,
+        analysis,
+    }
+}
+
+/// Banding analysis of every texture of every material.
+pub fn analyze_banding(materials: &[(PathBuf, MaterialSet)]) -> Vec<TextureBanding> {
+    material_textures(materials)
+        .into_par_iter()
+        .map(|(name, slot, texture)| texture_banding(name, slot, texture))
+        .collect()
+}
+
+/// Banding analysis of the textures of a single material.
+pub fn material_banding(set: &MaterialSet) -> Vec<TextureBanding> {
+    slot_textures(set).map(|(slot, texture)| texture_banding(set.name.clone(), slot, texture)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{gray, noise};
+
+    #[test]
+    fn shallow_gradient_is_banded() {
+        // 32 levels over 256 px: 8 px bands
+        let a = banding_analysis(&gray(256, 64, |x, _| 100 + (x / 8) as u8));
+        assert!(a.banding_score > 0.9, "{:?}", a);
+        assert!((a.mean_band_width - 8.0).abs() < 0.5, "{:?}", a);
+        assert!(a.smooth_fraction > 0.4);
+    }
+
+    #[test]
+    fn flat_noisy_and_steep_textures_are_not_banded() {
+        assert_eq!(banding_analysis(&gray(64, 64, |_, _| 128)).banding_score, 0.0);
+        assert!(banding_analysis(&gray(128, 128, noise)).banding_score < 0.05);
+        // One level per pixel: no flat runs to see
+        assert!(banding_analysis(&gray(200, 32, |x, _| x as u8)).banding_score < 0.05);
+        // Hard-edged stripes are edges, not gradient bands
+        assert!(banding_analysis(&gray(128, 32, |x, _| if (x / 16) % 2 == 0 { 40 } else { 200 })).banding_score < 0.05);
+    }
+
+    #[test]
+    fn analyze_banding_flags_height_map() {
+        let mut set = MaterialSet::new();
+        set.name = Some("Cliff".into());
+        set.height = Some(gray(128, 128, |_, y| 60 + (y / 6) as u8));
+        set.albedo = Some(gray(128, 128, noise));
+        let entries = analyze_banding(&[(PathBuf::from("Cliff"), set)]);
+        let height = entries.iter().find(|e| e.slot == "height").unwrap();
+        assert!(height.banded);
+        assert!(!entries.iter().find(|e| e.slot == "albedo").unwrap().banded);
+    }
+}
@@ -0,0 +1,467 @@
+//! Scene and level texture memory budgeting.
+//!
+//! A [`SceneManifest`] lists the materials a level uses, with instance counts
+//! and a [`StreamingPriority`], plus a VRAM budget per [`Platform`]. Each
+//! material is estimated once in the platform's GPU formats (instances share
+//! textures), reduced to the mip levels that mip streaming keeps resident for
+//! its priority, and summed. When the scene is over budget, materials are
+//! ranked as offenders and [`TargetResolution`] downgrades are suggested
+//! until it fits, cheapest in visible quality first.
+
+use crate::estimation::{estimate_vram_for_platform, format_bytes, texture_format_bytes, Platform, PlatformVramEstimate};
+use crate::material::MaterialSet;
+use crate::optimization::{compute_target_dimensions, TargetResolution};
+use crate::parallel::load_materials;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Resolutions a downgrade steps through (longest edge)
+pub(crate) const DOWNGRADE_STEPS: [u32; 6] = [4096, 2048, 1024, 512, 256, 128];
+
+/// Downgrades never go below this longest edge
+pub const MIN_DOWNGRADE_DIMENSION: u32 = 256;
+
+/// How a material's mips are streamed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamingPriority {
+    /// Not streamed (UI, first-person, hero assets): full chain resident, never downgraded
+    Pinned,
+    /// Full chain resident while in view
+    High,
+    /// Top mip usually not resident
+    #[default]
+    Normal,
+    /// Two top mips usually not resident (background, distant)
+    Low,
+}
+
+impl StreamingPriority {
+    /// Top mip levels assumed not resident under mip streaming
+    pub fn dropped_mips(self) -> u32 {
+        match self {
+            StreamingPriority::Pinned | StreamingPriority::High => 0,
+            StreamingPriority::Normal => 1,
+            StreamingPriority::Low => 2,
+        }
+    }
+
+    /// Relative visual cost of downgrading (higher = keep resolution longer)
+    fn downgrade_weight(self) -> f64 {
+        match self {
+            StreamingPriority::Pinned => f64::INFINITY,
+            StreamingPriority::High => 8.0,
+            StreamingPriority::Normal => 2.0,
+            StreamingPriority::Low => 1.0,
+        }
+    }
+}
+
+fn one() -> u32 {
+    1
+}
+
+/// One material used by the scene.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneMaterial {
+    /// Material folder, relative to the manifest
+    pub path: PathBuf,
+    /// Placed instances (textures are shared; weights how visible a downgrade is)
+    #[serde(default = "one")]
+    pub instances: u32,
+    #[serde(default)]
+    pub priority: StreamingPriority,
+}
+
+/// Level material list with per-platform budgets.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SceneManifest {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Texture memory budget per platform, in MiB
+    #[serde(default)]
+    pub budgets: BTreeMap<Platform, f64>,
+    #[serde(default)]
+    pub materials: Vec<SceneMaterial>,
+}
+
+impl SceneManifest {
+    /// Load a manifest from a JSON or TOML file (format inferred from extension).
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path)?;
+        let is_toml = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("toml"));
+        if is_toml {
+            toml::from_str(&s)
+                .map_err(|e| crate::Error::Other(format!("Invalid scene manifest {}: {}", path.display(), e)))
+        } else {
+            Ok(serde_json::from_str(&s)?)
+        }
+    }
+
+    /// Budget for `platform` in bytes, when configured
+    pub fn budget_bytes(&self, platform: Platform) -> Option<u64> {
+        self.budgets.get(&platform).map(|mb| (mb * 1024.0 * 1024.0).round() as u64)
+    }
+}
+
+/// A material's share of one platform's budget.
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetOffender {
+    pub material: String,
+    pub path: String,
+    pub instances: u32,
+    pub priority: StreamingPriority,
+    /// Largest texture dimensions in the material
+    pub width: u32,
+    pub height: u32,
+    /// Full mip chain
+    pub full_bytes: u64,
+    /// After streaming residency
+    pub resident_bytes: u64,
+    pub resident_formatted: String,
+    /// Fraction of the scene's resident bytes (0-1)
+    pub share: f32,
+}
+
+/// Suggested resolution cap for one material.
+#[derive(Debug, Clone, Serialize)]
+pub struct SuggestedDowngrade {
+    pub material: String,
+    pub path: String,
+    /// Current longest edge
+    pub from_max_dimension: u32,
+    /// Suggested longest edge
+    pub to_max_dimension: u32,
+    /// `TargetResolution` label (e.g. "1K")
+    pub target: String,
+    pub saved_bytes: u64,
+    pub saved_formatted: String,
+}
+
+impl SuggestedDowngrade {
+    /// Resolution to pass to the optimizer
+    pub fn resolution(&self) -> TargetResolution {
+        TargetResolution::from_max_dimension(self.to_max_dimension)
+    }
+}
+
+/// Scene memory on one platform against its budget.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformBudget {
+    pub platform: Platform,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_formatted: Option<String>,
+    /// Every material with its full mip chain
+    pub full_bytes: u64,
+    pub full_formatted: String,
+    /// With mip streaming residency applied (compared to the budget)
+    pub resident_bytes: u64,
+    pub resident_formatted: String,
+    /// True when no budget is configured
+    pub within_budget: bool,
+    pub over_by_bytes: u64,
+    /// Largest resident materials first
+    pub offenders: Vec<BudgetOffender>,
+    /// Empty when within budget
+    pub downgrades: Vec<SuggestedDowngrade>,
+    /// Resident bytes after applying `downgrades`
+    pub projected_bytes: u64,
+    pub projected_formatted: String,
+    pub fits_after_downgrades: bool,
+}
+
+/// Whole-scene texture memory report.
+#[derive(Debug, Clone, Serialize)]
+pub struct SceneBudgetReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub material_count: usize,
+    /// Manifest entries that could not be loaded
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+    pub platforms: Vec<PlatformBudget>,
+}
+
+impl SceneBudgetReport {
+    /// Serialize to formatted JSON string.
+    pub fn to_json(&self) -> std::result::Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// True when every budgeted platform is within budget
+    pub fn within_budget(&self) -> bool {
+        self.platforms.iter().all(|p| p.within_budget)
+    }
+}
+
+/// Load the manifest's materials (paths relative to `base_dir`) and evaluate every platform.
+pub fn evaluate_scene_budget(manifest: &SceneManifest, base_dir: &Path) -> SceneBudgetReport {
+    // The same folder listed twice shares its textures: merge instances, keep the highest priority
+    let mut merged: Vec<SceneMaterial> = Vec::new();
+    for m in &manifest.materials {
+        let path = base_dir.join(&m.path);
+        match merged.iter_mut().find(|e| e.path == path) {
+            Some(e) => {
+                e.instances += m.instances;
+                e.priority = e.priority.min(m.priority);
+            }
+            None => merged.push(SceneMaterial { path, ..m.clone() }),
+        }
+    }
+    let folders: Vec<PathBuf> = merged.iter().map(|m| m.path.clone()).collect();
+    let mut missing = Vec::new();
+    let mut loaded = Vec::new();
+    for (entry, (folder, result)) in merged.into_iter().zip(load_materials(&folders)) {
+        match result {
+            Ok(set) => loaded.push((entry, set)),
+            Err(e) => missing.push(format!("{}: {}", folder.display(), e)),
+        }
+    }
+    let mut report = evaluate_loaded_scene(manifest, &loaded);
+    report.missing = missing;
+    report
+}
+
+/// Evaluate already-loaded scene materials on every platform.
+pub fn evaluate_loaded_scene(manifest: &SceneManifest, materials: &[(SceneMaterial, MaterialSet)]) -> SceneBudgetReport {
+    SceneBudgetReport {
+        name: manifest.name.clone(),
+        material_count: materials.len(),
+        missing: Vec::new(),
+        platforms: Platform::ALL
+            .iter()
+            .map(|&p| platform_budget(p, manifest.budget_bytes(p), materials))
+            .collect(),
+    }
+}
+
+/// Resident bytes of a platform estimate with every texture capped at `max_dim`.
+fn resident_bytes(estimate: &PlatformVramEstimate, max_dim: u32, priority: StreamingPriority) -> u64 {
+    let drop = priority.dropped_mips();
+    estimate
+        .textures
+        .iter()
+        .map(|t| {
+            let (w, h) = compute_target_dimensions(t.width, t.height, max_dim);
+            texture_format_bytes(t.format, (w >> drop).max(1), (h >> drop).max(1), true)
+        })
+        .sum()
+}
+
+fn material_label(entry: &SceneMaterial, set: &MaterialSet) -> String {
+    set.name
+        .clone()
+        .or_else(|| entry.path.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| entry.path.display().to_string())
+}
+
+fn platform_budget(platform: Platform, budget: Option<u64>, materials: &[(SceneMaterial, MaterialSet)]) -> PlatformBudget {
+    let profile = platform.profile();
+    let estimates: Vec<PlatformVramEstimate> = materials
+        .iter()
+        .map(|(_, set)| estimate_vram_for_platform(set, &profile, true))
+        .collect();
+    let current_dims: Vec<u32> = estimates
+        .iter()
+        .map(|e| e.textures.iter().map(|t| t.width.max(t.height)).max().unwrap_or(0))
+        .collect();
+    let resident: Vec<u64> = estimates
+        .iter()
+        .zip(materials)
+        .zip(&current_dims)
+        .map(|((e, (m, _)), &dim)| resident_bytes(e, dim, m.priority))
+        .collect();
+    let full_bytes: u64 = estimates.iter().map(|e| e.bytes).sum();
+    let resident_total: u64 = resident.iter().sum();
+
+    let mut offenders: Vec<BudgetOffender> = materials
+        .iter()
+        .zip(&estimates)
+        .zip(&resident)
+        .map(|(((entry, set), estimate), &bytes)| {
+            let (width, height) = estimate
+                .textures
+                .iter()
+                .map(|t| (t.width, t.height))
+                .max_by_key(|&(w, h)| w as u64 * h as u64)
+                .unwrap_or((0, 0));
+            BudgetOffender {
+                material: material_label(entry, set),
+                path: entry.path.display().to_string(),
+                instances: entry.instances,
+                priority: entry.priority,
+                width,
+                height,
+                full_bytes: estimate.bytes,
+                resident_bytes: bytes,
+                resident_formatted: format_bytes(bytes),
+                share: if resident_total == 0 { 0.0 } else { bytes as f32 / resident_total as f32 },
+            }
+        })
+        .collect();
+    offenders.sort_by(|a, b| b.resident_bytes.cmp(&a.resident_bytes).then_with(|| a.path.cmp(&b.path)));
+
+    // Greedy: repeatedly take the one-step downgrade that saves the most bytes per unit of
+    // visual cost (priority weight scaled by how often the material is placed).
+    let mut dims = current_dims.clone();
+    let mut bytes = resident.clone();
+    let mut projected = resident_total;
+    if let Some(limit) = budget {
+        while projected > limit {
+            let mut best: Option<(usize, u32, u64, f64)> = None;
+            for (i, (entry, _)) in materials.iter().enumerate() {
+                let Some(next) = DOWNGRADE_STEPS.iter().copied().find(|&s| s < dims[i]) else {
+                    continue;
+                };
+                if next < MIN_DOWNGRADE_DIMENSION || entry.priority == StreamingPriority::Pinned {
+                    continue;
+                }
+                let after = resident_bytes(&estimates[i], next, entry.priority);
+                let saved = bytes[i].saturating_sub(after);
+                if saved == 0 {
+                    continue;
+                }
+                let cost = entry.priority.downgrade_weight() * (1.0 + (entry.instances.max(1) as f64).ln());
+                let value = saved as f64 / cost;
+                if best.is_none_or(|(_, _, _, v)| value > v) {
+                    best = Some((i, next, after, value));
+                }
+            }
+            let Some((i, next, after, _)) = best else {
+                break;
+            };
+            projected -= bytes[i] - after;
+            bytes[i] = after;
+            dims[i] = next;
+        }
+    }
+
+    let mut downgrades: Vec<SuggestedDowngrade> = materials
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| dims[i] != current_dims[i])
+        .map(|(i, (entry, set))| {
+            let saved = resident[i] - bytes[i];
+            SuggestedDowngrade {
+                material: material_label(entry, set),
+                path: entry.path.display().to_string(),
+                from_max_dimension: current_dims[i],
+                to_max_dimension: dims[i],
+                target: TargetResolution::from_max_dimension(dims[i]).label(),
+                saved_bytes: saved,
+                saved_formatted: format_bytes(saved),
+            }
+        })
+        .collect();
+    downgrades.sort_by(|a, b| b.saved_bytes.cmp(&a.saved_bytes).then_with(|| a.path.cmp(&b.path)));
+
+    let within_budget = budget.is_none_or(|b| resident_total <= b);
+    PlatformBudget {
+        platform,
+        budget_bytes: budget,
+        budget_formatted: budget.map(format_bytes),
+        full_bytes,
+        full_formatted: format_bytes(full_bytes),
+        resident_bytes: resident_total,
+        resident_formatted: format_bytes(resident_total),
+        within_budget,
+        over_by_bytes: budget.map_or(0, |b| resident_total.saturating_sub(b)),
+        offenders,
+        downgrades,
+        projected_bytes: projected,
+        projected_formatted: format_bytes(projected),
+        fits_after_downgrades: budget.is_none_or(|b| projected <= b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::TextureMap;
+
+    fn material(name: &str, size: u32) -> MaterialSet {
+        let texture = || TextureMap::new(size, size, [128, 128, 128, 255].repeat((size * size) as usize), None);
+        let mut set = MaterialSet::new();
+        set.name = Some(name.into());
+        set.albedo = Some(texture());
+        set.normal = Some(texture());
+        set
+    }
+
+    fn entry(path: &str, instances: u32, priority: StreamingPriority) -> SceneMaterial {
+        SceneMaterial { path: path.into(), instances, priority }
+    }
+
+    #[test]
+    fn manifest_parses_from_toml() {
+        let manifest: SceneManifest = toml::from_str(
+            r#"
+name = "Level01"
+
+[budgets]
+pc = 1024
+mobile = 128.5
+
+[[materials]]
+path = "Materials/Brick"
+instances = 40
+priority = "high"
+
+[[materials]]
+path = "Materials/Rubble"
+"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.budget_bytes(Platform::Pc), Some(1024 * 1024 * 1024));
+        assert_eq!(manifest.budget_bytes(Platform::Console), None);
+        assert_eq!(manifest.materials[0].priority, StreamingPriority::High);
+        assert_eq!(manifest.materials[1].instances, 1);
+        assert_eq!(manifest.materials[1].priority, StreamingPriority::Normal);
+    }
+
+    #[test]
+    fn streaming_priority_drops_top_mips() {
+        let set = material("a", 1024);
+        let est = estimate_vram_for_platform(&set, &Platform::Console.profile(), true);
+        let high = resident_bytes(&est, 1024, StreamingPriority::High);
+        let normal = resident_bytes(&est, 1024, StreamingPriority::Normal);
+        assert_eq!(high, est.bytes);
+        // Dropping the top mip leaves about a quarter of the chain
+        assert!(normal * 4 > high - high / 50 && normal * 4 < high + high / 50);
+    }
+
+    #[test]
+    fn over_budget_scene_downgrades_least_visible_material_first() {
+        let materials = vec![
+            (entry("hero", 200, StreamingPriority::High), material("hero", 2048)),
+            (entry("rock", 2, StreamingPriority::Low), material("rock", 2048)),
+            (entry("ui", 1, StreamingPriority::Pinned), material("ui", 1024)),
+        ];
+        let full = evaluate_loaded_scene(&SceneManifest::default(), &materials);
+        let console = &full.platforms[1];
+        assert!(console.within_budget && console.downgrades.is_empty());
+        assert_eq!(console.offenders[0].material, "hero");
+
+        let mut manifest = SceneManifest::default();
+        let rock = console.offenders.iter().find(|o| o.material == "rock").unwrap();
+        let target = console.resident_bytes - rock.resident_bytes / 2;
+        manifest.budgets.insert(Platform::Console, target as f64 / (1024.0 * 1024.0));
+        let report = evaluate_loaded_scene(&manifest, &materials);
+        let console = &report.platforms[1];
+        assert!(!console.within_budget);
+        assert!(console.fits_after_downgrades);
+        assert!(console.projected_bytes <= console.budget_bytes.unwrap());
+        assert_eq!(console.downgrades.len(), 1);
+        assert_eq!(console.downgrades[0].material, "rock");
+        assert_eq!(console.downgrades[0].resolution(), TargetResolution::Res1K);
+        assert!(!report.within_budget());
+    }
+}
@@ -0,0 +1,435 @@
+//! Budget-fitting optimizer.
+//!
+//! Chooses a resolution for every slot of every material so the set fits a
+//! platform memory budget with the least perceptual loss, then exports it.
+//! The loss of capping a slot is measured as `1 - SSIM` of the texture against
+//! itself downsampled and magnified back ([`resample_ssim`]), weighted by the
+//! slot's importance and by its complexity (variance and edge density from
+//! [`extract_features`]), since detailed textures show the loss more.
+//! Slots are lowered one resolution step at a time, always taking the step
+//! with the smallest loss per byte saved, until the budget is met.
+
+use crate::ai::extract_features;
+use crate::budget::{DOWNGRADE_STEPS, MIN_DOWNGRADE_DIMENSION};
+use crate::estimation::{estimate_vram_for_platform, format_bytes, texture_format_bytes, Platform, TextureFormat};
+use crate::incremental::material_output_names;
+use crate::material::{MaterialSet, TextureMap};
+use crate::optimization::{
+    compute_target_dimensions, export_levels, resize_texture, OptimizationPreset, PackingLayout, TargetResolution,
+};
+use crate::quality::resample_ssim;
+use crate::{Error, Result};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// GPU textures a material can have on a platform
+const UNIT_SLOTS: [&str; 7] = ["albedo", "normal", "orm", "roughness", "metallic", "ao", "height"];
+
+/// Fitting parameters.
+#[derive(Debug, Clone)]
+pub struct FitOptions {
+    /// Formats and ORM packing come from this platform's profile
+    pub platform: Platform,
+    pub budget_bytes: u64,
+    /// No slot is capped below this longest edge
+    pub min_dimension: u32,
+}
+
+impl FitOptions {
+    pub fn new(platform: Platform, budget_bytes: u64) -> Self {
+        Self {
+            platform,
+            budget_bytes,
+            min_dimension: MIN_DOWNGRADE_DIMENSION,
+        }
+    }
+}
+
+/// Chosen resolution for one GPU texture of a material.
+#[derive(Debug, Clone, Serialize)]
+pub struct SlotFit {
+    /// "albedo", "normal", "orm", "roughness", ...
+    pub slot: String,
+    pub format: TextureFormat,
+    pub original_width: u32,
+    pub original_height: u32,
+    pub width: u32,
+    pub height: u32,
+    /// `TargetResolution` label of the chosen size
+    pub target: String,
+    pub original_bytes: u64,
+    pub bytes: u64,
+    /// SSIM of the chosen size against the original (1.0 = unchanged)
+    pub ssim: f64,
+    /// Variance/edge-density complexity (0-1)
+    pub complexity: f32,
+    /// Weighted perceptual loss of the chosen size
+    pub loss: f64,
+}
+
+impl SlotFit {
+    /// Resolution to resize this slot to
+    pub fn resolution(&self) -> TargetResolution {
+        TargetResolution::from_max_dimension(self.width.max(self.height))
+    }
+
+    pub fn downgraded(&self) -> bool {
+        self.width != self.original_width || self.height != self.original_height
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MaterialFit {
+    pub material: String,
+    pub path: String,
+    pub original_bytes: u64,
+    pub bytes: u64,
+    pub loss: f64,
+    pub slots: Vec<SlotFit>,
+}
+
+/// Per-material, per-slot resolutions that fit the budget.
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetFitPlan {
+    pub platform: Platform,
+    pub budget_bytes: u64,
+    pub budget_formatted: String,
+    pub original_bytes: u64,
+    pub original_formatted: String,
+    pub fitted_bytes: u64,
+    pub fitted_formatted: String,
+    /// False when every slot reached the minimum size and the set is still over budget
+    pub fits: bool,
+    pub total_loss: f64,
+    pub materials: Vec<MaterialFit>,
+}
+
+impl BudgetFitPlan {
+    /// Serialize to formatted JSON string.
+    pub fn to_json(&self) -> std::result::Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// One resolution a slot can take.
+struct Candidate {
+    width: u32,
+    height: u32,
+    bytes: u64,
+    ssim: f64,
+    loss: f64,
+}
+
+/// A GPU texture of one material and its candidate sizes (largest first).
+struct Unit {
+    material: usize,
+    slot: &'static str,
+    format: TextureFormat,
+    complexity: f32,
+    candidates: Vec<Candidate>,
+}
+
+/// How much a loss in this slot shows in the final render.
+fn slot_weight(slot: &str) -> f64 {
+    match slot {
+        "albedo" | "normal" => 1.0,
+        "orm" => 0.6,
+        "height" => 0.4,
+        _ => 0.5,
+    }
+}
+
+/// Variance and edge density mapped to 0-1.
+fn complexity(texture: &TextureMap) -> f32 {
+    let f = extract_features(texture);
+    ((f.variance.sqrt() / 64.0).min(1.0) + f.edge_density.min(1.0)) / 2.0
+}
+
+/// Source textures behind a platform slot (ORM is three maps at one size).
+fn unit_sources<'a>(set: &'a MaterialSet, slot: &str) -> Vec<&'a TextureMap> {
+    match slot {
+        "orm" => [&set.roughness, &set.metallic, &set.ao].into_iter().flatten().collect(),
+        _ => crate::analysis::slot_textures(set)
+            .filter(|(s, _)| *s == slot)
+            .map(|(_, t)| t)
+            .collect(),
+    }
+}
+
+fn material_label(folder: &Path, set: &MaterialSet) -> String {
+    set.name
+        .clone()
+        .or_else(|| folder.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "material".to_string())
+}
+
+fn build_unit(
+    material: usize,
+    set: &MaterialSet,
+    slot: &'static str,
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+    options: &FitOptions,
+) -> Result<Unit> {
+    let sources = unit_sources(set, slot);
+    let complexity = if sources.is_empty() {
+        0.0
+    } else {
+        sources.iter().map(|t| complexity(t)).sum::<f32>() / sources.len() as f32
+    };
+    let weight = slot_weight(slot) * (0.5 + complexity as f64);
+    let longest = width.max(height);
+    let mut sizes = vec![longest];
+    sizes.extend(DOWNGRADE_STEPS.iter().copied().filter(|&s| s < longest && s >= options.min_dimension));
+
+    let mut candidates = Vec::with_capacity(sizes.len());
+    for max_dim in sizes {
+        let (w, h) = compute_target_dimensions(width, height, max_dim);
+        let ssim = if sources.is_empty() {
+            1.0
+        } else {
+            let mut sum = 0.0;
+            for t in &sources {
+                sum += resample_ssim(t, max_dim)?;
+            }
+            sum / sources.len() as f64
+        };
+        candidates.push(Candidate {
+            width: w,
+            height: h,
+            bytes: texture_format_bytes(format, w, h, true),
+            ssim,
+            loss: (1.0 - ssim).max(0.0) * weight,
+        });
+    }
+    Ok(Unit {
+        material,
+        slot,
+        format,
+        complexity,
+        candidates,
+    })
+}
+
+/// Compute per-material, per-slot resolutions that fit `options.budget_bytes`.
+pub fn plan_budget_fit(materials: &[(PathBuf, MaterialSet)], options: &FitOptions) -> Result<BudgetFitPlan> {
+    let profile = options.platform.profile();
+    let mut specs = Vec::new();
+    for (i, (_, set)) in materials.iter().enumerate() {
+        let estimate = estimate_vram_for_platform(set, &profile, true);
+        for t in estimate.textures {
+            let Some(slot) = UNIT_SLOTS.iter().copied().find(|s| *s == t.slot) else {
+                continue;
+            };
+            specs.push((i, slot, t.format, t.width, t.height));
+        }
+    }
+    let units: Vec<Unit> = specs
+        .par_iter()
+        .map(|&(i, slot, format, w, h)| build_unit(i, &materials[i].1, slot, format, w, h, options))
+        .collect::<Result<_>>()?;
+
+    let mut chosen = vec![0usize; units.len()];
+    let original_bytes: u64 = units.iter().map(|u| u.candidates[0].bytes).sum();
+    let mut total = original_bytes;
+    while total > options.budget_bytes {
+        let mut best: Option<(usize, f64)> = None;
+        for (u, unit) in units.iter().enumerate() {
+            let (cur, next) = match (unit.candidates.get(chosen[u]), unit.candidates.get(chosen[u] + 1)) {
+                (Some(c), Some(n)) => (c, n),
+                _ => continue,
+            };
+            let saved = cur.bytes.saturating_sub(next.bytes);
+            if saved == 0 {
+                continue;
+            }
+            let cost = (next.loss - cur.loss).max(0.0) / saved as f64;
+            if best.is_none_or(|(_, c)| cost < c) {
+                best = Some((u, cost));
+            }
+        }
+        let Some((u, _)) = best else {
+            break;
+        };
+        let unit = &units[u];
+        total -= unit.candidates[chosen[u]].bytes - unit.candidates[chosen[u] + 1].bytes;
+        chosen[u] += 1;
+    }
+
+    let mut fits: Vec<MaterialFit> = materials
+        .iter()
+        .map(|(folder, set)| MaterialFit {
+            material: material_label(folder, set),
+            path: folder.display().to_string(),
+            original_bytes: 0,
+            bytes: 0,
+            loss: 0.0,
+            slots: Vec::new(),
+        })
+        .collect();
+    for (unit, &c) in units.iter().zip(&chosen) {
+        let original = &unit.candidates[0];
+        let pick = &unit.candidates[c];
+        let fit = &mut fits[unit.material];
+        fit.original_bytes += original.bytes;
+        fit.bytes += pick.bytes;
+        fit.loss += pick.loss;
+        fit.slots.push(SlotFit {
+            slot: unit.slot.to_string(),
+            format: unit.format,
+            original_width: original.width,
+            original_height: original.height,
+            width: pick.width,
+            height: pick.height,
+            target: TargetResolution::from_max_dimension(pick.width.max(pick.height)).label(),
+            original_bytes: original.bytes,
+            bytes: pick.bytes,
+            ssim: pick.ssim,
+            complexity: unit.complexity,
+            loss: pick.loss,
+        });
+    }
+
+    Ok(BudgetFitPlan {
+        platform: options.platform,
+        budget_bytes: options.budget_bytes,
+        budget_formatted: format_bytes(options.budget_bytes),
+        original_bytes,
+        original_formatted: format_bytes(original_bytes),
+        fitted_bytes: total,
+        fitted_formatted: format_bytes(total),
+        fits: total <= options.budget_bytes,
+        total_loss: fits.iter().map(|m| m.loss).sum(),
+        materials: fits,
+    })
+}
+
+/// Resize every slot of a material to its planned resolution.
+fn apply_fit(set: &MaterialSet, fit: &MaterialFit) -> Result<MaterialSet> {
+    let mut out = set.clone();
+    for slot in &fit.slots {
+        let target = slot.resolution();
+        let resize = |t: &Option<TextureMap>| -> Result<Option<TextureMap>> {
+            t.as_ref().map(|t| resize_texture(t, target)).transpose()
+        };
+        match slot.slot.as_str() {
+            "albedo" => out.albedo = resize(&set.albedo)?,
+            "normal" => out.normal = resize(&set.normal)?,
+            "orm" => {
+                out.roughness = resize(&set.roughness)?;
+                out.metallic = resize(&set.metallic)?;
+                out.ao = resize(&set.ao)?;
+            }
+            "roughness" => out.roughness = resize(&set.roughness)?,
+            "metallic" => out.metallic = resize(&set.metallic)?,
+            "ao" => out.ao = resize(&set.ao)?,
+            "height" => out.height = resize(&set.height)?,
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+/// Export each material to `output_root/<folder name>/` at its planned
+/// resolutions; folders sharing a name get a numeric suffix (see
+/// [`material_output_names`]). `materials` must be the list the plan was
+/// computed from.
+pub fn export_budget_fit<P: AsRef<Path>>(
+    materials: &[(PathBuf, MaterialSet)],
+    plan: &BudgetFitPlan,
+    output_root: P,
+) -> Result<Vec<PathBuf>> {
+    if plan.materials.len() != materials.len()
+        || plan.materials.iter().zip(materials).any(|(fit, (folder, _))| fit.path != folder.display().to_string())
+    {
+        return Err(Error::Other("Budget fit plan does not match the materials being exported".into()));
+    }
+    let output_root = output_root.as_ref();
+    std::fs::create_dir_all(output_root)?;
+    let packing = if plan.platform.profile().pack_orm { PackingLayout::Orm } else { PackingLayout::Separate };
+    let preset = OptimizationPreset::unreal()
+        .with_packing(packing)
+        .with_compression(plan.platform)
+        .with_name(format!("fit-budget {} {}", plan.platform, plan.budget_formatted));
+    let folders: Vec<PathBuf> = materials.iter().map(|(folder, _)| folder.clone()).collect();
+    let mut written = Vec::new();
+    for (((_, set), fit), name) in materials.iter().zip(&plan.materials).zip(material_output_names(&folders)) {
+        let resized = apply_fit(set, fit)?;
+        let dir = output_root.join(name);
+        written.extend(export_levels(set, &resized, &Default::default(), &dir, &preset, false, &Default::default())?);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::noise_texture;
+
+    fn flat(size: u32) -> TextureMap {
+        TextureMap::new(size, size, [120, 110, 100, 255].repeat((size * size) as usize), None)
+    }
+
+    fn material(name: &str, albedo: TextureMap) -> (PathBuf, MaterialSet) {
+        let mut set = MaterialSet::new();
+        set.name = Some(name.into());
+        set.albedo = Some(albedo);
+        (PathBuf::from(name), set)
+    }
+
+    #[test]
+    fn fits_budget_by_downgrading_the_smooth_material() {
+        let materials = vec![material("detailed", noise_texture(1024, 1024, 1)), material("plain", flat(1024))];
+        let full = plan_budget_fit(&materials, &FitOptions::new(Platform::Console, u64::MAX)).unwrap();
+        assert!(full.fits);
+        assert_eq!(full.fitted_bytes, full.original_bytes);
+        assert!(full.materials.iter().all(|m| m.slots.iter().all(|s| !s.downgraded())));
+
+        // Room for one full-size albedo plus a bit
+        let budget = full.original_bytes * 6 / 10;
+        let plan = plan_budget_fit(&materials, &FitOptions::new(Platform::Console, budget)).unwrap();
+        assert!(plan.fits);
+        assert!(plan.fitted_bytes <= budget);
+        let detailed = &plan.materials[0].slots[0];
+        let plain = &plan.materials[1].slots[0];
+        assert!(!detailed.downgraded());
+        assert!(plain.downgraded());
+        assert!(plain.ssim > 0.99);
+    }
+
+    #[test]
+    fn reports_when_minimum_size_cannot_fit() {
+        let materials = vec![material("a", noise_texture(512, 512, 2))];
+        let plan = plan_budget_fit(&materials, &FitOptions::new(Platform::Pc, 1024)).unwrap();
+        assert!(!plan.fits);
+        assert_eq!(plan.materials[0].slots[0].width, MIN_DOWNGRADE_DIMENSION);
+    }
+
+    #[test]
+    fn export_writes_planned_resolutions() {
+        let materials = vec![material("plain", flat(512))];
+        let mut plan = plan_budget_fit(&materials, &FitOptions::new(Platform::Console, u64::MAX)).unwrap();
+        plan.materials[0].slots[0].width = 256;
+        plan.materials[0].slots[0].height = 256;
+        let out = std::env::temp_dir().join(format!("pbr_budget_fit_{}", std::process::id()));
+        let written = export_budget_fit(&materials, &plan, &out).unwrap();
+        assert_eq!(written.len(), 1);
+        let img = image::open(&written[0]).unwrap();
+        assert_eq!((img.width(), img.height()), (256, 256));
+        let _ = std::fs::remove_dir_all(&out);
+    }
+
+    #[test]
+    fn export_keeps_materials_with_the_same_name_apart() {
+        let materials = vec![material("a/Brick", flat(64)), material("b/Brick", noise_texture(64, 64, 3))];
+        let plan = plan_budget_fit(&materials, &FitOptions::new(Platform::Console, u64::MAX)).unwrap();
+        let out = std::env::temp_dir().join(format!("pbr_budget_fit_names_{}", std::process::id()));
+        let written = export_budget_fit(&materials, &plan, &out).unwrap();
+        assert_eq!(written.len(), 2);
+        assert!(written[0].starts_with(out.join("Brick")));
+        assert!(written[1].starts_with(out.join("Brick_2")));
+        let _ = std::fs::remove_dir_all(&out);
+    }
+}
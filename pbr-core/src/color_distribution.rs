@@ -0,0 +1,492 @@
+//! Histogram and color distribution analysis.
+//!
+//! For each texture this produces what an artist would otherwise read off a
+//! Levels panel: per-channel and luminance histograms with their cumulative
+//! distributions and percentiles, the dominant colors (k-means in CIE Lab),
+//! and a gamut summary of lightness, chroma and hue. Histograms come from the
+//! memoized [`TextureStats`](crate::texture_stats::TextureStats); the color
+//! work runs on a bounded pixel sample so 8K textures cost the same as 1K.
+
+use crate::analysis::material_textures;
+use crate::material::{MaterialSet, TextureMap};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Pixels sampled for the gamut summary and k-means
+const MAX_COLOR_SAMPLES: usize = 65_536;
+
+/// Samples clustered by k-means (taken evenly from the color samples)
+const MAX_KMEANS_SAMPLES: usize = 4_096;
+
+/// Dominant colors reported per texture
+pub const DOMINANT_COLOR_COUNT: usize = 5;
+
+const KMEANS_ITERATIONS: usize = 12;
+
+/// Lab distance below which no further seed is added (flat textures get fewer colors)
+const MIN_SEED_DISTANCE: f32 = 2.0;
+
+/// Lab chroma below which a pixel counts as neutral gray
+const GRAY_CHROMA: f32 = 4.0;
+
+/// Hue histogram bins (30° each, starting at 0° = Lab +a)
+pub const HUE_BINS: usize = 12;
+
+/// Value percentiles of an 8-bit channel.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Percentiles {
+    pub p1: u8,
+    pub p5: u8,
+    pub p25: u8,
+    pub p50: u8,
+    pub p75: u8,
+    pub p95: u8,
+    pub p99: u8,
+}
+
+/// Histogram, cumulative distribution and summary values for one channel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelDistribution {
+    /// 256-bin value histogram (pixel counts)
+    pub histogram: Vec<u32>,
+    /// Fraction of pixels at or below each value (256 entries, ends at 1.0)
+    pub cumulative: Vec<f32>,
+    pub percentiles: Percentiles,
+    pub mean: f64,
+    /// Population standard deviation
+    pub stddev: f64,
+    pub min: u8,
+    pub max: u8,
+}
+
+impl ChannelDistribution {
+    pub fn from_histogram(histogram: &[u32]) -> Self {
+        let total: u64 = histogram.iter().map(|&c| c as u64).sum();
+        let mut cumulative = Vec::with_capacity(histogram.len());
+        let mut running = 0u64;
+        for &c in histogram {
+            running += c as u64;
+            cumulative.push(if total == 0 { 0.0 } else { (running as f64 / total as f64) as f32 });
+        }
+        let (mean, stddev) = if total == 0 {
+            (0.0, 0.0)
+        } else {
+            let mean = histogram.iter().enumerate().map(|(v, &c)| v as f64 * c as f64).sum::<f64>() / total as f64;
+            let var = histogram
+                .iter()
+                .enumerate()
+                .map(|(v, &c)| (v as f64 - mean).powi(2) * c as f64)
+                .sum::<f64>()
+                / total as f64;
+            (mean, var.sqrt())
+        };
+        let percentile = |p: f32| -> u8 {
+            cumulative.iter().position(|&c| c >= p).unwrap_or(0).min(255) as u8
+        };
+        let percentiles = Percentiles {
+            p1: percentile(0.01),
+            p5: percentile(0.05),
+            p25: percentile(0.25),
+            p50: percentile(0.50),
+            p75: percentile(0.75),
+            p95: percentile(0.95),
+            p99: percentile(0.99),
+        };
+        Self {
+            histogram: histogram.to_vec(),
+            percentiles,
+            mean,
+            stddev,
+            min: histogram.iter().position(|&c| c > 0).unwrap_or(0) as u8,
+            max: histogram.iter().rposition(|&c| c > 0).unwrap_or(0) as u8,
+            cumulative,
+        }
+    }
+}
+
+/// One k-means cluster center.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DominantColor {
+    /// Mean sRGB color of the cluster members
+    pub rgb: [u8; 3],
+    /// `#rrggbb`
+    pub hex: String,
+    /// Fraction of sampled pixels in this cluster (0-1)
+    pub share: f32,
+}
+
+/// Where the texture's colors sit in CIE Lab (D65).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GamutSummary {
+    /// Pixels sampled for this summary
+    pub samples: usize,
+    /// L* (0-100)
+    pub lightness_p1: f32,
+    pub lightness_mean: f32,
+    pub lightness_p99: f32,
+    /// Lab chroma (0 = gray; saturated sRGB primaries reach ~130)
+    pub chroma_mean: f32,
+    pub chroma_p95: f32,
+    pub chroma_max: f32,
+    /// Fraction of pixels with chroma below the gray threshold
+    pub grayscale_ratio: f32,
+    /// Fraction of pixels with any RGB channel at 0 or 255
+    pub clipped_ratio: f32,
+    /// Fraction of chromatic pixels pushed against the sRGB gamut boundary
+    pub gamut_edge_ratio: f32,
+    /// Share of chromatic pixels per 30° hue bin (sums to 1, or all 0 for gray textures)
+    pub hue_histogram: Vec<f32>,
+}
+
+/// Color distribution of one texture.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextureColorDistribution {
+    pub path: String,
+    pub slot: String,
+    pub material_name: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    pub red: ChannelDistribution,
+    pub green: ChannelDistribution,
+    pub blue: ChannelDistribution,
+    /// Only present when some pixel is not fully opaque
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alpha: Option<ChannelDistribution>,
+    /// Rec.601 luminance
+    pub luminance: ChannelDistribution,
+    /// Largest cluster first
+    pub dominant_colors: Vec<DominantColor>,
+    pub gamut: GamutSummary,
+}
+
+/// Analyze one texture.
+pub fn color_distribution(texture: &TextureMap) -> TextureColorDistribution {
+    let stats = texture.stats();
+    let channel = |i: usize| ChannelDistribution::from_histogram(&stats.channels[i].histogram);
+    let alpha = (stats.channels[3].min < 255).then(|| channel(3));
+
+    let samples = sample_pixels(texture);
+    let labs: Vec<[f32; 3]> = samples.iter().map(|&p| srgb_to_lab(p)).collect();
+    let clipped_ratio = if stats.pixel_count == 0 {
+        0.0
+    } else {
+        stats.clipped_pixels as f32 / stats.pixel_count as f32
+    };
+
+    TextureColorDistribution {
+        path: texture.path.as_ref().map(|p| p.display().to_string()).unwrap_or_default(),
+        slot: String::new(),
+        material_name: None,
+        width: texture.width,
+        height: texture.height,
+        red: channel(0),
+        green: channel(1),
+        blue: channel(2),
+        alpha,
+        luminance: ChannelDistribution::from_histogram(&stats.luminance_histogram),
+        dominant_colors: dominant_colors(&samples, &labs, DOMINANT_COLOR_COUNT),
+        gamut: gamut_summary(&samples, &labs, clipped_ratio),
+    }
+}
+
+/// Analyze every texture of every material, in material and slot order.
+pub fn analyze_color_distributions(materials: &[(PathBuf, MaterialSet)]) -> Vec<TextureColorDistribution> {
+    material_textures(materials)
+        .par_iter()
+        .map(|(name, slot, texture)| TextureColorDistribution {
+            slot: slot.to_string(),
+            material_name: name.clone(),
+            ..color_distribution(texture)
+        })
+        .collect()
+}
+
+/// Analyze the textures of a single material (for per-material reports).
+/// Textures without pixel data are skipped.
+pub fn material_color_distributions(set: &MaterialSet) -> Vec<TextureColorDistribution> {
+    crate::analysis::slot_textures(set)
+        .filter(|(_, texture)| texture.has_pixels())
+        .collect::<Vec<_>>()
+        .par_iter()
+        .map(|(slot, texture)| TextureColorDistribution {
+            slot: slot.to_string(),
+            material_name: set.name.clone(),
+            ..color_distribution(texture)
+        })
+        .collect()
+}
+
+/// Evenly strided RGB samples, at most [`MAX_COLOR_SAMPLES`].
+fn sample_pixels(texture: &TextureMap) -> Vec<[u8; 3]> {
+    let (w, h) = (texture.width as usize, texture.height as usize);
+    let available = (w * h).min(texture.data.len() / 4);
+    if available == 0 {
+        return Vec::new();
+    }
+    let step = ((available as f64 / MAX_COLOR_SAMPLES as f64).sqrt().ceil() as usize).max(1);
+    let mut out = Vec::with_capacity((w / step + 1) * (h / step + 1));
+    for y in (0..h).step_by(step) {
+        for x in (0..w).step_by(step) {
+            let i = (y * w + x) * 4;
+            if i + 3 < texture.data.len() {
+                out.push([texture.data[i], texture.data[i + 1], texture.data[i + 2]]);
+            }
+        }
+    }
+    out
+}
+
+fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// sRGB (D65) to CIE L*a*b*.
+fn srgb_to_lab(rgb: [u8; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb.map(srgb_to_linear);
+    let x = (0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b) / 0.950_47;
+    let y = 0.212_672_9 * r + 0.715_152_2 * g + 0.072_175 * b;
+    let z = (0.019_333_9 * r + 0.119_192 * g + 0.950_304_1 * b) / 1.088_83;
+    let f = |t: f32| {
+        if t > 216.0 / 24_389.0 {
+            t.cbrt()
+        } else {
+            (24_389.0 / 27.0 * t + 16.0) / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+fn lab_distance_sq(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+fn nearest(centers: &[[f32; 3]], lab: &[f32; 3]) -> usize {
+    let mut best = 0;
+    let mut best_d = f32::MAX;
+    for (i, c) in centers.iter().enumerate() {
+        let d = lab_distance_sq(c, lab);
+        if d < best_d {
+            best_d = d;
+            best = i;
+        }
+    }
+    best
+}
+
+/// Deterministic k-means in Lab: farthest-point seeding from the sample
+/// closest to the mean, then Lloyd iterations until assignments settle.
+fn dominant_colors(samples: &[[u8; 3]], labs: &[[f32; 3]], k: usize) -> Vec<DominantColor> {
+    if samples.is_empty() || k == 0 {
+        return Vec::new();
+    }
+    let stride = samples.len().div_ceil(MAX_KMEANS_SAMPLES);
+    let idx: Vec<usize> = (0..samples.len()).step_by(stride).collect();
+
+    let n = idx.len() as f32;
+    let mean = idx.iter().fold([0.0f32; 3], |acc, &i| {
+        [acc[0] + labs[i][0] / n, acc[1] + labs[i][1] / n, acc[2] + labs[i][2] / n]
+    });
+    let first = idx
+        .iter()
+        .copied()
+        .min_by(|&a, &b| lab_distance_sq(&labs[a], &mean).total_cmp(&lab_distance_sq(&labs[b], &mean)))
+        .unwrap_or(0);
+    let mut centers = vec![labs[first]];
+    let mut min_dist: Vec<f32> = idx.iter().map(|&i| lab_distance_sq(&labs[i], &labs[first])).collect();
+    while centers.len() < k {
+        let (far, &d) = min_dist
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap_or((0, &0.0));
+        if d < MIN_SEED_DISTANCE * MIN_SEED_DISTANCE {
+            break;
+        }
+        let lab = labs[idx[far]];
+        centers.push(lab);
+        for (m, &i) in min_dist.iter_mut().zip(&idx) {
+            *m = m.min(lab_distance_sq(&labs[i], &lab));
+        }
+    }
+
+    let mut assignment = vec![usize::MAX; idx.len()];
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut changed = false;
+        for (a, &i) in assignment.iter_mut().zip(&idx) {
+            let c = nearest(&centers, &labs[i]);
+            if *a != c {
+                *a = c;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+        let mut sums = vec![[0.0f32; 3]; centers.len()];
+        let mut counts = vec![0usize; centers.len()];
+        for (&a, &i) in assignment.iter().zip(&idx) {
+            for (s, v) in sums[a].iter_mut().zip(labs[i]) {
+                *s += v;
+            }
+            counts[a] += 1;
+        }
+        for ((center, sum), &count) in centers.iter_mut().zip(&sums).zip(&counts) {
+            if count > 0 {
+                *center = sum.map(|s| s / count as f32);
+            }
+        }
+    }
+
+    let mut rgb_sums = vec![[0u64; 3]; centers.len()];
+    let mut counts = vec![0usize; centers.len()];
+    for (&a, &i) in assignment.iter().zip(&idx) {
+        for (s, v) in rgb_sums[a].iter_mut().zip(samples[i]) {
+            *s += v as u64;
+        }
+        counts[a] += 1;
+    }
+    let mut colors: Vec<DominantColor> = rgb_sums
+        .iter()
+        .zip(&counts)
+        .filter(|(_, &count)| count > 0)
+        .map(|(sum, &count)| {
+            let rgb = sum.map(|s| ((s as f64 / count as f64).round()) as u8);
+            DominantColor {
+                rgb,
+                hex: format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2]),
+                share: count as f32 / idx.len() as f32,
+            }
+        })
+        .collect();
+    colors.sort_by(|a, b| b.share.total_cmp(&a.share).then_with(|| a.hex.cmp(&b.hex)));
+    colors
+}
+
+fn gamut_summary(samples: &[[u8; 3]], labs: &[[f32; 3]], clipped_ratio: f32) -> GamutSummary {
+    let n = labs.len();
+    if n == 0 {
+        return GamutSummary {
+            samples: 0,
+            lightness_p1: 0.0,
+            lightness_mean: 0.0,
+            lightness_p99: 0.0,
+            chroma_mean: 0.0,
+            chroma_p95: 0.0,
+            chroma_max: 0.0,
+            grayscale_ratio: 0.0,
+            clipped_ratio,
+            gamut_edge_ratio: 0.0,
+            hue_histogram: vec![0.0; HUE_BINS],
+        };
+    }
+    let mut lightness: Vec<f32> = labs.iter().map(|l| l[0]).collect();
+    let mut chroma: Vec<f32> = labs.iter().map(|l| l[1].hypot(l[2])).collect();
+    let mut hue_counts = [0usize; HUE_BINS];
+    let mut gray = 0usize;
+    let mut edge = 0usize;
+    for ((lab, &c), rgb) in labs.iter().zip(&chroma).zip(samples) {
+        if c < GRAY_CHROMA {
+            gray += 1;
+            continue;
+        }
+        let hue = lab[2].atan2(lab[1]).to_degrees().rem_euclid(360.0);
+        hue_counts[((hue / (360.0 / HUE_BINS as f32)) as usize).min(HUE_BINS - 1)] += 1;
+        if rgb.iter().any(|&v| v == 0 || v == 255) {
+            edge += 1;
+        }
+    }
+    let chromatic = n - gray;
+    let lightness_mean = lightness.iter().sum::<f32>() / n as f32;
+    let chroma_mean = chroma.iter().sum::<f32>() / n as f32;
+    lightness.sort_by(f32::total_cmp);
+    chroma.sort_by(f32::total_cmp);
+    let at = |v: &[f32], p: f32| v[((v.len() - 1) as f32 * p).round() as usize];
+
+    GamutSummary {
+        samples: n,
+        lightness_p1: at(&lightness, 0.01),
+        lightness_mean,
+        lightness_p99: at(&lightness, 0.99),
+        chroma_mean,
+        chroma_p95: at(&chroma, 0.95),
+        chroma_max: chroma[n - 1],
+        grayscale_ratio: gray as f32 / n as f32,
+        clipped_ratio,
+        gamut_edge_ratio: if chromatic == 0 { 0.0 } else { edge as f32 / chromatic as f32 },
+        hue_histogram: hue_counts
+            .iter()
+            .map(|&c| if chromatic == 0 { 0.0 } else { c as f32 / chromatic as f32 })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_tone(w: u32, h: u32, left: [u8; 3], right: [u8; 3]) -> TextureMap {
+        let mut data = Vec::with_capacity((w * h * 4) as usize);
+        for _y in 0..h {
+            for x in 0..w {
+                let c = if x < w / 4 { left } else { right };
+                data.extend_from_slice(&[c[0], c[1], c[2], 255]);
+            }
+        }
+        TextureMap::new(w, h, data, None)
+    }
+
+    #[test]
+    fn cumulative_and_percentiles_follow_histogram() {
+        let mut hist = vec![0u32; 256];
+        hist[10] = 50;
+        hist[200] = 50;
+        let d = ChannelDistribution::from_histogram(&hist);
+        assert_eq!(d.cumulative.len(), 256);
+        assert!((d.cumulative[10] - 0.5).abs() < 1e-6);
+        assert!((d.cumulative[255] - 1.0).abs() < 1e-6);
+        assert_eq!(d.percentiles.p25, 10);
+        assert_eq!(d.percentiles.p50, 10);
+        assert_eq!(d.percentiles.p75, 200);
+        assert_eq!((d.min, d.max), (10, 200));
+        assert!((d.mean - 105.0).abs() < 1e-9);
+        assert!((d.stddev - 95.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn dominant_colors_find_both_tones_with_shares() {
+        let t = two_tone(64, 64, [220, 30, 30], [40, 40, 40]);
+        let d = color_distribution(&t);
+        assert_eq!(d.dominant_colors.len(), 2);
+        assert_eq!(d.dominant_colors[0].hex, "#282828");
+        assert!((d.dominant_colors[0].share - 0.75).abs() < 0.02);
+        assert_eq!(d.dominant_colors[1].rgb, [220, 30, 30]);
+        assert!(d.alpha.is_none());
+    }
+
+    #[test]
+    fn gamut_summary_reports_gray_share_and_red_hue() {
+        let t = two_tone(64, 64, [220, 30, 30], [128, 128, 128]);
+        let g = color_distribution(&t).gamut;
+        assert!((g.grayscale_ratio - 0.75).abs() < 0.02);
+        assert!(g.chroma_max > 60.0);
+        assert_eq!(g.hue_histogram.len(), HUE_BINS);
+        // sRGB red sits around 40° in Lab
+        assert!(g.hue_histogram[1] > 0.99);
+        assert!(g.lightness_p99 > 50.0 && g.lightness_p1 < 55.0);
+    }
+
+    #[test]
+    fn lab_conversion_matches_reference_white_and_black() {
+        let white = srgb_to_lab([255, 255, 255]);
+        assert!((white[0] - 100.0).abs() < 0.1 && white[1].abs() < 0.1 && white[2].abs() < 0.1);
+        let black = srgb_to_lab([0, 0, 0]);
+        assert!(black[0].abs() < 0.1);
+    }
+}
@@ -0,0 +1,564 @@
+//! Exact duplicate detection and deduplication.
+//!
+//! Runs before any perceptual comparison: textures are grouped by a SHA-256
+//! of their pixels at full precision (16-bit and float sources are hashed
+//! from their own decode, not the RGBA8 copy, so quantization never makes two
+//! files equal), and each duplicate records whether its file is
+//! byte-identical to the shared texture or only pixel-identical in a different
+//! container (e.g. the same data saved as PNG and TGA). The report totals the
+//! disk and VRAM that deduplication would save; [`apply_dedup`] keeps one
+//! texture per group, deletes the others and records them in a
+//! [`DEDUP_MANIFEST_FILE`] that [`MaterialSet::load_from_folder`] follows.
+
+use crate::analysis::material_textures;
+use crate::estimation::{estimate_texture_bytes, format_bytes};
+use crate::material::{MaterialSet, TextureMap};
+use crate::{Error, Result};
+use image::{ColorType, ImageDecoder};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Manifest written by [`apply_dedup`] to the manifest folder and to every
+/// material folder that lost a texture
+pub const DEDUP_MANIFEST_FILE: &str = "dedup-manifest.json";
+
+/// How a duplicate matches the shared texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExactMatch {
+    /// Same file bytes
+    Bytes,
+    /// Same decoded pixels, different file (container, compression or metadata)
+    Pixels,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExactDuplicate {
+    pub path: String,
+    pub slot: String,
+    pub material_name: Option<String>,
+    #[serde(rename = "match")]
+    pub match_kind: ExactMatch,
+    /// File size on disk
+    pub file_bytes: u64,
+}
+
+/// Textures with identical pixels; `shared_path` is kept, `duplicates` can go.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExactDuplicateGroup {
+    /// SHA-256 of the pixels at full precision (hex)
+    pub pixel_hash: String,
+    pub width: u32,
+    pub height: u32,
+    /// Texture kept as the shared copy (the smallest file of the group)
+    pub shared_path: String,
+    pub shared_slot: String,
+    pub shared_material: Option<String>,
+    pub shared_file_bytes: u64,
+    pub duplicates: Vec<ExactDuplicate>,
+    /// Disk freed by removing the duplicates
+    pub disk_bytes_saved: u64,
+    /// VRAM (RGBA8 with mipmaps) freed by loading the texture once
+    pub vram_bytes_saved: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExactDuplicateReport {
+    pub texture_count: usize,
+    pub duplicate_count: usize,
+    /// Duplicates whose file bytes match the shared texture
+    pub byte_identical_count: usize,
+    /// Duplicates with identical pixels in a different file
+    pub pixel_identical_count: usize,
+    pub disk_bytes_saved: u64,
+    pub disk_saved: String,
+    pub vram_bytes_saved: u64,
+    pub vram_saved: String,
+    pub groups: Vec<ExactDuplicateGroup>,
+}
+
+impl ExactDuplicateReport {
+    pub fn to_json(&self) -> std::result::Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// One shared texture and the files it replaced. In a manifest file, paths are
+/// relative to the folder the manifest is in, so the library can be moved as a
+/// whole.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DedupManifestEntry {
+    pub shared: String,
+    pub pixel_hash: String,
+    pub replaced: Vec<String>,
+}
+
+/// Written by [`apply_dedup`]; maps every removed texture path to its shared copy.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DedupManifest {
+    pub disk_bytes_saved: u64,
+    pub entries: Vec<DedupManifestEntry>,
+}
+
+impl DedupManifest {
+    pub fn to_json(&self) -> std::result::Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Read a manifest written by [`apply_dedup`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json)
+            .map_err(|e| Error::Other(format!("Invalid dedup manifest {}: {}", path.display(), e)))
+    }
+
+    /// Write the manifest to `dir` with its paths made relative to `dir`,
+    /// merging with one already there.
+    fn merge_into(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(DEDUP_MANIFEST_FILE);
+        let mut merged = if path.exists() { Self::load(&path)? } else { Self::default() };
+        merged.disk_bytes_saved += self.disk_bytes_saved;
+        merged.entries.extend(self.relative_to(dir).entries);
+        fs::write(&path, merged.to_json()?)?;
+        Ok(())
+    }
+
+    /// Copy with every path relative to `dir`.
+    fn relative_to(&self, dir: &Path) -> Self {
+        let relative = |p: &str| relative_path(dir, Path::new(p)).display().to_string();
+        Self {
+            disk_bytes_saved: self.disk_bytes_saved,
+            entries: self
+                .entries
+                .iter()
+                .map(|e| DedupManifestEntry {
+                    shared: relative(&e.shared),
+                    pixel_hash: e.pixel_hash.clone(),
+                    replaced: e.replaced.iter().map(|r| relative(r)).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Removed textures of `folder` (the folder this manifest was read from)
+    /// that have not been put back, as (removed path, shared path). Fails when
+    /// the shared copy of one of them is gone.
+    pub fn replacements_in(&self, folder: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+        let mut replacements = Vec::new();
+        for entry in &self.entries {
+            let shared = normalize_path(&folder.join(&entry.shared));
+            for replaced in &entry.replaced {
+                let Some(name) = Path::new(replaced).file_name() else {
+                    continue;
+                };
+                let local = folder.join(name);
+                if local.exists() {
+                    continue;
+                }
+                if !shared.is_file() {
+                    return Err(Error::Other(format!(
+                        "Shared copy {} of {} is missing (see {})",
+                        shared.display(),
+                        local.display(),
+                        folder.join(DEDUP_MANIFEST_FILE).display()
+                    )));
+                }
+                replacements.push((local, shared.clone()));
+            }
+        }
+        Ok(replacements)
+    }
+
+    /// Shared texture that replaced `path`, for a manifest read from `dir`.
+    pub fn resolve(&self, dir: &Path, path: &Path) -> Option<PathBuf> {
+        let path = normalize_path(path);
+        self.entries
+            .iter()
+            .find(|e| e.replaced.iter().any(|r| normalize_path(&dir.join(r)) == path))
+            .map(|e| normalize_path(&dir.join(&e.shared)))
+    }
+}
+
+/// `path` relative to `dir` (both made absolute first), or `path` itself when
+/// they share no root, e.g. on different drives.
+fn relative_path(dir: &Path, path: &Path) -> PathBuf {
+    let (Ok(dir), Ok(abs)) = (std::path::absolute(dir), std::path::absolute(path)) else {
+        return path.to_path_buf();
+    };
+    let (dir, abs) = (normalize_path(&dir), normalize_path(&abs));
+    let dir_parts: Vec<_> = dir.components().collect();
+    let path_parts: Vec<_> = abs.components().collect();
+    let common = dir_parts.iter().zip(&path_parts).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return abs;
+    }
+    let mut relative: PathBuf = dir_parts[common..].iter().map(|_| Component::ParentDir).collect();
+    relative.extend(&path_parts[common..]);
+    relative
+}
+
+/// `path` with `.` and `..` components folded away, without touching the disk.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(out.components().next_back(), Some(Component::Normal(_))) => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// SHA-256 over the dimensions and RGBA8 data, so equal pixels hash equally
+/// regardless of the file format they were loaded from.
+pub(crate) fn pixel_digest(map: &TextureMap) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(map.width.to_le_bytes());
+    hasher.update(map.height.to_le_bytes());
+    hasher.update(&map.data);
+    hasher.finalize().into()
+}
+
+/// SHA-256 of the texture at full precision. 8-bit sources hash their RGBA8
+/// data ([`pixel_digest`]); 16-bit and float sources hash their unclamped
+/// float decode, so they never match an 8-bit copy that only agrees after
+/// quantization. A file that can't be re-decoded hashes its path and so
+/// matches nothing.
+pub(crate) fn precision_digest(map: &TextureMap) -> [u8; 32] {
+    let Some(path) = map.path.as_deref() else {
+        return pixel_digest(map);
+    };
+    match high_precision_pixels(path) {
+        Ok(None) => pixel_digest(map),
+        Ok(Some(pixels)) => {
+            let mut hasher = Sha256::new();
+            hasher.update(b"rgba32f");
+            hasher.update(map.width.to_le_bytes());
+            hasher.update(map.height.to_le_bytes());
+            for v in pixels {
+                hasher.update(v.to_le_bytes());
+            }
+            hasher.finalize().into()
+        }
+        Err(_) => {
+            let mut hasher = Sha256::new();
+            hasher.update(b"unreadable");
+            hasher.update(path.display().to_string());
+            hasher.finalize().into()
+        }
+    }
+}
+
+/// Float pixels of `path` when it stores more than 8 bits per channel;
+/// `None` for 8-bit sources (only the header is read).
+fn high_precision_pixels(path: &Path) -> Result<Option<Vec<f32>>> {
+    let decoder = image::ImageReader::open(path)?.with_guessed_format()?.into_decoder()?;
+    if matches!(decoder.color_type(), ColorType::L8 | ColorType::La8 | ColorType::Rgb8 | ColorType::Rgba8) {
+        return Ok(None);
+    }
+    let image = image::DynamicImage::from_decoder(decoder)?;
+    Ok(Some(image.to_rgba32f().into_raw()))
+}
+
+/// SHA-256 of the texture's file, or `None` when it has no readable file.
+pub(crate) fn file_digest(map: &TextureMap) -> Option<[u8; 32]> {
+    let bytes = fs::read(map.path.as_ref()?).ok()?;
+    Some(Sha256::digest(&bytes).into())
+}
+
+fn hex(digest: &[u8; 32]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+struct Candidate<'a> {
+    material: Option<String>,
+    slot: &'static str,
+    map: &'a TextureMap,
+    path: &'a Path,
+    pixels: [u8; 32],
+    file: Option<[u8; 32]>,
+    file_bytes: u64,
+}
+
+/// Group byte- and pixel-identical textures across all materials and slots.
+/// Textures without a file are skipped, as are repeated references to the same
+/// file (those are already shared).
+pub fn find_exact_duplicates(materials: &[(PathBuf, MaterialSet)]) -> ExactDuplicateReport {
+    let mut seen = HashSet::new();
+    let textures: Vec<_> = material_textures(materials)
+        .into_iter()
+        .filter(|(_, _, t)| t.path.as_ref().is_some_and(|p| seen.insert(p.clone())))
+        .collect();
+
+    let candidates: Vec<Candidate> = textures
+        .par_iter()
+        .map(|(material, slot, map)| {
+            let path = map.path.as_deref().unwrap_or(Path::new(""));
+            Candidate {
+                material: material.clone(),
+                slot,
+                map,
+                path,
+                pixels: precision_digest(map),
+                file: file_digest(map),
+                file_bytes: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            }
+        })
+        .collect();
+
+    let mut by_pixels: HashMap<[u8; 32], Vec<usize>> = HashMap::new();
+    let mut order = Vec::new();
+    for (i, c) in candidates.iter().enumerate() {
+        let members = by_pixels.entry(c.pixels).or_default();
+        if members.is_empty() {
+            order.push(c.pixels);
+        }
+        members.push(i);
+    }
+
+    let mut groups = Vec::new();
+    for key in order {
+        let members = &by_pixels[&key];
+        if members.len() < 2 {
+            continue;
+        }
+        // Keep the smallest file; ties keep the first texture
+        let shared = *members
+            .iter()
+            .min_by_key(|&&i| candidates[i].file_bytes)
+            .expect("group has members");
+        let s = &candidates[shared];
+        let vram = estimate_texture_bytes(s.map.width, s.map.height, true);
+        let duplicates: Vec<ExactDuplicate> = members
+            .iter()
+            .filter(|&&i| i != shared)
+            .map(|&i| {
+                let c = &candidates[i];
+                ExactDuplicate {
+                    path: c.path.display().to_string(),
+                    slot: c.slot.to_string(),
+                    material_name: c.material.clone(),
+                    match_kind: if c.file.is_some() && c.file == s.file {
+                        ExactMatch::Bytes
+                    } else {
+                        ExactMatch::Pixels
+                    },
+                    file_bytes: c.file_bytes,
+                }
+            })
+            .collect();
+        groups.push(ExactDuplicateGroup {
+            pixel_hash: hex(&key),
+            width: s.map.width,
+            height: s.map.height,
+            shared_path: s.path.display().to_string(),
+            shared_slot: s.slot.to_string(),
+            shared_material: s.material.clone(),
+            shared_file_bytes: s.file_bytes,
+            disk_bytes_saved: duplicates.iter().map(|d| d.file_bytes).sum(),
+            vram_bytes_saved: vram * duplicates.len() as u64,
+            duplicates,
+        });
+    }
+
+    let all = || groups.iter().flat_map(|g| &g.duplicates);
+    let disk_bytes_saved = groups.iter().map(|g| g.disk_bytes_saved).sum();
+    let vram_bytes_saved = groups.iter().map(|g| g.vram_bytes_saved).sum();
+    ExactDuplicateReport {
+        texture_count: candidates.len(),
+        duplicate_count: all().count(),
+        byte_identical_count: all().filter(|d| d.match_kind == ExactMatch::Bytes).count(),
+        pixel_identical_count: all().filter(|d| d.match_kind == ExactMatch::Pixels).count(),
+        disk_bytes_saved,
+        disk_saved: format_bytes(disk_bytes_saved),
+        vram_bytes_saved,
+        vram_saved: format_bytes(vram_bytes_saved),
+        groups,
+    }
+}
+
+/// Rewrite each duplicate group as one shared texture.
+///
+/// The shared texture stays where it is and the duplicates are deleted. The
+/// manifest is written before anything is removed: the full manifest to
+/// `manifest_dir`, and the entries for each affected material folder to that
+/// folder, where [`MaterialSet::load_from_folder`] resolves the removed maps
+/// to their shared copy. Returns the manifest as written to `manifest_dir`.
+/// Destructive: run [`find_exact_duplicates`] first and review the report.
+pub fn apply_dedup(report: &ExactDuplicateReport, manifest_dir: &Path) -> Result<DedupManifest> {
+    let manifest = DedupManifest {
+        disk_bytes_saved: report.disk_bytes_saved,
+        entries: report
+            .groups
+            .iter()
+            .map(|group| DedupManifestEntry {
+                shared: group.shared_path.clone(),
+                pixel_hash: group.pixel_hash.clone(),
+                replaced: group.duplicates.iter().map(|d| d.path.clone()).collect(),
+            })
+            .collect(),
+    };
+    for entry in &manifest.entries {
+        if !Path::new(&entry.shared).is_file() {
+            return Err(Error::Other(format!("Shared texture {} no longer exists", entry.shared)));
+        }
+    }
+
+    fs::create_dir_all(manifest_dir)?;
+    manifest.merge_into(manifest_dir)?;
+    let mut by_folder: HashMap<PathBuf, DedupManifest> = HashMap::new();
+    for entry in &manifest.entries {
+        for replaced in &entry.replaced {
+            let folder = Path::new(replaced).parent().unwrap_or(Path::new("")).to_path_buf();
+            let local = by_folder.entry(folder).or_default();
+            match local.entries.iter_mut().find(|e| e.shared == entry.shared) {
+                Some(e) => e.replaced.push(replaced.clone()),
+                None => local.entries.push(DedupManifestEntry { replaced: vec![replaced.clone()], ..entry.clone() }),
+            }
+        }
+    }
+    for (folder, local) in &by_folder {
+        local.merge_into(folder)?;
+    }
+
+    for path in manifest.entries.iter().flat_map(|e| &e.replaced) {
+        fs::remove_file(path)
+            .map_err(|e| Error::Other(format!("Failed to remove {}: {}", path, e)))?;
+    }
+    Ok(manifest.relative_to(manifest_dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization::save_texture;
+    use crate::test_util::gray;
+
+    fn texture(seed: u8) -> TextureMap {
+        gray(16, 16, |_, y| (y as u8).wrapping_mul(seed))
+    }
+
+    fn saved(dir: &Path, name: &str, tex: &TextureMap) -> TextureMap {
+        let path = dir.join(name);
+        save_texture(tex, &path).unwrap();
+        TextureMap::new(tex.width, tex.height, tex.data.clone(), Some(path))
+    }
+
+    fn material(dir: &Path, albedo: TextureMap) -> (PathBuf, MaterialSet) {
+        let mut set = MaterialSet::new();
+        set.albedo = Some(albedo);
+        (dir.to_path_buf(), set)
+    }
+
+    #[test]
+    fn groups_byte_and_pixel_identical_textures() {
+        let dir = std::env::temp_dir().join(format!("pbr_dedup_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let tex = texture(7);
+        let materials = vec![
+            material(&dir, saved(&dir, "a.png", &tex)),
+            material(&dir, saved(&dir, "b.png", &tex)),
+            material(&dir, saved(&dir, "c.tga", &tex)),
+            material(&dir, saved(&dir, "e.tga", &tex)),
+            material(&dir, saved(&dir, "d.png", &texture(3))),
+        ];
+
+        let report = find_exact_duplicates(&materials);
+        assert_eq!(report.texture_count, 5);
+        assert_eq!(report.groups.len(), 1);
+        let group = &report.groups[0];
+        let kind = |name: &str| {
+            if group.shared_path.ends_with(name) {
+                return None;
+            }
+            group.duplicates.iter().find(|d| d.path.ends_with(name)).map(|d| d.match_kind)
+        };
+        // The (RLE-compressed, smaller) first TGA is kept; the other TGA matches it
+        // byte-for-byte, the PNGs only by pixels
+        assert!(group.shared_path.ends_with("c.tga"));
+        assert_eq!(kind("e.tga"), Some(ExactMatch::Bytes));
+        assert_eq!(kind("a.png"), Some(ExactMatch::Pixels));
+        assert_eq!(kind("b.png"), Some(ExactMatch::Pixels));
+        assert_eq!(report.byte_identical_count, 1);
+        assert_eq!(report.vram_bytes_saved, 3 * estimate_texture_bytes(16, 16, true));
+        assert!(report.disk_bytes_saved > 0);
+
+        let manifest_dir = dir.join("manifest");
+        let manifest = apply_dedup(&report, &manifest_dir).unwrap();
+        let shared = manifest.resolve(&manifest_dir, &dir.join("a.png")).unwrap();
+        assert_eq!(shared, dir.join("c.tga"));
+        assert_eq!(Path::new(&manifest.entries[0].shared), Path::new("../c.tga"));
+        assert!(dir.join("c.tga").exists());
+        assert!(!dir.join("b.png").exists());
+        assert!(dir.join("d.png").exists());
+        assert!(manifest_dir.join(DEDUP_MANIFEST_FILE).exists());
+        assert!(dir.join(DEDUP_MANIFEST_FILE).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn sixteen_bit_source_does_not_match_its_8_bit_copy() {
+        let dir = std::env::temp_dir().join(format!("pbr_dedup_16_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // 16-bit values that all round to the same 8-bit level
+        let wide = image::ImageBuffer::<image::Rgba<u16>, _>::from_fn(16, 16, |x, y| {
+            let v = 32896 + (x + y * 16) as u16;
+            image::Rgba([v, v, v, 65535])
+        });
+        let wide_path = dir.join("wide.png");
+        wide.save(&wide_path).unwrap();
+        let loaded = TextureMap::from_loaded(crate::ImageLoader::load(&wide_path).unwrap(), Some(wide_path));
+        let narrow = saved(&dir, "narrow.png", &loaded);
+        assert_eq!(pixel_digest(&loaded), pixel_digest(&narrow));
+
+        let report = find_exact_duplicates(&[material(&dir, loaded), material(&dir, narrow)]);
+        assert!(report.groups.is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn deduplicated_material_loads_maps_from_shared_copy() {
+        let root = std::env::temp_dir().join(format!("pbr_dedup_load_test_{}", std::process::id()));
+        let (a, b) = (root.join("A"), root.join("B"));
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        let tex = texture(5);
+        saved(&a, "albedo.tga", &tex);
+        saved(&b, "albedo.png", &tex);
+        saved(&b, "normal.png", &texture(9));
+        let materials: Vec<_> = [&a, &b]
+            .iter()
+            .map(|f| (f.to_path_buf(), MaterialSet::load_from_folder(f).unwrap()))
+            .collect();
+
+        let report = find_exact_duplicates(&materials);
+        assert_eq!(report.duplicate_count, 1);
+        apply_dedup(&report, &root).unwrap();
+        assert!(!b.join("albedo.png").exists());
+
+        let set = MaterialSet::load_from_folder(&b).unwrap();
+        let albedo = set.albedo.as_ref().unwrap();
+        assert_eq!(albedo.path.as_deref(), Some(a.join("albedo.tga").as_path()));
+        assert_eq!(albedo.data, tex.data);
+        assert!(set.normal.is_some());
+
+        // The manifests hold relative paths, so the library can move
+        let moved = root.with_file_name(format!("pbr_dedup_moved_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&moved);
+        fs::rename(&root, &moved).unwrap();
+        let set = MaterialSet::load_from_folder(moved.join("B")).unwrap();
+        assert_eq!(set.albedo.unwrap().path, Some(moved.join("A/albedo.tga")));
+
+        // A missing shared copy is an error, not a silently missing map
+        fs::remove_file(moved.join("A/albedo.tga")).unwrap();
+        assert!(MaterialSet::load_from_folder(moved.join("B")).is_err());
+        let _ = fs::remove_dir_all(&moved);
+    }
+}
@@ -0,0 +1,387 @@
+//! Dithered bit-depth reduction for 16-bit and float sources.
+//!
+//! Textures are analyzed as RGBA8, so a 16-bit height map or a float EXR is
+//! rounded to 256 levels on load. In smooth gradients that rounding leaves
+//! flat bands separated by one-level steps, which parallax and displacement
+//! turn into visible terraces. When an export dithers a slot
+//! ([`DitherSettings`]), the slot is decoded again at full precision, resized
+//! in floating point and quantized with an ordered (Bayer) or blue-noise
+//! threshold, so the steps become fine noise that averages out on the GPU.
+//! 8-bit sources are exported unchanged.
+
+use crate::analysis::slot_textures;
+use crate::material::{MaterialSet, TextureMap};
+use crate::optimization::{compute_target_dimensions, TargetResolution};
+use crate::tiled::slot_key;
+use crate::Result;
+use image::imageops::FilterType;
+use image::{ColorType, Rgba32FImage};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Edge of the tiling blue-noise threshold map
+const BLUE_NOISE_SIZE: usize = 64;
+
+/// Gaussian sigma (pixels) of the void-and-cluster energy filter
+const VOID_CLUSTER_SIGMA: f64 = 1.5;
+
+/// How 16-bit and float values are reduced to 8 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DitherMode {
+    /// Round to the nearest level (bands in smooth gradients)
+    #[default]
+    None,
+    /// 8x8 Bayer matrix: cheap, with a faint regular cross-hatch
+    Ordered,
+    /// 64x64 void-and-cluster blue noise: no visible pattern
+    BlueNoise,
+}
+
+impl DitherMode {
+    pub const ALL: [DitherMode; 3] = [DitherMode::None, DitherMode::Ordered, DitherMode::BlueNoise];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DitherMode::None => "none",
+            DitherMode::Ordered => "ordered",
+            DitherMode::BlueNoise => "blue_noise",
+        }
+    }
+
+    /// Quantization threshold in 0-1 for pixel (x, y); 0.5 rounds.
+    fn threshold(&self, x: u32, y: u32) -> f32 {
+        match self {
+            DitherMode::None => 0.5,
+            DitherMode::Ordered => (bayer8(x, y) as f32 + 0.5) / 64.0,
+            DitherMode::BlueNoise => {
+                let n = BLUE_NOISE_SIZE;
+                blue_noise()[(y as usize % n) * n + x as usize % n]
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for DitherMode {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let key = s.trim().to_lowercase().replace(['-', ' '], "_");
+        DitherMode::ALL
+            .into_iter()
+            .find(|m| m.name() == key || (key == "bayer" && *m == DitherMode::Ordered))
+            .ok_or_else(|| crate::Error::Other(format!("Unknown dither mode: {}. Use none, ordered or blue_noise.", s)))
+    }
+}
+
+/// Dither mode per exported slot.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DitherSettings {
+    /// Mode for slots without an entry in `slots`
+    pub default: DitherMode,
+    /// Per-slot modes keyed by albedo, normal, roughness, metallic, ao or height
+    pub slots: BTreeMap<String, DitherMode>,
+}
+
+impl DitherSettings {
+    /// Same mode for every slot.
+    pub fn all(mode: DitherMode) -> Self {
+        Self { default: mode, slots: BTreeMap::new() }
+    }
+
+    /// Override the mode of one slot (any name [`TextureSlot::from_name`](crate::image_loading::TextureSlot::from_name) accepts).
+    pub fn with_slot(mut self, slot: &str, mode: DitherMode) -> Result<Self> {
+        let key = crate::image_loading::TextureSlot::from_name(slot)
+            .and_then(slot_key)
+            .ok_or_else(|| crate::Error::Other(format!("Unknown slot for dithering: {}", slot)))?;
+        self.slots.insert(key.to_string(), mode);
+        Ok(self)
+    }
+
+    /// Mode for a slot key ("albedo", "height", ...).
+    pub fn mode(&self, slot: &str) -> DitherMode {
+        self.slots.get(slot).copied().unwrap_or(self.default)
+    }
+
+    /// True when any slot is dithered.
+    pub fn is_enabled(&self) -> bool {
+        self.default != DitherMode::None || self.slots.values().any(|&m| m != DitherMode::None)
+    }
+}
+
+impl std::str::FromStr for DitherSettings {
+    type Err = crate::Error;
+
+    /// A mode for every slot ("blue_noise"), per-slot modes
+    /// ("height=blue_noise,albedo=ordered") or both ("ordered,normal=none").
+    fn from_str(s: &str) -> Result<Self> {
+        let mut settings = DitherSettings::default();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            settings = match part.split_once('=') {
+                Some((slot, mode)) => settings.with_slot(slot.trim(), mode.parse()?)?,
+                None => DitherSettings { default: part.parse()?, ..settings },
+            };
+        }
+        Ok(settings)
+    }
+}
+
+/// Decode `path` at full precision when it stores more than 8 bits per
+/// channel (16-bit PNG/TGA, float EXR). `None` for 8-bit sources. Values are
+/// clamped to 0-1 like the RGBA8 conversion used on load.
+pub fn load_high_precision<P: AsRef<Path>>(path: P) -> Result<Option<Rgba32FImage>> {
+    let image = image::open(path)?;
+    let high_precision = matches!(
+        image.color(),
+        ColorType::L16 | ColorType::La16 | ColorType::Rgb16 | ColorType::Rgba16 | ColorType::Rgb32F | ColorType::Rgba32F
+    );
+    if !high_precision {
+        return Ok(None);
+    }
+    let mut rgba = image.to_rgba32f();
+    for v in rgba.iter_mut() {
+        *v = v.clamp(0.0, 1.0);
+    }
+    Ok(Some(rgba))
+}
+
+/// Quantize a 0-1 float image to RGBA8 with `mode`. The same threshold is
+/// used for every channel of a pixel so gray maps stay gray.
+pub fn quantize(image: &Rgba32FImage, mode: DitherMode) -> Vec<u8> {
+    let mut data = Vec::with_capacity(image.len());
+    for (x, y, px) in image.enumerate_pixels() {
+        let t = mode.threshold(x, y);
+        data.extend(px.0.iter().map(|&v| (v.clamp(0.0, 1.0) * 255.0 + t).floor().min(255.0) as u8));
+    }
+    data
+}
+
+/// `texture` resized to `target` from its high-precision source file and
+/// dithered down to 8 bits. `None` when `mode` is [`DitherMode::None`], the
+/// texture has no source file, the source is 8-bit or it no longer matches
+/// the loaded texture; callers then resize the RGBA8 data as usual.
+pub fn resize_dithered(texture: &TextureMap, target: TargetResolution, mode: DitherMode) -> Result<Option<TextureMap>> {
+    Ok(high_precision_source(texture, mode)?.map(|source| resize_source(&source, texture, target, mode)))
+}
+
+/// Full-precision sources of the slots an export dithers, decoded once and
+/// reused for the base level and every LOD level.
+#[derive(Debug, Default)]
+pub(crate) struct DitherSources {
+    slots: BTreeMap<&'static str, (DitherMode, Rgba32FImage)>,
+}
+
+impl DitherSources {
+    /// Decode every slot of `material` that `settings` dithers (see [`resize_dithered`]).
+    pub(crate) fn load(material: &MaterialSet, settings: &DitherSettings) -> Result<Self> {
+        let mut slots = BTreeMap::new();
+        for (slot, texture) in slot_textures(material) {
+            let mode = settings.mode(slot);
+            if let Some(source) = high_precision_source(texture, mode)? {
+                slots.insert(slot, (mode, source));
+            }
+        }
+        Ok(Self { slots })
+    }
+
+    /// The `slot` texture resized to `target` and dithered, or `None` when the
+    /// slot is not dithered.
+    pub(crate) fn resize(&self, slot: &str, texture: &TextureMap, target: TargetResolution) -> Option<TextureMap> {
+        let (mode, source) = self.slots.get(slot)?;
+        Some(resize_source(source, texture, target, *mode))
+    }
+}
+
+fn high_precision_source(texture: &TextureMap, mode: DitherMode) -> Result<Option<Rgba32FImage>> {
+    let Some(path) = texture.path.as_ref().filter(|_| mode != DitherMode::None) else {
+        return Ok(None);
+    };
+    let Some(source) = load_high_precision(path)? else {
+        return Ok(None);
+    };
+    Ok((source.dimensions() == (texture.width, texture.height)).then_some(source))
+}
+
+fn resize_source(source: &Rgba32FImage, texture: &TextureMap, target: TargetResolution, mode: DitherMode) -> TextureMap {
+    let (width, height) = compute_target_dimensions(texture.width, texture.height, target.max_dimension());
+    let data = if (width, height) == source.dimensions() {
+        quantize(source, mode)
+    } else {
+        let resized = image::imageops::resize(source, width, height, FilterType::Lanczos3);
+        quantize(&resized, mode)
+    };
+    TextureMap::new(width, height, data, texture.path.clone())
+}
+
+/// Rank 0-63 of (x, y) in the 8x8 Bayer matrix.
+fn bayer8(x: u32, y: u32) -> u32 {
+    let mut v = 0;
+    for bit in 0..3 {
+        let (xb, yb) = ((x >> bit) & 1, (y >> bit) & 1);
+        v = (v << 2) | ((xb ^ yb) << 1) | xb;
+    }
+    v
+}
+
+/// Blue-noise thresholds in 0-1, [`BLUE_NOISE_SIZE`] squared, built once.
+fn blue_noise() -> &'static [f32] {
+    static TILE: OnceLock<Vec<f32>> = OnceLock::new();
+    TILE.get_or_init(|| void_and_cluster(BLUE_NOISE_SIZE))
+}
+
+/// Ulichney's void-and-cluster method on an `n` x `n` torus: relax a sparse
+/// random pattern, rank its points by removing the tightest clusters, then
+/// rank the rest by filling the largest voids. Returns (rank + 0.5) / n².
+fn void_and_cluster(n: usize) -> Vec<f32> {
+    let len = n * n;
+    let kernel: Vec<f64> = (0..len)
+        .map(|i| {
+            let (dx, dy) = (i % n, i / n);
+            let (dx, dy) = (dx.min(n - dx) as f64, dy.min(n - dy) as f64);
+            (-(dx * dx + dy * dy) / (2.0 * VOID_CLUSTER_SIGMA * VOID_CLUSTER_SIGMA)).exp()
+        })
+        .collect();
+    let toggle = |energy: &mut [f64], on: &mut [bool], p: usize, set: bool| {
+        on[p] = set;
+        let sign = if set { 1.0 } else { -1.0 };
+        let (px, py) = (p % n, p / n);
+        for (i, e) in energy.iter_mut().enumerate() {
+            let (dx, dy) = ((i % n + n - px) % n, (i / n + n - py) % n);
+            *e += sign * kernel[dy * n + dx];
+        }
+    };
+    // Tightest cluster among set points / largest void among unset ones
+    let extreme = |energy: &[f64], on: &[bool], set: bool| {
+        let candidates = (0..len).filter(|&i| on[i] == set);
+        if set {
+            candidates.max_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+        } else {
+            candidates.min_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+        }
+    };
+
+    let mut energy = vec![0.0; len];
+    let mut on = vec![false; len];
+    let initial = len / 10;
+    let mut state = 0x2545_f491_u32;
+    let mut placed = 0;
+    while placed < initial {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        let p = state as usize % len;
+        if !on[p] {
+            toggle(&mut energy, &mut on, p, true);
+            placed += 1;
+        }
+    }
+    for _ in 0..len {
+        let Some(cluster) = extreme(&energy, &on, true) else { break };
+        toggle(&mut energy, &mut on, cluster, false);
+        let Some(void) = extreme(&energy, &on, false) else { break };
+        toggle(&mut energy, &mut on, void, true);
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut rank = vec![0usize; len];
+    let (mut e, mut o) = (energy.clone(), on.clone());
+    for r in (0..initial).rev() {
+        if let Some(p) = extreme(&e, &o, true) {
+            toggle(&mut e, &mut o, p, false);
+            rank[p] = r;
+        }
+    }
+    for r in initial..len {
+        if let Some(p) = extreme(&energy, &on, false) {
+            toggle(&mut energy, &mut on, p, true);
+            rank[p] = r;
+        }
+    }
+    rank.into_iter().map(|r| (r as f32 + 0.5) / len as f32).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::banding::banding_analysis;
+
+    fn ramp(width: u32, height: u32) -> Rgba32FImage {
+        // 0.3 -> 0.4 over the width: ~25 levels, 10+ px wide bands when rounded
+        Rgba32FImage::from_fn(width, height, |x, _| {
+            let v = 0.3 + 0.1 * x as f32 / width as f32;
+            image::Rgba([v, v, v, 1.0])
+        })
+    }
+
+    #[test]
+    fn threshold_maps_are_permutations() {
+        let mut bayer: Vec<u32> = (0..64).map(|i| bayer8(i % 8, i / 8)).collect();
+        bayer.sort_unstable();
+        assert_eq!(bayer, (0..64).collect::<Vec<_>>());
+
+        let len = BLUE_NOISE_SIZE * BLUE_NOISE_SIZE;
+        let mut ranks: Vec<usize> = blue_noise().iter().map(|t| (t * len as f32) as usize).collect();
+        ranks.sort_unstable();
+        assert_eq!(ranks, (0..len).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn dithering_keeps_the_mean_and_breaks_up_bands() {
+        let image = ramp(256, 64);
+        let mean = |d: &[u8]| d.chunks_exact(4).map(|p| p[0] as f64).sum::<f64>() / (d.len() / 4) as f64;
+        let expected = image.pixels().map(|p| p[0] as f64 * 255.0).sum::<f64>() / (256.0 * 64.0);
+        let banding = |mode| {
+            let data = quantize(&image, mode);
+            assert!((mean(&data) - expected).abs() < 0.1, "{:?}", mode);
+            banding_analysis(&TextureMap::new(256, 64, data, None)).banding_score
+        };
+        let rounded = banding(DitherMode::None);
+        assert!(rounded > 0.8, "{}", rounded);
+        assert!(banding(DitherMode::Ordered) < 0.1);
+        assert!(banding(DitherMode::BlueNoise) < 0.1);
+    }
+
+    #[test]
+    fn resize_dithered_reads_sixteen_bit_sources() {
+        let dir = std::env::temp_dir().join("pbr_dither_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cliff_height.png");
+        let height: image::ImageBuffer<image::Luma<u16>, Vec<u16>> =
+            image::ImageBuffer::from_fn(128, 32, |x, _| image::Luma([20_000 + x as u16 * 50]));
+        height.save(&path).unwrap();
+        let loaded = TextureMap::from_loaded(crate::image_loading::ImageLoader::load(&path).unwrap(), Some(path.clone()));
+
+        let same = resize_dithered(&loaded, TargetResolution::Res256, DitherMode::BlueNoise).unwrap().unwrap();
+        assert_eq!((same.width, same.height), (128, 32));
+        assert!(same.data.chunks_exact(4).all(|p| p[0] == p[1] && p[1] == p[2] && p[3] == 255));
+        assert!(banding_analysis(&same).banding_score < banding_analysis(&loaded).banding_score);
+
+        let half = resize_dithered(&loaded, TargetResolution::Custom(64), DitherMode::Ordered).unwrap().unwrap();
+        assert_eq!((half.width, half.height), (64, 16));
+        assert!(resize_dithered(&loaded, TargetResolution::Res256, DitherMode::None).unwrap().is_none());
+
+        let eight_bit = dir.join("albedo.png");
+        crate::optimization::save_texture(&loaded, &eight_bit).unwrap();
+        let eight = TextureMap::from_loaded(crate::image_loading::ImageLoader::load(&eight_bit).unwrap(), Some(eight_bit));
+        assert!(resize_dithered(&eight, TargetResolution::Res256, DitherMode::Ordered).unwrap().is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn settings_parse_default_and_slot_modes() {
+        let s: DitherSettings = "ordered, height=blue-noise, AO=none".parse().unwrap();
+        assert_eq!(s.mode("albedo"), DitherMode::Ordered);
+        assert_eq!(s.mode("height"), DitherMode::BlueNoise);
+        assert_eq!(s.mode("ao"), DitherMode::None);
+        assert!(s.is_enabled());
+        assert!(!DitherSettings::default().is_enabled());
+        assert!("height=sparkle".parse::<DitherSettings>().is_err());
+        assert!("emissive=ordered".parse::<DitherSettings>().is_err());
+    }
+}
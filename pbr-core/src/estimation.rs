@@ -1,10 +1,13 @@
 //! GPU/CPU estimation for PBR texture sets.
 //!
-//! Estimates VRAM usage for material sets. Assumes uncompressed RGBA8
-//! format for GPU upload; mipmap overhead (~33%) is optional.
+//! Estimates VRAM usage for material sets. [`estimate_vram`] reports the
+//! uncompressed RGBA8 size (mipmap overhead ~33%, optional) and, side by side,
+//! the size on each [`Platform`] with the block-compressed format its
+//! [`PlatformProfile`] picks for every slot (BC on PC/console, ASTC on mobile).
 
 use crate::material::{MaterialSet, TextureMap};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 /// Bytes per pixel for RGBA8 (uncompressed)
 const BYTES_PER_PIXEL_RGBA8: u64 = 4;
@@ -25,6 +28,9 @@ pub struct VramEstimate {
     pub packed_orm: bool,
     /// Per-texture breakdown
     pub textures: Vec<TextureVramEntry>,
+    /// The same material in each platform's GPU formats
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub platforms: Vec<PlatformVramEstimate>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,7 +42,7 @@ pub struct TextureVramEntry {
 }
 
 /// Estimate VRAM for a single texture (uncompressed RGBA8)
-fn estimate_texture_bytes(width: u32, height: u32, include_mipmaps: bool) -> u64 {
+pub(crate) fn estimate_texture_bytes(width: u32, height: u32, include_mipmaps: bool) -> u64 {
     let base = (width as u64) * (height as u64) * BYTES_PER_PIXEL_RGBA8;
     if include_mipmaps {
         (base as f64 * MIPMAP_OVERHEAD).round() as u64
@@ -103,10 +109,317 @@ pub fn estimate_vram(
         include_mipmaps,
         packed_orm,
         textures,
+        platforms: Platform::ALL
+            .iter()
+            .map(|p| estimate_vram_for_platform(material, &p.profile(), include_mipmaps))
+            .collect(),
     }
 }
 
-fn format_bytes(bytes: u64) -> String {
+/// GPU texture format. Compressed formats are stored in fixed-size blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextureFormat {
+    Rgba8,
+    Rg8,
+    R8,
+    Bc1,
+    Bc2,
+    Bc3,
+    Bc4,
+    Bc5,
+    Bc6h,
+    Bc7,
+    Etc2Rgb,
+    Etc2Rgba,
+    Astc4x4,
+    Astc5x5,
+    Astc6x6,
+    Astc8x8,
+    Astc10x10,
+    Astc12x12,
+}
+
+impl TextureFormat {
+    pub const ALL: [TextureFormat; 18] = [
+        TextureFormat::Rgba8,
+        TextureFormat::Rg8,
+        TextureFormat::R8,
+        TextureFormat::Bc1,
+        TextureFormat::Bc2,
+        TextureFormat::Bc3,
+        TextureFormat::Bc4,
+        TextureFormat::Bc5,
+        TextureFormat::Bc6h,
+        TextureFormat::Bc7,
+        TextureFormat::Etc2Rgb,
+        TextureFormat::Etc2Rgba,
+        TextureFormat::Astc4x4,
+        TextureFormat::Astc5x5,
+        TextureFormat::Astc6x6,
+        TextureFormat::Astc8x8,
+        TextureFormat::Astc10x10,
+        TextureFormat::Astc12x12,
+    ];
+
+    /// Block width, block height and bytes per block (1x1 for uncompressed formats)
+    pub fn block(self) -> (u32, u32, u64) {
+        match self {
+            TextureFormat::Rgba8 => (1, 1, 4),
+            TextureFormat::Rg8 => (1, 1, 2),
+            TextureFormat::R8 => (1, 1, 1),
+            TextureFormat::Bc1 | TextureFormat::Bc4 | TextureFormat::Etc2Rgb => (4, 4, 8),
+            TextureFormat::Bc2
+            | TextureFormat::Bc3
+            | TextureFormat::Bc5
+            | TextureFormat::Bc6h
+            | TextureFormat::Bc7
+            | TextureFormat::Etc2Rgba => (4, 4, 16),
+            TextureFormat::Astc4x4 => (4, 4, 16),
+            TextureFormat::Astc5x5 => (5, 5, 16),
+            TextureFormat::Astc6x6 => (6, 6, 16),
+            TextureFormat::Astc8x8 => (8, 8, 16),
+            TextureFormat::Astc10x10 => (10, 10, 16),
+            TextureFormat::Astc12x12 => (12, 12, 16),
+        }
+    }
+
+    pub fn bits_per_pixel(self) -> f64 {
+        let (w, h, bytes) = self.block();
+        (bytes * 8) as f64 / (w * h) as f64
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TextureFormat::Rgba8 => "rgba8",
+            TextureFormat::Rg8 => "rg8",
+            TextureFormat::R8 => "r8",
+            TextureFormat::Bc1 => "bc1",
+            TextureFormat::Bc2 => "bc2",
+            TextureFormat::Bc3 => "bc3",
+            TextureFormat::Bc4 => "bc4",
+            TextureFormat::Bc5 => "bc5",
+            TextureFormat::Bc6h => "bc6h",
+            TextureFormat::Bc7 => "bc7",
+            TextureFormat::Etc2Rgb => "etc2_rgb",
+            TextureFormat::Etc2Rgba => "etc2_rgba",
+            TextureFormat::Astc4x4 => "astc4x4",
+            TextureFormat::Astc5x5 => "astc5x5",
+            TextureFormat::Astc6x6 => "astc6x6",
+            TextureFormat::Astc8x8 => "astc8x8",
+            TextureFormat::Astc10x10 => "astc10x10",
+            TextureFormat::Astc12x12 => "astc12x12",
+        }
+    }
+}
+
+impl FromStr for TextureFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let key = s.to_lowercase().replace(['-', ' '], "_");
+        TextureFormat::ALL
+            .into_iter()
+            .find(|f| f.name() == key)
+            .ok_or_else(|| format!("Unknown texture format: {}", s))
+    }
+}
+
+/// Bytes for one texture in `format`, with the full mip chain down to 1x1 when
+/// `include_mipmaps` is set. Each level is rounded up to whole blocks.
+pub fn texture_format_bytes(format: TextureFormat, width: u32, height: u32, include_mipmaps: bool) -> u64 {
+    let (bw, bh, block_bytes) = format.block();
+    let (mut w, mut h) = (width.max(1), height.max(1));
+    let mut total = 0u64;
+    loop {
+        total += w.div_ceil(bw) as u64 * h.div_ceil(bh) as u64 * block_bytes;
+        if !include_mipmaps || (w == 1 && h == 1) {
+            return total;
+        }
+        w = (w / 2).max(1);
+        h = (h / 2).max(1);
+    }
+}
+
+/// Target platform class for VRAM estimates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Platform {
+    /// Desktop GPU (BC7/BC5)
+    Pc,
+    /// Console-class GPU (BC1/BC3/BC5)
+    Console,
+    /// Mobile GPU with ASTC
+    Mobile,
+}
+
+impl Platform {
+    pub const ALL: [Platform; 3] = [Platform::Pc, Platform::Console, Platform::Mobile];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Platform::Pc => "pc",
+            Platform::Console => "console",
+            Platform::Mobile => "mobile",
+        }
+    }
+
+    /// Built-in per-slot format selection for this platform
+    pub fn profile(self) -> PlatformProfile {
+        use TextureFormat::*;
+        match self {
+            Platform::Pc => PlatformProfile {
+                platform: self,
+                albedo: Bc7,
+                albedo_alpha: Bc7,
+                normal: Bc5,
+                orm: Bc7,
+                mask: Bc4,
+                height: R8,
+                pack_orm: true,
+            },
+            Platform::Console => PlatformProfile {
+                platform: self,
+                albedo: Bc1,
+                albedo_alpha: Bc3,
+                normal: Bc5,
+                orm: Bc1,
+                mask: Bc4,
+                height: Bc4,
+                pack_orm: true,
+            },
+            Platform::Mobile => PlatformProfile {
+                platform: self,
+                albedo: Astc6x6,
+                albedo_alpha: Astc6x6,
+                normal: Astc5x5,
+                orm: Astc8x8,
+                mask: Astc8x8,
+                height: Astc8x8,
+                pack_orm: true,
+            },
+        }
+    }
+}
+
+impl FromStr for Platform {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Platform::ALL
+            .into_iter()
+            .find(|p| p.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown platform: {}. Use pc, console, or mobile.", s))
+    }
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// GPU format chosen for each slot on one platform.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlatformProfile {
+    pub platform: Platform,
+    /// Opaque albedo
+    pub albedo: TextureFormat,
+    /// Albedo with a non-opaque alpha channel
+    pub albedo_alpha: TextureFormat,
+    pub normal: TextureFormat,
+    /// Packed occlusion/roughness/metallic
+    pub orm: TextureFormat,
+    /// Unpacked single-channel roughness, metallic or AO
+    pub mask: TextureFormat,
+    pub height: TextureFormat,
+    /// Pack roughness/metallic/AO into one ORM texture when all three exist
+    pub pack_orm: bool,
+}
+
+impl PlatformProfile {
+    /// Format for a texture in `slot` ("albedo", "normal", "orm", "roughness", ...)
+    pub fn format_for(&self, slot: &str, has_alpha: bool) -> TextureFormat {
+        match slot {
+            "albedo" if has_alpha => self.albedo_alpha,
+            "albedo" => self.albedo,
+            "normal" => self.normal,
+            "orm" => self.orm,
+            "height" => self.height,
+            _ => self.mask,
+        }
+    }
+}
+
+/// VRAM for a material in one platform's formats.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformVramEstimate {
+    pub platform: Platform,
+    pub bytes: u64,
+    pub formatted: String,
+    pub packed_orm: bool,
+    /// Uncompressed RGBA8 size of the same textures divided by `bytes`
+    pub compression_ratio: f64,
+    pub textures: Vec<FormatVramEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormatVramEntry {
+    pub slot: String,
+    pub width: u32,
+    pub height: u32,
+    pub format: TextureFormat,
+    pub bits_per_pixel: f64,
+    pub bytes: u64,
+}
+
+/// Estimate VRAM for a material with the formats `profile` picks for each slot.
+pub fn estimate_vram_for_platform(
+    material: &MaterialSet,
+    profile: &PlatformProfile,
+    include_mipmaps: bool,
+) -> PlatformVramEstimate {
+    let packed_orm =
+        profile.pack_orm && material.roughness.is_some() && material.metallic.is_some() && material.ao.is_some();
+    let mut slots: Vec<(&str, &TextureMap)> = Vec::new();
+    for (slot, texture) in crate::analysis::slot_textures(material) {
+        match slot {
+            "roughness" if packed_orm => slots.push(("orm", texture)),
+            "metallic" | "ao" if packed_orm => {}
+            _ => slots.push((slot, texture)),
+        }
+    }
+
+    let mut textures = Vec::with_capacity(slots.len());
+    let mut total = 0u64;
+    let mut uncompressed = 0u64;
+    for (slot, t) in slots {
+        let has_alpha = slot == "albedo" && t.has_alpha();
+        let format = profile.format_for(slot, has_alpha);
+        let bytes = texture_format_bytes(format, t.width, t.height, include_mipmaps);
+        total += bytes;
+        uncompressed += texture_format_bytes(TextureFormat::Rgba8, t.width, t.height, include_mipmaps);
+        textures.push(FormatVramEntry {
+            slot: slot.to_string(),
+            width: t.width,
+            height: t.height,
+            format,
+            bits_per_pixel: format.bits_per_pixel(),
+            bytes,
+        });
+    }
+
+    PlatformVramEstimate {
+        platform: profile.platform,
+        bytes: total,
+        formatted: format_bytes(total),
+        packed_orm,
+        compression_ratio: if total == 0 { 1.0 } else { uncompressed as f64 / total as f64 },
+        textures,
+    }
+}
+
+pub(crate) fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
     const GB: u64 = MB * 1024;
@@ -127,12 +440,7 @@ mod tests {
     use super::*;
 
     fn make_texture(w: u32, h: u32) -> TextureMap {
-        TextureMap {
-            width: w,
-            height: h,
-            data: vec![0; (w as usize) * (h as usize) * 4],
-            path: None,
-        }
+        TextureMap::new(w, h, vec![0; (w as usize) * (h as usize) * 4], None)
     }
 
     #[test]
@@ -151,4 +459,55 @@ mod tests {
         let est = estimate_vram(&set, true, false);
         assert!(est.bytes > 1024 * 1024 * 4);
     }
+
+    #[test]
+    fn block_formats_round_up_to_whole_blocks() {
+        assert_eq!(texture_format_bytes(TextureFormat::Bc1, 1024, 1024, false), 1024 * 1024 / 2);
+        assert_eq!(texture_format_bytes(TextureFormat::Bc7, 1024, 1024, false), 1024 * 1024);
+        // 6x6 blocks: 171 x 171 blocks of 16 bytes
+        assert_eq!(texture_format_bytes(TextureFormat::Astc6x6, 1024, 1024, false), 171 * 171 * 16);
+        // 1x1 and 2x2 mips still occupy a full 4x4 block
+        assert_eq!(texture_format_bytes(TextureFormat::Bc1, 4, 4, true), 3 * 8);
+        assert_eq!(texture_format_bytes(TextureFormat::R8, 4, 4, true), 16 + 4 + 1);
+        assert!((TextureFormat::Astc8x8.bits_per_pixel() - 2.0).abs() < 1e-9);
+        assert_eq!("ETC2-RGBA".parse::<TextureFormat>(), Ok(TextureFormat::Etc2Rgba));
+    }
+
+    #[test]
+    fn platform_estimates_pack_orm_and_compress() {
+        let opaque = || TextureMap::new(1024, 1024, [0, 0, 0, 255].repeat(1024 * 1024), None);
+        let mut set = MaterialSet::new();
+        set.albedo = Some(opaque());
+        set.normal = Some(opaque());
+        set.roughness = Some(opaque());
+        set.metallic = Some(opaque());
+        set.ao = Some(opaque());
+        let est = estimate_vram(&set, true, false);
+        assert_eq!(est.platforms.len(), Platform::ALL.len());
+
+        let console = &est.platforms[1];
+        assert_eq!(console.platform, Platform::Console);
+        assert!(console.packed_orm);
+        let slots: Vec<_> = console.textures.iter().map(|t| (t.slot.as_str(), t.format)).collect();
+        assert_eq!(slots, vec![("albedo", TextureFormat::Bc1), ("normal", TextureFormat::Bc5), ("orm", TextureFormat::Bc1)]);
+        // 4 + 8 + 4 bits per pixel against 3 x 32 uncompressed
+        assert!((console.compression_ratio - 6.0).abs() < 0.01);
+
+        let mobile = &est.platforms[2];
+        assert!(mobile.bytes < console.bytes);
+        assert!(est.bytes > 5 * est.platforms[0].bytes);
+        // Sizing never runs a statistics pass
+        assert!(set.albedo.as_ref().unwrap().cached_stats().is_none());
+    }
+
+    #[test]
+    fn translucent_albedo_uses_alpha_format() {
+        let mut data = [0, 0, 0, 255].repeat(64 * 64);
+        data[3] = 0;
+        let mut set = MaterialSet::new();
+        set.albedo = Some(TextureMap::new(64, 64, data, None));
+        let console = estimate_vram_for_platform(&set, &Platform::Console.profile(), false);
+        assert_eq!(console.textures[0].format, TextureFormat::Bc3);
+        assert!(set.albedo.as_ref().unwrap().cached_stats().is_none());
+    }
 }
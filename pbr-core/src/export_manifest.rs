@@ -0,0 +1,261 @@
+//! Export manifest with checksums and provenance.
+//!
+//! Every export writes [`EXPORT_MANIFEST_FILE`] next to its textures. It lists
+//! each output file with its slot, format, dimensions, color space, channel
+//! layout and SHA-256, the SHA-256 of every source texture, the preset used
+//! and the pbr-core version. Engine importers can read slot assignments from
+//! it, and [`verify_export`] checks later that deployed files still match.
+
+use crate::analysis::slot_textures;
+use crate::estimation::TextureFormat;
+use crate::material::MaterialSet;
+use crate::optimization::ExportedTexture;
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// File name of the manifest written into every export folder
+pub const EXPORT_MANIFEST_FILE: &str = "manifest.json";
+
+/// Bumped when the manifest layout changes incompatibly
+pub const MANIFEST_FORMAT: u32 = 1;
+
+/// How the pixel values of a file are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorSpace {
+    /// Color data; sample with sRGB decoding
+    Srgb,
+    /// Data maps; sample as-is
+    Linear,
+}
+
+/// One texture file written by an export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestFile {
+    /// Path relative to the manifest, with `/` separators
+    pub path: String,
+    /// "albedo", "normal", "orm", "rma", "mask", "roughness", "metallic", "ao", "height"
+    pub slot: String,
+    /// 0 for the base level
+    pub lod: u32,
+    /// File format ("png")
+    pub format: String,
+    pub width: u32,
+    pub height: u32,
+    pub color_space: ColorSpace,
+    /// Meaning of each channel, e.g. "R=AO G=Roughness B=Metallic A=1"
+    pub channels: String,
+    /// GPU format the importer should compress to, when the preset names a platform
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu_format: Option<TextureFormat>,
+    pub sha256: String,
+}
+
+/// Source texture the export was made from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestSource {
+    pub slot: String,
+    pub path: Option<String>,
+    /// None when the texture was not loaded from a file or the file is gone
+    pub sha256: Option<String>,
+}
+
+/// Contents of [`EXPORT_MANIFEST_FILE`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub format: u32,
+    pub generator: String,
+    pub generator_version: String,
+    pub created_at: DateTime<Utc>,
+    pub material: Option<String>,
+    /// Preset or target the export used (e.g. "Unreal Engine", "2K")
+    pub preset: String,
+    /// [`crate::optimization::OptimizationPreset::fingerprint`] of the export settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset_fingerprint: Option<String>,
+    pub sources: Vec<ManifestSource>,
+    pub files: Vec<ManifestFile>,
+}
+
+impl ManifestSource {
+    /// Source texture `path` for `slot`, hashed.
+    pub fn for_file(slot: &str, path: &Path) -> Self {
+        Self {
+            slot: slot.to_string(),
+            path: Some(path.display().to_string()),
+            sha256: sha256_file(path).ok(),
+        }
+    }
+}
+
+impl ExportManifest {
+    /// Start a manifest for an export of `material`, hashing its source files.
+    pub fn new(material: &MaterialSet, preset: &str) -> Self {
+        let sources = slot_textures(material)
+            .map(|(slot, tex)| match &tex.path {
+                Some(path) => ManifestSource::for_file(slot, path),
+                None => ManifestSource { slot: slot.to_string(), path: None, sha256: None },
+            })
+            .collect();
+        Self::with_sources(material.name.clone(), preset, sources)
+    }
+
+    /// Start a manifest from already described sources (exports that never
+    /// load the material, such as tiled ones).
+    pub fn with_sources(material: Option<String>, preset: &str, sources: Vec<ManifestSource>) -> Self {
+        Self {
+            format: MANIFEST_FORMAT,
+            generator: "pbr-core".to_string(),
+            generator_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: Utc::now(),
+            material,
+            preset: preset.to_string(),
+            preset_fingerprint: None,
+            sources,
+            files: Vec::new(),
+        }
+    }
+
+    /// Record files written under `root` (the folder the manifest goes to).
+    pub fn add_outputs(&mut self, root: &Path, lod: u32, outputs: &[ExportedTexture]) -> Result<()> {
+        for out in outputs {
+            let relative = out.path.strip_prefix(root).unwrap_or(&out.path);
+            self.files.push(ManifestFile {
+                path: relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                slot: out.slot.to_string(),
+                lod,
+                format: out
+                    .path
+                    .extension()
+                    .map(|e| e.to_string_lossy().to_lowercase())
+                    .unwrap_or_default(),
+                width: out.width,
+                height: out.height,
+                color_space: slot_color_space(out.slot),
+                channels: out.channels.clone(),
+                gpu_format: out.gpu_format,
+                sha256: sha256_file(&out.path)?,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(Error::from)
+    }
+
+    /// Write to `dir/manifest.json`.
+    pub fn write(&self, dir: &Path) -> Result<PathBuf> {
+        let path = dir.join(EXPORT_MANIFEST_FILE);
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+}
+
+/// sRGB for albedo, linear for data maps.
+pub fn slot_color_space(slot: &str) -> ColorSpace {
+    if slot == "albedo" {
+        ColorSpace::Srgb
+    } else {
+        ColorSpace::Linear
+    }
+}
+
+pub(crate) fn sha256_file(path: &Path) -> Result<String> {
+    Ok(format!("{:x}", Sha256::digest(std::fs::read(path)?)))
+}
+
+/// `relative` (a manifest file path) under `dir`, or `None` when it is
+/// absolute or has `..`, root or prefix components and could leave `dir`.
+pub(crate) fn resolve_manifest_path(dir: &Path, relative: &str) -> Option<PathBuf> {
+    let relative = Path::new(relative);
+    let normal = relative.components().all(|c| matches!(c, std::path::Component::Normal(_)));
+    (normal && !relative.as_os_str().is_empty()).then(|| dir.join(relative))
+}
+
+/// Re-hash every file listed in `dir/manifest.json` and describe each one
+/// that is missing or changed, or whose path would leave `dir`. An empty list
+/// means the export is intact.
+pub fn verify_export(dir: &Path) -> Result<Vec<String>> {
+    let manifest = ExportManifest::load(&dir.join(EXPORT_MANIFEST_FILE))?;
+    let mut problems = Vec::new();
+    for file in &manifest.files {
+        let Some(path) = resolve_manifest_path(dir, &file.path) else {
+            problems.push(format!("{}: path outside the export folder", file.path));
+            continue;
+        };
+        if !path.is_file() {
+            problems.push(format!("{}: missing", file.path));
+        } else if sha256_file(&path)? != file.sha256 {
+            problems.push(format!("{}: checksum mismatch", file.path));
+        }
+    }
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization::{export_with_target_and_lod, TargetResolution};
+    use crate::test_util::uniform;
+
+    #[test]
+    fn export_writes_manifest_that_verifies() {
+        let dir = std::env::temp_dir().join("pbr_export_manifest_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let src = dir.join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        let albedo_path = src.join("m_albedo.png");
+        crate::optimization::save_texture(&uniform(64, 64, 200), &albedo_path).unwrap();
+        let mut albedo = uniform(64, 64, 200);
+        albedo.path = Some(albedo_path.clone());
+        let material = MaterialSet {
+            name: Some("m".into()),
+            albedo: Some(albedo),
+            roughness: Some(uniform(64, 64, 120)),
+            metallic: Some(uniform(64, 64, 0)),
+            ao: Some(uniform(64, 64, 255)),
+            ..Default::default()
+        };
+
+        let out = dir.join("out");
+        let written =
+            export_with_target_and_lod(&material, &out, TargetResolution::Res1K, &[TargetResolution::Res128]).unwrap();
+        assert_eq!(written.len(), 4);
+        let manifest = ExportManifest::load(&out.join(EXPORT_MANIFEST_FILE)).unwrap();
+        assert_eq!(manifest.generator_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(manifest.preset, "1K");
+        assert_eq!(manifest.files.len(), 4);
+        let orm = manifest.files.iter().find(|f| f.path == "LOD1/ORM.png").unwrap();
+        assert_eq!((orm.slot.as_str(), orm.lod, orm.width), ("orm", 1, 64));
+        assert_eq!(orm.color_space, ColorSpace::Linear);
+        assert_eq!(manifest.files[0].color_space, ColorSpace::Srgb);
+        let source = &manifest.sources[0];
+        assert_eq!(source.sha256.as_deref(), Some(sha256_file(&albedo_path).unwrap().as_str()));
+        assert!(manifest.sources[1].sha256.is_none());
+
+        assert!(verify_export(&out).unwrap().is_empty());
+        std::fs::write(out.join("LOD0/ORM.png"), b"tampered").unwrap();
+        std::fs::remove_file(out.join("LOD1/BaseColor.png")).unwrap();
+        let problems = verify_export(&out).unwrap();
+        assert_eq!(problems, vec!["LOD0/ORM.png: checksum mismatch", "LOD1/BaseColor.png: missing"]);
+
+        let mut escaping = manifest.clone();
+        escaping.files.truncate(2);
+        escaping.files[0].path = "../src/m_albedo.png".into();
+        escaping.files[1].path = albedo_path.display().to_string();
+        escaping.write(&out).unwrap();
+        let problems = verify_export(&out).unwrap();
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().all(|p| p.ends_with("path outside the export folder")), "{:?}", problems);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
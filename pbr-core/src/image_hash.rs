@@ -0,0 +1,473 @@
+//! Robust perceptual hashes and a BK-tree index for duplicate lookup.
+//!
+//! Each texture gets:
+//!
+//! - **DCT pHash** – signs of the low-frequency DCT coefficients of a 32x32
+//!   luminance grid (64 bits). Robust to rescaling, compression and small edits.
+//! - **dHash** – signs of horizontal luminance gradients on a 9x8 grid (64 bits).
+//! - **Color signature** – normalized 4x4x4 RGB histogram, since both hashes
+//!   ignore color.
+//!
+//! Hashes are also computed for the seven other rotations/mirrors and for
+//! center crops, so a lookup finds rotated, mirrored, rescaled and cropped
+//! variants and reports the transform that relates them. Lookups use a BK-tree
+//! over pHash Hamming distance instead of comparing every pair.
+
+use crate::material::TextureMap;
+use serde::{Deserialize, Serialize};
+
+/// Side of the luminance grid the pHash DCT runs on
+const GRID: usize = 32;
+
+/// Side of the low-frequency DCT block kept for the pHash
+const PHASH_BLOCK: usize = 8;
+
+/// Bins per channel of the color signature
+const COLOR_BINS: usize = 4;
+
+/// Center crops (fraction of each side) hashed for crop detection
+const CROP_FRACTIONS: [f32; 2] = [0.75, 0.5];
+
+/// How the second texture of a match relates to the first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashTransform {
+    Identity,
+    /// Rotated 90° clockwise
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Mirrored left↔right
+    FlipHorizontal,
+    /// Mirrored top↔bottom
+    FlipVertical,
+    /// Mirrored along the main diagonal
+    Transpose,
+    /// Mirrored along the anti-diagonal
+    Transverse,
+}
+
+impl HashTransform {
+    pub const ALL: [HashTransform; 8] = [
+        HashTransform::Identity,
+        HashTransform::Rotate90,
+        HashTransform::Rotate180,
+        HashTransform::Rotate270,
+        HashTransform::FlipHorizontal,
+        HashTransform::FlipVertical,
+        HashTransform::Transpose,
+        HashTransform::Transverse,
+    ];
+
+    /// Source cell of output cell `(x, y)` in an `n` x `n` grid.
+    fn source(self, x: usize, y: usize, n: usize) -> (usize, usize) {
+        let m = n - 1;
+        match self {
+            HashTransform::Identity => (x, y),
+            HashTransform::Rotate90 => (y, m - x),
+            HashTransform::Rotate180 => (m - x, m - y),
+            HashTransform::Rotate270 => (m - y, x),
+            HashTransform::FlipHorizontal => (m - x, y),
+            HashTransform::FlipVertical => (x, m - y),
+            HashTransform::Transpose => (y, x),
+            HashTransform::Transverse => (m - y, m - x),
+        }
+    }
+}
+
+/// Hashes of one transformed (or cropped) version of a texture.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashVariant {
+    pub transform: HashTransform,
+    /// Fraction of each side kept when this is a center crop
+    pub crop: Option<f32>,
+    pub phash: u64,
+    pub dhash: u64,
+}
+
+/// Perceptual hashes of a texture.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextureHashes {
+    pub width: u32,
+    pub height: u32,
+    /// Identity first, then the other rotations/mirrors, then center crops
+    pub variants: Vec<HashVariant>,
+    /// Normalized RGB histogram (sums to 1)
+    pub color: Vec<f32>,
+}
+
+impl TextureHashes {
+    /// Hash a texture (prefer [`TextureMap::hashes`], which memoizes).
+    pub fn compute(map: &TextureMap) -> Self {
+        let (w, h) = (map.width as usize, map.height as usize);
+        let full = luminance_grid(map, (0, 0, w, h));
+        let mut variants: Vec<HashVariant> = HashTransform::ALL
+            .iter()
+            .map(|&transform| {
+                let grid = transform_grid(&full, transform);
+                HashVariant {
+                    transform,
+                    crop: None,
+                    phash: phash(&grid),
+                    dhash: dhash(&grid),
+                }
+            })
+            .collect();
+        for fraction in CROP_FRACTIONS {
+            let (cw, ch) = ((w as f32 * fraction).round() as usize, (h as f32 * fraction).round() as usize);
+            if cw < 8 || ch < 8 {
+                continue;
+            }
+            let (x0, y0) = ((w - cw) / 2, (h - ch) / 2);
+            let grid = luminance_grid(map, (x0, y0, x0 + cw, y0 + ch));
+            variants.push(HashVariant {
+                transform: HashTransform::Identity,
+                crop: Some(fraction),
+                phash: phash(&grid),
+                dhash: dhash(&grid),
+            });
+        }
+        Self {
+            width: map.width,
+            height: map.height,
+            variants,
+            color: color_signature(map),
+        }
+    }
+
+    /// Hashes of the texture as is.
+    pub fn identity(&self) -> &HashVariant {
+        &self.variants[0]
+    }
+
+    /// Similarity (0-1) of `other` to variant `variant` of this texture:
+    /// pHash and dHash agreement, scaled down by color difference.
+    pub fn similarity(&self, variant: usize, other: &TextureHashes) -> f32 {
+        let v = &self.variants[variant];
+        let o = other.identity();
+        let dp = (v.phash ^ o.phash).count_ones() as f32;
+        let dd = (v.dhash ^ o.dhash).count_ones() as f32;
+        let hash_similarity = 1.0 - (2.0 * dp + dd) / 192.0;
+        hash_similarity * (0.5 + 0.5 * self.color_similarity(other))
+    }
+
+    /// Histogram intersection of the color signatures (1 = same color distribution).
+    pub fn color_similarity(&self, other: &TextureHashes) -> f32 {
+        self.color.iter().zip(&other.color).map(|(a, b)| a.min(*b)).sum::<f32>().min(1.0)
+    }
+}
+
+/// Largest pHash distance at which [`TextureHashes::similarity`] can still reach `min_similarity`.
+pub fn max_phash_distance(min_similarity: f32) -> u32 {
+    ((1.0 - min_similarity.clamp(0.0, 1.0)) * 96.0).floor() as u32
+}
+
+/// Match of a query against an indexed texture.
+#[derive(Debug, Clone, Copy)]
+pub struct HashMatch {
+    /// Item id given to [`HashIndex::insert`]
+    pub item: usize,
+    /// Index into the item's [`TextureHashes::variants`]
+    pub variant: usize,
+    pub phash_distance: u32,
+}
+
+/// BK-tree over pHash Hamming distance. Every variant of an inserted texture
+/// is a node, so lookups find transformed and cropped versions too.
+#[derive(Debug, Default)]
+pub struct HashIndex {
+    nodes: Vec<BkNode>,
+}
+
+#[derive(Debug)]
+struct BkNode {
+    hash: u64,
+    item: usize,
+    variant: usize,
+    /// (distance to this node, child node index)
+    children: Vec<(u32, usize)>,
+}
+
+impl HashIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Add all variants of a texture under `item`.
+    pub fn insert(&mut self, item: usize, hashes: &TextureHashes) {
+        for (variant, v) in hashes.variants.iter().enumerate() {
+            self.insert_hash(v.phash, item, variant);
+        }
+    }
+
+    fn insert_hash(&mut self, hash: u64, item: usize, variant: usize) {
+        let new = self.nodes.len();
+        self.nodes.push(BkNode {
+            hash,
+            item,
+            variant,
+            children: Vec::new(),
+        });
+        if new == 0 {
+            return;
+        }
+        let mut at = 0;
+        loop {
+            let d = (self.nodes[at].hash ^ hash).count_ones();
+            match self.nodes[at].children.iter().find(|(cd, _)| *cd == d) {
+                Some(&(_, child)) => at = child,
+                None => {
+                    self.nodes[at].children.push((d, new));
+                    return;
+                }
+            }
+        }
+    }
+
+    /// All indexed variants within `max_distance` pHash bits of `hash`.
+    pub fn query(&self, hash: u64, max_distance: u32) -> Vec<HashMatch> {
+        let mut out = Vec::new();
+        if self.nodes.is_empty() {
+            return out;
+        }
+        let mut stack = vec![0usize];
+        while let Some(at) = stack.pop() {
+            let node = &self.nodes[at];
+            let d = (node.hash ^ hash).count_ones();
+            if d <= max_distance {
+                out.push(HashMatch {
+                    item: node.item,
+                    variant: node.variant,
+                    phash_distance: d,
+                });
+            }
+            for &(cd, child) in &node.children {
+                if cd + max_distance >= d && cd <= d + max_distance {
+                    stack.push(child);
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Mean Rec.601 luminance of `GRID` x `GRID` cells over `region` (x0, y0, x1, y1).
+fn luminance_grid(map: &TextureMap, region: (usize, usize, usize, usize)) -> Vec<f32> {
+    let (x0, y0, x1, y1) = region;
+    let w = map.width as usize;
+    let (rw, rh) = (x1.saturating_sub(x0).max(1), y1.saturating_sub(y0).max(1));
+    let mut sum = vec![0.0f64; GRID * GRID];
+    let mut count = vec![0u32; GRID * GRID];
+    for y in y0..y1 {
+        let gy = (y - y0) * GRID / rh;
+        for x in x0..x1 {
+            let i = (y * w + x) * 4;
+            if i + 3 > map.data.len() {
+                continue;
+            }
+            let d = &map.data;
+            let cell = gy * GRID + (x - x0) * GRID / rw;
+            sum[cell] += 0.299 * d[i] as f64 + 0.587 * d[i + 1] as f64 + 0.114 * d[i + 2] as f64;
+            count[cell] += 1;
+        }
+    }
+    // Textures smaller than the grid leave cells empty: take the covering pixel instead
+    (0..GRID * GRID)
+        .map(|cell| {
+            if count[cell] > 0 {
+                return (sum[cell] / count[cell] as f64) as f32;
+            }
+            let (gx, gy) = (cell % GRID, cell / GRID);
+            let x = x0 + gx * rw / GRID;
+            let y = y0 + gy * rh / GRID;
+            let i = (y * w + x) * 4;
+            map.data
+                .get(i..i + 3)
+                .map(|p| 0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32)
+                .unwrap_or(0.0)
+        })
+        .collect()
+}
+
+fn transform_grid(grid: &[f32], transform: HashTransform) -> Vec<f32> {
+    let mut out = vec![0.0; GRID * GRID];
+    for y in 0..GRID {
+        for x in 0..GRID {
+            let (sx, sy) = transform.source(x, y, GRID);
+            out[y * GRID + x] = grid[sy * GRID + sx];
+        }
+    }
+    out
+}
+
+/// 64-bit DCT pHash: low-frequency coefficients (without DC) above their median.
+fn phash(grid: &[f32]) -> u64 {
+    // Separable DCT-II, keeping only the first PHASH_BLOCK frequencies per axis
+    let basis: Vec<f32> = (0..PHASH_BLOCK)
+        .flat_map(|k| {
+            (0..GRID).map(move |n| (std::f32::consts::PI * (2 * n + 1) as f32 * k as f32 / (2 * GRID) as f32).cos())
+        })
+        .collect();
+    let mut rows = vec![0.0f32; GRID * PHASH_BLOCK];
+    for y in 0..GRID {
+        for k in 0..PHASH_BLOCK {
+            rows[y * PHASH_BLOCK + k] = (0..GRID).map(|x| grid[y * GRID + x] * basis[k * GRID + x]).sum();
+        }
+    }
+    let mut coeffs = vec![0.0f32; PHASH_BLOCK * PHASH_BLOCK];
+    for l in 0..PHASH_BLOCK {
+        for k in 0..PHASH_BLOCK {
+            coeffs[l * PHASH_BLOCK + k] = (0..GRID).map(|y| rows[y * PHASH_BLOCK + k] * basis[l * GRID + y]).sum();
+        }
+    }
+    let mut ac: Vec<f32> = coeffs[1..].to_vec();
+    ac.sort_by(f32::total_cmp);
+    let median = ac[ac.len() / 2];
+    coeffs
+        .iter()
+        .enumerate()
+        .fold(0u64, |bits, (i, &c)| if i > 0 && c > median { bits | (1 << i) } else { bits })
+}
+
+/// 64-bit dHash: whether each of 8x8 cells is brighter than its right neighbor (9x8 grid).
+fn dhash(grid: &[f32]) -> u64 {
+    let cell = |x0: usize, x1: usize, y0: usize, y1: usize| {
+        let mut sum = 0.0;
+        for y in y0..y1 {
+            for x in x0..x1 {
+                sum += grid[y * GRID + x];
+            }
+        }
+        sum / ((x1 - x0) * (y1 - y0)).max(1) as f32
+    };
+    let mut bits = 0u64;
+    for row in 0..8 {
+        let (y0, y1) = (row * GRID / 8, (row + 1) * GRID / 8);
+        let values: Vec<f32> = (0..9)
+            .map(|col| {
+                let x0 = col * GRID / 9;
+                cell(x0, ((col + 1) * GRID / 9).max(x0 + 1), y0, y1)
+            })
+            .collect();
+        for col in 0..8 {
+            if values[col] > values[col + 1] {
+                bits |= 1 << (row * 8 + col);
+            }
+        }
+    }
+    bits
+}
+
+/// Normalized `COLOR_BINS`³ RGB histogram (every 4th pixel on large textures).
+fn color_signature(map: &TextureMap) -> Vec<f32> {
+    let mut hist = vec![0u32; COLOR_BINS * COLOR_BINS * COLOR_BINS];
+    let pixels = map.data.len() / 4;
+    let stride = if pixels > 1 << 20 { 4 } else { 1 };
+    let mut total = 0u32;
+    for p in map.data.chunks_exact(4).step_by(stride) {
+        let bin = |v: u8| v as usize * COLOR_BINS / 256;
+        hist[(bin(p[0]) * COLOR_BINS + bin(p[1])) * COLOR_BINS + bin(p[2])] += 1;
+        total += 1;
+    }
+    hist.iter().map(|&c| c as f32 / total.max(1) as f32).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asymmetric test image: blobs with a bright corner
+    fn pattern(w: u32, h: u32) -> TextureMap {
+        let mut data = Vec::new();
+        for y in 0..h {
+            for x in 0..w {
+                let (u, v) = (x as f32 / w as f32, y as f32 / h as f32);
+                let l = 100.0 + 60.0 * (u * 9.0).sin() * (v * 5.0 + u * 3.0).cos() + if u < 0.3 && v < 0.2 { 80.0 } else { 0.0 };
+                let l = l.clamp(0.0, 255.0) as u8;
+                data.extend_from_slice(&[l, l / 2, 200 - l / 2, 255]);
+            }
+        }
+        TextureMap::new(w, h, data, None)
+    }
+
+    fn transformed(map: &TextureMap, transform: HashTransform) -> TextureMap {
+        let n = map.width as usize;
+        let mut data = vec![0u8; map.data.len()];
+        for y in 0..n {
+            for x in 0..n {
+                let (sx, sy) = transform.source(x, y, n);
+                let (s, d) = ((sy * n + sx) * 4, (y * n + x) * 4);
+                data[d..d + 4].copy_from_slice(&map.data[s..s + 4]);
+            }
+        }
+        TextureMap::new(map.width, map.height, data, None)
+    }
+
+    #[test]
+    fn rescaled_copy_matches_closely() {
+        let a = TextureHashes::compute(&pattern(256, 256));
+        let b = TextureHashes::compute(&pattern(128, 128));
+        let d = (a.identity().phash ^ b.identity().phash).count_ones();
+        assert!(d <= 4, "distance {}", d);
+        assert!(a.similarity(0, &b) > 0.9);
+    }
+
+    #[test]
+    fn index_finds_rotated_and_mirrored_variants() {
+        let original = pattern(64, 64);
+        let mut index = HashIndex::new();
+        let base = TextureHashes::compute(&original);
+        index.insert(0, &base);
+        for transform in &HashTransform::ALL[1..] {
+            let query = TextureHashes::compute(&transformed(&original, *transform));
+            let best = index
+                .query(query.identity().phash, 8)
+                .into_iter()
+                .min_by_key(|m| m.phash_distance)
+                .unwrap();
+            assert_eq!(base.variants[best.variant].transform, *transform);
+        }
+    }
+
+    #[test]
+    fn center_crop_is_detected() {
+        let original = pattern(128, 128);
+        let mut data = Vec::new();
+        for y in 16..112 {
+            let start = (y * 128 + 16) * 4;
+            data.extend_from_slice(&original.data[start..start + 96 * 4]);
+        }
+        let crop = TextureHashes::compute(&TextureMap::new(96, 96, data, None));
+        let base = TextureHashes::compute(&original);
+        let mut index = HashIndex::new();
+        index.insert(0, &base);
+        let best = index
+            .query(crop.identity().phash, 10)
+            .into_iter()
+            .min_by_key(|m| m.phash_distance)
+            .unwrap();
+        assert_eq!(base.variants[best.variant].crop, Some(0.75));
+    }
+
+    #[test]
+    fn bk_tree_query_matches_linear_scan() {
+        let mut index = HashIndex::new();
+        let hashes: Vec<u64> = (0..200u64).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15)).collect();
+        for (i, &h) in hashes.iter().enumerate() {
+            index.insert_hash(h, i, 0);
+        }
+        let query = hashes[17] ^ 0b1011;
+        let mut found: Vec<usize> = index.query(query, 20).into_iter().map(|m| m.item).collect();
+        found.sort();
+        let expected: Vec<usize> = (0..hashes.len()).filter(|&i| (hashes[i] ^ query).count_ones() <= 20).collect();
+        assert_eq!(found, expected);
+    }
+}
@@ -34,6 +34,22 @@ impl TextureSlot {
             TextureSlot::Height => &["height", "displacement", "bump"],
         }
     }
+
+    /// Slot for a name such as `albedo`, `normal` or `ao` (any of the common suffixes)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        [
+            TextureSlot::Albedo,
+            TextureSlot::Normal,
+            TextureSlot::Metallic,
+            TextureSlot::Roughness,
+            TextureSlot::AmbientOcclusion,
+            TextureSlot::Emissive,
+            TextureSlot::Height,
+        ]
+        .into_iter()
+        .find(|slot| slot.common_suffixes().contains(&name.as_str()))
+    }
 }
 
 /// Supported image formats for loading (PNG, JPG, TGA, EXR)
@@ -87,6 +103,11 @@ impl LoadedImage {
         ])
     }
 
+    /// Whether the decoded color type has an alpha channel
+    pub fn has_alpha_channel(&self) -> bool {
+        self.color_type.starts_with("Rgba") || self.color_type.starts_with("La")
+    }
+
     fn from_dynamic(image: DynamicImage, format: ImageFormat) -> Self {
         let (width, height) = image.dimensions();
         let color_type = format!("{:?}", image.color());
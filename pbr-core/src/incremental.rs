@@ -0,0 +1,320 @@
+//! Incremental export: re-export only materials whose sources or preset changed.
+//!
+//! Every export manifest records the SHA-256 of each source texture and the
+//! preset fingerprint ([`OptimizationPreset::fingerprint`]). A material is up
+//! to date when its folder still holds the same source files with the same
+//! hashes, the fingerprint matches and the previous outputs verify. Outputs
+//! the new export no longer writes, and output folders of materials that are
+//! gone from the library, are deleted. Which materials a library exported is
+//! kept in [`EXPORT_INDEX_FILE`] at the output root, per source root, so
+//! exports from other libraries or by hand are never removed.
+
+use crate::export_manifest::{resolve_manifest_path, sha256_file, verify_export, ExportManifest, EXPORT_MANIFEST_FILE};
+use crate::material::MaterialSet;
+use crate::optimization::OptimizationPreset;
+use crate::quality::{QualityFloor, QUALITY_REPORT_FILE};
+use crate::tiled::export_folder;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Index of incremental exports, written to the output root
+pub const EXPORT_INDEX_FILE: &str = "export-index.json";
+
+/// Contents of [`EXPORT_INDEX_FILE`]: the output folder names each source
+/// root exported in its last incremental run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportIndex {
+    pub roots: BTreeMap<String, Vec<String>>,
+}
+
+impl ExportIndex {
+    /// The index in `output_root`, or an empty one when there is none.
+    pub fn load(output_root: &Path) -> Result<Self> {
+        let path = output_root.join(EXPORT_INDEX_FILE);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content).map_err(Error::from)
+    }
+
+    /// Write to `output_root/export-index.json`.
+    pub fn write(&self, output_root: &Path) -> Result<PathBuf> {
+        let path = output_root.join(EXPORT_INDEX_FILE);
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+}
+
+/// Outcome of [`batch_export_incremental`].
+#[derive(Debug, Clone, Default)]
+pub struct IncrementalReport {
+    /// Materials that were exported (new or changed)
+    pub exported: Vec<String>,
+    /// Materials whose previous export was still current
+    pub skipped: Vec<String>,
+    /// Texture files written
+    pub written: Vec<PathBuf>,
+    /// Orphaned files deleted
+    pub removed: Vec<PathBuf>,
+}
+
+/// Output folder name of the material in `source_folder` (its folder name).
+pub fn material_output_name(source_folder: &Path) -> String {
+    source_folder
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "material".to_string())
+}
+
+/// [`material_output_name`] of every folder, with a numeric suffix
+/// ("Brick_2") on names an earlier folder already took (ignoring case), so no
+/// two materials export into the same folder.
+pub fn material_output_names(folders: &[PathBuf]) -> Vec<String> {
+    let mut taken = HashSet::new();
+    folders
+        .iter()
+        .map(|folder| {
+            let base = material_output_name(folder);
+            let mut name = base.clone();
+            let mut n = 1;
+            while !taken.insert(name.to_lowercase()) {
+                n += 1;
+                name = format!("{}_{}", base, n);
+            }
+            name
+        })
+        .collect()
+}
+
+/// True when `output_dir` holds an intact export of `source_folder` made with
+/// the same preset settings and quality floor. Only hashes files; nothing is
+/// decoded.
+pub fn export_is_current(
+    source_folder: &Path,
+    output_dir: &Path,
+    preset: &OptimizationPreset,
+    include_lod: bool,
+    floor: &QualityFloor,
+) -> Result<bool> {
+    let Ok(previous) = ExportManifest::load(&output_dir.join(EXPORT_MANIFEST_FILE)) else {
+        return Ok(false);
+    };
+    let fingerprint = preset.fingerprint_with_floor(include_lod, floor);
+    if previous.preset_fingerprint.as_deref() != Some(fingerprint.as_str()) {
+        return Ok(false);
+    }
+
+    let mut current = Vec::new();
+    for (_, path) in MaterialSet::source_files(source_folder)? {
+        current.push((file_name(&path.to_string_lossy()), sha256_file(&path)?));
+    }
+    let mut recorded = Vec::new();
+    for source in &previous.sources {
+        let (Some(path), Some(sha256)) = (&source.path, &source.sha256) else {
+            return Ok(false);
+        };
+        recorded.push((file_name(path), sha256.clone()));
+    }
+    current.sort();
+    recorded.sort();
+    if current != recorded {
+        return Ok(false);
+    }
+
+    Ok(verify_export(output_dir)?.is_empty())
+}
+
+/// Delete the files `previous` listed that the export now in `output_dir`
+/// no longer writes, and any folders left empty. Paths that would leave
+/// `output_dir` are skipped. Returns the deleted files.
+pub fn remove_orphaned_outputs(output_dir: &Path, previous: &ExportManifest) -> Result<Vec<PathBuf>> {
+    let current = ExportManifest::load(&output_dir.join(EXPORT_MANIFEST_FILE))?;
+    let kept: HashSet<&str> = current.files.iter().map(|f| f.path.as_str()).collect();
+    let mut removed = Vec::new();
+    for file in previous.files.iter().filter(|f| !kept.contains(f.path.as_str())) {
+        let Some(path) = resolve_manifest_path(output_dir, &file.path) else {
+            continue;
+        };
+        if path.is_file() {
+            std::fs::remove_file(&path)?;
+            remove_empty_parents(&path, output_dir);
+            removed.push(path);
+        }
+    }
+    Ok(removed)
+}
+
+/// Delete the exports in `output_root` that the last incremental run from
+/// `source_root` recorded in [`EXPORT_INDEX_FILE`] but are not named in
+/// `keep`: the listed files, the manifest, the quality report and folders
+/// left empty. Folders another source root also recorded, and unlisted files,
+/// are never touched. Then records `keep` as the exports of `source_root`.
+pub fn remove_orphaned_materials(output_root: &Path, source_root: &Path, keep: &[String]) -> Result<Vec<PathBuf>> {
+    let mut index = ExportIndex::load(output_root)?;
+    let root_key = source_root.display().to_string();
+    let recorded = index.roots.remove(&root_key).unwrap_or_default();
+    let shared: HashSet<&String> = index.roots.values().flatten().collect();
+
+    let mut removed = Vec::new();
+    for name in recorded.iter().filter(|n| !keep.contains(n) && !shared.contains(n)) {
+        let dir = output_root.join(name);
+        let manifest_path = dir.join(EXPORT_MANIFEST_FILE);
+        if !manifest_path.is_file() {
+            continue;
+        }
+        let manifest = ExportManifest::load(&manifest_path)?;
+        let files = manifest
+            .files
+            .iter()
+            .filter_map(|f| resolve_manifest_path(&dir, &f.path))
+            .chain([manifest_path, dir.join(QUALITY_REPORT_FILE)]);
+        for path in files {
+            if path.is_file() {
+                std::fs::remove_file(&path)?;
+                remove_empty_parents(&path, output_root);
+                removed.push(path);
+            }
+        }
+    }
+
+    let mut keep = keep.to_vec();
+    keep.sort();
+    keep.dedup();
+    index.roots.insert(root_key, keep);
+    index.write(output_root)?;
+    Ok(removed)
+}
+
+/// Export each material folder of the library in `source_root` into
+/// `output_root/<folder name>` (see [`material_output_names`]), skipping the
+/// ones whose previous export is current, then delete orphaned outputs.
+pub fn batch_export_incremental<P: AsRef<Path>>(
+    source_root: &Path,
+    folders: &[PathBuf],
+    output_root: P,
+    preset: &OptimizationPreset,
+    include_lod: bool,
+) -> Result<IncrementalReport> {
+    let output_root = output_root.as_ref();
+    std::fs::create_dir_all(output_root)?;
+
+    let mut report = IncrementalReport::default();
+    let names = material_output_names(folders);
+    for (folder, name) in folders.iter().zip(&names) {
+        let material_dir = output_root.join(name);
+        if export_is_current(folder, &material_dir, preset, include_lod, &QualityFloor::default())? {
+            report.skipped.push(name.clone());
+            continue;
+        }
+        let previous = ExportManifest::load(&material_dir.join(EXPORT_MANIFEST_FILE)).ok();
+        let written = export_folder(folder, &material_dir, preset.clone(), include_lod)?;
+        report.written.extend(written);
+        if let Some(previous) = previous {
+            report.removed.extend(remove_orphaned_outputs(&material_dir, &previous)?);
+        }
+        report.exported.push(name.clone());
+    }
+    report.removed.extend(remove_orphaned_materials(output_root, source_root, &names)?);
+    Ok(report)
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn remove_empty_parents(path: &Path, root: &Path) {
+    let mut dir = path.parent();
+    while let Some(d) = dir {
+        if d == root || !d.starts_with(root) || std::fs::remove_dir(d).is_err() {
+            break;
+        }
+        dir = d.parent();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::TextureMap;
+    use crate::optimization::{save_texture, TargetResolution};
+
+    fn write_gray(path: &Path, v: u8) {
+        save_texture(&TextureMap::new(64, 64, vec![v; 64 * 64 * 4], None), path).unwrap();
+    }
+
+    #[test]
+    fn incremental_export_skips_unchanged_and_removes_orphans() {
+        let dir = std::env::temp_dir().join("pbr_incremental_export_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let src = dir.join("src");
+        let (brick, wood) = (src.join("Brick"), src.join("Wood"));
+        std::fs::create_dir_all(&brick).unwrap();
+        std::fs::create_dir_all(&wood).unwrap();
+        write_gray(&brick.join("brick_albedo.png"), 200);
+        write_gray(&brick.join("brick_roughness.png"), 120);
+        write_gray(&wood.join("wood_albedo.png"), 90);
+        let out = dir.join("out");
+        let preset = OptimizationPreset::unreal().with_resolution(TargetResolution::Res512);
+        let folders = vec![brick.clone(), wood.clone()];
+
+        let first = batch_export_incremental(&src, &folders, &out, &preset, false).unwrap();
+        assert_eq!(first.exported, ["Brick", "Wood"]);
+        assert!(out.join("Brick/Roughness.png").is_file());
+
+        let second = batch_export_incremental(&src, &folders, &out, &preset, false).unwrap();
+        assert_eq!(second.skipped, ["Brick", "Wood"]);
+        assert!(second.written.is_empty());
+
+        // Changed source and a new preset setting both force a re-export
+        write_gray(&wood.join("wood_albedo.png"), 91);
+        let third = batch_export_incremental(&src, &folders, &out, &preset, false).unwrap();
+        assert_eq!(third.exported, ["Wood"]);
+        assert_eq!(third.skipped, ["Brick"]);
+        assert!(!export_is_current(&brick, &out.join("Brick"), &preset, true, &QualityFloor::default()).unwrap());
+        let floor = QualityFloor { min_psnr: Some(40.0), ..Default::default() };
+        assert!(!export_is_current(&brick, &out.join("Brick"), &preset, false, &floor).unwrap());
+
+        // Dropped source slot and removed material leave orphans behind;
+        // exports this library never recorded are left alone
+        export_folder(&wood, out.join("Manual"), preset.clone(), false).unwrap();
+        std::fs::remove_file(brick.join("brick_roughness.png")).unwrap();
+        let fourth = batch_export_incremental(&src, &folders[..1], &out, &preset, false).unwrap();
+        assert_eq!(fourth.exported, ["Brick"]);
+        assert!(!out.join("Brick/Roughness.png").exists());
+        assert!(!out.join("Wood").exists());
+        assert_eq!(fourth.removed.len(), 3);
+        assert!(out.join("Manual").join(EXPORT_MANIFEST_FILE).is_file());
+        assert_eq!(ExportIndex::load(&out).unwrap().roots[&src.display().to_string()], ["Brick"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn same_named_folders_export_apart_and_stay_current() {
+        let dir = std::env::temp_dir().join("pbr_incremental_same_name_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let src = dir.join("src");
+        let (walls, floors) = (src.join("walls/Brick"), src.join("floors/Brick"));
+        std::fs::create_dir_all(&walls).unwrap();
+        std::fs::create_dir_all(&floors).unwrap();
+        write_gray(&walls.join("brick_albedo.png"), 200);
+        write_gray(&floors.join("brick_albedo.png"), 60);
+        let out = dir.join("out");
+        let preset = OptimizationPreset::unreal().with_resolution(TargetResolution::Res512);
+        let folders = vec![walls, floors];
+
+        let first = batch_export_incremental(&src, &folders, &out, &preset, false).unwrap();
+        assert_eq!(first.exported, ["Brick", "Brick_2"]);
+        assert!(first.removed.is_empty());
+        let second = batch_export_incremental(&src, &folders, &out, &preset, false).unwrap();
+        assert_eq!(second.skipped, ["Brick", "Brick_2"]);
+        assert!(second.removed.is_empty());
+        assert_eq!(ExportIndex::load(&out).unwrap().roots[&src.display().to_string()], ["Brick", "Brick_2"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -2,8 +2,11 @@
 //!
 //! Exports structured reports as JSON using serde.
 
+use crate::color_distribution::{material_color_distributions, TextureColorDistribution};
 use crate::estimation::{estimate_vram, VramEstimate};
 use crate::material::{MaterialSet, TextureSet};
+use crate::scoring::{ScoreBreakdown, ScoringModel};
+use crate::texel_density::TexelDensityTarget;
 use crate::validation::Issue;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -107,6 +110,12 @@ pub struct MaterialReport {
     /// AI-assisted insights (classification, smart suggestions, anomalies)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ai_insights: Option<crate::ai::AiInsights>,
+    /// How the score was computed (model, category sub-scores, per-rule penalties)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_breakdown: Option<ScoreBreakdown>,
+    /// Per-texture histograms and color distribution (HTML reports)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_distributions: Option<Vec<TextureColorDistribution>>,
 }
 
 /// Summary of material texture set
@@ -146,11 +155,33 @@ impl MaterialReport {
         set: &MaterialSet,
         issues: Vec<Issue>,
         onnx_path: Option<&Path>,
+    ) -> Self {
+        Self::from_material_set_with_scoring(set, issues, onnx_path, &ScoringModel::default())
+    }
+
+    /// Build a report scored with the given scoring model
+    pub fn from_material_set_with_scoring(
+        set: &MaterialSet,
+        issues: Vec<Issue>,
+        onnx_path: Option<&Path>,
+        scoring: &ScoringModel,
+    ) -> Self {
+        Self::from_material_set_with_target(set, issues, onnx_path, scoring, &TexelDensityTarget::default())
+    }
+
+    /// Build a report scored with the given scoring model, with resolution
+    /// suggestions against the given texel density target
+    pub fn from_material_set_with_target(
+        set: &MaterialSet,
+        issues: Vec<Issue>,
+        onnx_path: Option<&Path>,
+        scoring: &ScoringModel,
+        texel_density: &TexelDensityTarget,
     ) -> Self {
         let texture_set = TextureSet::from(set);
         let analysis = crate::material::MaterialAnalyzer::analyze(&texture_set);
 
-        let ai_insights = crate::ai::analyze_material(set, onnx_path);
+        let ai_insights = crate::ai::analyze_material_with_target(set, onnx_path, texel_density);
 
         // Append AI anomalies as minor issues
         let mut issues = issues;
@@ -167,7 +198,8 @@ impl MaterialReport {
         let error_count = issues.iter().filter(|i| i.severity == crate::validation::Severity::Critical).count();
         let warning_count = issues.iter().filter(|i| i.severity == crate::validation::Severity::Major).count();
         let passed = error_count == 0;
-        let score = crate::validation::compute_score(&issues);
+        let score_breakdown = scoring.evaluate(&issues);
+        let score = score_breakdown.score;
 
         let mut optimization_suggestions = Self::derive_suggestions(set, &issues);
 
@@ -210,9 +242,17 @@ impl MaterialReport {
             warning_count,
             vram_estimate: Some(vram_estimate),
             ai_insights: Some(ai_insights),
+            score_breakdown: Some(score_breakdown),
+            color_distributions: None,
         }
     }
 
+    /// Attach histograms, percentiles and dominant colors for every texture of `set`
+    pub fn with_color_distributions(mut self, set: &MaterialSet) -> Self {
+        self.color_distributions = Some(material_color_distributions(set));
+        self
+    }
+
     fn can_pack_orm(set: &MaterialSet) -> bool {
         set.roughness.is_some() && set.metallic.is_some() && set.ao.is_some()
     }
@@ -287,12 +327,7 @@ mod tests {
     fn material_report_serializes_to_json() {
         let mut set = MaterialSet::new();
         set.name = Some("TestMaterial".into());
-        set.albedo = Some(TextureMap {
-            width: 4,
-            height: 4,
-            data: vec![128; 4 * 4 * 4],
-            path: None,
-        });
+        set.albedo = Some(TextureMap::new(4, 4, vec![128; 4 * 4 * 4], None));
 
         let issues = vec![
             crate::validation::Issue::new("test_rule", crate::validation::Severity::Major, "Test issue message"),
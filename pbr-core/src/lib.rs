@@ -8,21 +8,63 @@
 //! - [`image_loading`] - Image loading and texture metadata
 //! - [`material`] - Material and texture set analysis
 //! - [`validation`] - Validation rules and checks
+//! - [`budget`] - Scene/level texture memory budgets per platform with mip streaming residency
+//! - [`budget_fit`] - Per-slot resolutions that fit a memory budget with the least SSIM-weighted loss
+//! - [`color_distribution`] - Histograms, percentiles, dominant colors and gamut summary per texture
+//! - [`dedup`] - Exact (byte/pixel) duplicate detection, savings report and deduplication
+//! - [`image_hash`] - Perceptual hashes (DCT pHash, dHash, color) and BK-tree index
+//! - [`texture_stats`] - Memoized single-pass per-texture statistics
+//! - [`tileability`] - Seam analysis (gradient profile + FFT cross score)
+//! - [`seamless`] - Seamless tiling fixer (min-error seam quilting + Poisson blending)
+//! - [`scoring`] - Configurable scoring model (rule weights, caps, category sub-scores)
+//! - [`quality`] - Image quality metrics (SSIM) between original and optimized textures
 //! - [`report`] - Report generation from analysis results
-//! - [`analysis`] - Advanced analysis (duplicates, cross-material, tileability)
-//! - [`estimation`] - GPU/CPU VRAM estimation
+//! - [`analysis`] - Advanced analysis (duplicates, cross-material, tileability, repetition)
+//! - [`analysis_cache`] - Persistent on-disk cache of rule results and texture stats
+//! - [`atlas`] - Atlas / trim-sheet packing of small materials (MaxRects, skyline) with UV remap tables
+//! - [`estimation`] - GPU/CPU VRAM estimation (RGBA8 and per-platform compressed formats)
+//! - [`texture_search`] - Persistent similar-texture search index (pHash + feature embeddings)
+//! - [`texel_density`] - Texel density against mesh UVs (OBJ/glTF)
+//! - [`parallel`] - Parallel job pool for batch loading and per-texture stats
+//! - [`incremental`] - Incremental export: skip unchanged materials, delete orphaned outputs
+//! - [`tiled`] - Row-streamed export and statistics for 16K+ textures
+//! - [`dither`] - Ordered / blue-noise dithering of 16-bit and float sources down to 8 bits
+//! - [`banding`] - Banding (stair-stepping) detection in smooth gradients
 
 pub mod ai;
 pub mod analysis;
+pub mod analysis_cache;
+pub mod atlas;
 pub mod audit_log;
+pub mod banding;
+pub mod budget;
+pub mod budget_fit;
+pub mod color_distribution;
+pub mod dedup;
+pub mod dither;
 pub mod estimation;
+pub mod export_manifest;
+pub mod image_hash;
 pub mod image_loading;
+pub mod incremental;
 pub mod json_report;
 pub mod material;
+pub mod naming;
 pub mod optimization;
+pub mod parallel;
 pub mod plugin;
+pub mod quality;
 pub mod report;
 pub mod report_export;
+pub mod scoring;
+pub mod seamless;
+pub mod texel_density;
+pub mod texture_search;
+pub mod texture_stats;
+#[cfg(test)]
+pub(crate) mod test_util;
+pub mod tileability;
+pub mod tiled;
 pub mod validation;
 pub mod undo_stack;
 pub mod version_tracker;
@@ -45,16 +87,70 @@ pub use optimization::{
     export_with_optimization_preset, export_with_preset, export_with_target,
     export_with_target_and_lod, generate_lod_chain,
     pack_rma, pack_rma_from_material, resize_and_save_texture, resize_material_set,
-    resize_texture, save_texture, ExportPreset, OptimizationPreset, TargetResolution,
+    resize_texture, save_texture, flip_normal_green, pack_channels, pack_material, slot_channels, ExportPreset, ExportedTexture,
+    NormalConvention, OptimizationPreset, OutputFormat, PackingLayout, TargetResolution,
+};
+pub use atlas::{
+    build_atlas, export_atlas, pack_atlas, AtlasEntry, AtlasLayout, AtlasOptions, AtlasPage, AtlasReport,
+    PackingAlgorithm, SkippedMaterial, ATLAS_CSV_FILE, ATLAS_JSON_FILE,
+};
+pub use estimation::{
+    estimate_vram, estimate_vram_for_platform, texture_format_bytes, FormatVramEntry, Platform, PlatformProfile,
+    PlatformVramEstimate, TextureFormat, VramEstimate,
 };
-pub use estimation::{estimate_vram, VramEstimate};
 pub use validation::{compute_score, Issue, ValidationResult, ValidationRule, Validator};
+pub use scoring::{CategoryScore, RulePenalty, ScoreBreakdown, ScoreCategory, ScoringModel};
+pub use texture_stats::{ChannelStats, TextureStats};
+pub use tileability::SeamAnalysis;
+pub use budget::{
+    evaluate_loaded_scene, evaluate_scene_budget, BudgetOffender, PlatformBudget, SceneBudgetReport, SceneManifest,
+    SceneMaterial, StreamingPriority, SuggestedDowngrade,
+};
+pub use budget_fit::{export_budget_fit, plan_budget_fit, BudgetFitPlan, FitOptions, MaterialFit, SlotFit};
+pub use color_distribution::{
+    analyze_color_distributions, color_distribution, material_color_distributions, ChannelDistribution,
+    DominantColor, GamutSummary, Percentiles, TextureColorDistribution,
+};
+pub use dedup::{
+    apply_dedup, find_exact_duplicates, DedupManifest, DedupManifestEntry, ExactDuplicate, ExactDuplicateGroup,
+    ExactDuplicateReport, ExactMatch, DEDUP_MANIFEST_FILE,
+};
+pub use banding::{
+    analyze_banding, banding_analysis, material_banding, BandingAnalysis, TextureBanding, BANDING_THRESHOLD,
+};
+pub use dither::{load_high_precision, quantize, resize_dithered, DitherMode, DitherSettings};
+pub use image_hash::{HashIndex, HashMatch, HashTransform, HashVariant, TextureHashes};
+pub use seamless::{
+    fix_material_folder, make_material_seamless, make_seamless, make_seamless_with_report, MaterialFixReport,
+    SeamPlan, SeamlessMethod, SeamlessOptions, SlotFixResult, FIX_REPORT_FILE,
+};
+pub use texture_search::{IndexUpdate, IndexedMaterial, IndexedTexture, SearchHit, SearchIndex, SEARCH_INDEX_FILE};
+pub use export_manifest::{verify_export, ColorSpace, ExportManifest, ManifestFile, ManifestSource, EXPORT_MANIFEST_FILE};
+pub use naming::{NameContext, NamingTemplate};
+pub use quality::{
+    compare_textures, export_with_quality_floor, material_quality, ms_ssim, normal_angle_error, psnr, resample_ssim, ssim,
+    ExportQualityReport, NormalAngleError, QualityFloor, QualityMetrics, TextureQuality, QUALITY_REPORT_FILE,
+};
+pub use analysis_cache::{AnalysisCache, CachedAnalysis};
+pub use texel_density::{
+    analyze_texel_density, DensityStatus, MeshUvStats, TexelDensityReport, TexelDensityRule, TexelDensityTarget,
+};
 pub use ai::{
-    ai_analyze_json, analyze_material, classify_material, detect_anomalies, suggest_optimizations,
+    ai_analyze_json, analyze_material, analyze_material_with_target, classify_material, detect_anomalies,
+    suggest_optimizations, suggest_optimizations_with_target,
     AiInsights, AiSuggestion, Anomaly, MaterialClass, AI_ONNX_ENABLED,
 };
+pub use incremental::{
+    batch_export_incremental, export_is_current, material_output_name, material_output_names,
+    remove_orphaned_materials,
+    remove_orphaned_outputs, ExportIndex, IncrementalReport, EXPORT_INDEX_FILE,
+};
+pub use tiled::{
+    export_folder, export_folder_tiled, file_stats, largest_source_dimension, load_folder, needs_tiling, write_rows, FileRows,
+    FlipGreenRows, PackedRows, ResizedRows, RowSource, TextureRows, TILED_THRESHOLD,
+};
 pub use plugin::{
-    PluginInfo, PluginLoader, PluginManifest, PresetConfig, RuleConfig, RuleCondition,
+    resolve_preset, PluginInfo, PluginLoader, PluginManifest, PresetConfig, RuleConfig, RuleCondition,
 };
 pub use analysis::{
     analyze_tileability, detect_duplicates, analyze_cross_material, edge_difference,
@@ -62,7 +158,9 @@ pub use analysis::{
     run_advanced_analysis, run_advanced_analysis_and_write,
     AdvancedAnalysisReport, CrossMaterialResult, DuplicateAnalysisResult, DuplicatePair,
     TileabilityAnalysisEntry, TileabilityFixResult,
-    TILEABILITY_THRESHOLD,
+    analyze_repetition, repetition_analysis, RepetitionAnalysis, RepetitionAnalysisEntry, RepetitionHotspot,
+    REPETITION_THRESHOLD, TILEABILITY_THRESHOLD,
+    ClassValueDistribution, MaterialValueStats, MetricDistribution, ValueMetric, ValueOutlier, VALUE_OUTLIER_Z,
 };
 
 
@@ -3,13 +3,18 @@
 //! Analyzes PBR texture sets for consistency, completeness,
 //! and physical correctness.
 
+use crate::dedup::{DedupManifest, DEDUP_MANIFEST_FILE};
+use crate::image_hash::TextureHashes;
 use crate::image_loading::{ImageLoader, LoadedImage, TextureSlot};
+use crate::texture_stats::TextureStats;
+use crate::tileability::SeamAnalysis;
 use crate::Result;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 
 /// Supported image extensions for folder scanning
-const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tga", "exr"];
+pub(crate) const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tga", "exr"];
 
 /// A texture map with resolution and pixel data
 #[derive(Debug, Clone)]
@@ -22,16 +27,93 @@ pub struct TextureMap {
     pub data: Vec<u8>,
     /// Source path when loaded from file
     pub path: Option<PathBuf>,
+    /// Lazily computed pixel statistics shared by rules and analyses
+    stats: OnceLock<Arc<TextureStats>>,
+    /// Lazily computed seam analysis
+    tileability: OnceLock<Arc<SeamAnalysis>>,
+    /// Lazily computed perceptual hashes
+    hashes: OnceLock<Arc<TextureHashes>>,
+    /// Whether any pixel is translucent, known up front for sources decoded without alpha
+    alpha: OnceLock<bool>,
 }
 
 impl TextureMap {
-    pub fn from_loaded(image: LoadedImage, path: Option<PathBuf>) -> Self {
+    pub fn new(width: u32, height: u32, data: Vec<u8>, path: Option<PathBuf>) -> Self {
         Self {
-            width: image.width,
-            height: image.height,
-            data: image.data,
+            width,
+            height,
+            data,
             path,
+            stats: OnceLock::new(),
+            tileability: OnceLock::new(),
+            hashes: OnceLock::new(),
+            alpha: OnceLock::new(),
+        }
+    }
+
+    pub fn from_loaded(image: LoadedImage, path: Option<PathBuf>) -> Self {
+        let opaque = !image.has_alpha_channel();
+        let map = Self::new(image.width, image.height, image.data, path);
+        if opaque {
+            let _ = map.alpha.set(false);
         }
+        map
+    }
+
+    /// Pixel statistics, computed in a single pass on first access and memoized.
+    /// Call [`invalidate_stats`](Self::invalidate_stats) after modifying `data` in place.
+    pub fn stats(&self) -> &TextureStats {
+        self.stats.get_or_init(|| Arc::new(TextureStats::compute(self)))
+    }
+
+    /// Statistics if they have already been computed or seeded.
+    pub fn cached_stats(&self) -> Option<&TextureStats> {
+        self.stats.get().map(|s| s.as_ref())
+    }
+
+    /// Seed the statistics cache, e.g. from a persisted analysis cache.
+    pub fn set_stats(&mut self, stats: TextureStats) {
+        self.stats = OnceLock::from(Arc::new(stats));
+    }
+
+    /// Seed the seam analysis cache, e.g. from a preview of a streamed texture.
+    pub fn set_tileability(&mut self, seams: SeamAnalysis) {
+        self.tileability = OnceLock::from(Arc::new(seams));
+    }
+
+    /// False for textures loaded without pixel data (see [`crate::tiled::load_folder`]);
+    /// their statistics and seam analysis are seeded instead.
+    pub fn has_pixels(&self) -> bool {
+        self.data.len() >= self.width as usize * self.height as usize * 4
+    }
+
+    /// Seam analysis of the wrap-around edges, computed on first access and memoized.
+    pub fn tileability(&self) -> &SeamAnalysis {
+        self.tileability.get_or_init(|| Arc::new(SeamAnalysis::compute(self)))
+    }
+
+    /// Perceptual hashes for duplicate lookup, computed on first access and memoized.
+    pub fn hashes(&self) -> &TextureHashes {
+        self.hashes.get_or_init(|| Arc::new(TextureHashes::compute(self)))
+    }
+
+    /// Whether any pixel is not fully opaque. Known without looking at the
+    /// pixels for sources decoded without alpha, read from the statistics when
+    /// they are already computed, and otherwise found by scanning the alpha
+    /// bytes (no statistics pass). Memoized.
+    pub fn has_alpha(&self) -> bool {
+        *self.alpha.get_or_init(|| match self.cached_stats() {
+            Some(stats) => stats.channels[3].min < 255,
+            None => self.data.chunks_exact(4).any(|px| px[3] < 255),
+        })
+    }
+
+    /// Drop memoized statistics, seam analysis, hashes and alpha so they are recomputed on next access.
+    pub fn invalidate_stats(&mut self) {
+        self.stats = OnceLock::new();
+        self.tileability = OnceLock::new();
+        self.hashes = OnceLock::new();
+        self.alpha = OnceLock::new();
     }
 
     /// Get pixel at (x, y) as [R, G, B, A]
@@ -179,8 +261,29 @@ impl MaterialSet {
             ..Default::default()
         };
 
-        let entries = std::fs::read_dir(path)?;
+        for (slot, file_path) in Self::source_files(path)? {
+            let map = TextureMap::from_loaded(ImageLoader::load(&file_path)?, Some(file_path));
+            match slot {
+                TextureSlot::Albedo => set.albedo = Some(map),
+                TextureSlot::Normal => set.normal = Some(map),
+                TextureSlot::Roughness => set.roughness = Some(map),
+                TextureSlot::Metallic => set.metallic = Some(map),
+                TextureSlot::AmbientOcclusion => set.ao = Some(map),
+                TextureSlot::Height => set.height = Some(map),
+                TextureSlot::Emissive => {}
+            }
+        }
+
+        Ok(set)
+    }
+
+    /// Files [`MaterialSet::load_from_folder`] would load, one per slot,
+    /// without decoding them. Maps removed by [`crate::apply_dedup`] resolve
+    /// to their shared copy through the folder's dedup manifest.
+    pub fn source_files<P: AsRef<Path>>(path: P) -> Result<Vec<(TextureSlot, PathBuf)>> {
+        let entries = std::fs::read_dir(path.as_ref())?;
         let mut candidates: Vec<(PathBuf, TextureSlot)> = Vec::new();
+        let mut shared_copies: HashMap<usize, PathBuf> = HashMap::new();
 
         for entry in entries {
             let entry = entry?;
@@ -216,40 +319,30 @@ impl MaterialSet {
             }
         }
 
-        // Sort for deterministic ordering (first match wins per slot)
-        candidates.sort_by(|a, b| a.0.file_name().cmp(&b.0.file_name()));
-
-        for (file_path, slot) in candidates {
-            match slot {
-                TextureSlot::Albedo if set.albedo.is_none() => {
-                    let img = ImageLoader::load(&file_path)?;
-                    set.albedo = Some(TextureMap::from_loaded(img, Some(file_path)));
-                }
-                TextureSlot::Normal if set.normal.is_none() => {
-                    let img = ImageLoader::load(&file_path)?;
-                    set.normal = Some(TextureMap::from_loaded(img, Some(file_path)));
-                }
-                TextureSlot::Roughness if set.roughness.is_none() => {
-                    let img = ImageLoader::load(&file_path)?;
-                    set.roughness = Some(TextureMap::from_loaded(img, Some(file_path)));
-                }
-                TextureSlot::Metallic if set.metallic.is_none() => {
-                    let img = ImageLoader::load(&file_path)?;
-                    set.metallic = Some(TextureMap::from_loaded(img, Some(file_path)));
-                }
-                TextureSlot::AmbientOcclusion if set.ao.is_none() => {
-                    let img = ImageLoader::load(&file_path)?;
-                    set.ao = Some(TextureMap::from_loaded(img, Some(file_path)));
+        // Maps removed by `dedup --apply` load from their shared copy
+        let manifest_path = path.as_ref().join(DEDUP_MANIFEST_FILE);
+        if manifest_path.is_file() {
+            for (removed, shared) in DedupManifest::load(&manifest_path)?.replacements_in(path.as_ref())? {
+                if let Some(slot) = ImageLoader::detect_slot_from_path(&removed) {
+                    candidates.push((removed, slot));
+                    shared_copies.insert(candidates.len() - 1, shared);
                 }
-                TextureSlot::Height if set.height.is_none() => {
-                    let img = ImageLoader::load(&file_path)?;
-                    set.height = Some(TextureMap::from_loaded(img, Some(file_path)));
-                }
-                _ => {}
             }
         }
 
-        Ok(set)
+        // Sort for deterministic ordering (first match wins per slot)
+        let mut order: Vec<usize> = (0..candidates.len()).collect();
+        order.sort_by(|&a, &b| candidates[a].0.file_name().cmp(&candidates[b].0.file_name()));
+
+        let mut files: Vec<(TextureSlot, PathBuf)> = Vec::new();
+        for i in order {
+            let slot = candidates[i].1;
+            let file_path = shared_copies.remove(&i).unwrap_or_else(|| candidates[i].0.clone());
+            if !files.iter().any(|(s, _)| *s == slot) {
+                files.push((slot, file_path));
+            }
+        }
+        Ok(files)
     }
 }
 
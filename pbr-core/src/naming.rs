@@ -0,0 +1,238 @@
+//! Output naming templates for exports.
+//!
+//! File and LOD folder names are rendered from templates with `{token}`
+//! placeholders:
+//!
+//! | Token | Value |
+//! |-------|-------|
+//! | `{material}` | Material name (folder name) |
+//! | `{slot}` | `BaseColor`, `Normal`, `ORM` / `RMA` / `MaskMap`, `Roughness`, `Metallic`, `AmbientOcclusion`, `Height` |
+//! | `{slot:short}` | `D`, `N`, `ORM` / `RMA` / `MS`, `R`, `M`, `AO`, `H` |
+//! | `{lod}` | LOD index (0 = base level) |
+//! | `{res}` | Longest edge of the written texture in pixels |
+//! | `{ext}` | File extension of the preset's format (`png`, `tga`, `jpg`) |
+//!
+//! The default `{slot}.{ext}` in `LOD{lod}/` folders reproduces the classic
+//! layout; `T_{material}_{slot:short}.{ext}` gives Unreal's `T_Brick_D` style.
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// Default file name template
+pub const DEFAULT_FILE_TEMPLATE: &str = "{slot}.{ext}";
+
+/// Default LOD folder template
+pub const DEFAULT_LOD_DIR_TEMPLATE: &str = "LOD{lod}";
+
+const FILE_TOKENS: [&str; 6] = ["material", "slot", "slot:short", "lod", "res", "ext"];
+const LOD_DIR_TOKENS: [&str; 2] = ["material", "lod"];
+
+/// How exported files and LOD folders are named.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NamingTemplate {
+    /// File name, may contain `/` for subfolders
+    pub file: String,
+    /// Folder for each LOD level when exporting a LOD chain; empty puts every
+    /// level in the output folder (the file template must then use `{lod}`)
+    pub lod_dir: String,
+}
+
+impl Default for NamingTemplate {
+    fn default() -> Self {
+        Self {
+            file: DEFAULT_FILE_TEMPLATE.to_string(),
+            lod_dir: DEFAULT_LOD_DIR_TEMPLATE.to_string(),
+        }
+    }
+}
+
+/// Values substituted into a file template.
+#[derive(Debug, Clone, Copy)]
+pub struct NameContext<'a> {
+    pub material: &'a str,
+    /// Slot key: "albedo", "normal", "orm", "rma", "mask", "roughness", "metallic", "ao", "height"
+    pub slot: &'a str,
+    pub lod: u32,
+    pub res: u32,
+    pub ext: &'a str,
+}
+
+impl NamingTemplate {
+    /// File template with the default LOD folder template. Fails on unknown
+    /// tokens or when the template does not name the slot.
+    pub fn new(file: &str) -> Result<Self> {
+        let template = Self {
+            file: file.to_string(),
+            ..Default::default()
+        };
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Unreal Engine convention: `T_{material}_{slot:short}.{ext}`.
+    pub fn unreal() -> Self {
+        Self {
+            file: "T_{material}_{slot:short}.{ext}".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Override the LOD folder template.
+    pub fn with_lod_dir(mut self, lod_dir: &str) -> Result<Self> {
+        self.lod_dir = lod_dir.to_string();
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Checks tokens, that every slot gets its own file and that paths stay
+    /// inside the output folder.
+    pub fn validate(&self) -> Result<()> {
+        let file_tokens = tokens(&self.file)?;
+        let dir_tokens = tokens(&self.lod_dir)?;
+        if let Some(t) = file_tokens.iter().find(|t| !FILE_TOKENS.contains(&t.as_str())) {
+            return Err(Error::Other(format!("Unknown token {{{}}} in file template \"{}\"", t, self.file)));
+        }
+        if let Some(t) = dir_tokens.iter().find(|t| !LOD_DIR_TOKENS.contains(&t.as_str())) {
+            return Err(Error::Other(format!("Unknown token {{{}}} in LOD folder template \"{}\"", t, self.lod_dir)));
+        }
+        if !file_tokens.iter().any(|t| t == "slot" || t == "slot:short") {
+            return Err(Error::Other(format!(
+                "File template \"{}\" must contain {{slot}} or {{slot:short}}",
+                self.file
+            )));
+        }
+        for template in [&self.file, &self.lod_dir] {
+            if template.starts_with('/') || template.split(['/', '\\']).any(|part| part == "..") {
+                return Err(Error::Other(format!("Template \"{}\" must stay inside the output folder", template)));
+            }
+        }
+        Ok(())
+    }
+
+    /// True when every LOD level gets distinct paths.
+    pub fn separates_lods(&self) -> bool {
+        self.lod_dir.contains("{lod}") || self.file.contains("{lod}")
+    }
+
+    /// Relative path of one exported file.
+    pub fn file_name(&self, ctx: &NameContext) -> Result<String> {
+        render(&self.file, |token| match token {
+            "material" => Some(ctx.material.to_string()),
+            "slot" => Some(slot_long_name(ctx.slot).to_string()),
+            "slot:short" => Some(slot_short_name(ctx.slot).to_string()),
+            "lod" => Some(ctx.lod.to_string()),
+            "res" => Some(ctx.res.to_string()),
+            "ext" => Some(ctx.ext.to_string()),
+            _ => None,
+        })
+    }
+
+    /// Relative folder of a LOD level ("" for the output folder itself).
+    pub fn lod_dir_name(&self, material: &str, lod: u32) -> Result<String> {
+        render(&self.lod_dir, |token| match token {
+            "material" => Some(material.to_string()),
+            "lod" => Some(lod.to_string()),
+            _ => None,
+        })
+    }
+}
+
+/// Name used by `{slot}`.
+pub fn slot_long_name(slot: &str) -> &'static str {
+    match slot {
+        "albedo" => "BaseColor",
+        "normal" => "Normal",
+        "orm" => "ORM",
+        "rma" => "RMA",
+        "mask" => "MaskMap",
+        "roughness" => "Roughness",
+        "metallic" => "Metallic",
+        "ao" => "AmbientOcclusion",
+        "height" => "Height",
+        _ => "Texture",
+    }
+}
+
+/// Suffix used by `{slot:short}`.
+pub fn slot_short_name(slot: &str) -> &'static str {
+    match slot {
+        "albedo" => "D",
+        "normal" => "N",
+        "orm" => "ORM",
+        "rma" => "RMA",
+        "mask" => "MS",
+        "roughness" => "R",
+        "metallic" => "M",
+        "ao" => "AO",
+        "height" => "H",
+        _ => "T",
+    }
+}
+
+fn tokens(template: &str) -> Result<Vec<String>> {
+    let mut found = Vec::new();
+    render(template, |token| {
+        found.push(token.to_string());
+        Some(String::new())
+    })?;
+    Ok(found)
+}
+
+fn render(template: &str, mut value: impl FnMut(&str) -> Option<String>) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| Error::Other(format!("Unclosed {{ in template \"{}\"", template)))?;
+        let token = &rest[start + 1..start + end];
+        let v = value(token).ok_or_else(|| Error::Other(format!("Unknown token {{{}}} in \"{}\"", token, template)))?;
+        out.push_str(&v);
+        rest = &rest[start + end + 1..];
+    }
+    if rest.contains('}') {
+        return Err(Error::Other(format!("Unmatched }} in template \"{}\"", template)));
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(slot: &str, lod: u32) -> NameContext<'_> {
+        NameContext { material: "Brick", slot, lod, res: 2048, ext: "png" }
+    }
+
+    #[test]
+    fn renders_default_and_unreal_names() {
+        let default = NamingTemplate::default();
+        assert_eq!(default.file_name(&ctx("albedo", 0)).unwrap(), "BaseColor.png");
+        assert_eq!(default.lod_dir_name("Brick", 2).unwrap(), "LOD2");
+
+        let unreal = NamingTemplate::unreal();
+        assert_eq!(unreal.file_name(&ctx("albedo", 0)).unwrap(), "T_Brick_D.png");
+        assert_eq!(unreal.file_name(&ctx("orm", 0)).unwrap(), "T_Brick_ORM.png");
+
+        let flat = NamingTemplate::new("{material}_{slot}_{res}_LOD{lod}.{ext}")
+            .unwrap()
+            .with_lod_dir("")
+            .unwrap();
+        assert!(flat.separates_lods());
+        assert_eq!(flat.file_name(&ctx("normal", 1)).unwrap(), "Brick_Normal_2048_LOD1.png");
+        assert_eq!(flat.lod_dir_name("Brick", 1).unwrap(), "");
+    }
+
+    #[test]
+    fn rejects_bad_templates() {
+        assert!(NamingTemplate::new("{material}.png").is_err());
+        assert!(NamingTemplate::new("{slot}_{size}.png").is_err());
+        assert!(NamingTemplate::new("{slot.png").is_err());
+        assert!(NamingTemplate::new("../{slot}.png").is_err());
+        assert!(NamingTemplate::default().with_lod_dir("{res}").is_err());
+        assert!(!NamingTemplate::default().with_lod_dir("lods").unwrap().separates_lods());
+    }
+}
@@ -3,15 +3,27 @@
 //! Provides optimization presets for different targets (Unreal, Unity, Mobile):
 //!
 //! - **Resize textures**: 1K, 2K, 4K (longest edge) using Lanczos3 resampling
-//! - **Channel packing**: R=AO, G=Roughness, B=Metallic (ORM/RMA texture)
+//! - **Channel packing**: ORM (R=AO, G=Roughness, B=Metallic), RMA or Unity mask map
+//! - **Normal convention**: OpenGL (Y+) or DirectX (Y-) normal maps
 //! - **LOD generation**: Low-res textures (512, 256, 128) for streaming
+//! - **Naming**: file and LOD folder names from templates (see [`crate::naming`])
+//! - **Manifest**: every export folder gets a `manifest.json` with checksums
+//!   and provenance (see [`crate::export_manifest`])
 //!
 //! All outputs are saved locally; no cloud or backend.
 
+use crate::estimation::{Platform, TextureFormat};
+use crate::export_manifest::ExportManifest;
+use crate::dither::{DitherSettings, DitherSources};
 use crate::material::TextureMap;
+use crate::naming::{NameContext, NamingTemplate};
 use crate::Result;
 use image::imageops::FilterType;
-use image::{ImageBuffer, RgbaImage};
+use image::buffer::ConvertBuffer;
+use image::{ImageBuffer, Rgba};
+use std::borrow::Cow;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 /// Target resolution presets for texture optimization.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -59,15 +71,49 @@ impl TargetResolution {
         }
     }
 
+    /// Preset for a longest-edge size, or `Custom` when it matches none.
+    pub fn from_max_dimension(max_dim: u32) -> Self {
+        match max_dim {
+            4096 => TargetResolution::Res4K,
+            2048 => TargetResolution::Res2K,
+            1024 => TargetResolution::Res1K,
+            512 => TargetResolution::Res512,
+            256 => TargetResolution::Res256,
+            128 => TargetResolution::Res128,
+            d => TargetResolution::Custom(d),
+        }
+    }
+
     /// Default LOD chain (512, 256, 128) for streaming
     pub fn default_lod_levels() -> &'static [TargetResolution] {
         &[TargetResolution::Res512, TargetResolution::Res256, TargetResolution::Res128]
     }
 }
 
+impl std::str::FromStr for TargetResolution {
+    type Err = crate::Error;
+
+    /// "4k", "2k", "1k" or a longest edge in pixels ("512", "768px").
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_lowercase();
+        let max_dim = match s.as_str() {
+            "4k" => 4096,
+            "2k" => 2048,
+            "1k" => 1024,
+            _ => s
+                .trim_end_matches("px")
+                .parse::<u32>()
+                .ok()
+                .filter(|&d| d > 0)
+                .ok_or_else(|| crate::Error::Other(format!("Invalid resolution: {}. Use 4k, 2k, 1k or pixels.", s)))?,
+        };
+        Ok(Self::from_max_dimension(max_dim))
+    }
+}
+
 /// Computes new dimensions for an image when scaling the longest edge to the target.
 /// Preserves aspect ratio.
-fn compute_target_dimensions(
+pub(crate) fn compute_target_dimensions(
     width: u32,
     height: u32,
     max_dim: u32,
@@ -102,15 +148,8 @@ pub fn resize_texture(
         return Ok(texture.clone());
     }
 
-    let img: RgbaImage = ImageBuffer::from_raw(
-        texture.width,
-        texture.height,
-        texture.data.clone(),
-    )
-    .ok_or_else(|| crate::Error::Other("Invalid texture dimensions".into()))?;
-
     let resized = image::imageops::resize(
-        &img,
+        &borrowed_image(texture)?,
         new_width,
         new_height,
         FilterType::Lanczos3,
@@ -118,12 +157,7 @@ pub fn resize_texture(
 
     let data = resized.into_raw();
 
-    Ok(TextureMap {
-        width: new_width,
-        height: new_height,
-        data,
-        path: texture.path.clone(),
-    })
+    Ok(TextureMap::new(new_width, new_height, data, texture.path.clone()))
 }
 
 /// Resizes all textures in a material set to the target resolution.
@@ -132,28 +166,43 @@ pub fn resize_material_set(
     material: &crate::material::MaterialSet,
     target: TargetResolution,
 ) -> Result<crate::material::MaterialSet> {
-    let mut result = material.clone();
-
-    if let Some(ref t) = material.albedo {
-        result.albedo = Some(resize_texture(t, target)?);
-    }
-    if let Some(ref t) = material.normal {
-        result.normal = Some(resize_texture(t, target)?);
-    }
-    if let Some(ref t) = material.roughness {
-        result.roughness = Some(resize_texture(t, target)?);
-    }
-    if let Some(ref t) = material.metallic {
-        result.metallic = Some(resize_texture(t, target)?);
-    }
-    if let Some(ref t) = material.ao {
-        result.ao = Some(resize_texture(t, target)?);
-    }
-    if let Some(ref t) = material.height {
-        result.height = Some(resize_texture(t, target)?);
-    }
+    let resize = |t: &Option<TextureMap>| t.as_ref().map(|t| resize_texture(t, target)).transpose();
+    Ok(crate::material::MaterialSet {
+        name: material.name.clone(),
+        albedo: resize(&material.albedo)?,
+        normal: resize(&material.normal)?,
+        roughness: resize(&material.roughness)?,
+        metallic: resize(&material.metallic)?,
+        ao: resize(&material.ao)?,
+        height: resize(&material.height)?,
+    })
+}
 
-    Ok(result)
+/// Resizes every slot for export like [`resize_material_set`], except that
+/// slots `dither` covers are resized from their 16-bit or float source and
+/// dithered down to 8 bits (see [`crate::dither`]).
+pub(crate) fn resize_for_export(
+    material: &crate::material::MaterialSet,
+    target: TargetResolution,
+    dither: &DitherSources,
+) -> Result<crate::material::MaterialSet> {
+    let resize = |t: &Option<TextureMap>, slot: &str| {
+        t.as_ref()
+            .map(|t| match dither.resize(slot, t, target) {
+                Some(dithered) => Ok(dithered),
+                None => resize_texture(t, target),
+            })
+            .transpose()
+    };
+    Ok(crate::material::MaterialSet {
+        name: material.name.clone(),
+        albedo: resize(&material.albedo, "albedo")?,
+        normal: resize(&material.normal, "normal")?,
+        roughness: resize(&material.roughness, "roughness")?,
+        metallic: resize(&material.metallic, "metallic")?,
+        ao: resize(&material.ao, "ao")?,
+        height: resize(&material.height, "height")?,
+    })
 }
 
 /// Saves a TextureMap to the given path.
@@ -168,17 +217,15 @@ pub fn save_texture<P: AsRef<std::path::Path>>(
         .and_then(|e| e.to_str())
         .map(|s| s.to_lowercase());
 
-    let img: RgbaImage = ImageBuffer::from_raw(
-        texture.width,
-        texture.height,
-        texture.data.clone(),
-    )
-    .ok_or_else(|| crate::Error::Other("Invalid texture dimensions".into()))?;
+    let img = borrowed_image(texture)?;
 
     match ext.as_deref() {
-        Some("png") => img.save(path)?,
-        Some("jpg") | Some("jpeg") => img.save(path)?,
-        Some("tga") => img.save(path)?,
+        Some("png") | Some("tga") => img.save(path)?,
+        // JPEG has no alpha channel
+        Some("jpg") | Some("jpeg") => {
+            let rgb: image::RgbImage = img.convert();
+            rgb.save(path)?
+        }
         _ => {
             return Err(crate::Error::Other(format!(
                 "Unsupported output format: {:?}. Use .png, .jpg, or .tga.",
@@ -198,32 +245,7 @@ pub fn resize_and_save_texture<P: AsRef<std::path::Path>>(
     output_path: P,
 ) -> Result<TextureMap> {
     let resized = resize_texture(texture, target)?;
-
-    let path = output_path.as_ref();
-    let ext = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .map(|s| s.to_lowercase());
-
-    let img: RgbaImage = ImageBuffer::from_raw(
-        resized.width,
-        resized.height,
-        resized.data.clone(),
-    )
-    .ok_or_else(|| crate::Error::Other("Invalid texture dimensions".into()))?;
-
-    match ext.as_deref() {
-        Some("png") => img.save(path)?,
-        Some("jpg") | Some("jpeg") => img.save(path)?,
-        Some("tga") => img.save(path)?,
-        _ => {
-            return Err(crate::Error::Other(format!(
-                "Unsupported output format: {:?}. Use .png, .jpg, or .tga.",
-                ext
-            )))
-        }
-    }
-
+    save_texture(&resized, output_path)?;
     Ok(resized)
 }
 
@@ -239,27 +261,59 @@ fn sample_grayscale(data: &[u8], width: u32, _height: u32, x: u32, y: u32) -> u8
 }
 
 /// Resizes a texture to exact dimensions using Lanczos3.
-fn resize_to_exact(texture: &TextureMap, width: u32, height: u32) -> Result<TextureMap> {
+pub(crate) fn resize_to_exact(texture: &TextureMap, width: u32, height: u32) -> Result<TextureMap> {
     if texture.width == width && texture.height == height {
         return Ok(texture.clone());
     }
 
-    let img: RgbaImage = ImageBuffer::from_raw(
-        texture.width,
-        texture.height,
-        texture.data.clone(),
-    )
-    .ok_or_else(|| crate::Error::Other("Invalid texture dimensions".into()))?;
-
     let resized =
-        image::imageops::resize(&img, width, height, FilterType::Lanczos3);
+        image::imageops::resize(&borrowed_image(texture)?, width, height, FilterType::Lanczos3);
 
-    Ok(TextureMap {
-        width,
-        height,
-        data: resized.into_raw(),
-        path: texture.path.clone(),
-    })
+    Ok(TextureMap::new(width, height, resized.into_raw(), texture.path.clone()))
+}
+
+/// View of the texture's pixels as an image, without copying them.
+fn borrowed_image(texture: &TextureMap) -> Result<ImageBuffer<Rgba<u8>, &[u8]>> {
+    ImageBuffer::from_raw(texture.width, texture.height, texture.data.as_slice())
+        .ok_or_else(|| crate::Error::Other("Invalid texture dimensions".into()))
+}
+
+/// How roughness, metallic and AO are stored in the exported textures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackingLayout {
+    /// R=AO, G=Roughness, B=Metallic (Unreal)
+    #[default]
+    Orm,
+    /// R=Roughness, G=Metallic, B=AO
+    Rma,
+    /// Unity mask map: R=Metallic, G=AO, B=0, A=Smoothness (1 - roughness)
+    UnityMask,
+    /// Separate Roughness, Metallic and AmbientOcclusion textures
+    Separate,
+}
+
+impl PackingLayout {
+    /// Packed RGBA pixel for one roughness, metallic and AO sample. `Separate`
+    /// packs like ORM.
+    #[inline]
+    pub fn pack(&self, roughness: u8, metallic: u8, ao: u8) -> [u8; 4] {
+        match self {
+            PackingLayout::Orm | PackingLayout::Separate => [ao, roughness, metallic, 255],
+            PackingLayout::Rma => [roughness, metallic, ao, 255],
+            PackingLayout::UnityMask => [metallic, ao, 0, 255 - roughness],
+        }
+    }
+
+    /// Slot key of the packed texture (None for `Separate`).
+    pub fn slot(&self) -> Option<&'static str> {
+        match self {
+            PackingLayout::Orm => Some("orm"),
+            PackingLayout::Rma => Some("rma"),
+            PackingLayout::UnityMask => Some("mask"),
+            PackingLayout::Separate => None,
+        }
+    }
 }
 
 /// Packs roughness, metallic, and ambient occlusion maps into a single RGBA texture.
@@ -276,21 +330,30 @@ pub fn pack_rma(
     roughness: &TextureMap,
     metallic: &TextureMap,
     ao: &TextureMap,
+) -> Result<TextureMap> {
+    pack_channels(roughness, metallic, ao, PackingLayout::Orm)
+}
+
+/// Packs roughness, metallic and AO in the given layout. `Separate` is
+/// treated as ORM. Output dimensions match the roughness map.
+pub fn pack_channels<'a>(
+    roughness: &TextureMap,
+    metallic: &'a TextureMap,
+    ao: &'a TextureMap,
+    layout: PackingLayout,
 ) -> Result<TextureMap> {
     let width = roughness.width;
     let height = roughness.height;
 
-    let metallic = if metallic.width != width || metallic.height != height {
-        resize_to_exact(metallic, width, height)?
-    } else {
-        metallic.clone()
-    };
-
-    let ao = if ao.width != width || ao.height != height {
-        resize_to_exact(ao, width, height)?
-    } else {
-        ao.clone()
+    let matched = |t: &'a TextureMap| -> Result<Cow<'a, TextureMap>> {
+        if t.width != width || t.height != height {
+            Ok(Cow::Owned(resize_to_exact(t, width, height)?))
+        } else {
+            Ok(Cow::Borrowed(t))
+        }
     };
+    let metallic = matched(metallic)?;
+    let ao = matched(ao)?;
 
     let pixel_count = (width as usize) * (height as usize);
     let mut data = Vec::with_capacity(pixel_count * 4);
@@ -300,16 +363,66 @@ pub fn pack_rma(
             let ao_val = sample_grayscale(&ao.data, width, height, x, y);
             let r_val = sample_grayscale(&roughness.data, width, height, x, y);
             let m_val = sample_grayscale(&metallic.data, width, height, x, y);
-            data.extend_from_slice(&[ao_val, r_val, m_val, 255]);
+            data.extend_from_slice(&layout.pack(r_val, m_val, ao_val));
         }
     }
 
-    Ok(TextureMap {
-        width,
-        height,
-        data,
-        path: None,
-    })
+    Ok(TextureMap::new(width, height, data, None))
+}
+
+/// Image container written for each texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Png,
+    Tga,
+    /// Lossy, no alpha channel
+    Jpg,
+}
+
+impl OutputFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Tga => "tga",
+            OutputFormat::Jpg => "jpg",
+        }
+    }
+
+    /// Whether encoding loses detail, so the written file differs from the processed texture
+    pub fn is_lossy(&self) -> bool {
+        matches!(self, OutputFormat::Jpg)
+    }
+}
+
+/// Tangent-space normal map Y axis convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NormalConvention {
+    /// Y+ (green up): Unity, Blender, glTF
+    #[default]
+    OpenGl,
+    /// Y- (green down): Unreal, DirectX
+    DirectX,
+}
+
+impl NormalConvention {
+    pub fn label(&self) -> &'static str {
+        match self {
+            NormalConvention::OpenGl => "OpenGL Y+",
+            NormalConvention::DirectX => "DirectX Y-",
+        }
+    }
+}
+
+/// Inverts the green channel (converts between OpenGL and DirectX normals).
+pub fn flip_normal_green(texture: &TextureMap) -> TextureMap {
+    let mut data = texture.data.clone();
+    for px in data.chunks_exact_mut(4) {
+        px[1] = 255 - px[1];
+    }
+    TextureMap::new(texture.width, texture.height, data, texture.path.clone())
 }
 
 /// Export preset identifiers for game engine optimization.
@@ -326,6 +439,17 @@ pub enum ExportPreset {
 }
 
 impl ExportPreset {
+    /// Built-in preset for a CLI/UI id: 4k, unreal, unity or mobile.
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id.to_lowercase().as_str() {
+            "4k" | "4k_high" => Some(ExportPreset::Res4K),
+            "unreal" | "unreal_engine" => Some(ExportPreset::UnrealEngine),
+            "unity" => Some(ExportPreset::Unity),
+            "mobile" | "mobile_optimized" => Some(ExportPreset::MobileOptimized),
+            _ => None,
+        }
+    }
+
     /// Base resolution for this preset (1K, 2K, or 4K).
     pub fn target_resolution(&self) -> TargetResolution {
         match self {
@@ -357,59 +481,80 @@ impl ExportPreset {
 
 /// Configurable optimization preset for a target platform.
 ///
-/// Combines resolution (1K/2K/4K), channel packing (R=AO, G=Roughness, B=Metallic),
-/// and optional LOD generation. All files saved locally.
+/// Combines resolution (1K/2K/4K), channel packing, optional LOD generation,
+/// file format, normal map convention, GPU compression target, output
+/// naming and per-slot dithering of high-precision sources. Built-in presets and plugin presets ([`crate::plugin::PresetConfig`])
+/// both resolve to this type. All files saved locally.
 #[derive(Debug, Clone)]
 pub struct OptimizationPreset {
     /// Target platform (Unreal, Unity, Mobile).
     pub preset: ExportPreset,
     /// Override base resolution (None = use preset default).
     pub resolution: Option<TargetResolution>,
-    /// Enable channel packing of R=AO, G=Roughness, B=Metallic. Always true for presets.
-    pub pack_rma: bool,
+    /// How roughness, metallic and AO are stored (ORM for all built-in presets).
+    pub packing: PackingLayout,
     /// LOD levels for low-res textures (None = use preset default).
     pub lod_levels: Option<Vec<TargetResolution>>,
+    /// Export the LOD chain when the caller does not say otherwise.
+    pub include_lod: bool,
+    /// Image container for every texture.
+    pub format: OutputFormat,
+    /// Convention the source normal maps are authored in.
+    pub source_normal_convention: NormalConvention,
+    /// Convention to write normal maps in (None = keep the source convention).
+    pub normal_convention: Option<NormalConvention>,
+    /// Platform whose GPU block formats (BC/ASTC/ETC2) the importer should
+    /// compress to; recorded per file in the export manifest.
+    pub compression: Option<Platform>,
+    /// Output file and LOD folder names.
+    pub naming: NamingTemplate,
+    /// Dithering of 16-bit and float sources down to 8 bits, per slot.
+    pub dither: DitherSettings,
+    /// Display name recorded in the export manifest (None = preset label).
+    pub name: Option<String>,
 }
 
 impl OptimizationPreset {
-    /// Unreal Engine: 2K base, packed ORM, LOD 512/256/128.
-    pub fn unreal() -> Self {
+    fn new(preset: ExportPreset) -> Self {
         Self {
-            preset: ExportPreset::UnrealEngine,
+            preset,
             resolution: None,
-            pack_rma: true,
+            packing: PackingLayout::Orm,
             lod_levels: None,
+            include_lod: false,
+            format: OutputFormat::Png,
+            source_normal_convention: NormalConvention::OpenGl,
+            normal_convention: None,
+            compression: None,
+            naming: NamingTemplate::default(),
+            dither: DitherSettings::default(),
+            name: None,
         }
     }
 
+    /// Unreal Engine: 2K base, packed ORM, LOD 512/256/128.
+    pub fn unreal() -> Self {
+        Self::new(ExportPreset::UnrealEngine)
+    }
+
     /// Unity: 2K base, packed ORM, LOD 512/256/128.
     pub fn unity() -> Self {
-        Self {
-            preset: ExportPreset::Unity,
-            resolution: None,
-            pack_rma: true,
-            lod_levels: None,
-        }
+        Self::new(ExportPreset::Unity)
     }
 
     /// Mobile: 1K base, packed ORM, LOD 256/128.
     pub fn mobile() -> Self {
-        Self {
-            preset: ExportPreset::MobileOptimized,
-            resolution: None,
-            pack_rma: true,
-            lod_levels: None,
-        }
+        Self::new(ExportPreset::MobileOptimized)
     }
 
     /// Res4K: 4K base, packed ORM, LOD 512/256/128.
     pub fn res_4k() -> Self {
-        Self {
-            preset: ExportPreset::Res4K,
-            resolution: None,
-            pack_rma: true,
-            lod_levels: None,
-        }
+        Self::new(ExportPreset::Res4K)
+    }
+
+    /// Built-in preset for a CLI/UI id (see [`ExportPreset::from_id`]).
+    pub fn from_id(id: &str) -> Option<Self> {
+        ExportPreset::from_id(id).map(Self::from)
     }
 
     /// Override base resolution (1K, 2K, or 4K).
@@ -424,6 +569,65 @@ impl OptimizationPreset {
         self
     }
 
+    /// Override the channel packing layout.
+    pub fn with_packing(mut self, packing: PackingLayout) -> Self {
+        self.packing = packing;
+        self
+    }
+
+    /// Override the image container.
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Write normal maps in `target`, converting from `source` if they differ.
+    pub fn with_normal_convention(mut self, source: NormalConvention, target: NormalConvention) -> Self {
+        self.source_normal_convention = source;
+        self.normal_convention = Some(target);
+        self
+    }
+
+    /// Record the platform's GPU formats in the export manifest.
+    pub fn with_compression(mut self, platform: Platform) -> Self {
+        self.compression = Some(platform);
+        self
+    }
+
+    /// Override output file and LOD folder names.
+    pub fn with_naming(mut self, naming: NamingTemplate) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Check that the format can hold every packed channel: JPG has no alpha
+    /// for the Unity mask map's smoothness.
+    pub fn validate(&self) -> Result<()> {
+        if self.format == OutputFormat::Jpg && self.packing == PackingLayout::UnityMask {
+            return Err(crate::Error::Other(
+                "JPG has no alpha channel for the Unity mask map's smoothness; use png or tga".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Dither high-precision sources per slot instead of rounding them.
+    pub fn with_dither(mut self, dither: DitherSettings) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    /// Name recorded in the export manifest.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Manifest name: the custom name or the preset label.
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.preset.label())
+    }
+
     /// Effective base resolution (override or preset default).
     pub fn effective_resolution(&self) -> TargetResolution {
         self.resolution
@@ -436,6 +640,56 @@ impl OptimizationPreset {
             .clone()
             .unwrap_or_else(|| self.preset.default_lod_levels().to_vec())
     }
+
+    /// Convention normal maps are written in.
+    pub fn output_normal_convention(&self) -> NormalConvention {
+        self.normal_convention.unwrap_or(self.source_normal_convention)
+    }
+
+    /// SHA-256 over every setting that changes the written files (and the
+    /// pbr-core version). Incremental exports compare it with the previous
+    /// manifest; the display name is left out.
+    pub fn fingerprint(&self, include_lod: bool) -> String {
+        let lod_levels = if include_lod { self.effective_lod_levels() } else { Vec::new() };
+        let settings = format!(
+            "{}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{}|{}|{:?}",
+            env!("CARGO_PKG_VERSION"),
+            self.effective_resolution(),
+            lod_levels,
+            self.packing,
+            self.format,
+            self.source_normal_convention,
+            self.output_normal_convention(),
+            self.compression,
+            self.naming.file,
+            self.naming.lod_dir,
+            self.dither,
+        );
+        format!("{:x}", Sha256::digest(settings.as_bytes()))
+    }
+
+    /// [`Self::fingerprint`] of an export held to `floor`. Equal to the plain
+    /// fingerprint when the floor is empty, so tightening or adding a floor
+    /// makes incremental exports re-check the quality.
+    pub fn fingerprint_with_floor(&self, include_lod: bool, floor: &crate::quality::QualityFloor) -> String {
+        let fingerprint = self.fingerprint(include_lod);
+        if floor.is_empty() {
+            return fingerprint;
+        }
+        let settings = format!("{}|{:?}", fingerprint, floor);
+        format!("{:x}", Sha256::digest(settings.as_bytes()))
+    }
+}
+
+impl From<ExportPreset> for OptimizationPreset {
+    fn from(preset: ExportPreset) -> Self {
+        match preset {
+            ExportPreset::Res4K => Self::res_4k(),
+            ExportPreset::UnrealEngine => Self::unreal(),
+            ExportPreset::Unity => Self::unity(),
+            ExportPreset::MobileOptimized => Self::mobile(),
+        }
+    }
 }
 
 /// Export with a specific target resolution (e.g. from plugin preset).
@@ -444,10 +698,8 @@ pub fn export_with_target<P: AsRef<std::path::Path>>(
     output_dir: P,
     target: TargetResolution,
 ) -> Result<Vec<std::path::PathBuf>> {
-    let output_dir = output_dir.as_ref();
-    std::fs::create_dir_all(output_dir)?;
-    let optimized = resize_material_set(material, target)?;
-    export_material_to_dir(&optimized, output_dir)
+    let preset = OptimizationPreset::unreal().with_resolution(target).with_name(target.label());
+    export_with_optimization_preset(material, output_dir, preset, false)
 }
 
 /// Exports an optimized material set to the given output directory.
@@ -458,11 +710,7 @@ pub fn export_with_preset<P: AsRef<std::path::Path>>(
     output_dir: P,
     preset: ExportPreset,
 ) -> Result<Vec<std::path::PathBuf>> {
-    let output_dir = output_dir.as_ref();
-    std::fs::create_dir_all(output_dir)?;
-    let target = preset.target_resolution();
-    let optimized = resize_material_set(material, target)?;
-    export_material_to_dir(&optimized, output_dir)
+    export_with_optimization_preset(material, output_dir, preset.into(), false)
 }
 
 /// Generate LOD (low-res) versions of a material set.
@@ -479,8 +727,9 @@ pub fn generate_lod_chain(
     Ok(result)
 }
 
-/// Export with an optimization preset. Resizes to target resolution (1K/2K/4K),
-/// packs R=AO, G=Roughness, B=Metallic, and optionally generates LOD chain.
+/// Export with an optimization preset. Resizes to the target resolution,
+/// packs channels, converts normals and names files as the preset says, and
+/// optionally generates the LOD chain. Every other export goes through here.
 /// All files saved locally.
 pub fn export_with_optimization_preset<P: AsRef<std::path::Path>>(
     material: &crate::material::MaterialSet,
@@ -488,18 +737,9 @@ pub fn export_with_optimization_preset<P: AsRef<std::path::Path>>(
     preset: OptimizationPreset,
     include_lod: bool,
 ) -> Result<Vec<std::path::PathBuf>> {
-    let output_dir = output_dir.as_ref();
-    std::fs::create_dir_all(output_dir)?;
-
-    let target = preset.effective_resolution();
-    let optimized = resize_material_set(material, target)?;
-
-    if include_lod {
-        let lod_levels = preset.effective_lod_levels();
-        export_with_target_and_lod(material, output_dir, target, &lod_levels)
-    } else {
-        export_material_to_dir(&optimized, output_dir)
-    }
+    let dither = DitherSources::load(material, &preset.dither)?;
+    let optimized = resize_for_export(material, preset.effective_resolution(), &dither)?;
+    export_levels(material, &optimized, &dither, output_dir.as_ref(), &preset, include_lod, &Default::default())
 }
 
 /// Export with explicit target resolution and LOD chain. Creates LOD0/, LOD1/, LOD2/ subdirs.
@@ -510,22 +750,11 @@ pub fn export_with_target_and_lod<P: AsRef<std::path::Path>>(
     base_resolution: TargetResolution,
     lod_levels: &[TargetResolution],
 ) -> Result<Vec<std::path::PathBuf>> {
-    let output_dir = output_dir.as_ref();
-    let optimized = resize_material_set(material, base_resolution)?;
-
-    let mut written = Vec::new();
-    let lod0_dir = output_dir.join("LOD0");
-    std::fs::create_dir_all(&lod0_dir)?;
-    written.extend(export_material_to_dir(&optimized, &lod0_dir)?);
-
-    for (i, &level) in lod_levels.iter().enumerate() {
-        let lod_dir = output_dir.join(format!("LOD{}", i + 1));
-        std::fs::create_dir_all(&lod_dir)?;
-        let resized = resize_material_set(material, level)?;
-        written.extend(export_material_to_dir(&resized, &lod_dir)?);
-    }
-
-    Ok(written)
+    let preset = OptimizationPreset::unreal()
+        .with_resolution(base_resolution)
+        .with_lod_levels(lod_levels)
+        .with_name(base_resolution.label());
+    export_with_optimization_preset(material, output_dir, preset, !lod_levels.is_empty())
 }
 
 /// Export with preset plus LOD chain (low-res textures for streaming).
@@ -536,78 +765,223 @@ pub fn export_with_lod<P: AsRef<std::path::Path>>(
     preset: ExportPreset,
     lod_levels: &[TargetResolution],
 ) -> Result<Vec<std::path::PathBuf>> {
-    let output_dir = output_dir.as_ref();
-    std::fs::create_dir_all(output_dir)?;
+    let preset = OptimizationPreset::from(preset).with_lod_levels(lod_levels);
+    export_with_optimization_preset(material, output_dir, preset, !lod_levels.is_empty())
+}
 
-    let target = preset.target_resolution();
-    let optimized = resize_material_set(material, target)?;
+/// LOD levels an export writes after the base level; checks that the naming
+/// template keeps them apart and the preset is valid.
+pub(crate) fn export_lod_levels(preset: &OptimizationPreset, include_lod: bool) -> Result<Vec<TargetResolution>> {
+    let lod_levels = if include_lod { preset.effective_lod_levels() } else { Vec::new() };
+    preset.validate()?;
+    preset.naming.validate()?;
+    if !lod_levels.is_empty() && !preset.naming.separates_lods() {
+        return Err(crate::Error::Other(
+            "Naming template must use {lod} in the file or LOD folder name when exporting LODs".into(),
+        ));
+    }
+    Ok(lod_levels)
+}
 
+/// Writes `base` as LOD 0 and, with `include_lod`, `material` resized to each
+/// of the preset's LOD levels (dithered slots from `dither`) as LOD 1, 2, ...,
+/// then the export manifest (fingerprinted with `floor`). Without LODs the base level goes straight
+/// into `output_dir`. Returns the texture paths (the manifest is not included).
+pub(crate) fn export_levels(
+    material: &crate::material::MaterialSet,
+    base: &crate::material::MaterialSet,
+    dither: &DitherSources,
+    output_dir: &std::path::Path,
+    preset: &OptimizationPreset,
+    include_lod: bool,
+    floor: &crate::quality::QualityFloor,
+) -> Result<Vec<std::path::PathBuf>> {
+    let naming = &preset.naming;
+    let lod_levels = export_lod_levels(preset, include_lod)?;
+    std::fs::create_dir_all(output_dir)?;
+    let mut manifest = ExportManifest::new(material, preset.label());
+    manifest.preset_fingerprint = Some(preset.fingerprint_with_floor(include_lod, floor));
     let mut written = Vec::new();
+    if lod_levels.is_empty() {
+        let textures = export_material_textures(base, output_dir, preset, 0)?;
+        manifest.add_outputs(output_dir, 0, &textures)?;
+        written.extend(textures.into_iter().map(|t| t.path));
+    } else {
+        let material_name = material_name(material);
+        let lod0_dir = output_dir.join(naming.lod_dir_name(&material_name, 0)?);
+        let textures = export_material_textures(base, &lod0_dir, preset, 0)?;
+        manifest.add_outputs(output_dir, 0, &textures)?;
+        written.extend(textures.into_iter().map(|t| t.path));
+
+        for (i, &level) in lod_levels.iter().enumerate() {
+            let lod = i as u32 + 1;
+            let lod_dir = output_dir.join(naming.lod_dir_name(&material_name, lod)?);
+            let resized = resize_for_export(material, level, dither)?;
+            let textures = export_material_textures(&resized, &lod_dir, preset, lod)?;
+            manifest.add_outputs(output_dir, lod, &textures)?;
+            written.extend(textures.into_iter().map(|t| t.path));
+        }
+    }
+    manifest.write(output_dir)?;
+    Ok(written)
+}
 
-    // LOD 0 (full resolution)
-    let lod0_dir = output_dir.join("LOD0");
-    std::fs::create_dir_all(&lod0_dir)?;
-    written.extend(export_material_to_dir(&optimized, &lod0_dir)?);
+pub(crate) fn material_name(material: &crate::material::MaterialSet) -> String {
+    material.name.clone().unwrap_or_else(|| "material".to_string())
+}
 
-    // LOD 1, 2, 3...
-    for (i, &level) in lod_levels.iter().enumerate() {
-        let lod_dir = output_dir.join(format!("LOD{}", i + 1));
-        std::fs::create_dir_all(&lod_dir)?;
-        let resized = resize_material_set(material, level)?;
-        written.extend(export_material_to_dir(&resized, &lod_dir)?);
+/// One texture file written by an export.
+#[derive(Debug, Clone)]
+pub struct ExportedTexture {
+    pub path: std::path::PathBuf,
+    /// "albedo", "normal", "orm", "rma", "mask", "roughness", "metallic", "ao", "height"
+    pub slot: &'static str,
+    pub width: u32,
+    pub height: u32,
+    /// Meaning of each channel, e.g. "R=AO G=Roughness B=Metallic A=1"
+    pub channels: String,
+    /// GPU format the importer should compress to (preset `compression`)
+    pub gpu_format: Option<TextureFormat>,
+}
+
+/// Channel layout of an exported slot.
+pub fn slot_channels(slot: &str, normal: NormalConvention) -> String {
+    match slot {
+        "albedo" => "RGB=Color A=Opacity".to_string(),
+        "normal" => format!("RGB=Tangent-space XYZ ({})", normal.label()),
+        "orm" => "R=AO G=Roughness B=Metallic A=1".to_string(),
+        "rma" => "R=Roughness G=Metallic B=AO A=1".to_string(),
+        "mask" => "R=Metallic G=AO B=0 A=Smoothness".to_string(),
+        "roughness" => "RGB=Roughness".to_string(),
+        "metallic" => "RGB=Metallic".to_string(),
+        "ao" => "RGB=AO".to_string(),
+        "height" => "RGB=Height".to_string(),
+        _ => "RGBA".to_string(),
     }
+}
 
-    Ok(written)
+impl ExportedTexture {
+    /// Describe a written texture with the preset's channel layout and GPU
+    /// format. `has_alpha` matters for albedo only.
+    pub(crate) fn new(
+        path: std::path::PathBuf,
+        slot: &'static str,
+        width: u32,
+        height: u32,
+        has_alpha: bool,
+        preset: &OptimizationPreset,
+    ) -> Self {
+        let gpu_slot = if matches!(slot, "rma" | "mask") { "orm" } else { slot };
+        Self {
+            path,
+            slot,
+            width,
+            height,
+            channels: slot_channels(slot, preset.output_normal_convention()),
+            gpu_format: preset.compression.map(|p| p.profile().format_for(gpu_slot, has_alpha)),
+        }
+    }
 }
 
-/// Export material set to output dir (BaseColor, Normal, ORM, etc.)
-fn export_material_to_dir<P: AsRef<std::path::Path>>(
+/// Path of one exported texture named by the preset's template; creates its folder.
+pub(crate) fn output_path(
+    output_dir: &std::path::Path,
+    preset: &OptimizationPreset,
+    material_name: &str,
+    slot: &str,
+    lod: u32,
+    res: u32,
+) -> Result<std::path::PathBuf> {
+    let file = preset.naming.file_name(&NameContext {
+        material: material_name,
+        slot,
+        lod,
+        res,
+        ext: preset.format.extension(),
+    })?;
+    let path = output_dir.join(file);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    Ok(path)
+}
+
+/// Albedo with alpha exported as JPG, which would drop the alpha.
+pub(crate) fn jpg_alpha_error(material_name: &str) -> crate::Error {
+    crate::Error::Other(format!(
+        "{}: the albedo has alpha, which JPG can't store; use png or tga",
+        material_name
+    ))
+}
+
+/// Export material set to output dir (BaseColor, Normal, ORM, etc.) with the
+/// preset's packing, normal convention, format and naming, for LOD level `lod`.
+pub(crate) fn export_material_textures<P: AsRef<std::path::Path>>(
     material: &crate::material::MaterialSet,
     output_dir: P,
-) -> Result<Vec<std::path::PathBuf>> {
+    preset: &OptimizationPreset,
+    lod: u32,
+) -> Result<Vec<ExportedTexture>> {
     let output_dir = output_dir.as_ref();
+    let material_name = material_name(material);
+    let normal_convention = preset.output_normal_convention();
     let mut written = Vec::new();
+    let mut save = |t: &TextureMap, slot: &'static str| -> Result<()> {
+        let has_alpha = slot == "albedo" && t.has_alpha();
+        if has_alpha && preset.format == OutputFormat::Jpg {
+            return Err(jpg_alpha_error(&material_name));
+        }
+        let path = output_path(output_dir, preset, &material_name, slot, lod, t.width.max(t.height))?;
+        save_texture(t, &path)?;
+        written.push(ExportedTexture::new(path, slot, t.width, t.height, has_alpha, preset));
+        Ok(())
+    };
 
     if let Some(ref t) = material.albedo {
-        let path = output_dir.join("BaseColor.png");
-        save_texture(t, &path)?;
-        written.push(path);
+        save(t, "albedo")?;
     }
     if let Some(ref t) = material.normal {
-        let path = output_dir.join("Normal.png");
-        save_texture(t, &path)?;
-        written.push(path);
+        if normal_convention != preset.source_normal_convention {
+            save(&flip_normal_green(t), "normal")?;
+        } else {
+            save(t, "normal")?;
+        }
     }
-    if let Some(rma) = pack_rma_from_material(material)? {
-        let path = output_dir.join("ORM.png");
-        save_texture(&rma, &path)?;
-        written.push(path);
+    if let Some((slot, packed)) = pack_material(material, preset.packing)? {
+        save(&packed, slot)?;
     } else {
         if let Some(ref t) = material.roughness {
-            let path = output_dir.join("Roughness.png");
-            save_texture(t, &path)?;
-            written.push(path);
+            save(t, "roughness")?;
         }
         if let Some(ref t) = material.metallic {
-            let path = output_dir.join("Metallic.png");
-            save_texture(t, &path)?;
-            written.push(path);
+            save(t, "metallic")?;
         }
         if let Some(ref t) = material.ao {
-            let path = output_dir.join("AmbientOcclusion.png");
-            save_texture(t, &path)?;
-            written.push(path);
+            save(t, "ao")?;
         }
     }
     if let Some(ref t) = material.height {
-        let path = output_dir.join("Height.png");
-        save_texture(t, &path)?;
-        written.push(path);
+        save(t, "height")?;
     }
 
     Ok(written)
 }
 
+/// Packs roughness, metallic and AO of a material in `layout`, with the slot
+/// key of the packed texture. `None` when the layout is `Separate` or any map
+/// is missing.
+pub fn pack_material(
+    material: &crate::material::MaterialSet,
+    layout: PackingLayout,
+) -> Result<Option<(&'static str, TextureMap)>> {
+    let (Some(slot), Some(r), Some(m), Some(a)) =
+        (layout.slot(), material.roughness.as_ref(), material.metallic.as_ref(), material.ao.as_ref())
+    else {
+        return Ok(None);
+    };
+    Ok(Some((slot, pack_channels(r, m, a, layout)?)))
+}
+
 /// Batch export multiple materials with a preset.
 /// Each material is exported to output_root/<material_name>/.
 pub fn batch_export_with_preset<P: AsRef<std::path::Path>>(
@@ -680,12 +1054,7 @@ mod tests {
 
     fn make_test_texture(w: u32, h: u32) -> TextureMap {
         let len = (w as usize) * (h as usize) * 4;
-        TextureMap {
-            width: w,
-            height: h,
-            data: vec![128u8; len],
-            path: None,
-        }
+        TextureMap::new(w, h, vec![128u8; len], None)
     }
 
     #[test]
@@ -773,6 +1142,115 @@ mod tests {
         assert_eq!(unity_4k.effective_resolution(), TargetResolution::Res4K);
     }
 
+    #[test]
+    fn export_uses_naming_template() {
+        let dir = std::env::temp_dir().join("pbr_export_naming_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let material = crate::material::MaterialSet {
+            name: Some("Brick".into()),
+            albedo: Some(make_test_texture(64, 64)),
+            normal: Some(make_test_texture(64, 64)),
+            ..Default::default()
+        };
+        let naming = NamingTemplate::unreal().with_lod_dir("Brick_LOD{lod}").unwrap();
+        let preset = OptimizationPreset::unreal()
+            .with_lod_levels(&[TargetResolution::Res128])
+            .with_naming(naming);
+        let written = export_with_optimization_preset(&material, &dir, preset, true).unwrap();
+        assert_eq!(written[0], dir.join("Brick_LOD0").join("T_Brick_D.png"));
+        assert!(dir.join("Brick_LOD1").join("T_Brick_N.png").is_file());
+
+        let flat = OptimizationPreset::unreal().with_naming(NamingTemplate::unreal().with_lod_dir("").unwrap());
+        assert!(export_with_optimization_preset(&material, &dir, flat, true).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn pack_channels_layouts() {
+        let r = make_grayscale_texture(4, 4, 200);
+        let m = make_grayscale_texture(4, 4, 50);
+        let ao = make_grayscale_texture(4, 4, 100);
+        assert_eq!(&pack_channels(&r, &m, &ao, PackingLayout::Orm).unwrap().data[..4], &[100, 200, 50, 255]);
+        assert_eq!(&pack_channels(&r, &m, &ao, PackingLayout::Rma).unwrap().data[..4], &[200, 50, 100, 255]);
+        assert_eq!(&pack_channels(&r, &m, &ao, PackingLayout::UnityMask).unwrap().data[..4], &[50, 100, 0, 55]);
+    }
+
+    #[test]
+    fn export_applies_format_packing_and_normal_convention() {
+        let dir = std::env::temp_dir().join("pbr_export_full_preset_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut normal = make_test_texture(32, 32);
+        normal.data[1] = 40;
+        let material = crate::material::MaterialSet {
+            name: Some("Brick".into()),
+            normal: Some(normal),
+            roughness: Some(make_grayscale_texture(32, 32, 200)),
+            metallic: Some(make_grayscale_texture(32, 32, 0)),
+            ao: Some(make_grayscale_texture(32, 32, 255)),
+            ..Default::default()
+        };
+        let preset = OptimizationPreset::unity()
+            .with_packing(PackingLayout::Separate)
+            .with_format(OutputFormat::Tga)
+            .with_normal_convention(NormalConvention::OpenGl, NormalConvention::DirectX)
+            .with_compression(Platform::Mobile);
+        let written = export_with_optimization_preset(&material, &dir, preset, false).unwrap();
+        let names: Vec<_> = written.iter().map(|p| p.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        assert_eq!(names, ["Normal.tga", "Roughness.tga", "Metallic.tga", "AmbientOcclusion.tga"]);
+        let flipped = image::open(dir.join("Normal.tga")).unwrap().to_rgba8();
+        assert_eq!(flipped.get_pixel(0, 0)[1], 215);
+
+        let manifest = ExportManifest::load(&dir.join(crate::export_manifest::EXPORT_MANIFEST_FILE)).unwrap();
+        assert!(manifest.files[0].channels.contains("DirectX"));
+        assert!(manifest.files.iter().all(|f| f.gpu_format.is_some() && f.format == "tga"));
+
+        // JPG can't carry the mask map's smoothness or albedo alpha
+        let jpg = OptimizationPreset::mobile().with_packing(PackingLayout::UnityMask).with_format(OutputFormat::Jpg);
+        assert!(export_with_optimization_preset(&material, dir.join("jpg"), jpg.clone(), false).is_err());
+        let jpg = jpg.with_packing(PackingLayout::Rma);
+        let written = export_with_optimization_preset(&material, dir.join("jpg"), jpg.clone(), false).unwrap();
+        assert_eq!(written.last().unwrap(), &dir.join("jpg").join("RMA.jpg"));
+        let mut with_alpha = material.clone();
+        with_alpha.albedo = Some(TextureMap::new(4, 4, [200, 100, 50, 128].repeat(16), None));
+        assert!(export_with_optimization_preset(&with_alpha, dir.join("jpg_alpha"), jpg, false).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn export_dithers_sixteen_bit_height_only_for_dithered_slots() {
+        let dir = std::env::temp_dir().join("pbr_export_dither_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let src = dir.join("Cliff");
+        std::fs::create_dir_all(&src).unwrap();
+        let height: image::ImageBuffer<image::Luma<u16>, Vec<u16>> =
+            image::ImageBuffer::from_fn(256, 64, |x, _| image::Luma([30_000 + x as u16 * 20]));
+        height.save(src.join("cliff_height.png")).unwrap();
+        let material = crate::material::MaterialSet::load_from_folder(&src).unwrap();
+
+        let banding = |dither: DitherSettings, out: &str| {
+            let preset = OptimizationPreset::unreal().with_dither(dither);
+            let written = export_with_optimization_preset(&material, dir.join(out), preset, false).unwrap();
+            let loaded = crate::image_loading::ImageLoader::load(&written[0]).unwrap();
+            crate::banding::banding_analysis(&TextureMap::from_loaded(loaded, None)).banding_score
+        };
+        let rounded = banding(DitherSettings::default(), "rounded");
+        assert!(rounded > 0.8, "{}", rounded);
+        assert_eq!(banding("albedo=blue_noise".parse().unwrap(), "albedo_only"), rounded);
+        assert!(banding("height=blue_noise".parse().unwrap(), "dithered") < 0.1);
+
+        let dithered = OptimizationPreset::unreal().with_dither(DitherSettings::all(crate::dither::DitherMode::Ordered));
+        assert_ne!(dithered.fingerprint(false), OptimizationPreset::unreal().fingerprint(false));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn target_resolution_from_str() {
+        assert_eq!("2K".parse::<TargetResolution>().unwrap(), TargetResolution::Res2K);
+        assert_eq!("512".parse::<TargetResolution>().unwrap(), TargetResolution::Res512);
+        assert_eq!("300px".parse::<TargetResolution>().unwrap(), TargetResolution::Custom(300));
+        assert!("huge".parse::<TargetResolution>().is_err());
+    }
+
     #[test]
     fn export_preset_lod_levels() {
         assert_eq!(ExportPreset::UnrealEngine.default_lod_levels().len(), 3);
@@ -781,11 +1259,6 @@ mod tests {
 
     fn make_grayscale_texture(w: u32, h: u32, value: u8) -> TextureMap {
         let len = (w as usize) * (h as usize) * 4;
-        TextureMap {
-            width: w,
-            height: h,
-            data: (0..len).map(|i| if i % 4 == 0 { value } else { value }).collect(),
-            path: None,
-        }
+        TextureMap::new(w, h, (0..len).map(|i| if i % 4 == 0 { value } else { value }).collect(), None)
     }
 }
@@ -0,0 +1,64 @@
+//! Parallel execution layer (rayon job pool).
+//!
+//! Used for folder loading, per-rule evaluation, and per-texture statistics
+//! (see [`crate::texture_stats`]). All helpers preserve input order so batch
+//! output is deterministic.
+
+use crate::material::MaterialSet;
+use rayon::prelude::*;
+use std::path::PathBuf;
+
+/// Configure the global job pool. `None` or `Some(0)` uses one thread per CPU.
+/// Must be called before any parallel work; later calls are ignored.
+pub fn configure_threads(jobs: Option<usize>) {
+    let mut builder = rayon::ThreadPoolBuilder::new();
+    if let Some(n) = jobs.filter(|&n| n > 0) {
+        builder = builder.num_threads(n);
+    }
+    let _ = builder.build_global();
+}
+
+/// Number of threads in the current job pool.
+pub fn current_threads() -> usize {
+    rayon::current_num_threads()
+}
+
+/// Map `f` over `items` in parallel, returning results in input order.
+pub fn par_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync + Send,
+{
+    items.par_iter().map(f).collect()
+}
+
+/// Load material folders in parallel. Results keep the order of `folders`.
+pub fn load_materials(folders: &[PathBuf]) -> Vec<(PathBuf, crate::Result<MaterialSet>)> {
+    par_map(folders, |folder| (folder.clone(), MaterialSet::load_from_folder(folder)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn par_map_preserves_order() {
+        let items: Vec<u32> = (0..1000).collect();
+        let out = par_map(&items, |x| x * 2);
+        assert_eq!(out, items.iter().map(|x| x * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn load_materials_preserves_order_and_errors() {
+        let folders = vec![
+            PathBuf::from("/nonexistent/pbr_parallel_a"),
+            PathBuf::from("/nonexistent/pbr_parallel_b"),
+        ];
+        let loaded = load_materials(&folders);
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].0, folders[0]);
+        assert_eq!(loaded[1].0, folders[1]);
+        assert!(loaded.iter().all(|(_, r)| r.is_err()));
+    }
+}
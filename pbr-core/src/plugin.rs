@@ -5,7 +5,13 @@
 //! - External script plugins (Python, Lua, etc.) via stdin/stdout
 //! - Dynamic plugin discovery from config directories
 
+use crate::dither::DitherSettings;
+use crate::estimation::Platform;
 use crate::material::MaterialSet;
+use crate::naming::NamingTemplate;
+use crate::optimization::{
+    ExportPreset, NormalConvention, OptimizationPreset, OutputFormat, PackingLayout, TargetResolution,
+};
 use crate::validation::{Issue, Severity, ValidationRule};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
@@ -64,18 +70,109 @@ pub enum RuleCondition {
     },
 }
 
-/// Custom export preset from config
+/// Custom export preset from config. Every field except `id` and `name` is
+/// optional and falls back to the `base` built-in preset.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PresetConfig {
     pub id: String,
     pub name: String,
-    /// Target resolution: 4k, 2k, 1k, 512, 256, 128
+    /// Built-in preset to start from: unreal (default), unity, mobile, 4k
+    #[serde(default)]
+    pub base: Option<String>,
+    /// Target resolution: 4k, 2k, 1k, 512, 256, 128 or pixels (empty = base default)
+    #[serde(default)]
     pub target_resolution: String,
     #[serde(default)]
     pub include_lod: bool,
+    /// LOD resolutions, e.g. ["512", "256"] (default: the base preset's chain)
+    #[serde(default)]
+    pub lod_levels: Option<Vec<String>>,
+    /// Channel packing: orm (default), rma, unity_mask or separate
+    #[serde(default)]
+    pub packing: Option<PackingLayout>,
+    /// Image container: png (default), tga or jpg
+    #[serde(default)]
+    pub format: Option<OutputFormat>,
+    /// Convention the source normal maps use: opengl (default) or directx
+    #[serde(default)]
+    pub source_normal_convention: Option<NormalConvention>,
+    /// Convention to write normal maps in (default: keep the source convention)
+    #[serde(default)]
+    pub normal_convention: Option<NormalConvention>,
+    /// Platform whose GPU formats (pc, console, mobile) go into the export manifest
+    #[serde(default)]
+    pub compression: Option<Platform>,
+    /// File name template, e.g. "T_{material}_{slot:short}.{ext}" (default "{slot}.{ext}")
+    #[serde(default)]
+    pub naming: Option<String>,
+    /// LOD folder template (default "LOD{lod}")
+    #[serde(default)]
+    pub lod_folder: Option<String>,
+    /// Dithering of 16-bit/float sources, e.g. {"default": "none", "slots": {"height": "blue_noise"}}
+    #[serde(default)]
+    pub dither: Option<DitherSettings>,
 }
 
 impl PresetConfig {
+    /// Naming template from `naming` and `lod_folder`.
+    pub fn naming_template(&self) -> crate::Result<NamingTemplate> {
+        let mut template = NamingTemplate::default();
+        if let Some(ref file) = self.naming {
+            template.file = file.clone();
+        }
+        if let Some(ref dir) = self.lod_folder {
+            template.lod_dir = dir.clone();
+        }
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Resolve this config to the optimization preset every export uses.
+    pub fn optimization_preset(&self) -> crate::Result<OptimizationPreset> {
+        let base = match self.base.as_deref() {
+            Some(id) => OptimizationPreset::from_id(id).ok_or_else(|| {
+                crate::Error::Other(format!("Preset {}: unknown base {}. Use 4k, unreal, unity or mobile.", self.id, id))
+            })?,
+            None => OptimizationPreset::unreal(),
+        };
+        let mut preset = base.with_naming(self.naming_template()?).with_name(&self.name);
+        if !self.target_resolution.trim().is_empty() {
+            preset = preset.with_resolution(self.target_resolution.parse()?);
+        }
+        if let Some(ref levels) = self.lod_levels {
+            let levels = levels.iter().map(|l| l.parse()).collect::<crate::Result<Vec<TargetResolution>>>()?;
+            preset = preset.with_lod_levels(&levels);
+        }
+        if let Some(packing) = self.packing {
+            preset = preset.with_packing(packing);
+        }
+        if let Some(format) = self.format {
+            preset = preset.with_format(format);
+        }
+        if let Some(source) = self.source_normal_convention {
+            preset.source_normal_convention = source;
+        }
+        if self.normal_convention.is_some() {
+            preset.normal_convention = self.normal_convention;
+        }
+        if let Some(platform) = self.compression {
+            preset = preset.with_compression(platform);
+        }
+        if let Some(ref dither) = self.dither {
+            // Normalize slot aliases ("displacement", "AO") and reject unknown slots
+            let mut settings = DitherSettings::all(dither.default);
+            for (slot, &mode) in &dither.slots {
+                settings = settings.with_slot(slot, mode)?;
+            }
+            preset = preset.with_dither(settings);
+        }
+        preset.include_lod = self.include_lod;
+        preset
+            .validate()
+            .map_err(|e| crate::Error::Other(format!("Preset {}: {}", self.id, e)))?;
+        Ok(preset)
+    }
+
     /// Resolve target resolution string to max dimension (for export).
     pub fn max_dimension(&self) -> u32 {
         match self.target_resolution.to_lowercase().as_str() {
@@ -90,6 +187,29 @@ impl PresetConfig {
     }
 }
 
+/// Built-in preset (4k, unreal, unity, mobile) or plugin preset by id. A
+/// plugin preset reusing a built-in id is an error rather than being
+/// silently shadowed.
+pub fn resolve_preset(id: &str, presets: &[PresetConfig]) -> crate::Result<OptimizationPreset> {
+    if let Some(preset) = OptimizationPreset::from_id(id) {
+        let built_in = ExportPreset::from_id(id);
+        if let Some(clash) = presets.iter().find(|p| ExportPreset::from_id(&p.id) == built_in) {
+            return Err(crate::Error::Other(format!(
+                "Plugin preset {} ({}) uses the id of a built-in preset; rename it",
+                clash.id, clash.name
+            )));
+        }
+        return Ok(preset);
+    }
+    presets
+        .iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| {
+            crate::Error::Other(format!("Unknown preset: {}. Use 4k, unreal, unity, mobile or a plugin preset id.", id))
+        })?
+        .optimization_preset()
+}
+
 /// Material summary sent to external scripts (scripting API)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MaterialSummaryForScript {
@@ -161,6 +281,13 @@ impl ValidationRule for ConfigRule {
         }
         self.check(set).into_iter().collect()
     }
+
+    fn fingerprint(&self) -> Option<String> {
+        if matches!(self.config.condition, RuleCondition::Script { .. }) {
+            return None;
+        }
+        serde_json::to_string(&self.config).ok()
+    }
 }
 
 fn parse_severity(s: &str) -> Option<Severity> {
@@ -560,3 +687,75 @@ impl Default for PluginLoader {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dither::DitherMode;
+
+    #[test]
+    fn plugin_preset_resolves_to_optimization_preset() {
+        let manifest: PluginManifest = toml::from_str(
+            r#"
+name = "studio"
+version = "1.0.0"
+
+[[presets]]
+id = "unity_mobile_tga"
+name = "Unity Mobile TGA"
+base = "unity"
+target_resolution = "1k"
+include_lod = true
+lod_levels = ["256", "64"]
+packing = "unity_mask"
+format = "tga"
+normal_convention = "directx"
+compression = "mobile"
+naming = "{material}_{slot}.{ext}"
+dither = { slots = { displacement = "blue_noise", albedo = "ordered" } }
+
+[[presets]]
+id = "legacy"
+name = "Legacy"
+target_resolution = "512"
+"#,
+        )
+        .unwrap();
+
+        let preset = resolve_preset("unity_mobile_tga", &manifest.presets).unwrap();
+        assert_eq!(preset.preset, ExportPreset::Unity);
+        assert_eq!(preset.effective_resolution(), TargetResolution::Res1K);
+        assert_eq!(preset.effective_lod_levels(), [TargetResolution::Res256, TargetResolution::Custom(64)]);
+        assert!(preset.include_lod);
+        assert_eq!(preset.packing, PackingLayout::UnityMask);
+        assert_eq!(preset.format, OutputFormat::Tga);
+        assert_eq!(preset.output_normal_convention(), NormalConvention::DirectX);
+        assert_eq!(preset.compression, Some(Platform::Mobile));
+        assert_eq!(preset.naming.file, "{material}_{slot}.{ext}");
+        assert_eq!(preset.label(), "Unity Mobile TGA");
+        assert_eq!(preset.dither.mode("height"), DitherMode::BlueNoise);
+        assert_eq!(preset.dither.mode("albedo"), DitherMode::Ordered);
+        assert_eq!(preset.dither.mode("normal"), DitherMode::None);
+
+        let legacy = resolve_preset("legacy", &manifest.presets).unwrap();
+        assert_eq!(legacy.effective_resolution(), TargetResolution::Res512);
+        assert_eq!(legacy.packing, PackingLayout::Orm);
+
+        assert_eq!(resolve_preset("mobile", &[]).unwrap().preset, ExportPreset::MobileOptimized);
+        assert!(resolve_preset("nope", &manifest.presets).is_err());
+        let mut bad = manifest.presets[1].clone();
+        bad.target_resolution = "huge".into();
+        assert!(bad.optimization_preset().is_err());
+
+        // JPG can't hold the mask map's smoothness
+        let mut jpg = manifest.presets[0].clone();
+        jpg.format = Some(OutputFormat::Jpg);
+        assert!(jpg.optimization_preset().is_err());
+
+        // A plugin preset may not shadow a built-in id (or its alias)
+        let mut clash = manifest.presets[1].clone();
+        clash.id = "unreal_engine".into();
+        let err = resolve_preset("unreal", &[clash]).unwrap_err().to_string();
+        assert!(err.contains("unreal_engine"), "{}", err);
+    }
+}
@@ -0,0 +1,601 @@
+//! Image quality metrics for optimized textures.
+//!
+//! Compares an original texture with a processed one after bringing the
+//! processed one back to the original size, the way the GPU magnifies a
+//! lower-resolution texture. [`resample_ssim`] predicts how much structure a
+//! resolution cap would lose without exporting anything;
+//! [`export_with_quality_floor`] measures what an export actually lost (PSNR,
+//! SSIM, MS-SSIM and, for normal maps, the angle between decoded normals) and
+//! refuses to write textures that fall below a [`QualityFloor`]. Lossless
+//! output (PNG, TGA) stores exactly the measured pixels, channel packing
+//! included; lossy output (JPG) is refused when a floor is set.
+
+use crate::analysis::slot_textures;
+use crate::banding::banding_analysis;
+use crate::dither::DitherSources;
+use crate::material::{MaterialSet, TextureMap};
+use crate::optimization::{
+    compute_target_dimensions, export_levels, resize_for_export, resize_to_exact, OptimizationPreset,
+    TargetResolution,
+};
+use crate::{Error, Result};
+use image::imageops::FilterType;
+use image::{ImageBuffer, RgbaImage};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// SSIM window edge in pixels
+const SSIM_WINDOW: usize = 8;
+
+/// Step between SSIM windows (half-overlapping)
+const SSIM_STRIDE: usize = 4;
+
+/// SSIM stabilizers for 8-bit data: (0.01 * 255)^2 and (0.03 * 255)^2
+const SSIM_C1: f64 = 6.5025;
+const SSIM_C2: f64 = 58.5225;
+
+/// Largest center crop measured by [`resample_ssim`] (keeps 8K textures cheap)
+pub const RESAMPLE_CROP: u32 = 512;
+
+/// PSNR reported for identical textures (keeps the JSON report finite)
+pub const MAX_PSNR: f64 = 100.0;
+
+/// Per-scale exponents from Wang et al. (2003), finest scale first
+const MS_SSIM_WEIGHTS: [f64; 5] = [0.0448, 0.2856, 0.3001, 0.2363, 0.1333];
+
+/// File written next to the exported textures by [`export_with_quality_floor`]
+pub const QUALITY_REPORT_FILE: &str = "quality-report.json";
+
+/// Bilinear resize to exact dimensions, as texture sampling magnifies.
+pub(crate) fn upsample_bilinear(texture: &TextureMap, width: u32, height: u32) -> Result<TextureMap> {
+    if texture.width == width && texture.height == height {
+        return Ok(texture.clone());
+    }
+    let img: RgbaImage = ImageBuffer::from_raw(texture.width, texture.height, texture.data.clone())
+        .ok_or_else(|| Error::Other("Invalid texture dimensions".into()))?;
+    let resized = image::imageops::resize(&img, width, height, FilterType::Triangle);
+    Ok(TextureMap::new(width, height, resized.into_raw(), texture.path.clone()))
+}
+
+/// Center crop of at most `size` x `size` pixels.
+fn center_crop(texture: &TextureMap, size: u32) -> TextureMap {
+    let w = texture.width.min(size);
+    let h = texture.height.min(size);
+    if w == texture.width && h == texture.height {
+        return texture.clone();
+    }
+    let x0 = ((texture.width - w) / 2) as usize;
+    let y0 = ((texture.height - h) / 2) as usize;
+    let stride = texture.width as usize * 4;
+    let mut data = Vec::with_capacity(w as usize * h as usize * 4);
+    for y in y0..y0 + h as usize {
+        let start = y * stride + x0 * 4;
+        data.extend_from_slice(&texture.data[start..start + w as usize * 4]);
+    }
+    TextureMap::new(w, h, data, texture.path.clone())
+}
+
+fn check_same_size(a: &TextureMap, b: &TextureMap, metric: &str) -> Result<(usize, usize)> {
+    if a.width != b.width || a.height != b.height {
+        return Err(Error::Other(format!(
+            "{} needs equal dimensions ({}x{} vs {}x{})",
+            metric, a.width, a.height, b.width, b.height
+        )));
+    }
+    let (w, h) = (a.width as usize, a.height as usize);
+    if w * h * 4 > a.data.len().min(b.data.len()) {
+        return Err(Error::Other("Invalid texture dimensions".into()));
+    }
+    Ok((w, h))
+}
+
+/// Mean SSIM and mean contrast-structure term over the RGB channels, from
+/// 8x8 windows at a stride of 4.
+fn ssim_terms(a: &TextureMap, b: &TextureMap) -> Result<(f64, f64)> {
+    let (w, h) = check_same_size(a, b, "SSIM")?;
+    let win_w = SSIM_WINDOW.min(w);
+    let win_h = SSIM_WINDOW.min(h);
+    if win_w == 0 || win_h == 0 {
+        return Ok((1.0, 1.0));
+    }
+    let n = (win_w * win_h) as f64;
+    let rows: Vec<usize> = (0..=h - win_h).step_by(SSIM_STRIDE).collect();
+    let (total, total_cs, windows) = rows
+        .par_iter()
+        .map(|&y0| {
+            let (mut total, mut total_cs, mut windows) = (0.0, 0.0, 0usize);
+            for x0 in (0..=w - win_w).step_by(SSIM_STRIDE) {
+                for c in 0..3 {
+                    let (mut sa, mut sb, mut saa, mut sbb, mut sab) = (0.0, 0.0, 0.0, 0.0, 0.0);
+                    for y in y0..y0 + win_h {
+                        let row = y * w;
+                        for x in x0..x0 + win_w {
+                            let i = (row + x) * 4 + c;
+                            let (va, vb) = (a.data[i] as f64, b.data[i] as f64);
+                            sa += va;
+                            sb += vb;
+                            saa += va * va;
+                            sbb += vb * vb;
+                            sab += va * vb;
+                        }
+                    }
+                    let (ma, mb) = (sa / n, sb / n);
+                    let var_a = saa / n - ma * ma;
+                    let var_b = sbb / n - mb * mb;
+                    let cov = sab / n - ma * mb;
+                    let cs = (2.0 * cov + SSIM_C2) / (var_a + var_b + SSIM_C2);
+                    total += (2.0 * ma * mb + SSIM_C1) / (ma * ma + mb * mb + SSIM_C1) * cs;
+                    total_cs += cs;
+                }
+                windows += 3;
+            }
+            (total, total_cs, windows)
+        })
+        .reduce(|| (0.0, 0.0, 0), |x, y| (x.0 + y.0, x.1 + y.1, x.2 + y.2));
+    Ok((total / windows as f64, total_cs / windows as f64))
+}
+
+/// Mean SSIM over the RGB channels, from 8x8 windows at a stride of 4.
+/// Both textures must have the same dimensions. 1.0 means identical.
+pub fn ssim(a: &TextureMap, b: &TextureMap) -> Result<f64> {
+    ssim_terms(a, b).map(|(s, _)| s)
+}
+
+/// 2x2 box downsample (odd trailing rows/columns are dropped).
+fn halve(texture: &TextureMap) -> TextureMap {
+    let (w, h) = ((texture.width / 2).max(1), (texture.height / 2).max(1));
+    let src_w = texture.width as usize;
+    let mut data = Vec::with_capacity(w as usize * h as usize * 4);
+    for y in 0..h as usize {
+        for x in 0..w as usize {
+            let (x0, y0) = (x * 2, y * 2);
+            let x1 = (x0 + 1).min(src_w - 1);
+            let y1 = (y0 + 1).min(texture.height as usize - 1);
+            for c in 0..4 {
+                let sum: u32 = [(x0, y0), (x1, y0), (x0, y1), (x1, y1)]
+                    .iter()
+                    .map(|&(px, py)| texture.data[(py * src_w + px) * 4 + c] as u32)
+                    .sum();
+                data.push(((sum + 2) / 4) as u8);
+            }
+        }
+    }
+    TextureMap::new(w, h, data, None)
+}
+
+/// Multi-scale SSIM: contrast-structure at up to four finer scales and full
+/// SSIM at the coarsest, combined with the standard per-scale exponents.
+/// Small textures use fewer scales (at least 8 pixels per edge) with the
+/// exponents renormalized.
+pub fn ms_ssim(a: &TextureMap, b: &TextureMap) -> Result<f64> {
+    check_same_size(a, b, "MS-SSIM")?;
+    let mut scales = 1;
+    let mut edge = a.width.min(a.height) as usize;
+    while scales < MS_SSIM_WEIGHTS.len() && edge / 2 >= SSIM_WINDOW {
+        scales += 1;
+        edge /= 2;
+    }
+    let weight_sum: f64 = MS_SSIM_WEIGHTS[..scales].iter().sum();
+    let (mut a, mut b) = (a.clone(), b.clone());
+    let mut result = 1.0;
+    for (j, weight) in MS_SSIM_WEIGHTS[..scales].iter().enumerate() {
+        let (s, cs) = ssim_terms(&a, &b)?;
+        let term = if j + 1 == scales { s } else { cs };
+        result *= term.max(0.0).powf(weight / weight_sum);
+        if j + 1 < scales {
+            a = halve(&a);
+            b = halve(&b);
+        }
+    }
+    Ok(result)
+}
+
+/// Peak signal-to-noise ratio over the RGB channels in dB, capped at
+/// [`MAX_PSNR`] for identical textures.
+pub fn psnr(a: &TextureMap, b: &TextureMap) -> Result<f64> {
+    let (w, h) = check_same_size(a, b, "PSNR")?;
+    let sum: f64 = a.data[..w * h * 4]
+        .par_chunks(4)
+        .zip(b.data[..w * h * 4].par_chunks(4))
+        .map(|(pa, pb)| {
+            (0..3)
+                .map(|c| {
+                    let d = pa[c] as f64 - pb[c] as f64;
+                    d * d
+                })
+                .sum::<f64>()
+        })
+        .sum();
+    let mse = sum / (w * h * 3).max(1) as f64;
+    if mse == 0.0 {
+        return Ok(MAX_PSNR);
+    }
+    Ok((10.0 * (255.0 * 255.0 / mse).log10()).min(MAX_PSNR))
+}
+
+/// Angle between the normals two tangent-space normal maps encode.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct NormalAngleError {
+    pub mean_degrees: f64,
+    pub p95_degrees: f64,
+    pub max_degrees: f64,
+}
+
+fn decode_normal(px: &[u8]) -> Option<[f64; 3]> {
+    let v = [0, 1, 2].map(|c| px[c] as f64 / 127.5 - 1.0);
+    let len = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    (len > 1e-6).then(|| v.map(|x| x / len))
+}
+
+/// Per-pixel angle between decoded normals. Pixels that decode to a zero
+/// vector in either map are skipped.
+pub fn normal_angle_error(a: &TextureMap, b: &TextureMap) -> Result<NormalAngleError> {
+    let (w, h) = check_same_size(a, b, "Normal angle error")?;
+    let mut angles: Vec<f64> = a.data[..w * h * 4]
+        .par_chunks(4)
+        .zip(b.data[..w * h * 4].par_chunks(4))
+        .filter_map(|(pa, pb)| {
+            let (na, nb) = (decode_normal(pa)?, decode_normal(pb)?);
+            let dot = na[0] * nb[0] + na[1] * nb[1] + na[2] * nb[2];
+            Some(dot.clamp(-1.0, 1.0).acos().to_degrees())
+        })
+        .collect();
+    if angles.is_empty() {
+        return Ok(NormalAngleError { mean_degrees: 0.0, p95_degrees: 0.0, max_degrees: 0.0 });
+    }
+    let mean_degrees = angles.iter().sum::<f64>() / angles.len() as f64;
+    let max_degrees = angles.iter().cloned().fold(0.0, f64::max);
+    let rank = ((angles.len() - 1) as f64 * 0.95).round() as usize;
+    let (_, p95, _) = angles.select_nth_unstable_by(rank, |x, y| x.total_cmp(y));
+    Ok(NormalAngleError { mean_degrees, p95_degrees: *p95, max_degrees })
+}
+
+/// Quality of one processed texture against its source.
+#[derive(Debug, Clone, Serialize)]
+pub struct QualityMetrics {
+    /// dB over RGB; [`MAX_PSNR`] when identical
+    pub psnr: f64,
+    pub ssim: f64,
+    pub ms_ssim: f64,
+    /// Only for normal maps
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normal_angle: Option<NormalAngleError>,
+}
+
+/// Measures `processed` against `original` after magnifying it back to the
+/// original size with bilinear filtering.
+pub fn compare_textures(original: &TextureMap, processed: &TextureMap, is_normal: bool) -> Result<QualityMetrics> {
+    let restored = upsample_bilinear(processed, original.width, original.height)?;
+    Ok(QualityMetrics {
+        psnr: psnr(original, &restored)?,
+        ssim: ssim(original, &restored)?,
+        ms_ssim: ms_ssim(original, &restored)?,
+        normal_angle: if is_normal {
+            Some(normal_angle_error(original, &restored)?)
+        } else {
+            None
+        },
+    })
+}
+
+/// Lowest acceptable quality for an export. Unset limits are not checked.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QualityFloor {
+    /// Minimum PSNR in dB
+    pub min_psnr: Option<f64>,
+    pub min_ssim: Option<f64>,
+    pub min_ms_ssim: Option<f64>,
+    /// Maximum mean normal-angle error in degrees (normal maps only)
+    pub max_normal_angle: Option<f64>,
+}
+
+impl QualityFloor {
+    pub fn is_empty(&self) -> bool {
+        self.min_psnr.is_none()
+            && self.min_ssim.is_none()
+            && self.min_ms_ssim.is_none()
+            && self.max_normal_angle.is_none()
+    }
+
+    /// Human-readable description of every limit `metrics` breaks.
+    pub fn violations(&self, metrics: &QualityMetrics) -> Vec<String> {
+        let mut out = Vec::new();
+        if let Some(min) = self.min_psnr.filter(|&min| metrics.psnr < min) {
+            out.push(format!("PSNR {:.2} dB < {:.2} dB", metrics.psnr, min));
+        }
+        if let Some(min) = self.min_ssim.filter(|&min| metrics.ssim < min) {
+            out.push(format!("SSIM {:.4} < {:.4}", metrics.ssim, min));
+        }
+        if let Some(min) = self.min_ms_ssim.filter(|&min| metrics.ms_ssim < min) {
+            out.push(format!("MS-SSIM {:.4} < {:.4}", metrics.ms_ssim, min));
+        }
+        if let (Some(max), Some(angle)) = (self.max_normal_angle, metrics.normal_angle) {
+            if angle.mean_degrees > max {
+                out.push(format!("normal angle {:.2}° > {:.2}°", angle.mean_degrees, max));
+            }
+        }
+        out
+    }
+}
+
+/// Quality of one exported slot.
+#[derive(Debug, Clone, Serialize)]
+pub struct TextureQuality {
+    /// "albedo", "normal", "roughness", ...
+    pub slot: String,
+    pub original_width: u32,
+    pub original_height: u32,
+    pub width: u32,
+    pub height: u32,
+    pub metrics: QualityMetrics,
+    /// Stair-stepping of the processed texture (see [`crate::banding`])
+    pub banding_score: f32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<String>,
+}
+
+/// Quality of every slot of an exported material.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportQualityReport {
+    pub material: Option<String>,
+    /// `TargetResolution` label of the base export
+    pub target: String,
+    pub floor: QualityFloor,
+    pub passed: bool,
+    pub textures: Vec<TextureQuality>,
+    /// Files written (empty when the floor was violated)
+    pub outputs: Vec<PathBuf>,
+    /// Set for lossy output formats, whose encoding loss the metrics do not include
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl ExportQualityReport {
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(Error::from)
+    }
+
+    /// "slot: violation" for every broken limit
+    pub fn failures(&self) -> Vec<String> {
+        self.textures
+            .iter()
+            .flat_map(|t| t.violations.iter().map(move |v| format!("{}: {}", t.slot, v)))
+            .collect()
+    }
+}
+
+/// Compares every slot of `optimized` with the same slot of `original`.
+/// Dithered slots are measured against the 8-bit original too, so the dither
+/// noise counts as loss (about 54 dB PSNR on a smooth ramp) and strict PSNR
+/// floors can reject them.
+pub fn material_quality(
+    original: &MaterialSet,
+    optimized: &MaterialSet,
+    target: TargetResolution,
+    floor: &QualityFloor,
+) -> Result<ExportQualityReport> {
+    let pairs: Vec<_> = slot_textures(original).zip(slot_textures(optimized)).collect();
+    let textures = pairs
+        .par_iter()
+        .map(|&((slot, source), (_, processed))| {
+            let metrics = compare_textures(source, processed, slot == "normal")?;
+            Ok(TextureQuality {
+                slot: slot.to_string(),
+                original_width: source.width,
+                original_height: source.height,
+                width: processed.width,
+                height: processed.height,
+                banding_score: banding_analysis(processed).banding_score,
+                violations: floor.violations(&metrics),
+                metrics,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ExportQualityReport {
+        material: original.name.clone(),
+        target: target.label(),
+        floor: floor.clone(),
+        passed: textures.iter().all(|t| t.violations.is_empty()),
+        textures,
+        outputs: Vec::new(),
+        note: None,
+    })
+}
+
+/// Resizes `material` to the preset's base resolution, measures the loss and
+/// writes [`QUALITY_REPORT_FILE`] into `output_dir`. Textures are written only
+/// when every slot meets `floor`; otherwise the report is still written and an
+/// error listing the violations is returned. With `include_lod`, the preset's
+/// LOD chain is exported too (only the base level is held to the floor).
+///
+/// The metrics describe the resized textures, which PNG and TGA store
+/// unchanged. JPG encoding adds loss they do not include, so a JPG preset
+/// with a non-empty floor is refused, and without a floor the report notes it.
+pub fn export_with_quality_floor(
+    material: &MaterialSet,
+    output_dir: &Path,
+    preset: &OptimizationPreset,
+    include_lod: bool,
+    floor: &QualityFloor,
+) -> Result<ExportQualityReport> {
+    let target = preset.effective_resolution();
+    std::fs::create_dir_all(output_dir)?;
+    let dither = DitherSources::load(material, &preset.dither)?;
+    let optimized = resize_for_export(material, target, &dither)?;
+    let mut report = material_quality(material, &optimized, target, floor)?;
+    let report_path = output_dir.join(QUALITY_REPORT_FILE);
+    if preset.format.is_lossy() {
+        let format = preset.format.extension().to_uppercase();
+        report.note = Some(format!("{} encoding loss is not measured; metrics cover resizing only", format));
+        if !floor.is_empty() {
+            report.passed = false;
+            std::fs::write(&report_path, report.to_json()?)?;
+            return Err(Error::Other(format!(
+                "Quality floor for {} cannot be enforced with lossy {} output; export as PNG or TGA (see {})",
+                material.name.as_deref().unwrap_or("material"),
+                format,
+                report_path.display()
+            )));
+        }
+    }
+    if !report.passed {
+        std::fs::write(&report_path, report.to_json()?)?;
+        return Err(Error::Other(format!(
+            "Quality floor violated for {}: {} (see {})",
+            material.name.as_deref().unwrap_or("material"),
+            report.failures().join("; "),
+            report_path.display()
+        )));
+    }
+    report.outputs = export_levels(material, &optimized, &dither, output_dir, preset, include_lod, floor)?;
+    std::fs::write(&report_path, report.to_json()?)?;
+    Ok(report)
+}
+
+/// SSIM between a texture and the same texture capped at `max_dim` (longest
+/// edge) and magnified back. Measured on a center crop of up to
+/// [`RESAMPLE_CROP`] pixels at the original scale.
+pub fn resample_ssim(texture: &TextureMap, max_dim: u32) -> Result<f64> {
+    let longest = texture.width.max(texture.height);
+    if max_dim >= longest || longest == 0 {
+        return Ok(1.0);
+    }
+    let crop = center_crop(texture, RESAMPLE_CROP);
+    let (tw, th) = compute_target_dimensions(texture.width, texture.height, max_dim);
+    let scale_x = tw as f64 / texture.width as f64;
+    let scale_y = th as f64 / texture.height as f64;
+    let small_w = ((crop.width as f64 * scale_x).round() as u32).max(1);
+    let small_h = ((crop.height as f64 * scale_y).round() as u32).max(1);
+    let small = resize_to_exact(&crop, small_w, small_h)?;
+    let restored = upsample_bilinear(&small, crop.width, crop.height)?;
+    ssim(&crop, &restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{gray, noise_texture};
+
+    fn gradient(w: u32, h: u32) -> TextureMap {
+        gray(w, h, |x, _| (x * 255 / (w - 1)) as u8)
+    }
+
+    #[test]
+    fn ssim_of_identical_textures_is_one() {
+        let t = noise_texture(64, 64, 7);
+        assert!((ssim(&t, &t).unwrap() - 1.0).abs() < 1e-9);
+        assert!(ssim(&t, &noise_texture(64, 64, 8)).unwrap() < 0.2);
+        assert!(ssim(&t, &noise_texture(32, 64, 7)).is_err());
+    }
+
+    #[test]
+    fn resample_ssim_separates_detail_from_smooth_content() {
+        let detailed = noise_texture(256, 256, 3);
+        let smooth = gradient(256, 256);
+        assert_eq!(resample_ssim(&detailed, 256).unwrap(), 1.0);
+        let detail_loss = 1.0 - resample_ssim(&detailed, 64).unwrap();
+        let smooth_loss = 1.0 - resample_ssim(&smooth, 64).unwrap();
+        assert!(detail_loss > 0.5, "noise should lose structure: {}", detail_loss);
+        assert!(smooth_loss < 0.05, "gradient should survive: {}", smooth_loss);
+    }
+
+    fn flat_normal(w: u32, h: u32, rgb: [u8; 3]) -> TextureMap {
+        let data = (0..w * h).flat_map(|_| [rgb[0], rgb[1], rgb[2], 255]).collect();
+        TextureMap::new(w, h, data, None)
+    }
+
+    #[test]
+    fn psnr_and_ms_ssim_rank_degradation() {
+        let t = noise_texture(64, 64, 11);
+        assert_eq!(psnr(&t, &t).unwrap(), MAX_PSNR);
+        assert!((ms_ssim(&t, &t).unwrap() - 1.0).abs() < 1e-9);
+        let mild = upsample_bilinear(&resize_to_exact(&t, 48, 48).unwrap(), 64, 64).unwrap();
+        let harsh = upsample_bilinear(&resize_to_exact(&t, 16, 16).unwrap(), 64, 64).unwrap();
+        assert!(psnr(&t, &mild).unwrap() > psnr(&t, &harsh).unwrap());
+        assert!(ms_ssim(&t, &mild).unwrap() > ms_ssim(&t, &harsh).unwrap());
+        assert!(psnr(&t, &noise_texture(32, 32, 11)).is_err());
+    }
+
+    #[test]
+    fn normal_angle_error_measures_tilt() {
+        let up = flat_normal(8, 8, [128, 128, 255]);
+        // (0.5, 0, 0.866) encodes a 30 degree tilt
+        let tilted = flat_normal(8, 8, [191, 128, 238]);
+        let err = normal_angle_error(&up, &tilted).unwrap();
+        assert!((err.mean_degrees - 30.0).abs() < 1.0, "{:?}", err);
+        assert!((err.p95_degrees - err.max_degrees).abs() < 1e-9);
+        assert!(normal_angle_error(&up, &up).unwrap().max_degrees < 1e-6);
+    }
+
+    #[test]
+    fn quality_floor_blocks_lossy_export() {
+        let dir = std::env::temp_dir().join("pbr_quality_floor_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let material = MaterialSet {
+            name: Some("noisy".into()),
+            albedo: Some(noise_texture(512, 512, 5)),
+            normal: Some(flat_normal(512, 512, [128, 128, 255])),
+            ..Default::default()
+        };
+
+        let preset = OptimizationPreset::mobile().with_resolution(TargetResolution::Res128);
+        let lenient = QualityFloor { max_normal_angle: Some(1.0), ..Default::default() };
+        let report = export_with_quality_floor(&material, &dir, &preset, false, &lenient).unwrap();
+        assert!(report.passed);
+        assert_eq!(report.outputs.len(), 2);
+        assert!(report.textures[1].metrics.normal_angle.is_some());
+        assert!(report.textures[0].metrics.normal_angle.is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+        let strict = QualityFloor { min_ssim: Some(0.9), ..Default::default() };
+        let err = export_with_quality_floor(&material, &dir, &preset, false, &strict).unwrap_err();
+        assert!(err.to_string().contains("albedo: SSIM"), "{}", err);
+        assert!(dir.join(QUALITY_REPORT_FILE).is_file());
+        assert!(!dir.join("BaseColor.png").exists());
+
+        // JPG loss is not measured, so a floor is refused and the report says why
+        let _ = std::fs::remove_dir_all(&dir);
+        let jpg = preset.with_format(crate::optimization::OutputFormat::Jpg);
+        let err = export_with_quality_floor(&material, &dir, &jpg, false, &lenient).unwrap_err();
+        assert!(err.to_string().contains("lossy JPG"), "{}", err);
+        let written = std::fs::read_to_string(dir.join(QUALITY_REPORT_FILE)).unwrap();
+        assert!(written.contains("JPG encoding loss is not measured"), "{}", written);
+        assert!(!dir.join("BaseColor.jpg").exists());
+        let report = export_with_quality_floor(&material, &dir, &jpg, false, &QualityFloor::default()).unwrap();
+        assert!(report.note.is_some());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn dithered_slots_are_measured_against_the_rounded_source() {
+        let dir = std::env::temp_dir().join("pbr_quality_dither_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ramp_height.png");
+        let ramp: image::ImageBuffer<image::Luma<u16>, Vec<u16>> =
+            image::ImageBuffer::from_fn(256, 64, |x, _| image::Luma([20_000 + x as u16 * 40]));
+        ramp.save(&path).unwrap();
+        let loaded = crate::image_loading::ImageLoader::load(&path).unwrap();
+        let material = MaterialSet {
+            name: Some("ramp".into()),
+            height: Some(TextureMap::from_loaded(loaded, Some(path))),
+            ..Default::default()
+        };
+
+        let plain = OptimizationPreset::unreal().with_resolution(TargetResolution::Res256);
+        let out = dir.join("out");
+        let report = export_with_quality_floor(&material, &out, &plain, false, &QualityFloor::default()).unwrap();
+        assert_eq!(report.textures[0].metrics.psnr, MAX_PSNR);
+
+        // Dither noise counts as loss against the 8-bit original
+        let dithered = plain.with_dither(crate::dither::DitherSettings::all(crate::dither::DitherMode::Ordered));
+        let report = export_with_quality_floor(&material, &out, &dithered, false, &QualityFloor::default()).unwrap();
+        let psnr = report.textures[0].metrics.psnr;
+        assert!(psnr > 45.0 && psnr < 60.0, "{}", psnr);
+        let strict = QualityFloor { min_psnr: Some(60.0), ..Default::default() };
+        assert!(export_with_quality_floor(&material, &out, &dithered, false, &strict).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
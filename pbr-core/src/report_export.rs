@@ -1,8 +1,10 @@
 //! Report export to HTML and PDF formats.
 //!
 //! Supports single and batch report generation with material scores,
-//! issues, suggestions, and optimization actions.
+//! issues, suggestions, and optimization actions. HTML reports also chart each
+//! texture's color distribution when the report carries one.
 
+use crate::color_distribution::TextureColorDistribution;
 use crate::json_report::{MaterialReport, Severity};
 use std::path::Path;
 use std::fs;
@@ -45,6 +47,9 @@ pub fn export_pdf_single(report: &MaterialReport, output_path: &Path) -> Result<
     doc.push(Paragraph::new(format!("Status: {}", if report.passed { "Passed" } else { "Needs attention" })));
     if let Some(ref v) = report.vram_estimate {
         doc.push(Paragraph::new(format!("VRAM estimate: {}", v.formatted)));
+        for p in &v.platforms {
+            doc.push(Paragraph::new(format!("  {}: {} ({:.1}x smaller)", p.platform, p.formatted, p.compression_ratio)));
+        }
     }
 
     doc.push(Paragraph::default().styled_string("Issues", style::Style::new().with_font_size(14)));
@@ -316,11 +321,17 @@ fn render_html_single(report: &MaterialReport) -> String {
         .collect();
 
     let vram_html = report.vram_estimate.as_ref()
-        .map(|v| format!(
-            r#"<div class="vram">VRAM: {} | Packed ORM: {}</div>"#,
-            html_escape(&v.formatted),
-            v.packed_orm
-        ))
+        .map(|v| {
+            let platforms: String = v.platforms.iter()
+                .map(|p| format!(" | {}: {}", p.platform, html_escape(&p.formatted)))
+                .collect();
+            format!(
+                r#"<div class="vram">VRAM: {} | Packed ORM: {}{}</div>"#,
+                html_escape(&v.formatted),
+                v.packed_orm,
+                platforms
+            )
+        })
         .unwrap_or_default();
 
     let ai_html = report.ai_insights.as_ref().map(|ai| {
@@ -393,6 +404,11 @@ h1 {{ font-size: 1.5rem; }}
 .ai-insights {{ font-size: 0.9em; margin-top: 0.5rem; padding: 0.5rem; background: #f8f9fa; border-radius: 8px; }}
 .ai-class {{ color: #0d6efd; }}
 .ai-anomalies ul {{ margin: 0.25rem 0; padding-left: 1.25rem; }}
+.color-dist {{ margin: 0.75rem 0; }}
+.color-title {{ font-weight: 600; }}
+.color-title .dims {{ font-weight: normal; color: #6c757d; font-size: 0.9em; }}
+.color-dist svg {{ display: block; margin: 0.25rem 0; background: #f8f9fa; }}
+.color-stats {{ font-size: 0.85em; color: #6c757d; }}
 footer {{ margin-top: 2rem; font-size: 0.8em; color: #6c757d; }}
 </style>
 </head>
@@ -413,6 +429,7 @@ footer {{ margin-top: 2rem; font-size: 0.8em; color: #6c757d; }}
 <div class="section-title">Suggested Optimizations</div>
 <ul class="suggestion-list">{}</ul>
 </div>
+{}
 <footer>Generated by PBR Studio — {}</footer>
 </body>
 </html>"#,
@@ -426,6 +443,7 @@ footer {{ margin-top: 2rem; font-size: 0.8em; color: #6c757d; }}
         ai_html,
         issues_html,
         suggestions_html,
+        render_color_section(report.color_distributions.as_deref()),
         chrono::Utc::now().format("%Y-%m-%d %H:%M UTC")
     )
 }
@@ -453,6 +471,7 @@ fn render_html_batch(reports: &[(String, MaterialReport)]) -> String {
 <div class="score {}">Score: {} / 100</div>
 <div class="section"><strong>Issues</strong><ul>{}</ul></div>
 <div class="section"><strong>Optimizations</strong><ul>{}</ul></div>
+{}
 </div>"#,
                 html_escape(path),
                 html_escape(name),
@@ -460,7 +479,8 @@ fn render_html_batch(reports: &[(String, MaterialReport)]) -> String {
                 status_class,
                 report.score,
                 issues_html,
-                suggestions_html
+                suggestions_html,
+                render_color_section(report.color_distributions.as_deref())
             )
         })
         .collect();
@@ -485,6 +505,12 @@ h1 {{ font-size: 1.5rem; }}
 .severity-critical {{ color: #dc3545; }}
 .severity-major {{ color: #fd7e14; }}
 .severity-minor {{ color: #6c757d; }}
+.section-title {{ font-weight: bold; margin-bottom: 0.5rem; }}
+.color-dist {{ margin: 0.75rem 0; }}
+.color-title {{ font-weight: 600; }}
+.color-title .dims {{ font-weight: normal; color: #6c757d; font-size: 0.9em; }}
+.color-dist svg {{ display: block; margin: 0.25rem 0; background: #f8f9fa; }}
+.color-stats {{ font-size: 0.85em; color: #6c757d; }}
 footer {{ margin-top: 2rem; font-size: 0.8em; color: #6c757d; }}
 </style>
 </head>
@@ -500,6 +526,146 @@ footer {{ margin-top: 2rem; font-size: 0.8em; color: #6c757d; }}
     )
 }
 
+/// Histogram height in the inline SVG charts (width is one pixel per bin)
+const CHART_HEIGHT: f32 = 80.0;
+
+/// Levels-style charts for each texture: RGB + luminance histograms, dominant
+/// color swatches and a hue distribution, all as inline SVG.
+fn render_color_section(dists: Option<&[TextureColorDistribution]>) -> String {
+    let Some(dists) = dists.filter(|d| !d.is_empty()) else {
+        return String::new();
+    };
+    let items: String = dists.iter().map(render_color_distribution).collect();
+    format!(
+        r#"<div class="section">
+<div class="section-title">Color Distribution</div>
+{}
+</div>"#,
+        items
+    )
+}
+
+fn render_color_distribution(d: &TextureColorDistribution) -> String {
+    let channels = [
+        (&d.red, "#dc3545"),
+        (&d.green, "#198754"),
+        (&d.blue, "#0d6efd"),
+        (&d.luminance, "#495057"),
+    ];
+    let peak = channels
+        .iter()
+        .flat_map(|(c, _)| c.histogram.iter().copied())
+        .max()
+        .unwrap_or(0)
+        .max(1) as f32;
+    let polylines: String = channels
+        .iter()
+        .map(|(c, color)| {
+            let points: Vec<String> = c
+                .histogram
+                .iter()
+                .enumerate()
+                .map(|(i, &n)| format!("{},{:.1}", i, CHART_HEIGHT - CHART_HEIGHT * n as f32 / peak))
+                .collect();
+            format!(
+                r#"<polyline fill="none" stroke="{}" stroke-opacity="0.8" points="{}"/>"#,
+                color,
+                points.join(" ")
+            )
+        })
+        .collect();
+    let histogram_svg = format!(
+        r#"<svg class="histogram" width="256" height="{h}" viewBox="0 0 256 {h}" role="img" aria-label="RGB and luminance histogram">{}</svg>"#,
+        polylines,
+        h = CHART_HEIGHT
+    );
+
+    let mut x = 0.0f32;
+    let swatches: String = d
+        .dominant_colors
+        .iter()
+        .map(|c| {
+            let w = 256.0 * c.share;
+            let rect = format!(
+                r#"<rect x="{:.1}" y="0" width="{:.1}" height="20" fill="{}"><title>{} ({:.0}%)</title></rect>"#,
+                x,
+                w,
+                c.hex,
+                c.hex,
+                c.share * 100.0
+            );
+            x += w;
+            rect
+        })
+        .collect();
+    let swatch_svg = format!(
+        r#"<svg class="swatches" width="256" height="20" viewBox="0 0 256 20" role="img" aria-label="Dominant colors">{}</svg>"#,
+        swatches
+    );
+
+    let bar_width = 256.0 / d.gamut.hue_histogram.len().max(1) as f32;
+    let hue_peak = d.gamut.hue_histogram.iter().copied().fold(0.0f32, f32::max).max(f32::EPSILON);
+    let bars: String = d
+        .gamut
+        .hue_histogram
+        .iter()
+        .enumerate()
+        .map(|(i, &share)| {
+            let h = 30.0 * share / hue_peak;
+            format!(
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"/>"#,
+                i as f32 * bar_width,
+                30.0 - h,
+                bar_width - 1.0,
+                h,
+                lab_hue_css(i, d.gamut.hue_histogram.len())
+            )
+        })
+        .collect();
+    let hue_svg = format!(
+        r#"<svg class="hues" width="256" height="30" viewBox="0 0 256 30" role="img" aria-label="Hue distribution">{}</svg>"#,
+        bars
+    );
+
+    let l = &d.luminance.percentiles;
+    let title = match d.material_name {
+        Some(ref m) => format!("{} — {}", m, d.slot),
+        None => d.slot.clone(),
+    };
+    format!(
+        r#"<div class="color-dist">
+<div class="color-title">{} <span class="dims">{}x{}</span></div>
+{}
+{}
+{}
+<div class="color-stats">Luminance p1/p50/p99: {}/{}/{} | Mean {:.1} ± {:.1} | L* {:.0}–{:.0} | Chroma mean {:.1}, p95 {:.1} | Gray {:.0}% | Clipped {:.1}%</div>
+</div>"#,
+        html_escape(&title),
+        d.width,
+        d.height,
+        histogram_svg,
+        swatch_svg,
+        hue_svg,
+        l.p1,
+        l.p50,
+        l.p99,
+        d.luminance.mean,
+        d.luminance.stddev,
+        d.gamut.lightness_p1,
+        d.gamut.lightness_p99,
+        d.gamut.chroma_mean,
+        d.gamut.chroma_p95,
+        d.gamut.grayscale_ratio * 100.0,
+        d.gamut.clipped_ratio * 100.0,
+    )
+}
+
+/// Approximate display color for a Lab hue bin (Lab 0° is magenta-red, HSL 0° is red).
+fn lab_hue_css(bin: usize, bins: usize) -> String {
+    let lab_hue = (bin as f32 + 0.5) * 360.0 / bins as f32;
+    format!("hsl({:.0},65%,50%)", (lab_hue - 20.0).rem_euclid(360.0))
+}
+
 fn severity_class(s: Severity) -> &'static str {
     match s {
         Severity::Critical => "critical",
@@ -560,8 +726,11 @@ mod tests {
                 include_mipmaps: true,
                 packed_orm: false,
                 textures: vec![],
+                platforms: vec![],
             }),
             ai_insights: None,
+            score_breakdown: None,
+            color_distributions: None,
         }
     }
 
@@ -0,0 +1,402 @@
+//! Configurable scoring model for validation results.
+//!
+//! Replaces the flat "100 minus 20/10/5 per issue" score with a pluggable
+//! [`ScoringModel`]: per-severity penalties, per-rule weights and caps, and
+//! category sub-scores (completeness, physical correctness, performance, tiling).
+//!
+//! Models are loaded per project from `.pbr-studio/scoring.toml` (or `.json`)
+//! and the resulting [`ScoreBreakdown`] is serialized into `MaterialReport`.
+
+use crate::validation::{Issue, Severity};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const SCORING_TOML: &str = ".pbr-studio/scoring.toml";
+const SCORING_JSON: &str = ".pbr-studio/scoring.json";
+
+/// Score category used for sub-scores
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreCategory {
+    Completeness,
+    PhysicalCorrectness,
+    Performance,
+    Tiling,
+}
+
+impl ScoreCategory {
+    pub const ALL: [ScoreCategory; 4] = [
+        ScoreCategory::Completeness,
+        ScoreCategory::PhysicalCorrectness,
+        ScoreCategory::Performance,
+        ScoreCategory::Tiling,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScoreCategory::Completeness => "completeness",
+            ScoreCategory::PhysicalCorrectness => "physical_correctness",
+            ScoreCategory::Performance => "performance",
+            ScoreCategory::Tiling => "tiling",
+        }
+    }
+
+    /// Default category for built-in rule IDs. Unknown (plugin) rules return `None`.
+    pub fn for_builtin_rule(rule_id: &str) -> Option<Self> {
+        match rule_id {
+            "required_maps" | "resolution_mismatch" => Some(ScoreCategory::Completeness),
+            "albedo_brightness_range" | "roughness_uniformity" | "metallic_mid_gray"
+            | "normal_map_strength" | "ai_anomaly" => Some(ScoreCategory::PhysicalCorrectness),
+            "texture_resolution" | "non_power_of_two" | "texel_density" => Some(ScoreCategory::Performance),
+            "tileability" => Some(ScoreCategory::Tiling),
+            _ => None,
+        }
+    }
+}
+
+/// Base penalty per severity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeverityPenalties {
+    #[serde(default = "default_critical")]
+    pub critical: f32,
+    #[serde(default = "default_major")]
+    pub major: f32,
+    #[serde(default = "default_minor")]
+    pub minor: f32,
+}
+
+fn default_critical() -> f32 {
+    Severity::Critical.score_penalty() as f32
+}
+
+fn default_major() -> f32 {
+    Severity::Major.score_penalty() as f32
+}
+
+fn default_minor() -> f32 {
+    Severity::Minor.score_penalty() as f32
+}
+
+impl Default for SeverityPenalties {
+    fn default() -> Self {
+        Self {
+            critical: default_critical(),
+            major: default_major(),
+            minor: default_minor(),
+        }
+    }
+}
+
+impl SeverityPenalties {
+    pub fn penalty(&self, severity: Severity) -> f32 {
+        match severity {
+            Severity::Critical => self.critical,
+            Severity::Major => self.major,
+            Severity::Minor => self.minor,
+        }
+    }
+}
+
+fn default_model_name() -> String {
+    "default".to_string()
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// Pluggable scoring model (JSON/TOML).
+///
+/// Effective penalty of an issue = severity penalty × rule weight × category weight.
+/// Penalties of the same rule are summed, then clamped to the rule cap (if any).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoringModel {
+    /// Model identifier (shown in reports)
+    #[serde(default = "default_model_name")]
+    pub name: String,
+    /// Base penalty per severity
+    #[serde(default)]
+    pub severity_penalties: SeverityPenalties,
+    /// Per-rule weight multiplier (default 1.0)
+    #[serde(default)]
+    pub rule_weights: HashMap<String, f32>,
+    /// Maximum total penalty a single rule can contribute
+    #[serde(default)]
+    pub rule_caps: HashMap<String, f32>,
+    /// Cap applied to rules without an explicit entry in `rule_caps`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_rule_cap: Option<f32>,
+    /// Category override for rule IDs (plugin rules or re-mapped built-ins)
+    #[serde(default)]
+    pub rule_categories: HashMap<String, ScoreCategory>,
+    /// Per-category weight multiplier (default 1.0)
+    #[serde(default)]
+    pub category_weights: HashMap<ScoreCategory, f32>,
+}
+
+impl Default for ScoringModel {
+    fn default() -> Self {
+        Self {
+            name: default_model_name(),
+            severity_penalties: SeverityPenalties::default(),
+            rule_weights: HashMap::new(),
+            rule_caps: HashMap::new(),
+            default_rule_cap: None,
+            rule_categories: HashMap::new(),
+            category_weights: HashMap::new(),
+        }
+    }
+}
+
+/// Penalty contributed by a single rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulePenalty {
+    pub rule_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<ScoreCategory>,
+    pub issue_count: usize,
+    /// Penalty after weights, before the cap
+    pub raw_penalty: f32,
+    /// Penalty applied to the score
+    pub penalty: f32,
+    pub capped: bool,
+}
+
+/// Sub-score for one category (0-100)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryScore {
+    pub category: ScoreCategory,
+    pub score: i32,
+    pub penalty: f32,
+}
+
+/// Explains how a material score was computed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    /// Name of the scoring model used
+    pub model: String,
+    /// Overall score (0-100)
+    pub score: i32,
+    /// Sub-scores per category
+    pub categories: Vec<CategoryScore>,
+    /// Per-rule penalties, in order of first occurrence
+    pub rules: Vec<RulePenalty>,
+}
+
+impl ScoringModel {
+    /// Load a model from a JSON or TOML file (format inferred from extension).
+    pub fn load<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path)?;
+        let is_toml = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("toml"));
+        if is_toml {
+            toml::from_str(&s)
+                .map_err(|e| crate::Error::Other(format!("Invalid scoring model {}: {}", path.display(), e)))
+        } else {
+            Ok(serde_json::from_str(&s)?)
+        }
+    }
+
+    /// Find the project scoring model for a material folder.
+    /// Searches `.pbr-studio/scoring.toml` / `.json` in the folder and its ancestors.
+    /// Returns `None` when no project model exists.
+    pub fn for_project<P: AsRef<Path>>(folder: P) -> crate::Result<Option<Self>> {
+        match Self::project_file(folder.as_ref()) {
+            Some(path) => Self::load(&path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Project root of a material folder: the nearest folder (itself or an
+    /// ancestor) with a project scoring model, as found by [`Self::for_project`].
+    pub fn project_root<P: AsRef<Path>>(folder: P) -> Option<PathBuf> {
+        let file = Self::project_file(folder.as_ref())?;
+        file.parent()?.parent().map(Path::to_path_buf)
+    }
+
+    fn project_file(folder: &Path) -> Option<PathBuf> {
+        folder
+            .ancestors()
+            .flat_map(|d| [SCORING_TOML, SCORING_JSON].map(|name| d.join(name)))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Category for a rule: model override first, then built-in mapping.
+    pub fn category_for(&self, rule_id: &str) -> Option<ScoreCategory> {
+        self.rule_categories
+            .get(rule_id)
+            .copied()
+            .or_else(|| ScoreCategory::for_builtin_rule(rule_id))
+    }
+
+    fn rule_cap(&self, rule_id: &str) -> Option<f32> {
+        self.rule_caps.get(rule_id).copied().or(self.default_rule_cap)
+    }
+
+    /// Compute the overall score with category sub-scores and per-rule penalties.
+    pub fn evaluate(&self, issues: &[Issue]) -> ScoreBreakdown {
+        let mut rules: Vec<RulePenalty> = Vec::new();
+        for issue in issues {
+            let category = self.category_for(&issue.rule_id);
+            let weight = self.rule_weights.get(&issue.rule_id).copied().unwrap_or_else(default_weight)
+                * category
+                    .and_then(|c| self.category_weights.get(&c).copied())
+                    .unwrap_or_else(default_weight);
+            let penalty = self.severity_penalties.penalty(issue.severity) * weight;
+
+            match rules.iter_mut().find(|r| r.rule_id == issue.rule_id) {
+                Some(r) => {
+                    r.issue_count += 1;
+                    r.raw_penalty += penalty;
+                }
+                None => rules.push(RulePenalty {
+                    rule_id: issue.rule_id.clone(),
+                    category,
+                    issue_count: 1,
+                    raw_penalty: penalty,
+                    penalty,
+                    capped: false,
+                }),
+            }
+        }
+
+        for r in &mut rules {
+            r.penalty = r.raw_penalty.max(0.0);
+            if let Some(cap) = self.rule_cap(&r.rule_id) {
+                if r.penalty > cap {
+                    r.penalty = cap.max(0.0);
+                    r.capped = true;
+                }
+            }
+        }
+
+        let total: f32 = rules.iter().map(|r| r.penalty).sum();
+        let categories = ScoreCategory::ALL
+            .iter()
+            .map(|&category| {
+                let penalty: f32 = rules
+                    .iter()
+                    .filter(|r| r.category == Some(category))
+                    .map(|r| r.penalty)
+                    .sum();
+                CategoryScore {
+                    category,
+                    score: penalty_to_score(penalty),
+                    penalty,
+                }
+            })
+            .collect();
+
+        ScoreBreakdown {
+            model: self.name.clone(),
+            score: penalty_to_score(total),
+            categories,
+            rules,
+        }
+    }
+
+    /// Overall score only (0-100).
+    pub fn score(&self, issues: &[Issue]) -> i32 {
+        self.evaluate(issues).score
+    }
+}
+
+fn penalty_to_score(penalty: f32) -> i32 {
+    (100.0 - penalty).round().clamp(0.0, 100.0) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(rule: &str, severity: Severity) -> Issue {
+        Issue::new(rule, severity, "msg")
+    }
+
+    #[test]
+    fn default_model_matches_flat_score() {
+        let issues = vec![
+            issue("required_maps", Severity::Critical),
+            issue("tileability", Severity::Major),
+            issue("my_plugin_rule", Severity::Minor),
+        ];
+        assert_eq!(ScoringModel::default().score(&issues), crate::validation::compute_score(&issues));
+        assert_eq!(ScoringModel::default().score(&issues), 65);
+    }
+
+    #[test]
+    fn rule_cap_limits_repeated_rule() {
+        let issues = vec![
+            issue("script_rule", Severity::Critical),
+            issue("script_rule", Severity::Critical),
+            issue("script_rule", Severity::Critical),
+        ];
+        let mut model = ScoringModel::default();
+        model.rule_caps.insert("script_rule".into(), 25.0);
+        let breakdown = model.evaluate(&issues);
+        assert_eq!(breakdown.score, 75);
+        assert_eq!(breakdown.rules.len(), 1);
+        assert_eq!(breakdown.rules[0].issue_count, 3);
+        assert!(breakdown.rules[0].capped);
+    }
+
+    #[test]
+    fn weights_and_category_sub_scores() {
+        let issues = vec![
+            issue("texture_resolution", Severity::Major),
+            issue("tileability", Severity::Minor),
+        ];
+        let mut model = ScoringModel::default();
+        model.rule_weights.insert("texture_resolution".into(), 0.5);
+        model.category_weights.insert(ScoreCategory::Tiling, 2.0);
+        let breakdown = model.evaluate(&issues);
+        assert_eq!(breakdown.score, 85); // 100 - 5 - 10
+
+        let get = |c: ScoreCategory| breakdown.categories.iter().find(|s| s.category == c).unwrap().score;
+        assert_eq!(get(ScoreCategory::Performance), 95);
+        assert_eq!(get(ScoreCategory::Tiling), 90);
+        assert_eq!(get(ScoreCategory::Completeness), 100);
+    }
+
+    #[test]
+    fn model_parses_from_toml() {
+        let s = r#"
+            name = "mobile"
+            default_rule_cap = 30.0
+
+            [severity_penalties]
+            critical = 40.0
+
+            [rule_weights]
+            texture_resolution = 2.0
+
+            [rule_categories]
+            my_plugin_rule = "performance"
+        "#;
+        let model: ScoringModel = toml::from_str(s).unwrap();
+        assert_eq!(model.name, "mobile");
+        assert_eq!(model.severity_penalties.critical, 40.0);
+        assert_eq!(model.severity_penalties.minor, 5.0);
+        assert_eq!(model.category_for("my_plugin_rule"), Some(ScoreCategory::Performance));
+        assert_eq!(model.category_for("tileability"), Some(ScoreCategory::Tiling));
+    }
+
+    #[test]
+    fn project_model_is_found_from_nested_folders() {
+        let root = std::env::temp_dir().join("pbr_scoring_project_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let folder = root.join("walls").join("Brick");
+        std::fs::create_dir_all(&folder).unwrap();
+        assert!(ScoringModel::project_root(&folder).is_none());
+
+        std::fs::create_dir_all(root.join(".pbr-studio")).unwrap();
+        std::fs::write(root.join(SCORING_TOML), "name = \"project\"\n").unwrap();
+        assert_eq!(ScoringModel::project_root(&folder), Some(root.clone()));
+        assert_eq!(ScoringModel::for_project(&folder).unwrap().unwrap().name, "project");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
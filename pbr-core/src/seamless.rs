@@ -0,0 +1,640 @@
+//! High-quality seamless tiling.
+//!
+//! Two complementary fixes, applied identically to every slot of a material:
+//!
+//! - **Seam quilting** – the texture is offset by half so the wrap seam lies in
+//!   the middle, then the seam is covered with a patch of the original texture,
+//!   whose center is continuous. The patch borders follow minimum-error cuts
+//!   (image quilting) through the band around the seam and are feathered over a
+//!   few pixels. The left↔right seam is fixed first, then the top↔bottom seam
+//!   on the result, so the corners come out consistent.
+//! - **Poisson blending** – the smooth component of the periodic + smooth
+//!   decomposition (see [`crate::tileability`]) is subtracted, spreading any
+//!   remaining low-frequency mismatch (e.g. lighting gradients) smoothly over
+//!   the whole image.
+//!
+//! The cuts are computed once per material ([`SeamPlan`]) from a reference slot
+//! and reused for every slot, so albedo, normal and roughness stay aligned.
+//! Normal maps are blended as vectors and renormalized.
+
+use crate::analysis::{edge_difference, fix_tileability, TileabilityFixResult};
+use crate::image_loading::TextureSlot;
+use crate::material::{MaterialSet, TextureMap};
+use crate::optimization::save_texture;
+use crate::tileability::smooth_component;
+use crate::{Error, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Report written next to the fixed textures by [`fix_material_folder`]
+pub const FIX_REPORT_FILE: &str = "tileability-report.json";
+
+/// How seams are removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeamlessMethod {
+    /// Offset and patch the seam along minimum-error cuts
+    Quilt,
+    /// Gradient-domain correction only (no content is moved)
+    Poisson,
+    /// Seam patch, then gradient-domain cleanup of the remaining drift
+    #[default]
+    Hybrid,
+    /// Legacy: average opposite edge rows/columns over `feather` pixels
+    Blend,
+}
+
+impl SeamlessMethod {
+    fn quilts(self) -> bool {
+        matches!(self, Self::Quilt | Self::Hybrid)
+    }
+
+    fn poisson(self) -> bool {
+        matches!(self, Self::Poisson | Self::Hybrid)
+    }
+}
+
+/// Options for [`make_seamless`] and [`make_material_seamless`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeamlessOptions {
+    pub method: SeamlessMethod,
+    /// Slot the seam cuts are computed from for a whole material. Default: albedo,
+    /// or the first present slot
+    #[serde(default)]
+    pub reference_slot: Option<TextureSlot>,
+    /// Band searched for cuts on each side of the seam, as a fraction of the texture size
+    pub band_fraction: f32,
+    /// Feather width across each cut, in reference texture pixels
+    pub feather: u32,
+}
+
+impl Default for SeamlessOptions {
+    fn default() -> Self {
+        Self {
+            method: SeamlessMethod::Hybrid,
+            reference_slot: None,
+            band_fraction: 0.125,
+            feather: 4,
+        }
+    }
+}
+
+/// Seam cuts computed once from reference textures and applied to every slot.
+/// Textures of a different resolution than the reference get scaled cuts.
+#[derive(Debug, Clone)]
+pub struct SeamPlan {
+    method: SeamlessMethod,
+    width: usize,
+    height: usize,
+    feather: f32,
+    /// Patch borders (left, right) per row around the left↔right seam, in offset coordinates
+    column_cuts: Vec<(usize, usize)>,
+    /// Patch borders (top, bottom) per column around the top↔bottom seam, in offset coordinates
+    row_cuts: Vec<(usize, usize)>,
+}
+
+impl SeamPlan {
+    /// Compute the cuts from `reference` textures. Their per-pixel errors are
+    /// summed, so the cuts avoid visible features in all of them; references
+    /// with a different size than the first are ignored.
+    pub fn compute(reference: &[&TextureMap], options: &SeamlessOptions) -> Result<Self> {
+        let first = reference
+            .first()
+            .ok_or_else(|| Error::Other("No reference texture for seamless fix".into()))?;
+        let (w, h) = (first.width as usize, first.height as usize);
+        let feather = match options.method {
+            SeamlessMethod::Blend => options.feather.max(1) as usize,
+            _ => (options.feather as usize).min(w.min(h) / 16).max(1),
+        };
+        let mut plan = Self {
+            method: options.method,
+            width: w,
+            height: h,
+            feather: feather as f32,
+            column_cuts: Vec::new(),
+            row_cuts: Vec::new(),
+        };
+        if !options.method.quilts() {
+            return Ok(plan);
+        }
+
+        let refs: Vec<&[u8]> = reference
+            .iter()
+            .filter(|t| t.width as usize == w && t.height as usize == h && t.data.len() >= w * h * 4)
+            .map(|t| t.data.as_slice())
+            .collect();
+        plan.column_cuts = column_cuts(&refs, w, h, options.band_fraction, feather);
+
+        // Row cuts are searched on the column-fixed images, as transposed column cuts
+        let transposed: Vec<Vec<u8>> = refs
+            .iter()
+            .map(|data| {
+                let columns = blend_cuts(data, w, h, &plan.column_cuts, w, plan.feather, &mix_u8);
+                transpose(&columns, w, h)
+            })
+            .collect();
+        let transposed_refs: Vec<&[u8]> = transposed.iter().map(|d| d.as_slice()).collect();
+        plan.row_cuts = column_cuts(&transposed_refs, h, w, options.band_fraction, feather);
+        Ok(plan)
+    }
+
+    /// Apply the plan to one color or data texture.
+    pub fn apply(&self, texture: &TextureMap) -> TextureMap {
+        let (w, h) = (texture.width as usize, texture.height as usize);
+        if w < 2 || h < 2 || texture.data.len() < w * h * 4 {
+            return texture.clone();
+        }
+        if self.method == SeamlessMethod::Blend {
+            return self.edge_blend(texture);
+        }
+        let mut data = texture.data[..w * h * 4].to_vec();
+        if self.method.quilts() {
+            data = self.blend(&data, w, h, &mix_u8);
+        }
+        if self.method.poisson() {
+            remove_smooth_component(&mut data, w, h, |v| v as f64, |v| v.round().clamp(0.0, 255.0) as u8);
+        }
+        TextureMap::new(texture.width, texture.height, data, texture.path.clone())
+    }
+
+    /// Apply the plan to a tangent-space normal map: vectors are blended and
+    /// renormalized instead of averaging bytes.
+    pub fn apply_normal(&self, texture: &TextureMap) -> TextureMap {
+        let (w, h) = (texture.width as usize, texture.height as usize);
+        if w < 2 || h < 2 || texture.data.len() < w * h * 4 {
+            return texture.clone();
+        }
+        let source = if self.method == SeamlessMethod::Blend {
+            self.edge_blend(texture).data
+        } else {
+            texture.data[..w * h * 4].to_vec()
+        };
+        let mut vectors: Vec<f32> = source.iter().map(|&b| decode_normal(b)).collect();
+        if self.method.quilts() {
+            vectors = self.blend(&vectors, w, h, &mix_f32);
+        }
+        if self.method.poisson() {
+            remove_smooth_component(&mut vectors, w, h, |v| v as f64, |v| v as f32);
+        }
+        TextureMap::new(texture.width, texture.height, encode_normals(&vectors), texture.path.clone())
+    }
+
+    /// Legacy edge averaging, with the feather scaled to the texture.
+    fn edge_blend(&self, texture: &TextureMap) -> TextureMap {
+        let width = (self.feather * texture.width as f32 / self.width as f32).round().max(1.0) as u32;
+        fix_tileability(texture, width).unwrap_or_else(|_| texture.clone())
+    }
+
+    /// Patch the left↔right seam, then the top↔bottom seam (as transposed columns).
+    fn blend<T, F>(&self, data: &[T], w: usize, h: usize, mix: &F) -> Vec<T>
+    where
+        T: Copy + Default + Send + Sync,
+        F: Fn(T, T, f32) -> T + Sync,
+    {
+        let columns = blend_cuts(data, w, h, &self.column_cuts, self.width, self.feather, mix);
+        let rows = blend_cuts(&transpose(&columns, w, h), h, w, &self.row_cuts, self.height, self.feather, mix);
+        transpose(&rows, h, w)
+    }
+}
+
+/// Make a single texture tile seamlessly.
+pub fn make_seamless(texture: &TextureMap, options: &SeamlessOptions) -> Result<TextureMap> {
+    Ok(SeamPlan::compute(&[texture], options)?.apply(texture))
+}
+
+/// Make every slot of a material tile seamlessly with one shared plan,
+/// computed from [`SeamlessOptions::reference_slot`]. Normal maps are blended
+/// as vectors and renormalized.
+pub fn make_material_seamless(set: &MaterialSet, options: &SeamlessOptions) -> Result<MaterialSet> {
+    let (_, reference) = reference_texture(set, options.reference_slot)?;
+    let plan = SeamPlan::compute(&[reference], options)?;
+
+    let fix = |slot: &Option<TextureMap>| slot.as_ref().map(|t| plan.apply(t));
+    Ok(MaterialSet {
+        albedo: fix(&set.albedo),
+        normal: set.normal.as_ref().map(|t| plan.apply_normal(t)),
+        roughness: fix(&set.roughness),
+        metallic: fix(&set.metallic),
+        ao: fix(&set.ao),
+        height: fix(&set.height),
+        name: set.name.clone(),
+    })
+}
+
+/// Before/after tileability of one slot of a material fix.
+#[derive(Debug, Clone, Serialize)]
+pub struct SlotFixResult {
+    pub slot: String,
+    pub output_path: String,
+    #[serde(flatten)]
+    pub result: TileabilityFixResult,
+}
+
+/// Report of a material-wide tileability fix.
+#[derive(Debug, Clone, Serialize)]
+pub struct MaterialFixReport {
+    pub material_name: Option<String>,
+    pub source: String,
+    pub output: String,
+    pub reference_slot: TextureSlot,
+    pub method: SeamlessMethod,
+    pub slots: Vec<SlotFixResult>,
+}
+
+impl MaterialFixReport {
+    pub fn to_json(&self) -> std::result::Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// Fix every slot of the material in `folder` with one shared plan and write
+/// the fixed textures plus [`FIX_REPORT_FILE`] to `output`. Textures keep
+/// their file names; formats that cannot be written (EXR) become PNG.
+pub fn fix_material_folder(folder: &Path, output: &Path, options: &SeamlessOptions) -> Result<MaterialFixReport> {
+    let set = MaterialSet::load_from_folder(folder)?;
+    let (reference_slot, _) = reference_texture(&set, options.reference_slot)?;
+    let fixed = make_material_seamless(&set, options)?;
+    fs::create_dir_all(output)?;
+
+    let mut slots = Vec::new();
+    for ((slot, before), (_, after)) in material_slots(&set).into_iter().zip(material_slots(&fixed)) {
+        let (Some(before), Some(after)) = (before, after) else {
+            continue;
+        };
+        let out_path = output.join(output_file_name(slot, before));
+        save_texture(after, &out_path)?;
+        slots.push(SlotFixResult {
+            slot: slot.to_string(),
+            output_path: out_path.display().to_string(),
+            result: fix_report(before, after),
+        });
+    }
+
+    let report = MaterialFixReport {
+        material_name: set.name.clone(),
+        source: folder.display().to_string(),
+        output: output.display().to_string(),
+        reference_slot,
+        method: options.method,
+        slots,
+    };
+    fs::write(output.join(FIX_REPORT_FILE), report.to_json()?)?;
+    Ok(report)
+}
+
+/// The reference slot and its texture: `slot` if given, else albedo or the first present slot.
+fn reference_texture(set: &MaterialSet, slot: Option<TextureSlot>) -> Result<(TextureSlot, &TextureMap)> {
+    match slot {
+        Some(slot) => set
+            .get(slot)
+            .map(|t| (slot, t))
+            .ok_or_else(|| Error::Other(format!("Reference slot {:?} not found in material", slot))),
+        None => [
+            TextureSlot::Albedo,
+            TextureSlot::Normal,
+            TextureSlot::Roughness,
+            TextureSlot::Metallic,
+            TextureSlot::AmbientOcclusion,
+            TextureSlot::Height,
+        ]
+        .into_iter()
+        .find_map(|slot| set.get(slot).map(|t| (slot, t)))
+        .ok_or_else(|| Error::Other("Material has no textures".into())),
+    }
+}
+
+fn material_slots(set: &MaterialSet) -> [(&'static str, Option<&TextureMap>); 6] {
+    [
+        ("albedo", set.albedo.as_ref()),
+        ("normal", set.normal.as_ref()),
+        ("roughness", set.roughness.as_ref()),
+        ("metallic", set.metallic.as_ref()),
+        ("ao", set.ao.as_ref()),
+        ("height", set.height.as_ref()),
+    ]
+}
+
+fn output_file_name(slot: &str, texture: &TextureMap) -> String {
+    let Some(name) = texture.path.as_ref().and_then(|p| p.file_name()) else {
+        return format!("{}.png", slot);
+    };
+    let name = Path::new(name);
+    let writable = name
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| matches!(e.to_lowercase().as_str(), "png" | "jpg" | "jpeg" | "tga"));
+    if writable {
+        name.display().to_string()
+    } else {
+        name.with_extension("png").display().to_string()
+    }
+}
+
+/// Run the seamless fix and return before/after metrics.
+pub fn make_seamless_with_report(
+    texture: &TextureMap,
+    options: &SeamlessOptions,
+) -> Result<(TextureMap, TileabilityFixResult)> {
+    let fixed = make_seamless(texture, options)?;
+    let result = fix_report(texture, &fixed);
+    Ok((fixed, result))
+}
+
+/// Before/after metrics of a fix, judged by seam score.
+pub(crate) fn fix_report(original: &TextureMap, fixed: &TextureMap) -> TileabilityFixResult {
+    let path = original.path.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "unknown".into());
+    let original_seam_score = original.tileability().score;
+    let fixed_seam_score = fixed.tileability().score;
+    TileabilityFixResult {
+        path,
+        original_edge_difference: edge_difference(original) as f32,
+        fixed_edge_difference: edge_difference(fixed) as f32,
+        original_seam_score,
+        fixed_seam_score,
+        improved: fixed_seam_score < original_seam_score,
+    }
+}
+
+/// Minimum-error patch borders around the left↔right seam, one `(left, right)` pair per row.
+/// Empty when the texture is too narrow to fit a band.
+fn column_cuts(refs: &[&[u8]], w: usize, h: usize, band_fraction: f32, feather: usize) -> Vec<(usize, usize)> {
+    let half = w / 2;
+    // Offset column x shows original column (x + half) % w; the wrap lands here
+    let seam = w - half;
+    if refs.is_empty() || half < feather + 3 {
+        return Vec::new();
+    }
+    let band = ((w as f32 * band_fraction).round() as usize).clamp(feather + 2, half - 1);
+    // Cuts stay at least `feather` away from the seam so it is fully covered
+    let span = band - feather;
+    let left_start = seam - band;
+    let right_start = seam + feather + 1;
+
+    // Error between the offset image and the patch (the unshifted original)
+    let error = |x: usize, y: usize| -> f32 {
+        let mut e = 0.0;
+        for data in refs {
+            let a = (y * w + (x + half) % w) * 4;
+            let b = (y * w + x) * 4;
+            for c in 0..3 {
+                let d = (data[a + c] as f32 - data[b + c] as f32) / 255.0;
+                e += d * d;
+            }
+        }
+        e
+    };
+    let cost = |start: usize| -> Vec<f32> {
+        (0..h).flat_map(|y| (0..span).map(move |i| (y, i))).map(|(y, i)| error(start + i, y)).collect()
+    };
+    let left = min_cut(&cost(left_start), h, span);
+    let right = min_cut(&cost(right_start), h, span);
+    left.into_iter()
+        .zip(right)
+        .map(|(l, r)| (left_start + l, right_start + r))
+        .collect()
+}
+
+/// Minimum-cost path through `cost` (`lines` x `span`, row-major) with one
+/// position per line, moving at most one position between lines. The path is
+/// cyclic: it starts at the position with the lowest total cost and ends next
+/// to it, so the cut closes up when the texture wraps.
+fn min_cut(cost: &[f32], lines: usize, span: usize) -> Vec<usize> {
+    let total = |i: usize| (0..lines).map(|l| cost[l * span + i]).sum::<f32>();
+    let start = (0..span).min_by(|&a, &b| total(a).total_cmp(&total(b))).unwrap_or(0);
+
+    let mut acc = vec![f32::INFINITY; span];
+    acc[start] = cost[start];
+    let mut back = vec![0i8; lines * span];
+    for l in 1..lines {
+        let mut next = vec![f32::INFINITY; span];
+        for i in 0..span {
+            let (mut best, mut step) = (acc[i], 0i8);
+            if i > 0 && acc[i - 1] < best {
+                (best, step) = (acc[i - 1], -1);
+            }
+            if i + 1 < span && acc[i + 1] < best {
+                (best, step) = (acc[i + 1], 1);
+            }
+            next[i] = best + cost[l * span + i];
+            back[l * span + i] = step;
+        }
+        acc = next;
+    }
+
+    let mut pos = (start.saturating_sub(1)..=(start + 1).min(span - 1))
+        .min_by(|&a, &b| acc[a].total_cmp(&acc[b]))
+        .unwrap_or(start);
+    let mut path = vec![0; lines];
+    for l in (0..lines).rev() {
+        path[l] = pos;
+        pos = (pos as isize + back[l * span + pos] as isize) as usize;
+    }
+    path
+}
+
+/// Blend the half-shifted copy into `data` between each row's cuts (the
+/// left↔right seam patch, un-shifted back so the interior is untouched).
+/// `cuts` are in `ref_width` pixels and scaled to this texture.
+fn blend_cuts<T, F>(data: &[T], w: usize, h: usize, cuts: &[(usize, usize)], ref_width: usize, feather: f32, mix: &F) -> Vec<T>
+where
+    T: Copy + Send + Sync,
+    F: Fn(T, T, f32) -> T + Sync,
+{
+    let mut out = data.to_vec();
+    if cuts.is_empty() {
+        return out;
+    }
+    let half = w / 2;
+    let scale = w as f32 / ref_width as f32;
+    let feather = (feather * scale).max(1.0);
+    // Weight of the patch at distance `d` inside a cut
+    let ramp = |d: f32| ((d + 0.5) / feather + 0.5).clamp(0.0, 1.0);
+
+    out.par_chunks_mut(w * 4).enumerate().for_each(|(y, row)| {
+        let (l, r) = cuts[(y * cuts.len() / h).min(cuts.len() - 1)];
+        let (l, r) = (l as f32 * scale, r as f32 * scale);
+        for x in 0..w {
+            // Offset coordinate of this pixel; the patch there is the original pixel at `xo`
+            let xo = (x + w - half) % w;
+            let alpha = ramp(xo as f32 - l) * ramp(r - xo as f32);
+            if alpha <= 0.0 {
+                continue;
+            }
+            for c in 0..4 {
+                row[x * 4 + c] = mix(data[(y * w + x) * 4 + c], data[(y * w + xo) * 4 + c], alpha);
+            }
+        }
+    });
+    out
+}
+
+/// Subtract the smooth component from each color channel (alpha is left as is).
+fn remove_smooth_component<T: Copy>(data: &mut [T], w: usize, h: usize, to: impl Fn(T) -> f64, from: impl Fn(f64) -> T) {
+    for c in 0..3 {
+        let channel: Vec<f64> = (0..w * h).map(|i| to(data[i * 4 + c])).collect();
+        let smooth = smooth_component(&channel, w, h);
+        for (i, s) in smooth.iter().enumerate() {
+            data[i * 4 + c] = from(channel[i] - s);
+        }
+    }
+}
+
+fn mix_u8(a: u8, b: u8, t: f32) -> u8 {
+    (a as f32 + t * (b as f32 - a as f32)).round().clamp(0.0, 255.0) as u8
+}
+
+fn mix_f32(a: f32, b: f32, t: f32) -> f32 {
+    a + t * (b - a)
+}
+
+fn decode_normal(b: u8) -> f32 {
+    b as f32 / 127.5 - 1.0
+}
+
+fn encode_normal(v: f32) -> u8 {
+    ((v + 1.0) * 127.5).round().clamp(0.0, 255.0) as u8
+}
+
+/// Renormalize decoded normal vectors (alpha untouched) and encode them back to bytes.
+fn encode_normals(vectors: &[f32]) -> Vec<u8> {
+    vectors
+        .chunks_exact(4)
+        .flat_map(|p| {
+            let len = (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt();
+            let n = if len > 1e-6 { [p[0] / len, p[1] / len, p[2] / len] } else { [0.0, 0.0, 1.0] };
+            [encode_normal(n[0]), encode_normal(n[1]), encode_normal(n[2]), encode_normal(p[3])]
+        })
+        .collect()
+}
+
+fn transpose<T: Copy + Default>(data: &[T], w: usize, h: usize) -> Vec<T> {
+    let mut out = vec![T::default(); w * h * 4];
+    for y in 0..h {
+        for x in 0..w {
+            let src = (y * w + x) * 4;
+            let dst = (x * h + y) * 4;
+            out[dst..dst + 4].copy_from_slice(&data[src..src + 4]);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::gray;
+
+    /// Smooth non-periodic pattern: blobs whose periods do not divide the size
+    fn blobs(x: u32, y: u32) -> u8 {
+        let (x, y) = (x as f32, y as f32);
+        let v = (x * 0.21).sin() * (y * 0.17).cos() + 0.5 * (x * 0.05 + y * 0.09).sin();
+        (128.0 + 80.0 * v) as u8
+    }
+
+    #[test]
+    fn quilting_removes_seams_of_non_periodic_texture() {
+        let tex = gray(128, 128, blobs);
+        let options = SeamlessOptions {
+            method: SeamlessMethod::Quilt,
+            ..Default::default()
+        };
+        let (fixed, result) = make_seamless_with_report(&tex, &options).unwrap();
+        assert!(result.improved, "{:?}", result);
+        assert!(result.fixed_edge_difference < result.original_edge_difference);
+        // The interior away from the edges is untouched
+        let i = (64 * 128 + 64) * 4;
+        assert_eq!(fixed.data[i], tex.data[i]);
+    }
+
+    #[test]
+    fn poisson_makes_gradient_periodic() {
+        let tex = gray(64, 64, |x, _| 64 + x as u8 * 2);
+        let options = SeamlessOptions {
+            method: SeamlessMethod::Poisson,
+            ..Default::default()
+        };
+        let fixed = make_seamless(&tex, &options).unwrap();
+        assert!(edge_difference(&fixed) < edge_difference(&tex) / 4.0);
+    }
+
+    #[test]
+    fn material_slots_share_one_plan() {
+        let mut set = MaterialSet::new();
+        set.albedo = Some(gray(64, 64, blobs));
+        set.roughness = Some(gray(64, 64, blobs));
+        set.ao = Some(gray(32, 32, |x, y| blobs(x * 2, y * 2)));
+        let fixed = make_material_seamless(&set, &SeamlessOptions::default()).unwrap();
+        assert_eq!(fixed.albedo.as_ref().unwrap().data, fixed.roughness.as_ref().unwrap().data);
+        let ao = fixed.ao.unwrap();
+        assert_eq!((ao.width, ao.height), (32, 32));
+    }
+
+    #[test]
+    fn normals_stay_unit_length() {
+        let mut set = MaterialSet::new();
+        set.albedo = Some(gray(64, 64, blobs));
+        let mut data = Vec::new();
+        for y in 0..64u32 {
+            for x in 0..64u32 {
+                let (nx, ny) = (blobs(x, y) as f32 / 255.0 - 0.5, blobs(y, x) as f32 / 255.0 - 0.5);
+                let nz = (1.0 - nx * nx - ny * ny).sqrt();
+                data.extend_from_slice(&[encode_normal(nx), encode_normal(ny), encode_normal(nz), 255]);
+            }
+        }
+        set.normal = Some(TextureMap::new(64, 64, data, None));
+        let fixed = make_material_seamless(&set, &SeamlessOptions::default()).unwrap();
+        for p in fixed.normal.unwrap().data.chunks_exact(4) {
+            let v = [decode_normal(p[0]), decode_normal(p[1]), decode_normal(p[2])];
+            let len = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+            assert!((len - 1.0).abs() < 0.02, "length {}", len);
+        }
+    }
+
+    #[test]
+    fn fix_material_folder_writes_all_slots_and_report() {
+        let root = std::env::temp_dir().join("pbr_seamless_folder_test");
+        let _ = fs::remove_dir_all(&root);
+        let folder = root.join("Wood");
+        fs::create_dir_all(&folder).unwrap();
+        let write = |name: &str, f: fn(u32, u32) -> u8| {
+            let mut img = image::RgbaImage::new(64, 64);
+            for (x, y, p) in img.enumerate_pixels_mut() {
+                let v = f(x, y);
+                *p = image::Rgba([v, v, v, 255]);
+            }
+            img.save(folder.join(name)).unwrap();
+        };
+        write("wood_albedo.png", blobs);
+        write("wood_roughness.png", |x, y| blobs(y, x));
+
+        let out = root.join("Fixed");
+        let options = SeamlessOptions {
+            reference_slot: Some(TextureSlot::Roughness),
+            ..Default::default()
+        };
+        let report = fix_material_folder(&folder, &out, &options).unwrap();
+        assert_eq!(report.reference_slot, TextureSlot::Roughness);
+        assert_eq!(report.slots.len(), 2);
+        assert!(report.slots.iter().all(|s| s.result.improved));
+        assert!(out.join("wood_albedo.png").exists());
+        assert!(out.join("wood_roughness.png").exists());
+        assert!(out.join(FIX_REPORT_FILE).exists());
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn min_cut_follows_cheap_path_and_closes() {
+        // Zero-cost diagonal from column 1 to 3 and back on a 5-wide band
+        let lines = 6;
+        let span = 5;
+        let cheap = [1, 2, 3, 3, 2, 1];
+        let cost: Vec<f32> = (0..lines)
+            .flat_map(|l| (0..span).map(move |i| if i == cheap[l] { 0.0 } else { 1.0 }))
+            .collect();
+        let path = min_cut(&cost, lines, span);
+        assert_eq!(path, cheap);
+    }
+}
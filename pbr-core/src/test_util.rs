@@ -0,0 +1,36 @@
+//! Texture fixtures shared by the unit tests.
+
+use crate::material::TextureMap;
+
+/// Opaque gray texture with the value of `f(x, y)` at each pixel
+pub(crate) fn gray(w: u32, h: u32, f: impl Fn(u32, u32) -> u8) -> TextureMap {
+    let data = (0..w * h).flat_map(|i| {
+        let v = f(i % w, i / w);
+        [v, v, v, 255]
+    });
+    TextureMap::new(w, h, data.collect(), None)
+}
+
+/// Texture with every byte, alpha included, set to `value`
+pub(crate) fn uniform(w: u32, h: u32, value: u8) -> TextureMap {
+    TextureMap::new(w, h, vec![value; (w as usize) * (h as usize) * 4], None)
+}
+
+/// Deterministic pseudo-random noise in 0..=255
+pub(crate) fn noise(x: u32, y: u32) -> u8 {
+    let mut n = x.wrapping_mul(374_761_393) ^ y.wrapping_mul(668_265_263);
+    n = (n ^ (n >> 13)).wrapping_mul(1_274_126_177);
+    (n >> 24) as u8
+}
+
+/// Opaque gray white noise from a linear congruential generator seeded with `seed`
+pub(crate) fn noise_texture(w: u32, h: u32, seed: u32) -> TextureMap {
+    let mut state = seed;
+    let mut data = Vec::with_capacity((w * h * 4) as usize);
+    for _ in 0..w * h {
+        state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        let v = (state >> 16) as u8;
+        data.extend_from_slice(&[v, v, v, 255]);
+    }
+    TextureMap::new(w, h, data, None)
+}
@@ -0,0 +1,772 @@
+//! Texel density validation against mesh UV data.
+//!
+//! Loads UV-mapped geometry from OBJ, glTF or GLB files, compares UV area with
+//! world-space surface area, and checks that the texture resolution yields a
+//! texel density (pixels per centimeter) inside a project target range.
+//! Mesh units are assumed to be meters (glTF convention); node transforms are
+//! not applied. Use [`TexelDensityTarget::cm_per_unit`] for other unit scales.
+//!
+//! Besides [`TexelDensityRule`], a mesh in the material folder lets
+//! [`TextureResolutionRule`](crate::validation::TextureResolutionRule) accept
+//! textures above 4K that the mesh needs and makes
+//! [`suggest_optimizations`](crate::ai::suggest_optimizations) recommend the
+//! resolution that hits the target.
+
+use crate::material::MaterialSet;
+use crate::optimization::TargetResolution;
+use crate::validation::{Issue, Severity, ValidationRule};
+use crate::Result;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Mesh file extensions recognized for texel density checks
+pub const MESH_EXTENSIONS: &[&str] = &["obj", "gltf", "glb"];
+
+/// Triangles smaller than this (in mesh units² or UV units²) are ignored
+const DEGENERATE_AREA: f64 = 1e-12;
+
+/// UV coverage and surface area of a mesh.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MeshUvStats {
+    /// Source mesh file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<PathBuf>,
+    /// Triangles with non-degenerate UV and world area
+    pub triangle_count: usize,
+    /// World-space surface area (mesh units²)
+    pub surface_area: f64,
+    /// UV-space area (1.0 = the full 0-1 texture square once)
+    pub uv_area: f64,
+    /// Area-weighted 5th / 50th / 95th percentile of UV units per mesh unit
+    pub ratio_p05: f64,
+    pub ratio_median: f64,
+    pub ratio_p95: f64,
+}
+
+impl MeshUvStats {
+    /// Load a mesh (OBJ, glTF or GLB by extension) and compute UV statistics.
+    pub fn load(path: &Path) -> Result<Self> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+        let triangles = match ext.as_str() {
+            "obj" => parse_obj(&std::fs::read_to_string(path)?)?,
+            "gltf" | "glb" => parse_gltf(path)?,
+            _ => {
+                return Err(crate::Error::Other(format!(
+                    "Unsupported mesh format: {} (expected .obj, .gltf or .glb)",
+                    path.display()
+                )))
+            }
+        };
+        let mut stats = Self::from_triangles(&triangles);
+        stats.source = Some(path.to_path_buf());
+        Ok(stats)
+    }
+
+    /// First mesh file (sorted by name) directly inside `folder`, if any.
+    pub fn find_in_folder(folder: &Path) -> Option<PathBuf> {
+        let mut meshes: Vec<PathBuf> = std::fs::read_dir(folder)
+            .ok()?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.is_file()
+                    && p.extension()
+                        .and_then(|e| e.to_str())
+                        .is_some_and(|e| MESH_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+            })
+            .collect();
+        meshes.sort();
+        meshes.into_iter().next()
+    }
+
+    /// Files other than `path` that the mesh reads: external glTF buffers
+    /// (`.bin`), resolved against the mesh folder. Data URIs, GLB chunks and
+    /// OBJ files have none.
+    pub fn dependencies(path: &Path) -> Result<Vec<PathBuf>> {
+        let is_gltf = path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("gltf"));
+        if !is_gltf {
+            return Ok(Vec::new());
+        }
+        let doc: Gltf = serde_json::from_slice(&std::fs::read(path)?)?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        Ok(doc
+            .buffers
+            .iter()
+            .filter_map(|b| b.uri.as_deref())
+            .filter(|uri| !uri.starts_with("data:"))
+            .map(|uri| base.join(uri))
+            .collect())
+    }
+
+    /// Compute statistics from triangles of (position, uv) corners.
+    pub fn from_triangles(triangles: &[[Vertex; 3]]) -> Self {
+        // (ratio, world area) per usable triangle
+        let mut samples: Vec<(f64, f64)> = Vec::new();
+        let mut surface_area = 0.0;
+        let mut uv_area = 0.0;
+        for tri in triangles {
+            let world = triangle_area_3d(tri[0].position, tri[1].position, tri[2].position);
+            let uv = triangle_area_2d(tri[0].uv, tri[1].uv, tri[2].uv);
+            if world < DEGENERATE_AREA || uv < DEGENERATE_AREA {
+                continue;
+            }
+            surface_area += world;
+            uv_area += uv;
+            samples.push(((uv / world).sqrt(), world));
+        }
+        samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let percentile = |p: f64| -> f64 {
+            let mut acc = 0.0;
+            for &(ratio, area) in &samples {
+                acc += area;
+                if acc >= p * surface_area {
+                    return ratio;
+                }
+            }
+            samples.last().map(|s| s.0).unwrap_or(0.0)
+        };
+
+        Self {
+            source: None,
+            triangle_count: samples.len(),
+            surface_area,
+            uv_area,
+            ratio_p05: percentile(0.05),
+            ratio_median: percentile(0.5),
+            ratio_p95: percentile(0.95),
+        }
+    }
+
+    /// Overall UV units per mesh unit (area-weighted mean over the mesh).
+    pub fn uv_ratio(&self) -> f64 {
+        if self.surface_area > 0.0 {
+            (self.uv_area / self.surface_area).sqrt()
+        } else {
+            0.0
+        }
+    }
+}
+
+/// A triangle corner: world position and UV coordinate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex {
+    pub position: [f64; 3],
+    pub uv: [f64; 2],
+}
+
+/// Project texel density target.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TexelDensityTarget {
+    /// Target pixels per centimeter (e.g. 10.24 = 1024 px per meter)
+    #[serde(default = "default_px_per_cm")]
+    pub px_per_cm: f64,
+    /// Allowed relative deviation (0.25 = ±25%)
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64,
+    /// Centimeters per mesh unit (100 for meters)
+    #[serde(default = "default_cm_per_unit")]
+    pub cm_per_unit: f64,
+}
+
+fn default_px_per_cm() -> f64 {
+    10.24
+}
+fn default_tolerance() -> f64 {
+    0.25
+}
+fn default_cm_per_unit() -> f64 {
+    100.0
+}
+
+impl Default for TexelDensityTarget {
+    fn default() -> Self {
+        Self {
+            px_per_cm: default_px_per_cm(),
+            tolerance: default_tolerance(),
+            cm_per_unit: default_cm_per_unit(),
+        }
+    }
+}
+
+impl TexelDensityTarget {
+    pub fn min_px_per_cm(&self) -> f64 {
+        self.px_per_cm * (1.0 - self.tolerance).max(0.0)
+    }
+
+    pub fn max_px_per_cm(&self) -> f64 {
+        self.px_per_cm * (1.0 + self.tolerance)
+    }
+}
+
+/// Texel density relative to the target range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DensityStatus {
+    UnderDense,
+    WithinRange,
+    OverDense,
+}
+
+/// Texel density of a material on a mesh.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TexelDensityReport {
+    pub mesh: MeshUvStats,
+    pub target: TexelDensityTarget,
+    pub texture_width: u32,
+    pub texture_height: u32,
+    /// Overall density (px/cm)
+    pub px_per_cm: f64,
+    /// Area-weighted 5th / 95th percentile density across triangles (px/cm)
+    pub px_per_cm_p05: f64,
+    pub px_per_cm_p95: f64,
+    pub status: DensityStatus,
+    /// Longest edge that would hit the target density
+    pub ideal_max_dimension: u32,
+    /// Nearest power-of-two preset for the ideal dimension
+    pub recommended_resolution: String,
+}
+
+/// Compute texel density for `set` (largest texture) on `mesh`.
+/// Returns `None` when the set has no textures or the mesh has no usable UVs.
+pub fn analyze_texel_density(
+    set: &MaterialSet,
+    mesh: &MeshUvStats,
+    target: &TexelDensityTarget,
+) -> Option<TexelDensityReport> {
+    let (w, h) = [&set.albedo, &set.normal, &set.roughness, &set.metallic, &set.ao, &set.height]
+        .into_iter()
+        .flatten()
+        .map(|t| (t.width, t.height))
+        .max_by_key(|&(w, h)| w.max(h))?;
+    if mesh.triangle_count == 0 || target.cm_per_unit <= 0.0 || w == 0 || h == 0 {
+        return None;
+    }
+
+    let texture_px = ((w as f64) * (h as f64)).sqrt();
+    let to_density = |ratio: f64| ratio * texture_px / target.cm_per_unit;
+    let px_per_cm = to_density(mesh.uv_ratio());
+    let status = if px_per_cm < target.min_px_per_cm() {
+        DensityStatus::UnderDense
+    } else if px_per_cm > target.max_px_per_cm() {
+        DensityStatus::OverDense
+    } else {
+        DensityStatus::WithinRange
+    };
+
+    let max_dim = w.max(h) as f64;
+    let ideal = (max_dim * target.px_per_cm / px_per_cm).round().max(1.0) as u32;
+
+    Some(TexelDensityReport {
+        mesh: mesh.clone(),
+        target: target.clone(),
+        texture_width: w,
+        texture_height: h,
+        px_per_cm,
+        px_per_cm_p05: to_density(mesh.ratio_p05),
+        px_per_cm_p95: to_density(mesh.ratio_p95),
+        status,
+        ideal_max_dimension: ideal,
+        recommended_resolution: recommended_resolution(ideal).label(),
+    })
+}
+
+/// Mesh for `set`: the first OBJ/glTF/GLB in the folder of its textures.
+pub fn material_mesh(set: &MaterialSet) -> Option<MeshUvStats> {
+    let folder = [&set.albedo, &set.normal, &set.roughness, &set.metallic, &set.ao, &set.height]
+        .into_iter()
+        .flatten()
+        .find_map(|t| t.path.as_ref()?.parent().map(Path::to_path_buf))?;
+    MeshUvStats::load(&MeshUvStats::find_in_folder(&folder)?).ok()
+}
+
+/// Texel density of `set` on the mesh in its folder, if there is one.
+pub fn material_texel_density(set: &MaterialSet, target: &TexelDensityTarget) -> Option<TexelDensityReport> {
+    analyze_texel_density(set, &material_mesh(set)?, target)
+}
+
+/// Nearest power-of-two preset (by ratio) for a longest-edge size, 128 to 8192.
+pub fn recommended_resolution(max_dimension: u32) -> TargetResolution {
+    let exp = (max_dimension.max(1) as f64).log2().round().clamp(7.0, 13.0) as u32;
+    match 1u32 << exp {
+        4096 => TargetResolution::Res4K,
+        2048 => TargetResolution::Res2K,
+        1024 => TargetResolution::Res1K,
+        512 => TargetResolution::Res512,
+        256 => TargetResolution::Res256,
+        128 => TargetResolution::Res128,
+        d => TargetResolution::Custom(d),
+    }
+}
+
+/// Rule: texture resolution must give texel density within the target range on the mesh.
+/// Without an explicit mesh, the first OBJ/glTF/GLB in the material folder is used.
+pub struct TexelDensityRule {
+    pub target: TexelDensityTarget,
+    pub mesh: Option<MeshUvStats>,
+}
+
+impl TexelDensityRule {
+    pub fn new(target: TexelDensityTarget, mesh: Option<MeshUvStats>) -> Self {
+        Self { target, mesh }
+    }
+
+    fn mesh_for(&self, set: &MaterialSet) -> Option<MeshUvStats> {
+        self.mesh.clone().or_else(|| material_mesh(set))
+    }
+}
+
+impl ValidationRule for TexelDensityRule {
+    fn id(&self) -> &str {
+        "texel_density"
+    }
+
+    fn description(&self) -> &str {
+        "Texture resolution should give texel density within the project target on the mesh"
+    }
+
+    fn check(&self, set: &MaterialSet) -> Option<Issue> {
+        let mesh = self.mesh_for(set)?;
+        let report = analyze_texel_density(set, &mesh, &self.target)?;
+        match report.status {
+            DensityStatus::WithinRange => None,
+            DensityStatus::UnderDense => Some(Issue::new(
+                self.id(),
+                Severity::Major,
+                format!(
+                    "Under-dense: {:.2} px/cm (target {:.2} ±{:.0}%). Use a {} texture or reduce UV tiling.",
+                    report.px_per_cm,
+                    self.target.px_per_cm,
+                    self.target.tolerance * 100.0,
+                    report.recommended_resolution
+                ),
+            )),
+            DensityStatus::OverDense => Some(Issue::new(
+                self.id(),
+                Severity::Minor,
+                format!(
+                    "Over-dense: {:.2} px/cm (target {:.2} ±{:.0}%). Downscale to {} to save memory.",
+                    report.px_per_cm,
+                    self.target.px_per_cm,
+                    self.target.tolerance * 100.0,
+                    report.recommended_resolution
+                ),
+            )),
+        }
+    }
+
+    fn fingerprint(&self) -> Option<String> {
+        // Auto-discovered meshes are part of the analysis cache's content hash
+        let mesh = match &self.mesh {
+            Some(m) => serde_json::to_string(m).ok()?,
+            None => "auto".to_string(),
+        };
+        Some(format!("{}:{}:{}", self.id(), serde_json::to_string(&self.target).ok()?, mesh))
+    }
+}
+
+fn triangle_area_3d(a: [f64; 3], b: [f64; 3], c: [f64; 3]) -> f64 {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let cross = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    0.5 * (cross[0] * cross[0] + cross[1] * cross[1] + cross[2] * cross[2]).sqrt()
+}
+
+fn triangle_area_2d(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+    0.5 * ((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])).abs()
+}
+
+/// Parse `v`, `vt` and `f` records; polygons are fan-triangulated and faces
+/// without texture coordinates are skipped.
+fn parse_obj(source: &str) -> Result<Vec<[Vertex; 3]>> {
+    let mut positions: Vec<[f64; 3]> = Vec::new();
+    let mut uvs: Vec<[f64; 2]> = Vec::new();
+    let mut triangles = Vec::new();
+
+    let bad = |line_no: usize| crate::Error::Other(format!("Invalid OBJ data on line {}", line_no + 1));
+    // 1-based (or negative, relative) OBJ index into a list of `len` items
+    let resolve = |idx: &str, len: usize| -> Option<usize> {
+        let i: i64 = idx.parse().ok()?;
+        let i = if i < 0 { len as i64 + i } else { i - 1 };
+        (0..len as i64).contains(&i).then_some(i as usize)
+    };
+
+    for (line_no, line) in source.lines().enumerate() {
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("v") => {
+                let v: Vec<f64> = parts.take(3).map(str::parse).collect::<std::result::Result<_, _>>().map_err(|_| bad(line_no))?;
+                if v.len() != 3 {
+                    return Err(bad(line_no));
+                }
+                positions.push([v[0], v[1], v[2]]);
+            }
+            Some("vt") => {
+                let t: Vec<f64> = parts.take(2).map(str::parse).collect::<std::result::Result<_, _>>().map_err(|_| bad(line_no))?;
+                if t.is_empty() {
+                    return Err(bad(line_no));
+                }
+                uvs.push([t[0], t.get(1).copied().unwrap_or(0.0)]);
+            }
+            Some("f") => {
+                let corners: Option<Vec<Vertex>> = parts
+                    .map(|c| {
+                        let mut refs = c.split('/');
+                        let p = resolve(refs.next()?, positions.len())?;
+                        let t = resolve(refs.next()?, uvs.len())?;
+                        Some(Vertex { position: positions[p], uv: uvs[t] })
+                    })
+                    .collect();
+                if let Some(corners) = corners {
+                    for i in 1..corners.len().saturating_sub(1) {
+                        triangles.push([corners[0], corners[i], corners[i + 1]]);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(triangles)
+}
+
+#[derive(Deserialize)]
+struct Gltf {
+    #[serde(default)]
+    meshes: Vec<GltfMesh>,
+    #[serde(default)]
+    accessors: Vec<GltfAccessor>,
+    #[serde(default, rename = "bufferViews")]
+    buffer_views: Vec<GltfBufferView>,
+    #[serde(default)]
+    buffers: Vec<GltfBuffer>,
+}
+
+#[derive(Deserialize)]
+struct GltfMesh {
+    primitives: Vec<GltfPrimitive>,
+}
+
+#[derive(Deserialize)]
+struct GltfPrimitive {
+    attributes: std::collections::HashMap<String, usize>,
+    indices: Option<usize>,
+    #[serde(default = "default_gltf_mode")]
+    mode: u32,
+}
+
+fn default_gltf_mode() -> u32 {
+    4 // TRIANGLES
+}
+
+#[derive(Deserialize)]
+struct GltfAccessor {
+    #[serde(rename = "bufferView")]
+    buffer_view: Option<usize>,
+    #[serde(default, rename = "byteOffset")]
+    byte_offset: usize,
+    #[serde(rename = "componentType")]
+    component_type: u32,
+    #[serde(default)]
+    normalized: bool,
+    count: usize,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Deserialize)]
+struct GltfBufferView {
+    buffer: usize,
+    #[serde(default, rename = "byteOffset")]
+    byte_offset: usize,
+    #[serde(rename = "byteStride")]
+    byte_stride: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct GltfBuffer {
+    uri: Option<String>,
+}
+
+const GLB_MAGIC: u32 = 0x4654_6C67; // "glTF"
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A;
+const GLB_CHUNK_BIN: u32 = 0x004E_4942;
+
+fn gltf_err(msg: &str) -> crate::Error {
+    crate::Error::Other(format!("Invalid glTF: {}", msg))
+}
+
+/// Parse triangle primitives (POSITION + TEXCOORD_0) from a .gltf or .glb file.
+fn parse_gltf(path: &Path) -> Result<Vec<[Vertex; 3]>> {
+    let bytes = std::fs::read(path)?;
+    let (json, glb_bin) = if bytes.len() >= 12 && u32_le(&bytes, 0) == GLB_MAGIC {
+        split_glb(&bytes)?
+    } else {
+        (bytes.as_slice(), None)
+    };
+    let doc: Gltf = serde_json::from_slice(json)?;
+
+    let base = path.parent().unwrap_or(Path::new("."));
+    let buffers: Vec<Vec<u8>> = doc
+        .buffers
+        .iter()
+        .map(|b| match &b.uri {
+            None => glb_bin.map(<[u8]>::to_vec).ok_or_else(|| gltf_err("buffer without uri or GLB chunk")),
+            Some(uri) => match uri.strip_prefix("data:") {
+                Some(data) => {
+                    let encoded = data.split_once(";base64,").map(|(_, e)| e).ok_or_else(|| gltf_err("unsupported data URI"))?;
+                    base64::engine::general_purpose::STANDARD
+                        .decode(encoded)
+                        .map_err(|e| gltf_err(&e.to_string()))
+                }
+                None => Ok(std::fs::read(base.join(uri))?),
+            },
+        })
+        .collect::<Result<_>>()?;
+
+    let mut triangles = Vec::new();
+    for prim in doc.meshes.iter().flat_map(|m| &m.primitives) {
+        if prim.mode != 4 {
+            continue;
+        }
+        let (Some(&pos), Some(&uv)) = (prim.attributes.get("POSITION"), prim.attributes.get("TEXCOORD_0")) else {
+            continue;
+        };
+        let positions = read_accessor(&doc, &buffers, pos)?;
+        let uvs = read_accessor(&doc, &buffers, uv)?;
+        let indices: Vec<usize> = match prim.indices {
+            Some(i) => read_accessor(&doc, &buffers, i)?.iter().map(|v| v[0] as usize).collect(),
+            None => (0..positions.len()).collect(),
+        };
+        for tri in indices.chunks_exact(3) {
+            let corner = |i: usize| -> Option<Vertex> {
+                let p = positions.get(i)?;
+                let t = uvs.get(i)?;
+                Some(Vertex {
+                    position: [p[0], *p.get(1)?, *p.get(2)?],
+                    uv: [t[0], *t.get(1)?],
+                })
+            };
+            if let (Some(a), Some(b), Some(c)) = (corner(tri[0]), corner(tri[1]), corner(tri[2])) {
+                triangles.push([a, b, c]);
+            }
+        }
+    }
+    Ok(triangles)
+}
+
+fn u32_le(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+/// Split a GLB container into its JSON chunk and optional BIN chunk.
+fn split_glb(bytes: &[u8]) -> Result<(&[u8], Option<&[u8]>)> {
+    let mut json = None;
+    let mut bin = None;
+    let mut at = 12;
+    while at + 8 <= bytes.len() {
+        let len = u32_le(bytes, at) as usize;
+        let kind = u32_le(bytes, at + 4);
+        let chunk = bytes.get(at + 8..at + 8 + len).ok_or_else(|| gltf_err("truncated GLB chunk"))?;
+        match kind {
+            GLB_CHUNK_JSON => json = Some(chunk),
+            GLB_CHUNK_BIN => bin = Some(chunk),
+            _ => {}
+        }
+        at += 8 + len;
+    }
+    Ok((json.ok_or_else(|| gltf_err("GLB without JSON chunk"))?, bin))
+}
+
+/// Read an accessor as f64 components per element (normalized integers mapped to 0-1).
+fn read_accessor(doc: &Gltf, buffers: &[Vec<u8>], index: usize) -> Result<Vec<Vec<f64>>> {
+    let acc = doc.accessors.get(index).ok_or_else(|| gltf_err("accessor index out of range"))?;
+    let components = match acc.kind.as_str() {
+        "SCALAR" => 1,
+        "VEC2" => 2,
+        "VEC3" => 3,
+        "VEC4" => 4,
+        _ => return Err(gltf_err("unsupported accessor type")),
+    };
+    let size = match acc.component_type {
+        5120 | 5121 => 1,
+        5122 | 5123 => 2,
+        5125 | 5126 => 4,
+        _ => return Err(gltf_err("unsupported component type")),
+    };
+    let Some(view_index) = acc.buffer_view else {
+        // Sparse-only or zero-initialized accessors
+        return Ok(vec![vec![0.0; components]; acc.count]);
+    };
+    let view = doc.buffer_views.get(view_index).ok_or_else(|| gltf_err("bufferView index out of range"))?;
+    let data = buffers.get(view.buffer).ok_or_else(|| gltf_err("buffer index out of range"))?;
+    let stride = view.byte_stride.unwrap_or(components * size);
+    let start = view.byte_offset + acc.byte_offset;
+
+    (0..acc.count)
+        .map(|i| {
+            (0..components)
+                .map(|c| {
+                    let at = start + i * stride + c * size;
+                    let raw = data.get(at..at + size).ok_or_else(|| gltf_err("accessor exceeds buffer"))?;
+                    let value = match acc.component_type {
+                        5126 => f32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64,
+                        5125 => u32_le(raw, 0) as f64,
+                        5123 => {
+                            let v = u16::from_le_bytes([raw[0], raw[1]]) as f64;
+                            if acc.normalized { v / 65535.0 } else { v }
+                        }
+                        5122 => {
+                            let v = i16::from_le_bytes([raw[0], raw[1]]) as f64;
+                            if acc.normalized { (v / 32767.0).max(-1.0) } else { v }
+                        }
+                        5121 => {
+                            let v = raw[0] as f64;
+                            if acc.normalized { v / 255.0 } else { v }
+                        }
+                        _ => {
+                            let v = raw[0] as i8 as f64;
+                            if acc.normalized { (v / 127.0).max(-1.0) } else { v }
+                        }
+                    };
+                    Ok(value)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::TextureMap;
+
+    /// 1m x 1m quad mapped to the full 0-1 UV square
+    const QUAD_OBJ: &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\nf 1/1 2/2 3/3 4/4\n";
+
+    fn set_with_albedo(size: u32) -> MaterialSet {
+        let mut set = MaterialSet::new();
+        set.add_albedo(TextureMap::new(size, size, vec![128; (size * size * 4) as usize], None));
+        set
+    }
+
+    #[test]
+    fn obj_quad_areas() {
+        let stats = MeshUvStats::from_triangles(&parse_obj(QUAD_OBJ).unwrap());
+        assert_eq!(stats.triangle_count, 2);
+        assert!((stats.surface_area - 1.0).abs() < 1e-9);
+        assert!((stats.uv_area - 1.0).abs() < 1e-9);
+        assert!((stats.uv_ratio() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn density_status_and_recommendation() {
+        let mesh = MeshUvStats::from_triangles(&parse_obj(QUAD_OBJ).unwrap());
+        let target = TexelDensityTarget::default();
+
+        // 1024 px over 100 cm = 10.24 px/cm
+        let ok = analyze_texel_density(&set_with_albedo(1024), &mesh, &target).unwrap();
+        assert_eq!(ok.status, DensityStatus::WithinRange);
+        assert!((ok.px_per_cm - 10.24).abs() < 1e-9);
+
+        let over = analyze_texel_density(&set_with_albedo(4096), &mesh, &target).unwrap();
+        assert_eq!(over.status, DensityStatus::OverDense);
+        assert_eq!(over.recommended_resolution, "1K");
+
+        let rule = TexelDensityRule::new(target, Some(mesh));
+        let issue = rule.check(&set_with_albedo(256)).unwrap();
+        assert_eq!(issue.severity, Severity::Major);
+        assert!(issue.message.contains("Under-dense"));
+    }
+
+    #[test]
+    fn gltf_with_embedded_buffer() {
+        // One triangle: positions (0,0,0) (2,0,0) (0,2,0), UVs (0,0) (1,0) (0,1)
+        let mut bin = Vec::new();
+        for v in [0.0f32, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0] {
+            bin.extend_from_slice(&v.to_le_bytes());
+        }
+        let uri = format!(
+            "data:application/octet-stream;base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(&bin)
+        );
+        let gltf = serde_json::json!({
+            "buffers": [{ "uri": uri, "byteLength": bin.len() }],
+            "bufferViews": [{ "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+                            { "buffer": 0, "byteOffset": 36, "byteLength": 24 }],
+            "accessors": [{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" },
+                          { "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC2" }],
+            "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0, "TEXCOORD_0": 1 } }] }]
+        });
+        let dir = std::env::temp_dir().join("pbr_texel_density_gltf");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mesh.gltf");
+        std::fs::write(&path, gltf.to_string()).unwrap();
+
+        let stats = MeshUvStats::load(&path).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(stats.triangle_count, 1);
+        assert!((stats.surface_area - 2.0).abs() < 1e-9);
+        assert!((stats.uv_area - 0.5).abs() < 1e-9);
+        assert!((stats.uv_ratio() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn folder_mesh_drives_resolution_rule_and_suggestions() {
+        use crate::validation::TextureResolutionRule;
+        let dir = std::env::temp_dir().join("pbr_texel_density_consumers");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let textured = |size: u32| {
+            let mut set = MaterialSet::new();
+            let path = dir.join("albedo.png");
+            set.add_albedo(TextureMap::new(size, size, Vec::new(), Some(path)));
+            set
+        };
+        let rule = TextureResolutionRule::default();
+        // No mesh: plain 4K warning
+        assert!(rule.check(&textured(8192)).is_some());
+
+        // 8 m quad: 8192 px gives 10.24 px/cm, exactly the target
+        let large_quad = QUAD_OBJ.replace("v 1 0 0\nv 1 1 0\nv 0 1 0", "v 8 0 0\nv 8 8 0\nv 0 8 0");
+        std::fs::write(dir.join("wall.obj"), &large_quad).unwrap();
+        assert!(rule.check(&textured(8192)).is_none());
+        let suggestions = crate::ai::suggest_optimizations(&textured(8192));
+        assert!(suggestions.iter().all(|s| s.category != "resolution"));
+
+        // The plain quad needs only 1K
+        std::fs::write(dir.join("wall.obj"), QUAD_OBJ).unwrap();
+        let issue = rule.check(&textured(8192)).unwrap();
+        assert!(issue.message.contains("1K"), "{}", issue.message);
+        let suggestions = crate::ai::suggest_optimizations(&textured(8192));
+        assert_eq!(suggestions[0].target_resolution.as_deref(), Some("1K"));
+
+        // ...unless the configured target asks for 8K
+        let target = TexelDensityTarget { px_per_cm: 81.92, ..Default::default() };
+        assert!(TextureResolutionRule::new(target.clone(), None).check(&textured(8192)).is_none());
+        let suggestions = crate::ai::suggest_optimizations_with_target(&textured(8192), &target);
+        assert!(suggestions.iter().all(|s| s.category != "resolution"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn gltf_dependencies_list_external_buffers() {
+        let dir = std::env::temp_dir().join("pbr_texel_density_deps");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mesh.gltf");
+        let gltf = serde_json::json!({ "buffers": [{ "uri": "mesh.bin" }, { "uri": "data:application/octet-stream;base64,AAAA" }] });
+        std::fs::write(&path, gltf.to_string()).unwrap();
+        let deps = MeshUvStats::dependencies(&path).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(deps, vec![dir.join("mesh.bin")]);
+    }
+}
@@ -0,0 +1,386 @@
+//! Similar texture search: query a material library by example.
+//!
+//! Every texture of every library material is indexed by its perceptual
+//! hashes ([`TextureHashes`]) and a feature embedding built from
+//! [`crate::ai::extract_features`] (color, contrast, edge density). The index
+//! is stored in `<root>/.pbr-studio/search-index.json` and updated
+//! incrementally: a material is re-indexed only when the content hash of its
+//! files changes.
+//!
+//! A query (one image or a whole material) is scored against every indexed
+//! texture, and the best score per material ranks the results. Scores blend
+//! hash similarity (layout and structure, including rotated/mirrored/cropped
+//! variants) with embedding similarity (overall look), so "a rusty metal like
+//! this" finds materials with a similar palette and roughness even when the
+//! pixels differ.
+
+use crate::analysis::slot_textures;
+use crate::analysis_cache::{hash_material_files, read_json, write_json};
+use crate::ai::extract_features;
+use crate::image_hash::TextureHashes;
+use crate::image_loading::TextureSlot;
+use crate::material::{MaterialSet, TextureMap};
+use crate::Result;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Index file relative to the library root
+pub const SEARCH_INDEX_FILE: &str = ".pbr-studio/search-index.json";
+
+/// Bumped whenever the hashes or the embedding change
+const INDEX_FORMAT: &str = "pbr-search-v1";
+
+/// Weight of perceptual hash similarity; the embedding gets the rest
+const HASH_WEIGHT: f32 = 0.4;
+
+/// Embedding distance at which feature similarity reaches 0
+const FEATURE_SCALE: f32 = 1.0;
+
+/// One indexed texture.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedTexture {
+    pub slot: String,
+    pub path: String,
+    pub hashes: TextureHashes,
+    /// Normalized [`crate::ai::TextureFeatures`]
+    pub embedding: Vec<f32>,
+}
+
+/// One indexed material folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedMaterial {
+    pub folder: String,
+    pub name: Option<String>,
+    /// Hash over the folder's file hashes; the entry is rebuilt when it changes
+    pub content_hash: String,
+    pub textures: Vec<IndexedTexture>,
+}
+
+/// Persistent search index of a material library.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchIndex {
+    format: String,
+    pub materials: Vec<IndexedMaterial>,
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self {
+            format: INDEX_FORMAT.to_string(),
+            materials: Vec::new(),
+        }
+    }
+}
+
+/// What [`SearchIndex::update`] did.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IndexUpdate {
+    /// Materials hashed and analyzed
+    pub indexed: usize,
+    /// Materials whose files did not change
+    pub reused: usize,
+    /// Materials dropped (folder gone or failed to load)
+    pub removed: usize,
+}
+
+/// A library material similar to the query.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub folder: String,
+    pub material_name: Option<String>,
+    /// Best-matching texture (for image queries)
+    pub slot: Option<String>,
+    pub path: Option<String>,
+    /// Combined score (0-1)
+    pub similarity: f32,
+    /// Perceptual hash similarity (0-1)
+    pub hash_similarity: f32,
+    /// Feature embedding similarity (0-1)
+    pub feature_similarity: f32,
+}
+
+struct Score {
+    similarity: f32,
+    hash: f32,
+    feature: f32,
+}
+
+impl SearchIndex {
+    /// Load the index of `root`; a missing or outdated index yields an empty one.
+    pub fn load(root: &Path) -> Self {
+        read_json::<SearchIndex>(&root.join(SEARCH_INDEX_FILE))
+            .filter(|index| index.format == INDEX_FORMAT)
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, root: &Path) -> Result<()> {
+        write_json(&root.join(SEARCH_INDEX_FILE), self)
+    }
+
+    /// Load the index of `root`, bring it up to date with `folders` and save it.
+    pub fn open(root: &Path, folders: &[PathBuf]) -> Result<(Self, IndexUpdate)> {
+        let mut index = Self::load(root);
+        let update = index.update(folders);
+        index.save(root)?;
+        Ok((index, update))
+    }
+
+    /// Make the index cover exactly `folders`, re-indexing only changed materials.
+    pub fn update(&mut self, folders: &[PathBuf]) -> IndexUpdate {
+        let mut existing: HashMap<String, IndexedMaterial> =
+            self.materials.drain(..).map(|m| (m.folder.clone(), m)).collect();
+
+        let refreshed: Vec<(IndexedMaterial, bool)> = folders
+            .par_iter()
+            .filter_map(|folder| {
+                let key = folder.display().to_string();
+                let content_hash = content_hash(folder)?;
+                if let Some(entry) = existing.get(&key).filter(|m| m.content_hash == content_hash) {
+                    return Some((entry.clone(), false));
+                }
+                let set = MaterialSet::load_from_folder(folder).ok()?;
+                Some((index_material(key, set.name.clone(), content_hash, &set), true))
+            })
+            .collect();
+
+        let mut update = IndexUpdate::default();
+        for (material, fresh) in refreshed {
+            if fresh {
+                update.indexed += 1;
+            } else {
+                update.reused += 1;
+            }
+            existing.remove(&material.folder);
+            self.materials.push(material);
+        }
+        update.removed = existing.len();
+        self.materials.sort_by(|a, b| a.folder.cmp(&b.folder));
+        update
+    }
+
+    pub fn texture_count(&self) -> usize {
+        self.materials.iter().map(|m| m.textures.len()).sum()
+    }
+
+    /// Top `k` materials with a texture similar to `query`, optionally only
+    /// comparing against textures of `slot`.
+    pub fn search_texture(&self, query: &TextureMap, slot: Option<TextureSlot>, k: usize) -> Vec<SearchHit> {
+        let hashes = query.hashes();
+        let embedding = embedding(query);
+        let slot = slot.map(slot_name);
+        let mut hits: Vec<SearchHit> = self
+            .materials
+            .par_iter()
+            .filter_map(|material| {
+                material
+                    .textures
+                    .iter()
+                    .filter(|t| slot.is_none_or(|s| t.slot == s))
+                    .map(|t| (t, score(t, hashes, &embedding)))
+                    .max_by(|a, b| a.1.similarity.total_cmp(&b.1.similarity))
+                    .map(|(t, s)| hit(material, Some(t), s))
+            })
+            .collect();
+        top_k(&mut hits, k);
+        hits
+    }
+
+    /// Top `k` materials similar to `query`, comparing slot to slot and averaging
+    /// over the slots both have. Materials made of the query's own files are skipped.
+    pub fn search_material(&self, query: &MaterialSet, k: usize) -> Vec<SearchHit> {
+        let textures: Vec<_> = slot_textures(query)
+            .map(|(slot, t)| (slot, t.path.as_ref().map(|p| p.display().to_string()), t.hashes(), embedding(t)))
+            .collect();
+        let mut hits: Vec<SearchHit> = self
+            .materials
+            .par_iter()
+            .filter(|m| {
+                let own_file = |t: &IndexedTexture| textures.iter().any(|(_, path, _, _)| path.as_deref() == Some(t.path.as_str()));
+                !m.textures.iter().any(own_file)
+            })
+            .filter_map(|material| {
+                let scores: Vec<Score> = textures
+                    .iter()
+                    .filter_map(|(slot, _, hashes, embedding)| {
+                        let t = material.textures.iter().find(|t| t.slot == *slot)?;
+                        Some(score(t, hashes, embedding))
+                    })
+                    .collect();
+                if scores.is_empty() {
+                    return None;
+                }
+                let n = scores.len() as f32;
+                let mean = |f: fn(&Score) -> f32| scores.iter().map(f).sum::<f32>() / n;
+                Some(hit(
+                    material,
+                    None,
+                    Score {
+                        similarity: mean(|s| s.similarity),
+                        hash: mean(|s| s.hash),
+                        feature: mean(|s| s.feature),
+                    },
+                ))
+            })
+            .collect();
+        top_k(&mut hits, k);
+        hits
+    }
+}
+
+fn content_hash(folder: &Path) -> Option<String> {
+    let files = hash_material_files(folder).ok()?;
+    let mut hasher = Sha256::new();
+    for (path, hash) in &files {
+        let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        hasher.update(name.as_bytes());
+        hasher.update([0u8]);
+        hasher.update(hash.as_bytes());
+        hasher.update([0u8]);
+    }
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+fn index_material(folder: String, name: Option<String>, content_hash: String, set: &MaterialSet) -> IndexedMaterial {
+    let textures = slot_textures(set)
+        .map(|(slot, t)| IndexedTexture {
+            slot: slot.to_string(),
+            path: t.path.as_ref().map(|p| p.display().to_string()).unwrap_or_default(),
+            hashes: (*t.hashes()).clone(),
+            embedding: embedding(t),
+        })
+        .collect();
+    IndexedMaterial {
+        folder,
+        name,
+        content_hash,
+        textures,
+    }
+}
+
+/// Feature vector with every component in roughly 0-1.
+fn embedding(map: &TextureMap) -> Vec<f32> {
+    let f = extract_features(map);
+    vec![
+        f.mean_r / 255.0,
+        f.mean_g / 255.0,
+        f.mean_b / 255.0,
+        f.std_r / 128.0,
+        f.std_g / 128.0,
+        f.std_b / 128.0,
+        f.edge_density,
+        f.saturation_mean,
+        f.warm_ratio,
+    ]
+}
+
+fn score(indexed: &IndexedTexture, hashes: &TextureHashes, embedding: &[f32]) -> Score {
+    let hash = (0..indexed.hashes.variants.len())
+        .map(|v| indexed.hashes.similarity(v, hashes))
+        .fold(0.0f32, f32::max)
+        .max(0.0);
+    let distance = indexed
+        .embedding
+        .iter()
+        .zip(embedding)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f32>()
+        .sqrt();
+    let feature = (1.0 - distance / FEATURE_SCALE).max(0.0);
+    Score {
+        similarity: HASH_WEIGHT * hash + (1.0 - HASH_WEIGHT) * feature,
+        hash,
+        feature,
+    }
+}
+
+fn hit(material: &IndexedMaterial, texture: Option<&IndexedTexture>, score: Score) -> SearchHit {
+    SearchHit {
+        folder: material.folder.clone(),
+        material_name: material.name.clone(),
+        slot: texture.map(|t| t.slot.clone()),
+        path: texture.map(|t| t.path.clone()),
+        similarity: score.similarity,
+        hash_similarity: score.hash,
+        feature_similarity: score.feature,
+    }
+}
+
+fn top_k(hits: &mut Vec<SearchHit>, k: usize) {
+    hits.sort_by(|a, b| b.similarity.total_cmp(&a.similarity).then_with(|| a.folder.cmp(&b.folder)));
+    hits.truncate(k);
+}
+
+fn slot_name(slot: TextureSlot) -> &'static str {
+    match slot {
+        TextureSlot::Albedo => "albedo",
+        TextureSlot::Normal => "normal",
+        TextureSlot::Roughness => "roughness",
+        TextureSlot::Metallic => "metallic",
+        TextureSlot::AmbientOcclusion => "ao",
+        TextureSlot::Height => "height",
+        _ => "other",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write_material(dir: &Path, pixel: impl Fn(u32, u32) -> [u8; 4]) {
+        fs::create_dir_all(dir).unwrap();
+        let img = image::RgbaImage::from_fn(32, 32, |x, y| image::Rgba(pixel(x, y)));
+        img.save(dir.join("albedo.png")).unwrap();
+    }
+
+    fn rust(x: u32, y: u32) -> [u8; 4] {
+        let n = ((x * 37 + y * 91) % 23) as u8;
+        [150 + n, 70 + n / 2, 30, 255]
+    }
+
+    #[test]
+    fn finds_similar_material_and_reuses_index() {
+        let root = std::env::temp_dir().join(format!("pbr_search_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let folders = vec![root.join("rusty_metal"), root.join("blue_tiles"), root.join("grass")];
+        write_material(&folders[0], rust);
+        write_material(&folders[1], |x, y| if (x / 8 + y / 8) % 2 == 0 { [40, 60, 200, 255] } else { [230; 4] });
+        write_material(&folders[2], |x, y| [30, 120 + ((x * y) % 60) as u8, 40, 255]);
+
+        let (index, update) = SearchIndex::open(&root, &folders).unwrap();
+        assert_eq!(update.indexed, 3);
+        assert_eq!(index.texture_count(), 3);
+
+        // A mirrored, slightly brighter rust image finds the rusty metal first
+        let query = TextureMap::new(
+            32,
+            32,
+            (0..32 * 32)
+                .flat_map(|i| {
+                    let [r, g, b, a] = rust(31 - i % 32, i / 32);
+                    [r.saturating_add(5), g, b, a]
+                })
+                .collect(),
+            None,
+        );
+        let hits = index.search_texture(&query, Some(TextureSlot::Albedo), 2);
+        assert_eq!(hits.len(), 2);
+        assert!(hits[0].folder.ends_with("rusty_metal"));
+        assert!(hits[0].similarity > hits[1].similarity);
+
+        // Searching by a library material skips the material itself
+        let set = MaterialSet::load_from_folder(&folders[0]).unwrap();
+        let hits = index.search_material(&set, 5);
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|h| !h.folder.ends_with("rusty_metal")));
+
+        // Reopening reuses unchanged materials and drops removed ones
+        let (index, update) = SearchIndex::open(&root, &folders[..2]).unwrap();
+        assert_eq!((update.indexed, update.reused, update.removed), (0, 2, 1));
+        assert_eq!(index.materials.len(), 2);
+        let _ = fs::remove_dir_all(&root);
+    }
+}
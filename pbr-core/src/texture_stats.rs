@@ -0,0 +1,409 @@
+//! Shared per-texture pixel statistics.
+//!
+//! [`TextureStats`] gathers histograms, moments, min/max, luminance, clipping,
+//! gradient edges and the perceptual hash blocks in a single pass over the
+//! pixels. It is memoized on [`TextureMap::stats`] so validation rules, AI
+//! heuristics and advanced analysis all read the same numbers instead of
+//! walking every pixel again.
+
+use crate::material::TextureMap;
+use crate::tiled::RowSource;
+use crate::Result;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Perceptual hash grid size (8x8 = 64 luminance blocks)
+pub const PHASH_SIZE: usize = 8;
+
+/// Neighbor difference (sum of RGB) above which a pixel counts as an edge
+const EDGE_THRESHOLD: f32 = 30.0;
+
+/// Rows per parallel band. Band partials are merged in order so float sums
+/// are deterministic regardless of thread scheduling.
+const BAND_ROWS: usize = 64;
+
+/// Statistics for a single 8-bit channel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelStats {
+    /// 256-bin value histogram
+    pub histogram: Vec<u32>,
+    pub min: u8,
+    pub max: u8,
+    pub mean: f64,
+    /// Sample standard deviation (n - 1)
+    pub stddev: f64,
+    /// Population standard deviation (n)
+    pub population_stddev: f64,
+}
+
+impl ChannelStats {
+    fn from_histogram(histogram: Vec<u32>) -> Self {
+        let n: u64 = histogram.iter().map(|&c| c as u64).sum();
+        if n == 0 {
+            return Self {
+                histogram,
+                min: 0,
+                max: 0,
+                mean: 0.0,
+                stddev: 0.0,
+                population_stddev: 0.0,
+            };
+        }
+        let min = histogram.iter().position(|&c| c > 0).unwrap_or(0) as u8;
+        let max = histogram.iter().rposition(|&c| c > 0).unwrap_or(0) as u8;
+        let sum: u64 = histogram.iter().enumerate().map(|(v, &c)| v as u64 * c as u64).sum();
+        let mean = sum as f64 / n as f64;
+        let sq_dev: f64 = histogram
+            .iter()
+            .enumerate()
+            .map(|(v, &c)| (v as f64 - mean).powi(2) * c as f64)
+            .sum();
+        let stddev = if n > 1 { (sq_dev / (n - 1) as f64).sqrt() } else { 0.0 };
+        Self {
+            histogram,
+            min,
+            max,
+            mean,
+            stddev,
+            population_stddev: (sq_dev / n as f64).sqrt(),
+        }
+    }
+}
+
+/// Pixel statistics for one texture, computed in a single pass.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextureStats {
+    pub width: u32,
+    pub height: u32,
+    /// Pixels visited (width * height, bounded by data length)
+    pub pixel_count: usize,
+    /// R, G, B, A channel statistics
+    pub channels: [ChannelStats; 4],
+    /// 256-bin histogram of rounded Rec.601 luminance
+    pub luminance_histogram: Vec<u32>,
+    /// Rec.601 luminance (0-255 scale)
+    pub luminance_mean: f64,
+    pub luminance_min: f64,
+    pub luminance_max: f64,
+    /// Pixels with any RGB channel at 0 or 255
+    pub clipped_pixels: usize,
+    /// Mean HSV-style saturation (0-1)
+    pub saturation_mean: f64,
+    /// Mean red share of R+G+B (0.33 for black pixels)
+    pub warm_ratio: f64,
+    /// Interior pixels whose right or lower neighbor differs by more than the edge threshold
+    pub edge_pixels: usize,
+    /// Mean wrap-around border difference (top↔bottom, left↔right)
+    pub edge_difference: f64,
+    /// Mean luminance of each perceptual hash block, row-major (empty for 0-size textures)
+    pub block_luminance: Vec<f32>,
+}
+
+/// Per-band accumulator merged in band order.
+struct Partial {
+    histograms: [[u32; 256]; 4],
+    luminance_histogram: [u32; 256],
+    pixels: usize,
+    lum_min: f64,
+    lum_max: f64,
+    clipped: usize,
+    saturation: f64,
+    warm: f64,
+    edges: usize,
+    block_sum: [f64; PHASH_SIZE * PHASH_SIZE],
+    block_count: [usize; PHASH_SIZE * PHASH_SIZE],
+}
+
+impl Partial {
+    fn new() -> Self {
+        Self {
+            histograms: [[0; 256]; 4],
+            luminance_histogram: [0; 256],
+            pixels: 0,
+            lum_min: 255.0,
+            lum_max: 0.0,
+            clipped: 0,
+            saturation: 0.0,
+            warm: 0.0,
+            edges: 0,
+            block_sum: [0.0; PHASH_SIZE * PHASH_SIZE],
+            block_count: [0; PHASH_SIZE * PHASH_SIZE],
+        }
+    }
+
+    fn merge(&mut self, other: &Partial) {
+        for (dst, src) in self.histograms.iter_mut().zip(&other.histograms) {
+            for (d, s) in dst.iter_mut().zip(src) {
+                *d += s;
+            }
+        }
+        for (d, s) in self.luminance_histogram.iter_mut().zip(&other.luminance_histogram) {
+            *d += s;
+        }
+        self.pixels += other.pixels;
+        self.lum_min = self.lum_min.min(other.lum_min);
+        self.lum_max = self.lum_max.max(other.lum_max);
+        self.clipped += other.clipped;
+        self.saturation += other.saturation;
+        self.warm += other.warm;
+        self.edges += other.edges;
+        for (d, s) in self.block_sum.iter_mut().zip(&other.block_sum) {
+            *d += s;
+        }
+        for (d, s) in self.block_count.iter_mut().zip(&other.block_count) {
+            *d += s;
+        }
+    }
+}
+
+/// Map each column (or row) to its perceptual hash block, matching the
+/// `floor(i * size / PHASH_SIZE)` block bounds used for hashing.
+fn block_index(size: usize) -> Vec<Option<usize>> {
+    let mut index = vec![None; size];
+    let block = (size as f32 / PHASH_SIZE as f32).max(1.0);
+    for b in 0..PHASH_SIZE {
+        let start = (b as f32 * block) as usize;
+        let end = ((b as f32 + 1.0) * block).min(size as f32) as usize;
+        for slot in index.iter_mut().take(end).skip(start) {
+            *slot = Some(b);
+        }
+    }
+    index
+}
+
+fn rgb_sum(data: &[u8], i: usize) -> f32 {
+    data[i] as f32 + data[i + 1] as f32 + data[i + 2] as f32
+}
+
+impl Partial {
+    /// Accumulate row `y` of `h` (RGBA8); `below` is row `y + 1` when it exists.
+    fn add_row(&mut self, row: &[u8], below: &[u8], y: usize, h: usize, cols: &[Option<usize>], row_block: Option<usize>) {
+        let w = cols.len();
+        for (x, col_block) in cols.iter().enumerate() {
+            let i = x * 4;
+            if i + 4 > row.len() {
+                break;
+            }
+            let p = &row[i..i + 4];
+            for (c, hist) in self.histograms.iter_mut().enumerate() {
+                hist[p[c] as usize] += 1;
+            }
+            self.pixels += 1;
+
+            let (r, g, b) = (p[0] as f64, p[1] as f64, p[2] as f64);
+            let lum = 0.299 * r + 0.587 * g + 0.114 * b;
+            self.luminance_histogram[(lum.round() as usize).min(255)] += 1;
+            self.lum_min = self.lum_min.min(lum);
+            self.lum_max = self.lum_max.max(lum);
+            if let (Some(bx), Some(by)) = (col_block, row_block) {
+                self.block_sum[by * PHASH_SIZE + bx] += lum;
+                self.block_count[by * PHASH_SIZE + bx] += 1;
+            }
+
+            if p[..3].iter().any(|&v| v == 0 || v == 255) {
+                self.clipped += 1;
+            }
+            let maxc = r.max(g).max(b);
+            let minc = r.min(g).min(b);
+            self.saturation += if maxc > 0.0 { (maxc - minc) / maxc } else { 0.0 };
+            let total = r + g + b;
+            self.warm += if total > 0.0 { r / total } else { 0.33 };
+
+            let interior = x > 0 && x + 1 < w && y > 0 && y + 1 < h;
+            if interior && i + 3 <= below.len() {
+                let c = rgb_sum(row, i);
+                if (c - rgb_sum(row, i + 4)).abs() > EDGE_THRESHOLD || (c - rgb_sum(below, i)).abs() > EDGE_THRESHOLD {
+                    self.edges += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Row `y` of a `w`-wide RGBA8 buffer, cut short (or empty) where `data` ends.
+fn row_slice(data: &[u8], w: usize, y: usize) -> &[u8] {
+    let start = y * w * 4;
+    data.get(start..(start + w * 4).min(data.len())).unwrap_or(&[])
+}
+
+/// Sum of RGB differences between two pixels.
+fn rgb_diff(a: &[u8], b: &[u8]) -> f64 {
+    ((a[0] as i32 - b[0] as i32).abs() + (a[1] as i32 - b[1] as i32).abs() + (a[2] as i32 - b[2] as i32).abs()) as f64
+}
+
+impl TextureStats {
+    /// Compute all statistics in one parallel pass. Prefer [`TextureMap::stats`],
+    /// which memoizes the result.
+    pub fn compute(map: &TextureMap) -> Self {
+        let w = map.width as usize;
+        let h = map.height as usize;
+        let data = &map.data;
+        let cols = block_index(w);
+        let rows = block_index(h);
+
+        let bands: Vec<Partial> = (0..h.div_ceil(BAND_ROWS))
+            .into_par_iter()
+            .map(|band| {
+                let mut acc = Partial::new();
+                let rows_in_band = rows.iter().enumerate().skip(band * BAND_ROWS).take(BAND_ROWS);
+                for (y, &row_block) in rows_in_band {
+                    acc.add_row(row_slice(data, w, y), row_slice(data, w, y + 1), y, h, &cols, row_block);
+                }
+                acc
+            })
+            .collect();
+
+        let mut total = Partial::new();
+        for band in &bands {
+            total.merge(band);
+        }
+        let edge_difference = if w == 0 || h == 0 { 0.0 } else { crate::analysis::edge_difference(map) };
+        Self::finish(&total, map.width, map.height, edge_difference)
+    }
+
+    /// Same statistics as [`TextureStats::compute`], read one row at a time
+    /// so the texture never has to be in memory (see [`crate::tiled`]).
+    pub fn from_rows<S: RowSource>(source: &mut S) -> Result<Self> {
+        let (width, height) = (source.width(), source.height());
+        let w = width as usize;
+        let h = height as usize;
+        let cols = block_index(w);
+        let rows = block_index(h);
+        let mut row = vec![0u8; w * 4];
+        let mut below = vec![0u8; w * 4];
+        if h > 0 {
+            source.read_row(&mut row)?;
+        }
+        let first = row.clone();
+
+        let mut total = Partial::new();
+        let mut band = Partial::new();
+        let (mut border_sum, mut border_count) = (0.0f64, 0usize);
+        for (y, &row_block) in rows.iter().enumerate() {
+            let has_below = y + 1 < h;
+            if has_below {
+                source.read_row(&mut below)?;
+            }
+            band.add_row(&row, if has_below { &below } else { &[] }, y, h, &cols, row_block);
+            if w > 0 {
+                border_sum += rgb_diff(&row[..4], &row[(w - 1) * 4..]);
+                border_count += 1;
+            }
+            if !has_below && w > 0 {
+                for (top, bottom) in first.chunks_exact(4).zip(row.chunks_exact(4)) {
+                    border_sum += rgb_diff(top, bottom);
+                    border_count += 1;
+                }
+            }
+            if (y + 1) % BAND_ROWS == 0 || !has_below {
+                total.merge(&band);
+                band = Partial::new();
+            }
+            std::mem::swap(&mut row, &mut below);
+        }
+        let edge_difference = if border_count > 0 { border_sum / border_count as f64 } else { 0.0 };
+        Ok(Self::finish(&total, width, height, edge_difference))
+    }
+
+    fn finish(total: &Partial, width: u32, height: u32, edge_difference: f64) -> Self {
+        let channels = total
+            .histograms
+            .map(|hist| ChannelStats::from_histogram(hist.to_vec()));
+        let n = total.pixels;
+        let nf = n.max(1) as f64;
+        let luminance_mean = if n > 0 {
+            0.299 * channels[0].mean + 0.587 * channels[1].mean + 0.114 * channels[2].mean
+        } else {
+            0.0
+        };
+        let block_luminance = if width == 0 || height == 0 {
+            Vec::new()
+        } else {
+            total
+                .block_sum
+                .iter()
+                .zip(&total.block_count)
+                .map(|(&s, &c)| if c > 0 { (s / c as f64) as f32 } else { 0.0 })
+                .collect()
+        };
+
+        Self {
+            width,
+            height,
+            pixel_count: n,
+            channels,
+            luminance_histogram: total.luminance_histogram.to_vec(),
+            luminance_mean,
+            luminance_min: total.lum_min,
+            luminance_max: total.lum_max,
+            clipped_pixels: total.clipped,
+            saturation_mean: if n > 0 { total.saturation / nf } else { 0.0 },
+            warm_ratio: if n > 0 { total.warm / nf } else { 0.33 },
+            edge_pixels: total.edges,
+            edge_difference,
+            block_luminance,
+        }
+    }
+
+    /// Fraction of pixels classified as gradient edges.
+    pub fn edge_density(&self) -> f64 {
+        if self.pixel_count > 0 {
+            self.edge_pixels as f64 / self.pixel_count as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Fraction of pixels with a clipped RGB channel.
+    pub fn clipped_ratio(&self) -> f64 {
+        if self.pixel_count > 0 {
+            self.clipped_pixels as f64 / self.pixel_count as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moments_match_direct_computation() {
+        let data: Vec<u8> = (0..16 * 16).flat_map(|i| [(i % 256) as u8, 10, 200, 255]).collect();
+        let tex = TextureMap::new(16, 16, data.clone(), None);
+        let stats = tex.stats();
+
+        let reds: Vec<f64> = data.chunks_exact(4).map(|p| p[0] as f64).collect();
+        let mean = reds.iter().sum::<f64>() / reds.len() as f64;
+        let var = reds.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (reds.len() - 1) as f64;
+        assert_eq!(stats.pixel_count, 256);
+        assert!((stats.channels[0].mean - mean).abs() < 1e-9);
+        assert!((stats.channels[0].stddev - var.sqrt()).abs() < 1e-9);
+        assert_eq!(stats.channels[1].min, 10);
+        assert_eq!(stats.channels[1].max, 10);
+        assert_eq!(stats.channels[2].stddev, 0.0);
+        assert_eq!(stats.clipped_pixels, 2); // red == 0 and red == 255
+        assert_eq!(stats.block_luminance.len(), PHASH_SIZE * PHASH_SIZE);
+    }
+
+    #[test]
+    fn stats_are_memoized_and_invalidated() {
+        let mut tex = TextureMap::new(4, 4, vec![0; 64], None);
+        assert!(std::ptr::eq(tex.stats(), tex.stats()));
+        assert_eq!(tex.stats().luminance_max, 0.0);
+
+        tex.data.iter_mut().for_each(|v| *v = 255);
+        tex.invalidate_stats();
+        assert!((tex.stats().luminance_max - 255.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn empty_texture_has_zero_stats() {
+        let tex = TextureMap::new(0, 0, Vec::new(), None);
+        let stats = tex.stats();
+        assert_eq!(stats.pixel_count, 0);
+        assert!(stats.block_luminance.is_empty());
+        assert_eq!(stats.edge_density(), 0.0);
+    }
+}
@@ -0,0 +1,357 @@
+//! Frequency-domain and gradient-based tileability analysis.
+//!
+//! Works on the wrap-around offset image (shifted by half in each direction),
+//! so the tiling seams sit in the middle of the image where they can be
+//! compared with the surrounding content:
+//!
+//! - **Gradient discontinuity profile** – for each row (column), the jump
+//!   across the seam at several band depths that the local slope does not
+//!   explain, relative to the typical unexplained jump elsewhere in the image.
+//!   Smooth gradients are predicted by their slope and noisy textures have
+//!   large jumps everywhere, so neither is flagged; seams a few pixels deep
+//!   still show up at the larger band depths.
+//! - **Spectral cross score** – the image is split into a periodic and a smooth
+//!   component (Moisan's periodic + smooth decomposition). The smooth part is
+//!   caused purely by the wrap boundary and shows up as the classic cross on
+//!   the spectrum axes; its share of the on-axis energy is the artifact score.
+//!   Genuine axis-aligned structure (bricks, planks) stays in the periodic part.
+//!
+//! Results are memoized on [`TextureMap::tileability`].
+
+use crate::material::TextureMap;
+use rustfft::num_complex::Complex;
+use rustfft::{FftDirection, FftPlanner};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Longest edge of the luminance image the analysis runs on
+const ANALYSIS_MAX_DIM: usize = 1024;
+
+/// Band depths (analysis pixels) compared across the seam
+const BAND_DEPTHS: [usize; 4] = [1, 2, 4, 8];
+
+/// Added to the reference residual (luminance levels) so flat and smooth
+/// textures do not divide by ~0
+const RESIDUAL_FLOOR: f64 = 1.0;
+
+/// Weight of the gradient profile vs. spectral score in the per-direction seam score
+const GRADIENT_WEIGHT: f32 = 0.75;
+
+/// Seam analysis of one texture. Scores are 0 (seamless) to 1 (hard seam).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeamAnalysis {
+    /// Worst of the horizontal and vertical seam scores
+    pub score: f32,
+    /// Seam where the right edge wraps to the left edge
+    pub horizontal_seam: f32,
+    /// Seam where the bottom edge wraps to the top edge
+    pub vertical_seam: f32,
+    /// Share of on-axis spectral energy caused by the wrap boundary, per direction
+    pub horizontal_spectral: f32,
+    pub vertical_spectral: f32,
+    /// Seam strength per row along the left↔right seam (1.0 = like interior content)
+    pub horizontal_profile: Vec<f32>,
+    /// Seam strength per column along the top↔bottom seam
+    pub vertical_profile: Vec<f32>,
+    /// Texture pixels covered by one profile entry
+    pub profile_step: u32,
+}
+
+impl SeamAnalysis {
+    /// Analyze a texture (prefer [`TextureMap::tileability`], which memoizes).
+    pub fn compute(map: &TextureMap) -> Self {
+        let (lum, w, h, step) = luminance_grid(map, ANALYSIS_MAX_DIM);
+        if w < 4 || h < 4 {
+            return Self::seamless(step);
+        }
+        let offset = wrap_offset(&lum, w, h);
+
+        let horizontal_profile = seam_profile(&offset, w, h, Axis::Columns);
+        let vertical_profile = seam_profile(&offset, w, h, Axis::Rows);
+        let (horizontal_spectral, vertical_spectral) = spectral_cross(&lum, w, h);
+
+        let combine = |profile: &[f32], spectral: f32| -> f32 {
+            let mean = profile.iter().sum::<f32>() / profile.len().max(1) as f32;
+            let gradient = 1.0 - 1.0 / mean.max(1.0);
+            GRADIENT_WEIGHT * gradient + (1.0 - GRADIENT_WEIGHT) * spectral
+        };
+        let horizontal_seam = combine(&horizontal_profile, horizontal_spectral);
+        let vertical_seam = combine(&vertical_profile, vertical_spectral);
+
+        Self {
+            score: horizontal_seam.max(vertical_seam),
+            horizontal_seam,
+            vertical_seam,
+            horizontal_spectral,
+            vertical_spectral,
+            horizontal_profile,
+            vertical_profile,
+            profile_step: step as u32,
+        }
+    }
+
+    fn seamless(step: usize) -> Self {
+        Self {
+            score: 0.0,
+            horizontal_seam: 0.0,
+            vertical_seam: 0.0,
+            horizontal_spectral: 0.0,
+            vertical_spectral: 0.0,
+            horizontal_profile: Vec::new(),
+            vertical_profile: Vec::new(),
+            profile_step: step as u32,
+        }
+    }
+}
+
+/// Rec.601 luminance, box-downsampled by an integer step so the longest edge is
+/// at most `max_dim`: `(values, width, height, step)`. Blocks never straddle the
+/// wrap boundary.
+pub(crate) fn luminance_grid(map: &TextureMap, max_dim: usize) -> (Vec<f64>, usize, usize, usize) {
+    let (tw, th) = (map.width as usize, map.height as usize);
+    let step = tw.max(th).div_ceil(max_dim).max(1);
+    let (w, h) = (tw.div_ceil(step), th.div_ceil(step));
+    let mut sum = vec![0.0f64; w * h];
+    let mut count = vec![0u32; w * h];
+    for y in 0..th {
+        for x in 0..tw {
+            let i = (y * tw + x) * 4;
+            if i + 3 > map.data.len() {
+                continue;
+            }
+            let d = &map.data;
+            let o = (y / step) * w + x / step;
+            sum[o] += 0.299 * d[i] as f64 + 0.587 * d[i + 1] as f64 + 0.114 * d[i + 2] as f64;
+            count[o] += 1;
+        }
+    }
+    let lum = sum
+        .iter()
+        .zip(&count)
+        .map(|(&s, &c)| if c > 0 { s / c as f64 } else { 0.0 })
+        .collect();
+    (lum, w, h, step)
+}
+
+/// Shift by half in both directions so the wrap seams land at the center.
+fn wrap_offset(lum: &[f64], w: usize, h: usize) -> Vec<f64> {
+    let mut out = vec![0.0; w * h];
+    for y in 0..h {
+        let sy = (y + h / 2) % h;
+        for x in 0..w {
+            out[y * w + x] = lum[sy * w + (x + w / 2) % w];
+        }
+    }
+    out
+}
+
+#[derive(Clone, Copy)]
+enum Axis {
+    /// Seam between two columns (left↔right wrap); profile runs over rows
+    Columns,
+    /// Seam between two rows (top↔bottom wrap); profile runs over columns
+    Rows,
+}
+
+/// Seam-to-interior ratio of step residuals, averaged over band depths.
+///
+/// At position `c` and depth `d`, four bands of `d` pixels give means m1..m4
+/// (two before `c`, two after). The step `m3 - m2` minus the step predicted
+/// from the slopes on either side is the residual: near zero for smooth
+/// content, noise-sized for noise, and the jump height at a seam.
+fn seam_profile(img: &[f64], w: usize, h: usize, axis: Axis) -> Vec<f32> {
+    // Lines run across the seam: rows for a column seam, columns for a row seam
+    let (lines, len) = match axis {
+        Axis::Columns => (h, w),
+        Axis::Rows => (w, h),
+    };
+    let at = |line: usize, pos: usize| match axis {
+        Axis::Columns => img[line * w + pos],
+        Axis::Rows => img[pos * w + line],
+    };
+    // Original index 0 lands here after the half offset
+    let seam = len - len / 2;
+
+    // Prefix sums per line for O(1) band means
+    let prefix: Vec<Vec<f64>> = (0..lines)
+        .map(|l| {
+            let mut p = Vec::with_capacity(len + 1);
+            p.push(0.0);
+            for i in 0..len {
+                p.push(p[i] + at(l, i));
+            }
+            p
+        })
+        .collect();
+    let residual = |p: &[f64], c: usize, d: usize| -> f64 {
+        let mean = |lo: usize| (p[lo + d] - p[lo]) / d as f64;
+        let (m1, m2, m3, m4) = (mean(c - 2 * d), mean(c - d), mean(c), mean(c + d));
+        ((m3 - m2) - ((m2 - m1) + (m4 - m3)) / 2.0).abs()
+    };
+
+    let depths: Vec<usize> = BAND_DEPTHS
+        .iter()
+        .copied()
+        .filter(|&d| seam >= 2 * d && seam + 2 * d <= len)
+        .collect();
+    // Typical residual over the whole image (stationary textures look the same everywhere)
+    let references: Vec<f64> = depths
+        .iter()
+        .map(|&d| {
+            let mut sum = 0.0;
+            let mut n = 0usize;
+            for p in &prefix {
+                for c in (2 * d..=len - 2 * d).filter(|&c| c != seam) {
+                    sum += residual(p, c, d);
+                    n += 1;
+                }
+            }
+            sum / n.max(1) as f64
+        })
+        .collect();
+
+    prefix
+        .iter()
+        .map(|p| {
+            let ratios: f64 = depths
+                .iter()
+                .zip(&references)
+                .map(|(&d, &r)| residual(p, seam, d) / (r + RESIDUAL_FLOOR))
+                .sum();
+            (ratios / depths.len().max(1) as f64) as f32
+        })
+        .collect()
+}
+
+/// In-place 2D FFT of a row-major `w` x `h` buffer (unnormalized in both directions).
+pub(crate) fn fft2(buf: &mut [Complex<f64>], w: usize, h: usize, direction: FftDirection) {
+    let mut planner = FftPlanner::new();
+    let row_fft = planner.plan_fft(w, direction);
+    for row in buf.chunks_exact_mut(w) {
+        row_fft.process(row);
+    }
+    let col_fft = planner.plan_fft(h, direction);
+    let mut column = vec![Complex::new(0.0, 0.0); h];
+    for x in 0..w {
+        for y in 0..h {
+            column[y] = buf[y * w + x];
+        }
+        col_fft.process(&mut column);
+        for y in 0..h {
+            buf[y * w + x] = column[y];
+        }
+    }
+}
+
+/// Boundary image of the periodic + smooth decomposition: the jumps across the
+/// wrap, placed on the outermost pixels.
+fn boundary_image(img: &[f64], w: usize, h: usize) -> Vec<Complex<f64>> {
+    let mut v = vec![Complex::new(0.0, 0.0); w * h];
+    for y in 0..h {
+        let jump = img[y * w + w - 1] - img[y * w];
+        v[y * w].re += jump;
+        v[y * w + w - 1].re -= jump;
+    }
+    for x in 0..w {
+        let jump = img[(h - 1) * w + x] - img[x];
+        v[x].re += jump;
+        v[(h - 1) * w + x].re -= jump;
+    }
+    v
+}
+
+/// Discrete Laplacian eigenvalue of frequency `(q, r)`; zero only at DC.
+fn laplacian_eigenvalue(q: usize, r: usize, w: usize, h: usize) -> f64 {
+    2.0 * (2.0 * PI * q as f64 / w as f64).cos() + 2.0 * (2.0 * PI * r as f64 / h as f64).cos() - 4.0
+}
+
+/// Smooth component of one channel (zero mean). Subtracting it leaves the
+/// periodic component, which tiles without a seam: a Poisson solve that spreads
+/// the wrap-boundary mismatch smoothly over the whole image.
+pub(crate) fn smooth_component(img: &[f64], w: usize, h: usize) -> Vec<f64> {
+    let mut v = boundary_image(img, w, h);
+    fft2(&mut v, w, h, FftDirection::Forward);
+    for r in 0..h {
+        for q in 0..w {
+            let i = r * w + q;
+            v[i] = if q == 0 && r == 0 { Complex::new(0.0, 0.0) } else { v[i] / laplacian_eigenvalue(q, r, w, h) };
+        }
+    }
+    fft2(&mut v, w, h, FftDirection::Inverse);
+    let norm = (w * h) as f64;
+    v.iter().map(|c| c.re / norm).collect()
+}
+
+/// Share of energy on the spectrum axes that comes from the smooth (boundary)
+/// component: `(horizontal, vertical)` for the left↔right and top↔bottom seams.
+fn spectral_cross(lum: &[f64], w: usize, h: usize) -> (f32, f32) {
+    let mean = lum.iter().sum::<f64>() / lum.len() as f64;
+    let mut u: Vec<Complex<f64>> = lum.iter().map(|&v| Complex::new(v - mean, 0.0)).collect();
+
+    let mut v = boundary_image(lum, w, h);
+
+    fft2(&mut u, w, h, FftDirection::Forward);
+    fft2(&mut v, w, h, FftDirection::Forward);
+
+    // Smooth component s = v / (2cos(2πq/w) + 2cos(2πr/h) - 4); periodic p = u - s
+    let mut axis = [(0.0f64, 0.0f64); 2]; // (energy of u, energy of s) on kx and ky axes
+    for r in 0..h {
+        for q in 0..w {
+            if (q == 0) == (r == 0) {
+                continue; // DC or off-axis
+            }
+            let s = v[r * w + q] / laplacian_eigenvalue(q, r, w, h);
+            let u_val = u[r * w + q];
+            // r == 0: frequencies along x, produced by the left↔right seam
+            let slot = if r == 0 { 0 } else { 1 };
+            axis[slot].0 += u_val.norm_sqr();
+            axis[slot].1 += s.norm_sqr().min(u_val.norm_sqr());
+        }
+    }
+    let share = |(total, smooth): (f64, f64)| if total > 1e-9 { (smooth / total) as f32 } else { 0.0 };
+    (share(axis[0]), share(axis[1]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{gray, noise};
+
+    #[test]
+    fn periodic_pattern_is_seamless() {
+        let tex = gray(64, 64, |x, y| {
+            let v = (2.0 * PI * x as f64 / 64.0).sin() + (2.0 * PI * y as f64 / 32.0).cos();
+            (128.0 + 50.0 * v) as u8
+        });
+        let a = tex.tileability();
+        assert!(a.score < 0.15, "score {}", a.score);
+        assert_eq!(a.horizontal_profile.len(), 64);
+    }
+
+    #[test]
+    fn horizontal_gradient_has_horizontal_seam() {
+        let tex = gray(64, 64, |x, _| (x * 4) as u8);
+        let a = SeamAnalysis::compute(&tex);
+        assert!(a.horizontal_seam > 0.5, "horizontal {}", a.horizontal_seam);
+        assert!(a.vertical_seam < 0.1, "vertical {}", a.vertical_seam);
+        assert!(a.horizontal_spectral > a.vertical_spectral);
+    }
+
+    #[test]
+    fn noise_is_not_flagged() {
+        let tex = gray(64, 64, noise);
+        let a = SeamAnalysis::compute(&tex);
+        assert!(a.score < 0.35, "score {}", a.score);
+    }
+
+    #[test]
+    fn deep_seam_is_detected_in_noise() {
+        // Noise with a brightness ramp over the last 6 columns: no single-row jump
+        let tex = gray(64, 64, |x, y| {
+            let base = noise(x, y) / 4 + 64;
+            if x >= 58 { base + (x - 57) as u8 * 12 } else { base }
+        });
+        let a = SeamAnalysis::compute(&tex);
+        assert!(a.horizontal_seam > a.vertical_seam + 0.2, "h {} v {}", a.horizontal_seam, a.vertical_seam);
+    }
+}
@@ -0,0 +1,785 @@
+//! Tiled processing for very large textures (16K and up).
+//!
+//! A 16K RGBA8 texture is 1 GiB as a [`TextureMap`]; loading a full material,
+//! resizing and packing it needs several times that. Exports of materials
+//! with a source above [`TILED_THRESHOLD`] therefore stream every texture as
+//! rows instead: [`FileRows`] decodes, [`ResizedRows`] area-averages down
+//! while reading, [`PackedRows`] packs roughness, metallic and AO row by row
+//! and [`write_rows`] encodes. [`TextureStats::from_rows`] scans statistics
+//! the same way, and [`load_folder`] uses it to give validation and analysis
+//! a material without pixel data. Memory stays at a few rows per source plus,
+//! for TGA and JPG outputs, the (much smaller) output image.
+//!
+//! Non-interlaced PNG sources are decoded row by row. Other formats have no
+//! row decoder: they are decoded whole up to [`TILED_THRESHOLD`] and refused
+//! above it. Sources are reduced to 8 bits on decode, so exports with
+//! [`dither`](crate::dither) settings are refused.
+
+use crate::export_manifest::{ExportManifest, ManifestSource};
+use crate::image_loading::{ImageLoader, TextureSlot};
+use crate::material::{MaterialSet, TextureMap};
+use crate::optimization::{
+    compute_target_dimensions, export_lod_levels, export_with_optimization_preset, jpg_alpha_error, output_path,
+    save_texture, ExportedTexture, OptimizationPreset, OutputFormat, PackingLayout,
+};
+use crate::texture_stats::TextureStats;
+use crate::tileability::SeamAnalysis;
+use crate::{Error, Result};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Longest source edge above which exports stream instead of loading the
+/// material (16K sources stream, 8K ones still load).
+pub const TILED_THRESHOLD: u32 = 8192;
+
+/// Longest edge of the preview that seam analysis of a streamed texture runs on
+const SEAM_PREVIEW_SIZE: u32 = 2048;
+
+/// RGBA8 image read one row at a time, top to bottom.
+pub trait RowSource {
+    fn width(&self) -> u32;
+    fn height(&self) -> u32;
+    /// Fill `row` (`width * 4` bytes) with the next row.
+    fn read_row(&mut self, row: &mut [u8]) -> Result<()>;
+}
+
+impl<S: RowSource + ?Sized> RowSource for Box<S> {
+    fn width(&self) -> u32 {
+        (**self).width()
+    }
+    fn height(&self) -> u32 {
+        (**self).height()
+    }
+    fn read_row(&mut self, row: &mut [u8]) -> Result<()> {
+        (**self).read_row(row)
+    }
+}
+
+/// Rows of a texture already in memory.
+pub struct TextureRows<'a> {
+    texture: &'a TextureMap,
+    next: usize,
+}
+
+impl<'a> TextureRows<'a> {
+    pub fn new(texture: &'a TextureMap) -> Self {
+        Self { texture, next: 0 }
+    }
+}
+
+impl RowSource for TextureRows<'_> {
+    fn width(&self) -> u32 {
+        self.texture.width
+    }
+    fn height(&self) -> u32 {
+        self.texture.height
+    }
+    fn read_row(&mut self, row: &mut [u8]) -> Result<()> {
+        let start = self.next * row.len();
+        let src = self
+            .texture
+            .data
+            .get(start..start + row.len())
+            .ok_or_else(|| Error::Other("Read past the last texture row".into()))?;
+        row.copy_from_slice(src);
+        self.next += 1;
+        Ok(())
+    }
+}
+
+/// Rows decoded from an image file (non-interlaced PNG row by row, other
+/// formats whole up to [`TILED_THRESHOLD`]).
+pub struct FileRows {
+    path: PathBuf,
+    width: u32,
+    height: u32,
+    decoder: FileDecoder,
+}
+
+enum FileDecoder {
+    Png {
+        reader: Box<png::Reader<BufReader<File>>>,
+        samples: usize,
+        sixteen_bit: bool,
+    },
+    Whole {
+        data: Vec<u8>,
+        next: usize,
+    },
+}
+
+impl FileRows {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let is_png = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("png"));
+        if is_png {
+            let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+            decoder.set_transformations(png::Transformations::EXPAND);
+            let reader = decoder
+                .read_info()
+                .map_err(|e| Error::Other(format!("{}: {}", path.display(), e)))?;
+            // Adam7 rows arrive in passes; decode those whole
+            if !reader.info().interlaced {
+                let (width, height) = reader.info().size();
+                let (color, depth) = reader.output_color_type();
+                return Ok(Self {
+                    path: path.to_path_buf(),
+                    width,
+                    height,
+                    decoder: FileDecoder::Png {
+                        reader: Box::new(reader),
+                        samples: color.samples(),
+                        sixteen_bit: depth == png::BitDepth::Sixteen,
+                    },
+                });
+            }
+        }
+        let (width, height) = image::image_dimensions(path)?;
+        if width.max(height) > TILED_THRESHOLD {
+            return Err(Error::Other(format!(
+                "{}: {}x{} is above {}px and can't be decoded row by row; convert it to a non-interlaced PNG",
+                path.display(),
+                width,
+                height,
+                TILED_THRESHOLD
+            )));
+        }
+        let image = ImageLoader::load(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            width: image.width,
+            height: image.height,
+            decoder: FileDecoder::Whole { data: image.data, next: 0 },
+        })
+    }
+}
+
+impl RowSource for FileRows {
+    fn width(&self) -> u32 {
+        self.width
+    }
+    fn height(&self) -> u32 {
+        self.height
+    }
+    fn read_row(&mut self, row: &mut [u8]) -> Result<()> {
+        let truncated = || Error::Other(format!("{}: image data ends early", self.path.display()));
+        match &mut self.decoder {
+            FileDecoder::Png { reader, samples, sixteen_bit } => {
+                let src = reader
+                    .next_row()
+                    .map_err(|e| Error::Other(format!("{}: {}", self.path.display(), e)))?
+                    .ok_or_else(truncated)?;
+                let src = src.data();
+                let bytes = if *sixteen_bit { 2 } else { 1 };
+                // 16-bit samples round to 8 bits like `image`'s conversion
+                let sample = |i: usize| {
+                    if *sixteen_bit {
+                        ((u16::from_be_bytes([src[i * 2], src[i * 2 + 1]]) as u32 + 128) / 257) as u8
+                    } else {
+                        src[i]
+                    }
+                };
+                if src.len() < self.width as usize * *samples * bytes {
+                    return Err(truncated());
+                }
+                for (x, px) in row.chunks_exact_mut(4).enumerate() {
+                    let i = x * *samples;
+                    let value = match *samples {
+                        1 => [sample(i), sample(i), sample(i), 255],
+                        2 => [sample(i), sample(i), sample(i), sample(i + 1)],
+                        3 => [sample(i), sample(i + 1), sample(i + 2), 255],
+                        _ => [sample(i), sample(i + 1), sample(i + 2), sample(i + 3)],
+                    };
+                    px.copy_from_slice(&value);
+                }
+            }
+            FileDecoder::Whole { data, next } => {
+                let start = *next * row.len();
+                row.copy_from_slice(data.get(start..start + row.len()).ok_or_else(truncated)?);
+                *next += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Source resampled to a new size by area averaging: every output pixel is
+/// the coverage-weighted mean of the source pixels under it. Reads each
+/// source row once and keeps one of them.
+pub struct ResizedRows<S> {
+    source: S,
+    width: u32,
+    height: u32,
+    columns: Vec<(usize, Vec<f32>)>,
+    rows: Vec<(usize, Vec<f32>)>,
+    next_out: usize,
+    next_in: usize,
+    input: Vec<u8>,
+    filtered: Vec<f32>,
+    filtered_row: Option<usize>,
+    acc: Vec<f32>,
+}
+
+impl<S: RowSource> ResizedRows<S> {
+    pub fn new(source: S, width: u32, height: u32) -> Result<Self> {
+        if width == 0 || height == 0 || source.width() == 0 || source.height() == 0 {
+            return Err(Error::Other("Cannot resize an empty texture".into()));
+        }
+        let input = vec![0; source.width() as usize * 4];
+        Ok(Self {
+            columns: coverage(source.width(), width),
+            rows: coverage(source.height(), height),
+            source,
+            width,
+            height,
+            next_out: 0,
+            next_in: 0,
+            input,
+            filtered: vec![0.0; width as usize * 4],
+            filtered_row: None,
+            acc: vec![0.0; width as usize * 4],
+        })
+    }
+
+    /// Horizontally resampled source row `y` into `self.filtered`.
+    fn filter_row(&mut self, y: usize) -> Result<()> {
+        if self.filtered_row == Some(y) {
+            return Ok(());
+        }
+        while self.next_in <= y {
+            self.source.read_row(&mut self.input)?;
+            self.next_in += 1;
+        }
+        for ((first, weights), out) in self.columns.iter().zip(self.filtered.chunks_exact_mut(4)) {
+            out.fill(0.0);
+            for (k, &w) in weights.iter().enumerate() {
+                let px = &self.input[(first + k) * 4..(first + k) * 4 + 4];
+                for c in 0..4 {
+                    out[c] += w * px[c] as f32;
+                }
+            }
+        }
+        self.filtered_row = Some(y);
+        Ok(())
+    }
+}
+
+impl<S: RowSource> RowSource for ResizedRows<S> {
+    fn width(&self) -> u32 {
+        self.width
+    }
+    fn height(&self) -> u32 {
+        self.height
+    }
+    fn read_row(&mut self, row: &mut [u8]) -> Result<()> {
+        if self.width == self.source.width() && self.height == self.source.height() {
+            return self.source.read_row(row);
+        }
+        let (first, weights) = self
+            .rows
+            .get(self.next_out)
+            .cloned()
+            .ok_or_else(|| Error::Other("Read past the last texture row".into()))?;
+        self.acc.fill(0.0);
+        for (k, w) in weights.into_iter().enumerate() {
+            self.filter_row(first + k)?;
+            for (a, v) in self.acc.iter_mut().zip(&self.filtered) {
+                *a += w * v;
+            }
+        }
+        for (out, v) in row.iter_mut().zip(&self.acc) {
+            *out = v.round().clamp(0.0, 255.0) as u8;
+        }
+        self.next_out += 1;
+        Ok(())
+    }
+}
+
+/// For each of `dst` output pixels: the first source pixel it covers and the
+/// normalized coverage weight of each source pixel from there on.
+fn coverage(src: u32, dst: u32) -> Vec<(usize, Vec<f32>)> {
+    let scale = src as f64 / dst as f64;
+    (0..dst)
+        .map(|o| {
+            let a = o as f64 * scale;
+            let b = ((o + 1) as f64 * scale).min(src as f64);
+            let first = a.floor() as usize;
+            let last = (b.ceil() as usize).clamp(first + 1, src as usize);
+            let weights = (first..last)
+                .map(|i| ((b.min(i as f64 + 1.0) - a.max(i as f64)) / (b - a)) as f32)
+                .collect();
+            (first, weights)
+        })
+        .collect()
+}
+
+/// Roughness, metallic and AO rows packed in a [`PackingLayout`]. All three
+/// sources must have the same size (wrap them in [`ResizedRows`]).
+pub struct PackedRows<R, M, A> {
+    roughness: R,
+    metallic: M,
+    ao: A,
+    layout: PackingLayout,
+    buffers: [Vec<u8>; 3],
+}
+
+impl<R: RowSource, M: RowSource, A: RowSource> PackedRows<R, M, A> {
+    pub fn new(roughness: R, metallic: M, ao: A, layout: PackingLayout) -> Result<Self> {
+        let size = (roughness.width(), roughness.height());
+        if (metallic.width(), metallic.height()) != size || (ao.width(), ao.height()) != size {
+            return Err(Error::Other("Packed textures must have the same size".into()));
+        }
+        let len = size.0 as usize * 4;
+        Ok(Self {
+            roughness,
+            metallic,
+            ao,
+            layout,
+            buffers: [vec![0; len], vec![0; len], vec![0; len]],
+        })
+    }
+}
+
+impl<R: RowSource, M: RowSource, A: RowSource> RowSource for PackedRows<R, M, A> {
+    fn width(&self) -> u32 {
+        self.roughness.width()
+    }
+    fn height(&self) -> u32 {
+        self.roughness.height()
+    }
+    fn read_row(&mut self, row: &mut [u8]) -> Result<()> {
+        let [r, m, a] = &mut self.buffers;
+        self.roughness.read_row(r)?;
+        self.metallic.read_row(m)?;
+        self.ao.read_row(a)?;
+        // Grayscale maps: the R channel carries the value
+        for (i, px) in row.chunks_exact_mut(4).enumerate() {
+            px.copy_from_slice(&self.layout.pack(r[i * 4], m[i * 4], a[i * 4]));
+        }
+        Ok(())
+    }
+}
+
+/// Normal map rows with the green channel inverted (OpenGL ↔ DirectX).
+pub struct FlipGreenRows<S>(pub S);
+
+impl<S: RowSource> RowSource for FlipGreenRows<S> {
+    fn width(&self) -> u32 {
+        self.0.width()
+    }
+    fn height(&self) -> u32 {
+        self.0.height()
+    }
+    fn read_row(&mut self, row: &mut [u8]) -> Result<()> {
+        self.0.read_row(row)?;
+        for px in row.chunks_exact_mut(4) {
+            px[1] = 255 - px[1];
+        }
+        Ok(())
+    }
+}
+
+/// Write every row of `source` to `path` (format from the extension). PNG is
+/// encoded as rows arrive; TGA and JPG are assembled first. Returns whether
+/// any pixel has alpha below 255.
+pub fn write_rows<S: RowSource + ?Sized>(source: &mut S, path: &Path) -> Result<bool> {
+    let (width, height) = (source.width(), source.height());
+    let mut row = vec![0u8; width as usize * 4];
+    let mut has_alpha = false;
+    let is_png = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("png"));
+    if is_png {
+        let png_error = |e: png::EncodingError| Error::Other(format!("{}: {}", path.display(), e));
+        let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut stream = encoder.write_header().map_err(png_error)?.into_stream_writer().map_err(png_error)?;
+        for _ in 0..height {
+            source.read_row(&mut row)?;
+            has_alpha |= row.chunks_exact(4).any(|px| px[3] < 255);
+            stream.write_all(&row)?;
+        }
+        stream.finish().map_err(png_error)?;
+    } else {
+        let mut data = Vec::with_capacity(row.len() * height as usize);
+        for _ in 0..height {
+            source.read_row(&mut row)?;
+            has_alpha |= row.chunks_exact(4).any(|px| px[3] < 255);
+            data.extend_from_slice(&row);
+        }
+        save_texture(&TextureMap::new(width, height, data, None), path)?;
+    }
+    Ok(has_alpha)
+}
+
+/// Statistics of an image file, decoded row by row.
+pub fn file_stats<P: AsRef<Path>>(path: P) -> Result<TextureStats> {
+    TextureStats::from_rows(&mut FileRows::open(path)?)
+}
+
+/// Load the material in `folder` for validation and analysis. Below
+/// [`TILED_THRESHOLD`] this is [`MaterialSet::load_from_folder`]. Above it,
+/// every texture keeps its source dimensions but holds no pixel data: its
+/// statistics are streamed with [`TextureStats::from_rows`] and its seam
+/// analysis runs on an area-averaged preview. Rules and scores see the full
+/// texture; analyses that need the pixels skip it.
+pub fn load_folder<P: AsRef<Path>>(folder: P) -> Result<MaterialSet> {
+    let folder = folder.as_ref();
+    if !needs_tiling(folder)? {
+        return MaterialSet::load_from_folder(folder);
+    }
+    let mut set = MaterialSet {
+        name: folder.file_name().and_then(|n| n.to_str()).map(String::from),
+        ..Default::default()
+    };
+    for (slot, path) in MaterialSet::source_files(folder)? {
+        let mut rows = FileRows::open(&path)?;
+        let (width, height) = (rows.width(), rows.height());
+        let stats = TextureStats::from_rows(&mut rows)?;
+        let (pw, ph) = compute_target_dimensions(width, height, SEAM_PREVIEW_SIZE);
+        let mut preview = ResizedRows::new(FileRows::open(&path)?, pw, ph)?;
+        let mut data = vec![0u8; pw as usize * ph as usize * 4];
+        for row in data.chunks_exact_mut(pw as usize * 4) {
+            preview.read_row(row)?;
+        }
+        let mut map = TextureMap::new(width, height, Vec::new(), Some(path));
+        map.set_stats(stats);
+        map.set_tileability(SeamAnalysis::compute(&TextureMap::new(pw, ph, data, None)));
+        match slot {
+            TextureSlot::Albedo => set.albedo = Some(map),
+            TextureSlot::Normal => set.normal = Some(map),
+            TextureSlot::Roughness => set.roughness = Some(map),
+            TextureSlot::Metallic => set.metallic = Some(map),
+            TextureSlot::AmbientOcclusion => set.ao = Some(map),
+            TextureSlot::Height => set.height = Some(map),
+            TextureSlot::Emissive => {}
+        }
+    }
+    Ok(set)
+}
+
+/// Longest edge of the source textures in `folder`, read from file headers.
+pub fn largest_source_dimension<P: AsRef<Path>>(folder: P) -> Result<u32> {
+    let mut largest = 0;
+    for (_, path) in MaterialSet::source_files(folder)? {
+        let (w, h) = image::image_dimensions(&path)?;
+        largest = largest.max(w).max(h);
+    }
+    Ok(largest)
+}
+
+/// True when a source in `folder` exceeds [`TILED_THRESHOLD`].
+pub fn needs_tiling<P: AsRef<Path>>(folder: P) -> Result<bool> {
+    Ok(largest_source_dimension(folder)? > TILED_THRESHOLD)
+}
+
+/// Export the material in `folder` with an optimization preset, streaming
+/// it ([`export_folder_tiled`]) when a source exceeds [`TILED_THRESHOLD`]
+/// and loading it otherwise.
+pub fn export_folder<P: AsRef<Path>>(
+    folder: &Path,
+    output_dir: P,
+    preset: OptimizationPreset,
+    include_lod: bool,
+) -> Result<Vec<PathBuf>> {
+    if needs_tiling(folder)? {
+        export_folder_tiled(folder, output_dir.as_ref(), &preset, include_lod)
+    } else {
+        let material = MaterialSet::load_from_folder(folder)?;
+        export_with_optimization_preset(&material, output_dir, preset, include_lod)
+    }
+}
+
+struct TiledSource {
+    slot: &'static str,
+    path: PathBuf,
+    width: u32,
+    height: u32,
+}
+
+/// Export the material in `folder` like [`export_with_optimization_preset`]
+/// without loading it: every output is streamed from the source files. Each
+/// LOD level re-reads the sources. Resampling is area averaging rather than
+/// Lanczos. Fails when the preset dithers.
+pub fn export_folder_tiled(
+    folder: &Path,
+    output_dir: &Path,
+    preset: &OptimizationPreset,
+    include_lod: bool,
+) -> Result<Vec<PathBuf>> {
+    if preset.dither.is_enabled() {
+        return Err(Error::Other(format!(
+            "{}: dithering needs the full-precision source and is not supported for sources above {}px",
+            folder.display(),
+            TILED_THRESHOLD
+        )));
+    }
+    let lod_levels = export_lod_levels(preset, include_lod)?;
+    let material_name = folder
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("material")
+        .to_string();
+    let mut sources = Vec::new();
+    for (slot, path) in MaterialSet::source_files(folder)? {
+        let Some(slot) = slot_key(slot) else {
+            continue;
+        };
+        let (width, height) = image::image_dimensions(&path)?;
+        sources.push(TiledSource { slot, path, width, height });
+    }
+    sources.sort_by_key(|s| SLOT_ORDER.iter().position(|&k| k == s.slot));
+
+    std::fs::create_dir_all(output_dir)?;
+    let manifest_sources = sources.iter().map(|s| ManifestSource::for_file(s.slot, &s.path)).collect();
+    let mut manifest = ExportManifest::with_sources(Some(material_name.clone()), preset.label(), manifest_sources);
+    manifest.preset_fingerprint = Some(preset.fingerprint(include_lod));
+
+    let levels = std::iter::once(preset.effective_resolution()).chain(lod_levels.iter().copied());
+    let mut written = Vec::new();
+    for (lod, level) in levels.enumerate() {
+        let lod = lod as u32;
+        let dir = if lod_levels.is_empty() {
+            output_dir.to_path_buf()
+        } else {
+            output_dir.join(preset.naming.lod_dir_name(&material_name, lod)?)
+        };
+        let textures = export_tiled_level(&sources, &dir, preset, &material_name, lod, level.max_dimension())?;
+        manifest.add_outputs(output_dir, lod, &textures)?;
+        written.extend(textures.into_iter().map(|t| t.path));
+    }
+    manifest.write(output_dir)?;
+    Ok(written)
+}
+
+/// Slots in export order
+const SLOT_ORDER: [&str; 6] = ["albedo", "normal", "roughness", "metallic", "ao", "height"];
+
+pub(crate) fn slot_key(slot: TextureSlot) -> Option<&'static str> {
+    match slot {
+        TextureSlot::Albedo => Some("albedo"),
+        TextureSlot::Normal => Some("normal"),
+        TextureSlot::Roughness => Some("roughness"),
+        TextureSlot::Metallic => Some("metallic"),
+        TextureSlot::AmbientOcclusion => Some("ao"),
+        TextureSlot::Height => Some("height"),
+        TextureSlot::Emissive => None,
+    }
+}
+
+/// One level of a tiled export, mirroring the in-memory texture export.
+fn export_tiled_level(
+    sources: &[TiledSource],
+    dir: &Path,
+    preset: &OptimizationPreset,
+    material_name: &str,
+    lod: u32,
+    max_dim: u32,
+) -> Result<Vec<ExportedTexture>> {
+    let get = |slot: &str| sources.iter().find(|s| s.slot == slot);
+    let resized = |s: &TiledSource, size: Option<(u32, u32)>| {
+        let (w, h) = size.unwrap_or_else(|| compute_target_dimensions(s.width, s.height, max_dim));
+        ResizedRows::new(FileRows::open(&s.path)?, w, h)
+    };
+    let mut written = Vec::new();
+    let mut write = |rows: &mut dyn RowSource, slot: &'static str| -> Result<()> {
+        let (w, h) = (rows.width(), rows.height());
+        let path = output_path(dir, preset, material_name, slot, lod, w.max(h))?;
+        let has_alpha = write_rows(rows, &path)?;
+        if slot == "albedo" && has_alpha && preset.format == OutputFormat::Jpg {
+            let _ = std::fs::remove_file(&path);
+            return Err(jpg_alpha_error(material_name));
+        }
+        written.push(ExportedTexture::new(path, slot, w, h, slot == "albedo" && has_alpha, preset));
+        Ok(())
+    };
+
+    if let Some(s) = get("albedo") {
+        write(&mut resized(s, None)?, "albedo")?;
+    }
+    if let Some(s) = get("normal") {
+        let rows = resized(s, None)?;
+        if preset.output_normal_convention() != preset.source_normal_convention {
+            write(&mut FlipGreenRows(rows), "normal")?;
+        } else {
+            write(&mut { rows }, "normal")?;
+        }
+    }
+    match (preset.packing.slot(), get("roughness"), get("metallic"), get("ao")) {
+        (Some(slot), Some(r), Some(m), Some(a)) => {
+            let r = resized(r, None)?;
+            let size = Some((r.width(), r.height()));
+            let mut packed = PackedRows::new(r, resized(m, size)?, resized(a, size)?, preset.packing)?;
+            write(&mut packed, slot)?;
+        }
+        _ => {
+            for slot in ["roughness", "metallic", "ao"] {
+                if let Some(s) = get(slot) {
+                    write(&mut resized(s, None)?, s.slot)?;
+                }
+            }
+        }
+    }
+    if let Some(s) = get("height") {
+        write(&mut resized(s, None)?, "height")?;
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export_manifest::{verify_export, EXPORT_MANIFEST_FILE};
+    use crate::optimization::TargetResolution;
+
+    fn gradient(w: u32, h: u32, seed: u32) -> TextureMap {
+        let data = (0..w * h)
+            .flat_map(|i| {
+                let (x, y) = (i % w, i / w);
+                [(x * 255 / w) as u8, (y * 255 / h) as u8, ((x + y + seed) % 256) as u8, 255]
+            })
+            .collect();
+        TextureMap::new(w, h, data, None)
+    }
+
+    fn read_all<S: RowSource>(source: &mut S) -> Vec<u8> {
+        let mut row = vec![0; source.width() as usize * 4];
+        let mut out = Vec::new();
+        for _ in 0..source.height() {
+            source.read_row(&mut row).unwrap();
+            out.extend_from_slice(&row);
+        }
+        out
+    }
+
+    #[test]
+    fn file_rows_match_full_decode() {
+        let dir = std::env::temp_dir().join("pbr_tiled_rows_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let rgba = dir.join("rgba.png");
+        save_texture(&gradient(37, 19, 3), &rgba).unwrap();
+        let gray16 = dir.join("gray16.png");
+        let luma: image::ImageBuffer<image::Luma<u16>, Vec<u16>> =
+            image::ImageBuffer::from_fn(23, 11, |x, y| image::Luma([(x * 2851 + y * 977) as u16]));
+        luma.save(&gray16).unwrap();
+        let tga = dir.join("rgba.tga");
+        save_texture(&gradient(8, 8, 1), &tga).unwrap();
+
+        for path in [&rgba, &gray16, &tga] {
+            let mut rows = FileRows::open(path).unwrap();
+            assert_eq!(read_all(&mut rows), ImageLoader::load(path).unwrap().data, "{}", path.display());
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn resized_rows_average_covered_pixels() {
+        let tex = TextureMap::new(4, 2, vec![0, 0, 0, 255, 100, 0, 0, 255, 10, 0, 0, 255, 30, 0, 0, 255,
+            200, 0, 0, 255, 100, 0, 0, 255, 50, 0, 0, 255, 70, 0, 0, 255], None);
+        let mut half = ResizedRows::new(TextureRows::new(&tex), 2, 1).unwrap();
+        assert_eq!(read_all(&mut half), [100, 0, 0, 255, 40, 0, 0, 255]);
+
+        // Non-integer ratios and upscaling keep the mean of smooth content
+        let big = gradient(300, 200, 0);
+        for (w, h) in [(128, 85), (37, 512)] {
+            let mut rows = ResizedRows::new(TextureRows::new(&big), w, h).unwrap();
+            let out = TextureMap::new(w, h, read_all(&mut rows), None);
+            assert!((out.stats().channels[0].mean - big.stats().channels[0].mean).abs() < 1.0);
+        }
+    }
+
+    #[test]
+    fn streamed_stats_match_in_memory_stats() {
+        let tex = gradient(150, 131, 7);
+        let streamed = TextureStats::from_rows(&mut TextureRows::new(&tex)).unwrap();
+        assert_eq!(streamed, TextureStats::compute(&tex));
+    }
+
+    #[test]
+    fn tiled_export_matches_in_memory_export() {
+        let dir = std::env::temp_dir().join("pbr_tiled_export_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let src = dir.join("Rock");
+        std::fs::create_dir_all(&src).unwrap();
+        for (name, seed) in [("rock_albedo.png", 0), ("rock_normal.png", 1), ("rock_roughness.png", 2),
+            ("rock_metallic.png", 3), ("rock_ao.png", 4)] {
+            save_texture(&gradient(512, 256, seed), src.join(name)).unwrap();
+        }
+        let preset = OptimizationPreset::unity()
+            .with_resolution(TargetResolution::Res256)
+            .with_lod_levels(&[TargetResolution::Custom(64)]);
+
+        let tiled = export_folder_tiled(&src, &dir.join("tiled"), &preset, true).unwrap();
+        let material = MaterialSet::load_from_folder(&src).unwrap();
+        let memory = export_with_optimization_preset(&material, dir.join("memory"), preset.clone(), true).unwrap();
+        assert_eq!(tiled.len(), 6);
+        for (t, m) in tiled.iter().zip(&memory) {
+            assert_eq!(t.strip_prefix(dir.join("tiled")).unwrap(), m.strip_prefix(dir.join("memory")).unwrap());
+            let (t, m) = (ImageLoader::load(t).unwrap(), ImageLoader::load(m).unwrap());
+            assert_eq!((t.width, t.height), (m.width, m.height));
+            let diff = t.data.iter().zip(&m.data).map(|(a, b)| (*a as f64 - *b as f64).abs()).sum::<f64>();
+            assert!(diff / (t.data.len() as f64) < 2.0);
+        }
+        assert!(verify_export(&dir.join("tiled")).unwrap().is_empty());
+        let manifest = ExportManifest::load(&dir.join("tiled").join(EXPORT_MANIFEST_FILE)).unwrap();
+        assert_eq!(manifest.sources.len(), 5);
+        assert!(manifest.sources.iter().all(|s| s.sha256.is_some()));
+        assert_eq!(manifest.preset_fingerprint, Some(preset.fingerprint(true)));
+
+        let jpg = preset.with_format(OutputFormat::Jpg);
+        let written = export_folder_tiled(&src, &dir.join("jpg"), &jpg, false).unwrap();
+        assert_eq!(written[0], dir.join("jpg").join("BaseColor.jpg"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn load_folder_streams_oversized_sources_without_pixels() {
+        let dir = std::env::temp_dir().join("pbr_tiled_load_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let albedo = gradient(TILED_THRESHOLD + 808, 64, 5);
+        save_texture(&albedo, dir.join("wall_albedo.png")).unwrap();
+        save_texture(&gradient(256, 256, 2), dir.join("wall_roughness.png")).unwrap();
+
+        let set = load_folder(&dir).unwrap();
+        let streamed = set.albedo.as_ref().unwrap();
+        assert_eq!((streamed.width, streamed.height), (albedo.width, albedo.height));
+        assert!(!streamed.has_pixels());
+        let full = TextureStats::compute(&albedo);
+        for (a, b) in streamed.stats().channels.iter().zip(&full.channels) {
+            assert!((a.mean - b.mean).abs() < 1e-6 && (a.stddev - b.stddev).abs() < 1e-6);
+        }
+        // Validation and analysis run on the streamed material
+        crate::Validator::default().check(&set);
+        let materials = vec![(dir.clone(), set)];
+        assert!(crate::analyze_banding(&materials).iter().all(|b| b.slot == "roughness"));
+
+        // Dithering needs the full-precision source
+        let dithered = OptimizationPreset::unity().with_dither(crate::DitherSettings::all(crate::DitherMode::Ordered));
+        assert!(export_folder_tiled(&dir, &dir.join("out"), &dithered, false).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn oversized_sources_without_row_decoder_are_refused() {
+        let dir = std::env::temp_dir().join("pbr_tiled_refuse_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        // Uncompressed true-color TGA header claiming 9000x9000, no pixel data
+        let mut header = vec![0u8, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        header.extend_from_slice(&9000u16.to_le_bytes());
+        header.extend_from_slice(&9000u16.to_le_bytes());
+        header.extend_from_slice(&[32, 8]);
+        let path = dir.join("huge.tga");
+        std::fs::write(&path, header).unwrap();
+        let err = FileRows::open(&path).err().unwrap().to_string();
+        assert!(err.contains("row by row"), "{}", err);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -3,7 +3,10 @@
 //! Defines pluggable validation rules that can be composed
 //! for different validation strategies.
 
-use crate::material::{MaterialSet, TextureMap};
+use crate::analysis::TILEABILITY_THRESHOLD;
+use crate::material::MaterialSet;
+use crate::texel_density::{analyze_texel_density, material_texel_density, DensityStatus, MeshUvStats, TexelDensityTarget};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 /// Severity of a validation finding.
@@ -27,6 +30,8 @@ impl Severity {
 }
 
 /// Compute material score from issues. Start at 100, subtract penalties.
+/// Equivalent to [`crate::scoring::ScoringModel::default`]; use a scoring model
+/// for rule weights, caps, and category sub-scores.
 pub fn compute_score(issues: &[Issue]) -> i32 {
     let total: i32 = issues.iter().map(|i| i.severity.score_penalty()).sum();
     (100 - total).max(0)
@@ -86,6 +91,12 @@ pub trait ValidationRule: Send + Sync {
     fn check_all(&self, set: &MaterialSet) -> Vec<Issue> {
         self.check(set).into_iter().collect()
     }
+
+    /// Identity of the rule and its parameters, used to key cached results.
+    /// Return `None` when results cannot be cached (e.g. external scripts).
+    fn fingerprint(&self) -> Option<String> {
+        Some(self.id().to_string())
+    }
 }
 
 /// Runs validation rules against material sets
@@ -109,6 +120,14 @@ impl Validator {
         self.rules.push(Box::new(rule));
     }
 
+    /// Replace the rule with the same id in place, or add it when there is none.
+    pub fn replace_rule<R: ValidationRule + 'static>(&mut self, rule: R) {
+        match self.rules.iter().position(|r| r.id() == rule.id()) {
+            Some(i) => self.rules[i] = Box::new(rule),
+            None => self.rules.push(Box::new(rule)),
+        }
+    }
+
     /// Build validator with default rules + plugin rules from loader.
     pub fn with_plugins(mut self, loader: &crate::plugin::PluginLoader) -> Self {
         let (plugin_rules, _presets) = loader.load();
@@ -118,11 +137,20 @@ impl Validator {
         self
     }
 
+    /// Run all rules in parallel. Issues are returned in rule order.
     pub fn check(&self, set: &MaterialSet) -> Vec<Issue> {
-        self.rules
-            .iter()
-            .flat_map(|r| r.check_all(set))
-            .collect()
+        let per_rule: Vec<Vec<Issue>> = self
+            .rules
+            .par_iter()
+            .map(|r| r.check_all(set))
+            .collect();
+        per_rule.into_iter().flatten().collect()
+    }
+
+    /// Combined fingerprint of all rules, or `None` if any rule is not cacheable.
+    pub fn fingerprint(&self) -> Option<String> {
+        let parts: Option<Vec<String>> = self.rules.iter().map(|r| r.fingerprint()).collect();
+        parts.map(|p| p.join("\n"))
     }
 
     pub fn has_issues(&self, set: &MaterialSet) -> bool {
@@ -136,7 +164,7 @@ impl Default for Validator {
             .with_rule(RequiredMapsRule)
             .with_rule(ResolutionMismatchRule)
             .with_rule(NonPowerOfTwoRule)
-            .with_rule(TextureResolutionRule)
+            .with_rule(TextureResolutionRule::default())
             .with_rule(AlbedoBrightnessRule)
             .with_rule(RoughnessUniformityRule)
             .with_rule(MetallicMidGrayRule)
@@ -271,8 +299,9 @@ impl ValidationRule for AlbedoBrightnessRule {
     fn check(&self, set: &MaterialSet) -> Option<Issue> {
         let albedo = set.albedo.as_ref()?;
 
-        let (mean_lum, _min_lum, max_lum) = luminance_stats(albedo);
-        let clipped = count_clipped_pixels(albedo);
+        let stats = albedo.stats();
+        let (mean_lum, max_lum) = (stats.luminance_mean, stats.luminance_max);
+        let clipped = stats.clipped_pixels;
 
         if mean_lum < 5.0 {
             return Some(Issue::new(
@@ -327,7 +356,7 @@ impl ValidationRule for RoughnessUniformityRule {
     fn check(&self, set: &MaterialSet) -> Option<Issue> {
         let roughness = set.roughness.as_ref()?;
 
-        let mean = channel_mean(roughness, 0);
+        let mean = roughness.stats().channels[0].mean;
         if mean < 5.0 {
             return Some(Issue::new(
                 self.id(),
@@ -336,7 +365,7 @@ impl ValidationRule for RoughnessUniformityRule {
             ));
         }
 
-        let stddev = channel_stddev(roughness, 0);
+        let stddev = roughness.stats().channels[0].stddev;
         if stddev < 2.0 {
             return Some(Issue::new(
                 self.id(),
@@ -355,8 +384,21 @@ impl ValidationRule for RoughnessUniformityRule {
 /// Resolution threshold for 4K warning (4096)
 const RESOLUTION_4K: u32 = 4096;
 
-/// Rule: Warn if texture resolution exceeds 4K
-pub struct TextureResolutionRule;
+/// Rule: Warn if texture resolution exceeds 4K. With a mesh (`mesh`, or the
+/// first one in the material folder), textures the mesh needs to reach
+/// `texel_density` pass, and the warning names the resolution that would.
+#[derive(Debug, Clone, Default)]
+pub struct TextureResolutionRule {
+    pub texel_density: TexelDensityTarget,
+    pub mesh: Option<MeshUvStats>,
+}
+
+impl TextureResolutionRule {
+    /// Rule against `texel_density`, with `mesh` instead of the folder's own mesh
+    pub fn new(texel_density: TexelDensityTarget, mesh: Option<MeshUvStats>) -> Self {
+        Self { texel_density, mesh }
+    }
+}
 
 impl ValidationRule for TextureResolutionRule {
     fn id(&self) -> &str {
@@ -364,7 +406,7 @@ impl ValidationRule for TextureResolutionRule {
     }
 
     fn description(&self) -> &str {
-        "Warns when texture resolution exceeds 4K (4096px)"
+        "Warns when texture resolution exceeds 4K (4096px) and the mesh, if any, doesn't need it"
     }
 
     fn check(&self, set: &MaterialSet) -> Option<Issue> {
@@ -399,15 +441,33 @@ impl ValidationRule for TextureResolutionRule {
             .collect::<Vec<_>>()
             .join(", ");
 
+        let density = match &self.mesh {
+            Some(mesh) => analyze_texel_density(set, mesh, &self.texel_density),
+            None => material_texel_density(set, &self.texel_density),
+        };
+        let advice = match density {
+            Some(density) if density.status != DensityStatus::OverDense => return None,
+            Some(density) => format!(
+                "The mesh needs only {} for {:.2} px/cm.",
+                density.recommended_resolution, self.texel_density.px_per_cm
+            ),
+            None => "Large textures may impact performance.".to_string(),
+        };
         Some(Issue::new(
             self.id(),
             Severity::Major,
-            format!(
-                "Texture resolution exceeds 4K: {}. Large textures may impact performance.",
-                list
-            ),
+            format!("Texture resolution exceeds 4K: {}. {}", list, advice),
         ))
     }
+
+    fn fingerprint(&self) -> Option<String> {
+        // Auto-discovered meshes are part of the analysis cache's content hash
+        let mesh = match &self.mesh {
+            Some(m) => serde_json::to_string(m).ok()?,
+            None => "auto".to_string(),
+        };
+        Some(format!("{}:{}:{}", self.id(), serde_json::to_string(&self.texel_density).ok()?, mesh))
+    }
 }
 
 /// Rule: Metallic mid-gray detection (uniformly 128 may indicate placeholder)
@@ -425,8 +485,8 @@ impl ValidationRule for MetallicMidGrayRule {
     fn check(&self, set: &MaterialSet) -> Option<Issue> {
         let metallic = set.metallic.as_ref()?;
 
-        let mean = channel_mean(metallic, 0);
-        let stddev = channel_stddev(metallic, 0);
+        let mean = metallic.stats().channels[0].mean;
+        let stddev = metallic.stats().channels[0].stddev;
 
         if (mean - 128.0).abs() < 5.0 && stddev < 2.0 {
             return Some(Issue::new(
@@ -454,7 +514,7 @@ impl ValidationRule for NormalMapStrengthRule {
     fn check(&self, set: &MaterialSet) -> Option<Issue> {
         let normal = set.normal.as_ref()?;
 
-        let mean_b = channel_mean(normal, 2);
+        let mean_b = normal.stats().channels[2].mean;
         if mean_b < 100.0 {
             return Some(Issue::new(
                 self.id(),
@@ -478,7 +538,7 @@ impl ValidationRule for TileabilityRule {
     }
 
     fn description(&self) -> &str {
-        "Detect obvious seams at texture edges (simple edge difference)"
+        "Detect visible wrap-around seams (gradient profile and spectral cross artifacts)"
     }
 
     fn check(&self, set: &MaterialSet) -> Option<Issue> {
@@ -489,117 +549,23 @@ impl ValidationRule for TileabilityRule {
             return None;
         }
 
-        let edge_diff = edge_difference(albedo);
-        if edge_diff > 40.0 {
+        let seams = albedo.tileability();
+        if seams.score > TILEABILITY_THRESHOLD {
             return Some(Issue::new(
                 self.id(),
                 Severity::Minor,
                 format!(
-                    "High edge difference ({:.1}). Texture may not tile seamlessly.",
-                    edge_diff
+                    "Visible tiling seam (horizontal {:.2}, vertical {:.2}). Texture may not tile seamlessly.",
+                    seams.horizontal_seam, seams.vertical_seam
                 ),
             ));
         }
         None
     }
-}
 
-fn count_clipped_pixels(map: &TextureMap) -> usize {
-    map.data
-        .chunks_exact(4)
-        .filter(|p| p[0] == 0 || p[0] == 255 || p[1] == 0 || p[1] == 255 || p[2] == 0 || p[2] == 255)
-        .count()
-}
-
-fn edge_difference(map: &TextureMap) -> f64 {
-    let w = map.width as usize;
-    let h = map.height as usize;
-    let mut sum = 0.0f64;
-    let mut count = 0usize;
-
-    for x in 0..w {
-        let top = (0 * w + x) * 4;
-        let bottom = ((h - 1) * w + x) * 4;
-        if top + 3 < map.data.len() && bottom + 3 < map.data.len() {
-            let d = (map.data[top] as i32 - map.data[bottom] as i32).abs()
-                + (map.data[top + 1] as i32 - map.data[bottom + 1] as i32).abs()
-                + (map.data[top + 2] as i32 - map.data[bottom + 2] as i32).abs();
-            sum += d as f64;
-            count += 1;
-        }
-    }
-    for y in 0..h {
-        let left = (y * w + 0) * 4;
-        let right = (y * w + (w - 1)) * 4;
-        if left + 3 < map.data.len() && right + 3 < map.data.len() {
-            let d = (map.data[left] as i32 - map.data[right] as i32).abs()
-                + (map.data[left + 1] as i32 - map.data[right + 1] as i32).abs()
-                + (map.data[left + 2] as i32 - map.data[right + 2] as i32).abs();
-            sum += d as f64;
-            count += 1;
-        }
-    }
-
-    if count > 0 {
-        sum / count as f64
-    } else {
-        0.0
-    }
-}
-
-/// Compute luminance stats (0-255 scale) for RGB
-fn luminance_stats(map: &TextureMap) -> (f64, f64, f64) {
-    let mut sum = 0.0f64;
-    let mut min_val = 255.0f64;
-    let mut max_val = 0.0f64;
-    let mut count = 0usize;
-
-    for i in (0..map.data.len()).step_by(4) {
-        if i + 3 > map.data.len() {
-            break;
-        }
-        let r = map.data[i] as f64;
-        let g = map.data[i + 1] as f64;
-        let b = map.data[i + 2] as f64;
-        let lum = 0.299 * r + 0.587 * g + 0.114 * b;
-
-        sum += lum;
-        min_val = min_val.min(lum);
-        max_val = max_val.max(lum);
-        count += 1;
-    }
-
-    let mean = if count > 0 { sum / count as f64 } else { 0.0 };
-    (mean, min_val, max_val)
-}
-
-fn channel_mean(map: &TextureMap, channel: usize) -> f64 {
-    let mut sum = 0.0f64;
-    let mut count = 0usize;
-    for i in (channel..map.data.len()).step_by(4) {
-        sum += map.data[i] as f64;
-        count += 1;
-    }
-    if count > 0 {
-        sum / count as f64
-    } else {
-        0.0
-    }
-}
-
-fn channel_stddev(map: &TextureMap, channel: usize) -> f64 {
-    let mean = channel_mean(map, channel);
-    let mut sum_sq = 0.0f64;
-    let mut count = 0usize;
-    for i in (channel..map.data.len()).step_by(4) {
-        let v = map.data[i] as f64 - mean;
-        sum_sq += v * v;
-        count += 1;
-    }
-    if count > 1 {
-        (sum_sq / (count - 1) as f64).sqrt()
-    } else {
-        0.0
+    fn fingerprint(&self) -> Option<String> {
+        // Versioned: the seam score changed from edge difference to spectral analysis
+        Some(format!("{}:v2:{}", self.id(), TILEABILITY_THRESHOLD))
     }
 }
 
@@ -609,12 +575,7 @@ mod tests {
     use crate::material::{MaterialSet, TextureMap};
 
     fn make_texture_map(width: u32, height: u32, data: Vec<u8>) -> TextureMap {
-        TextureMap {
-            width,
-            height,
-            data,
-            path: None,
-        }
+        TextureMap::new(width, height, data, None)
     }
 
     #[test]
@@ -665,7 +626,7 @@ mod tests {
         let mut set = MaterialSet::new();
         set.albedo = Some(make_texture_map(4097, 2, vec![128; 4097 * 2 * 4]));
         set.normal = Some(make_texture_map(4097, 2, vec![128; 4097 * 2 * 4]));
-        let issue = TextureResolutionRule.check(&set);
+        let issue = TextureResolutionRule::default().check(&set);
         assert!(issue.is_some());
         assert!(issue.unwrap().message.contains("4K"));
     }
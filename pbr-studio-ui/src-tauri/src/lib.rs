@@ -1,13 +1,13 @@
 use pbr_core::{
-    ai_analyze_json, batch_export_with_preset, export_html_batch,
-    export_html_single, export_pdf_batch, export_pdf_single, export_with_lod, export_with_preset,
-    export_with_target, fix_tileability_with_report, load_audit_log, record_analysis,
+    ai_analyze_json, export_html_batch,
+    export_html_single, export_pdf_batch, export_pdf_single,
+    export_folder, material_output_names, resolve_preset, fix_material_folder, load_audit_log, make_seamless_with_report, record_analysis,
     record_optimization as audit_record_optimization, record_report as audit_record_report,
     record_validation as audit_record_validation, run_advanced_analysis, save_audit_log_text,
-    save_texture, ExportPreset, MaterialReport, MaterialSet, PluginInfo, PluginLoader,
+    save_texture, AnalysisCache, CachedAnalysis, MaterialReport, MaterialSet,
+    MaterialFixReport, OptimizationPreset, PluginInfo, PluginLoader, ScoringModel, SearchHit, SearchIndex, SeamlessMethod, SeamlessOptions, TextureSlot,
     Validator,
 };
-use pbr_core::optimization::TargetResolution;
 use serde::Deserialize;
 use std::path::{Path, PathBuf};
 
@@ -32,12 +32,42 @@ fn get_validator(plugins_dir: Option<&str>) -> Validator {
     Validator::default().with_plugins(&loader)
 }
 
+/// Project scoring model (.pbr-studio/scoring.toml) for a material folder, or default.
+/// An invalid project model is an error rather than a silent fallback.
+fn get_scoring(path: &str) -> Result<ScoringModel, String> {
+    match ScoringModel::for_project(path) {
+        Ok(model) => Ok(model.unwrap_or_default()),
+        Err(e) => Err(format!("Invalid project scoring model for {}: {}", path, e)),
+    }
+}
+
+/// Where the analysis cache of a material folder lives: the project root (see
+/// `ScoringModel::project_root`), else the library folder containing it, so no
+/// `.pbr-studio/` is written into the material folder itself.
+fn cache_root(folder: &Path) -> PathBuf {
+    let folder = folder.canonicalize().unwrap_or_else(|_| folder.to_path_buf());
+    ScoringModel::project_root(&folder)
+        .or_else(|| folder.parent().map(Path::to_path_buf))
+        .unwrap_or(folder)
+}
+
+/// Validate a material folder, served from the project's `.pbr-studio/cache` when the images are unchanged.
+fn analyze_cached(path: &str, validator: &Validator) -> Result<CachedAnalysis, String> {
+    let scoring = get_scoring(path)?;
+    let cache = AnalysisCache::open(&cache_root(Path::new(path)), validator);
+    cache
+        .evaluate(Path::new(path), &scoring, |set| {
+            let issues = validator.check(set);
+            let report = MaterialReport::from_material_set_with_scoring(set, issues.clone(), None, &scoring);
+            (issues, report)
+        })
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn analyze_folder(path: String, plugins_dir: Option<String>) -> Result<String, String> {
-    let set = MaterialSet::load_from_folder(&path).map_err(|e| e.to_string())?;
     let validator = get_validator(plugins_dir.as_deref());
-    let issues = validator.check(&set);
-    let report = MaterialReport::from_material_set(&set, issues.clone());
+    let CachedAnalysis { issues, report, .. } = analyze_cached(&path, &validator)?;
     let score = report.score;
     let passed = report.passed;
     let min_score = 70;
@@ -60,11 +90,13 @@ fn analyze_folder(path: String, plugins_dir: Option<String>) -> Result<String, S
 fn analyze_folders(paths: Vec<String>, plugins_dir: Option<String>) -> Result<Vec<String>, String> {
     let validator = get_validator(plugins_dir.as_deref());
     let mut results = Vec::with_capacity(paths.len());
-    for path in paths {
-        match MaterialSet::load_from_folder(&path) {
-            Ok(set) => {
-                let issues = validator.check(&set);
-                let report = MaterialReport::from_material_set(&set, issues.clone());
+    // Load and validate in parallel; audit logging stays sequential and in input order
+    let evaluated = pbr_core::parallel::par_map(&paths, |path| {
+        analyze_cached(path, &validator).map(|a| (a.issues, a.report))
+    });
+    for (path, evaluation) in paths.into_iter().zip(evaluated) {
+        match evaluation {
+            Ok((issues, report)) => {
                 let min_score = 70;
                 let critical = issues.iter().filter(|i| i.severity == pbr_core::validation::Severity::Critical).count();
                 let major = issues.iter().filter(|i| i.severity == pbr_core::validation::Severity::Major).count();
@@ -99,6 +131,13 @@ fn analyze_folders(paths: Vec<String>, plugins_dir: Option<String>) -> Result<Ve
     Ok(results)
 }
 
+/// Built-in preset id (4k, unreal, unity, mobile) or plugin preset id.
+fn load_preset(preset: &str, plugins_dir: Option<&str>) -> Result<OptimizationPreset, String> {
+    let loader = build_loader(plugins_dir);
+    let (_, presets) = loader.load();
+    resolve_preset(preset, &presets).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn export_preset(
     source_path: String,
@@ -107,45 +146,10 @@ fn export_preset(
     include_lod: Option<bool>,
     plugins_dir: Option<String>,
 ) -> Result<Vec<String>, String> {
-    let material = MaterialSet::load_from_folder(&source_path).map_err(|e| e.to_string())?;
-
-    let written = if let Some(preset_enum) = match preset.to_lowercase().as_str() {
-        "4k" | "4k_high" => Some(ExportPreset::Res4K),
-        "unreal" | "unreal_engine" => Some(ExportPreset::UnrealEngine),
-        "unity" => Some(ExportPreset::Unity),
-        "mobile" | "mobile_optimized" => Some(ExportPreset::MobileOptimized),
-        _ => None,
-    } {
-        if include_lod == Some(true) {
-            let levels = TargetResolution::default_lod_levels();
-            export_with_lod(&material, &output_path, preset_enum, levels).map_err(|e| e.to_string())?
-        } else {
-            export_with_preset(&material, &output_path, preset_enum).map_err(|e| e.to_string())?
-        }
-    } else {
-        // Custom preset from plugin
-        let loader = build_loader(plugins_dir.as_deref());
-        let (_, presets) = loader.load();
-        let custom = presets.iter().find(|p| p.id == preset);
-        let target = custom
-            .map(|p| TargetResolution::Custom(p.max_dimension()))
-            .ok_or_else(|| format!("Unknown preset: {}", preset))?;
-        if include_lod == Some(true) {
-            let levels = TargetResolution::default_lod_levels();
-            let mut written = Vec::new();
-            let lod0 = export_with_target(&material, &output_path, target).map_err(|e| e.to_string())?;
-            written.extend(lod0);
-            for (i, &level) in levels.iter().enumerate() {
-                let lod_dir = std::path::Path::new(&output_path).join(format!("LOD{}", i + 1));
-                std::fs::create_dir_all(&lod_dir).map_err(|e| e.to_string())?;
-                let lod_written = export_with_target(&material, &lod_dir, level).map_err(|e| e.to_string())?;
-                written.extend(lod_written);
-            }
-            written
-        } else {
-            export_with_target(&material, &output_path, target).map_err(|e| e.to_string())?
-        }
-    };
+    let optimization = load_preset(&preset, plugins_dir.as_deref())?;
+    let include_lod = include_lod.unwrap_or(optimization.include_lod);
+    let written = export_folder(Path::new(&source_path), &output_path, optimization, include_lod)
+        .map_err(|e| e.to_string())?;
 
     let count = written.len();
     let _ = audit_record_optimization(
@@ -174,102 +178,28 @@ fn batch_export_preset(
         return Err("No source paths provided".into());
     }
 
-    let mut materials: Vec<(PathBuf, MaterialSet)> = Vec::new();
-    for path_str in &source_paths {
-        let material = MaterialSet::load_from_folder(path_str).map_err(|e| e.to_string())?;
-        materials.push((PathBuf::from(path_str), material));
+    let optimization = load_preset(&preset, plugins_dir.as_deref())?;
+    let include_lod = include_lod.unwrap_or(optimization.include_lod);
+    let output_root = std::path::Path::new(&output_root);
+    std::fs::create_dir_all(output_root).map_err(|e| e.to_string())?;
+    let folders: Vec<PathBuf> = source_paths.iter().map(PathBuf::from).collect();
+    let mut written = Vec::new();
+    // Each folder is loaded (or streamed, for 16K+ sources) only while it exports
+    for (folder, name) in folders.iter().zip(material_output_names(&folders)) {
+        let material_dir = output_root.join(name);
+        let w = export_folder(folder, &material_dir, optimization.clone(), include_lod)
+            .map_err(|e| e.to_string())?;
+        let count = w.len();
+        written.extend(w.into_iter().filter_map(|p| p.to_str().map(String::from)));
+        let _ = audit_record_optimization(
+            folder.as_path(),
+            &material_dir,
+            &preset,
+            count,
+            None,
+        );
     }
 
-    let preset_enum = match preset.to_lowercase().as_str() {
-        "4k" | "4k_high" => Some(ExportPreset::Res4K),
-        "unreal" | "unreal_engine" => Some(ExportPreset::UnrealEngine),
-        "unity" => Some(ExportPreset::Unity),
-        "mobile" | "mobile_optimized" => Some(ExportPreset::MobileOptimized),
-        _ => None,
-    };
-
-    let written: Vec<String> = if let Some(preset_enum) = preset_enum {
-        if include_lod == Some(true) {
-            use pbr_core::optimization::export_with_lod;
-            let output_root = std::path::Path::new(&output_root);
-            std::fs::create_dir_all(output_root).map_err(|e| e.to_string())?;
-            let levels = pbr_core::optimization::TargetResolution::default_lod_levels();
-            let mut all_written = Vec::new();
-            for (folder, material) in &materials {
-                let name = material
-                    .name
-                    .clone()
-                    .or_else(|| folder.file_name().map(|n| n.to_string_lossy().into_owned()))
-                    .unwrap_or_else(|| "material".to_string());
-                let material_dir = output_root.join(&name);
-                let w = export_with_lod(material, &material_dir, preset_enum, levels).map_err(|e| e.to_string())?;
-                let count = w.len();
-                all_written.extend(w.into_iter().filter_map(|p| p.to_str().map(String::from)));
-                let _ = audit_record_optimization(
-                    folder.as_path(),
-                    material_dir.as_path(),
-                    &preset,
-                    count,
-                    None,
-                );
-            }
-            all_written
-        } else {
-            let written = batch_export_with_preset(&materials, &output_root, preset_enum).map_err(|e| e.to_string())?;
-            let output_root = std::path::Path::new(&output_root);
-            for (folder, material) in &materials {
-                let name = material
-                    .name
-                    .clone()
-                    .or_else(|| folder.file_name().map(|n| n.to_string_lossy().into_owned()))
-                    .unwrap_or_else(|| "material".to_string());
-                let material_dir = output_root.join(&name);
-                let _prefix = material_dir.to_string_lossy();
-                let count = written.iter().filter(|p| p.starts_with(&material_dir)).count();
-                let _ = audit_record_optimization(
-                    folder.as_path(),
-                    &material_dir,
-                    &preset,
-                    count,
-                    None,
-                );
-            }
-            written.into_iter().filter_map(|p| p.to_str().map(String::from)).collect()
-        }
-    } else {
-        // Custom preset from plugin
-        let loader = build_loader(plugins_dir.as_deref());
-        let (_, presets) = loader.load();
-        let custom = presets.iter().find(|p| p.id == preset);
-        let target = custom
-            .map(|p| TargetResolution::Custom(p.max_dimension()))
-            .ok_or_else(|| format!("Unknown preset: {}", preset))?;
-        use pbr_core::optimization::export_with_target;
-        let output_root = std::path::Path::new(&output_root);
-        std::fs::create_dir_all(output_root).map_err(|e| e.to_string())?;
-        let mut all_written = Vec::new();
-        for (folder, material) in &materials {
-            let name = material
-                .name
-                .clone()
-                .or_else(|| folder.file_name().map(|n| n.to_string_lossy().into_owned()))
-                .unwrap_or_else(|| "material".to_string());
-            let material_dir = output_root.join(&name);
-            std::fs::create_dir_all(&material_dir).map_err(|e| e.to_string())?;
-            let w = export_with_target(material, &material_dir, target).map_err(|e| e.to_string())?;
-            let count = w.len();
-            all_written.extend(w.into_iter().filter_map(|p| p.to_str().map(String::from)));
-            let _ = audit_record_optimization(
-                folder.as_path(),
-                &material_dir,
-                &preset,
-                count,
-                None,
-            );
-        }
-        all_written
-    };
-
     Ok(written)
 }
 
@@ -401,11 +331,13 @@ fn export_report(
     }
     let validator = Validator::default();
     let mut reports: Vec<(String, MaterialReport)> = Vec::new();
+    let html = format.eq_ignore_ascii_case("html");
 
     for path in &paths {
         let set = MaterialSet::load_from_folder(path).map_err(|e| e.to_string())?;
         let issues = validator.check(&set);
-        let report = MaterialReport::from_material_set(&set, issues);
+        let report = MaterialReport::from_material_set_with_scoring(&set, issues, None, &get_scoring(path)?);
+        let report = if html { report.with_color_distributions(&set) } else { report };
         if track == Some(true) {
             let _ = record_analysis(
                 std::path::Path::new(path),
@@ -514,10 +446,10 @@ fn run_advanced_analysis_cmd(
     if paths.is_empty() {
         return Err("No material paths provided".into());
     }
-    let mut materials: Vec<(PathBuf, MaterialSet)> = Vec::new();
-    for path_str in &paths {
-        let material = MaterialSet::load_from_folder(path_str).map_err(|e| e.to_string())?;
-        materials.push((PathBuf::from(path_str), material));
+    let folders: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+    let mut materials: Vec<(PathBuf, MaterialSet)> = Vec::with_capacity(folders.len());
+    for (folder, result) in pbr_core::parallel::load_materials(&folders) {
+        materials.push((folder, result.map_err(|e| e.to_string())?));
     }
     let dup = duplicate_threshold.unwrap_or(0.99);
     let sim = similar_threshold.unwrap_or(0.8);
@@ -526,51 +458,116 @@ fn run_advanced_analysis_cmd(
     Ok(json)
 }
 
+/// Find library materials similar to a query image or material folder.
+/// Indexes `library_root` first (incrementally, in `<root>/.pbr-studio/search-index.json`).
+#[tauri::command]
+fn search_similar_materials(
+    query_path: String,
+    library_root: String,
+    top_k: Option<usize>,
+    slot: Option<String>,
+) -> Result<Vec<SearchHit>, String> {
+    let root = PathBuf::from(&library_root);
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", library_root));
+    }
+    let slot = match slot {
+        Some(name) => Some(TextureSlot::from_name(&name).ok_or_else(|| format!("Unknown slot '{}'", name))?),
+        None => None,
+    };
+    let mut folders = Vec::new();
+    find_material_folders(&root, &root, &mut folders).map_err(|e| e.to_string())?;
+    folders.sort();
+    let (index, _) = SearchIndex::open(&root, &folders).map_err(|e| e.to_string())?;
+
+    let query = PathBuf::from(&query_path);
+    let k = top_k.unwrap_or(10);
+    if query.is_dir() {
+        let set = MaterialSet::load_from_folder(&query).map_err(|e| e.to_string())?;
+        Ok(index.search_material(&set, k))
+    } else {
+        let img = pbr_core::ImageLoader::load(&query).map_err(|e| e.to_string())?;
+        let texture = pbr_core::material::TextureMap::from_loaded(img, Some(query));
+        Ok(index.search_texture(&texture, slot, k))
+    }
+}
+
 #[derive(serde::Serialize)]
 struct FixTileabilityResult {
     output_path: String,
     original_edge_difference: f32,
     fixed_edge_difference: f32,
+    original_seam_score: f32,
+    fixed_seam_score: f32,
     improved: bool,
+    /// Per-slot before/after report when a whole material folder was fixed
+    material: Option<MaterialFixReport>,
 }
 
+/// Fix a texture file, or every slot of a material folder with one shared seam plan.
 #[tauri::command]
 fn fix_tileability_texture(
     path: String,
     output_path: String,
     blend_width: Option<u32>,
+    method: Option<String>,
+    reference_slot: Option<String>,
 ) -> Result<FixTileabilityResult, String> {
     use std::ffi::OsStr;
     let path_buf = PathBuf::from(&path);
     let path_buf = path_buf.canonicalize().unwrap_or(path_buf);
     let output_buf = PathBuf::from(&output_path);
 
-    let (texture, out_path) = if path_buf.is_dir() {
-        let set = MaterialSet::load_from_folder(&path_buf).map_err(|e| e.to_string())?;
-        let albedo = set.albedo.ok_or("No albedo texture found in material folder")?;
-        let out = if output_buf.is_dir() {
-            output_buf.join("albedo.png")
-        } else {
-            output_buf
-        };
-        (albedo, out)
+    let method = match method.as_deref().map(str::to_lowercase).as_deref() {
+        None | Some("hybrid") => SeamlessMethod::Hybrid,
+        Some("quilt") => SeamlessMethod::Quilt,
+        Some("poisson") => SeamlessMethod::Poisson,
+        Some("blend") => SeamlessMethod::Blend,
+        Some(other) => return Err(format!("Unknown fix method '{}'", other)),
+    };
+    let reference_slot = match reference_slot {
+        Some(name) => Some(TextureSlot::from_name(&name).ok_or_else(|| format!("Unknown reference slot '{}'", name))?),
+        None => None,
+    };
+    let options = SeamlessOptions {
+        method,
+        reference_slot,
+        feather: blend_width.unwrap_or(4),
+        ..Default::default()
+    };
+
+    if path_buf.is_dir() {
+        let report = fix_material_folder(&path_buf, &output_buf, &options).map_err(|e| e.to_string())?;
+        let reference = report
+            .slots
+            .iter()
+            .find(|s| TextureSlot::from_name(&s.slot) == Some(report.reference_slot))
+            .or(report.slots.first())
+            .ok_or("No textures found in material folder")?;
+        return Ok(FixTileabilityResult {
+            output_path: reference.output_path.clone(),
+            original_edge_difference: reference.result.original_edge_difference,
+            fixed_edge_difference: reference.result.fixed_edge_difference,
+            original_seam_score: reference.result.original_seam_score,
+            fixed_seam_score: reference.result.fixed_seam_score,
+            improved: report.slots.iter().all(|s| s.result.improved),
+            material: Some(report),
+        });
+    }
+
+    let img = pbr_core::ImageLoader::load(&path_buf).map_err(|e| e.to_string())?;
+    let texture = pbr_core::material::TextureMap::from_loaded(img, Some(path_buf.clone()));
+    let out_path = if output_buf.is_dir() {
+        output_buf.join(
+            path_buf
+                .file_name()
+                .unwrap_or(OsStr::new("albedo.png")),
+        )
     } else {
-        let img = pbr_core::ImageLoader::load(&path_buf).map_err(|e| e.to_string())?;
-        let texture = pbr_core::material::TextureMap::from_loaded(img, Some(path_buf.clone()));
-        let out = if output_buf.is_dir() {
-            output_buf.join(
-                path_buf
-                    .file_name()
-                    .unwrap_or(OsStr::new("albedo.png")),
-            )
-        } else {
-            output_buf
-        };
-        (texture, out)
+        output_buf
     };
 
-    let blend = blend_width.unwrap_or(4);
-    let (fixed, result) = fix_tileability_with_report(&texture, blend).map_err(|e| e.to_string())?;
+    let (fixed, result) = make_seamless_with_report(&texture, &options).map_err(|e| e.to_string())?;
     if let Some(parent) = out_path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
@@ -584,7 +581,10 @@ fn fix_tileability_texture(
         output_path: output_str,
         original_edge_difference: result.original_edge_difference,
         fixed_edge_difference: result.fixed_edge_difference,
+        original_seam_score: result.original_seam_score,
+        fixed_seam_score: result.fixed_seam_score,
         improved: result.improved,
+        material: None,
     })
 }
 
@@ -649,6 +649,7 @@ pub fn run() {
             list_plugins,
             resolve_material_folder,
             run_advanced_analysis_cmd,
+            search_similar_materials,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
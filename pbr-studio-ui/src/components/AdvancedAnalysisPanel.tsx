@@ -8,6 +8,42 @@ interface DuplicatePair {
   material_a?: string;
   material_b?: string;
   similarity: number;
+  transform: string;
+  crop?: number | null;
+  scale: number;
+  phash_distance: number;
+  color_similarity: number;
+  exact?: 'bytes' | 'pixels' | null;
+}
+
+interface ExactDuplicateReport {
+  texture_count: number;
+  duplicate_count: number;
+  byte_identical_count: number;
+  pixel_identical_count: number;
+  disk_bytes_saved: number;
+  disk_saved: string;
+  vram_bytes_saved: number;
+  vram_saved: string;
+}
+
+function describeTransform(p: DuplicatePair): string {
+  const parts: string[] = [];
+  if (p.exact) parts.push(p.exact === 'bytes' ? 'identical file' : 'identical pixels');
+  if (p.transform !== 'identity') parts.push(p.transform.replace(/_/g, ' '));
+  if (p.crop) parts.push(`crop ${Math.round(p.crop * 100)}%`);
+  if (Math.abs(p.scale - 1) > 0.01) parts.push(`×${p.scale.toFixed(2)}`);
+  return parts.length > 0 ? ` (${parts.join(', ')})` : '';
+}
+
+interface SearchHit {
+  folder: string;
+  material_name?: string | null;
+  slot?: string | null;
+  path?: string | null;
+  similarity: number;
+  hash_similarity: number;
+  feature_similarity: number;
 }
 
 interface DuplicateAnalysisResult {
@@ -32,11 +68,21 @@ interface MapCoverage {
   missing_in: string[];
 }
 
+interface ValueOutlier {
+  material: string;
+  class: string;
+  metric: string;
+  value: number;
+  class_mean: number;
+  z_score: number;
+}
+
 interface CrossMaterialResult {
   material_count: number;
   resolution_distributions: ResolutionDistribution[];
   resolution_inconsistent: boolean;
   map_coverage: MapCoverage[];
+  value_outliers: ValueOutlier[];
   recommendations: string[];
 }
 
@@ -46,12 +92,117 @@ interface TileabilityAnalysisEntry {
   material_name?: string;
   edge_difference: number;
   needs_fix: boolean;
+  score: number;
+  horizontal_seam: number;
+  vertical_seam: number;
+  horizontal_spectral: number;
+  vertical_spectral: number;
+  horizontal_profile: number[];
+  vertical_profile: number[];
+  profile_step: number;
+}
+
+interface SlotFixResult {
+  slot: string;
+  output_path: string;
+  original_edge_difference: number;
+  fixed_edge_difference: number;
+  original_seam_score: number;
+  fixed_seam_score: number;
+  improved: boolean;
+}
+
+interface FixTileabilityResult {
+  output_path: string;
+  original_edge_difference: number;
+  fixed_edge_difference: number;
+  original_seam_score: number;
+  fixed_seam_score: number;
+  improved: boolean;
+  material?: { slots: SlotFixResult[] } | null;
+}
+
+interface RepetitionHotspot {
+  x: number;
+  y: number;
+  radius: number;
+  strength: number;
+}
+
+interface RepetitionAnalysisEntry {
+  path: string;
+  slot: string;
+  material_name?: string;
+  visible: boolean;
+  repetition_score: number;
+  internal_correlation: number;
+  low_frequency_contrast: number;
+  outlier_score: number;
+  hotspots: RepetitionHotspot[];
+}
+
+interface Percentiles {
+  p1: number;
+  p5: number;
+  p25: number;
+  p50: number;
+  p75: number;
+  p95: number;
+  p99: number;
+}
+
+interface ChannelDistribution {
+  histogram: number[];
+  cumulative: number[];
+  percentiles: Percentiles;
+  mean: number;
+  stddev: number;
+  min: number;
+  max: number;
+}
+
+interface DominantColor {
+  rgb: [number, number, number];
+  hex: string;
+  share: number;
+}
+
+interface GamutSummary {
+  samples: number;
+  lightness_p1: number;
+  lightness_mean: number;
+  lightness_p99: number;
+  chroma_mean: number;
+  chroma_p95: number;
+  chroma_max: number;
+  grayscale_ratio: number;
+  clipped_ratio: number;
+  gamut_edge_ratio: number;
+  hue_histogram: number[];
+}
+
+interface TextureColorDistribution {
+  path: string;
+  slot: string;
+  material_name: string | null;
+  width: number;
+  height: number;
+  red: ChannelDistribution;
+  green: ChannelDistribution;
+  blue: ChannelDistribution;
+  alpha?: ChannelDistribution;
+  luminance: ChannelDistribution;
+  dominant_colors: DominantColor[];
+  gamut: GamutSummary;
 }
 
 interface AdvancedAnalysisReport {
+  exact_duplicates: ExactDuplicateReport;
   duplicates: DuplicateAnalysisResult;
   cross_material: CrossMaterialResult;
   tileability_analysis: TileabilityAnalysisEntry[];
+  repetition_analysis: RepetitionAnalysisEntry[];
+  color_distributions: TextureColorDistribution[];
 }
 
 interface AdvancedAnalysisPanelProps {
@@ -74,7 +225,11 @@ export function AdvancedAnalysisPanel({
   const [report, setReport] = useState<AdvancedAnalysisReport | null>(null);
   const [loading, setLoading] = useState(false);
   const [fixingPath, setFixingPath] = useState<string | null>(null);
-  const [activeSection, setActiveSection] = useState<'duplicates' | 'cross' | 'tileability'>('duplicates');
+  const [searching, setSearching] = useState(false);
+  const [searchHits, setSearchHits] = useState<SearchHit[] | null>(null);
+  const [activeSection, setActiveSection] = useState<
+    'duplicates' | 'cross' | 'tileability' | 'repetition' | 'colors'
+  >('duplicates');
 
   const runAnalysis = useCallback(async () => {
     if (!isTauri || materialPaths.length === 0) return;
@@ -105,35 +260,41 @@ export function AdvancedAnalysisPanel({
   }, [isTauri, materialPaths, onLog]);
 
   const handleFixTileability = useCallback(
-    async (entry: TileabilityAnalysisEntry) => {
+    async (entry: TileabilityAnalysisEntry, wholeMaterial = false) => {
       if (!isTauri) return;
       setFixingPath(entry.path);
       onClearTileabilityPreview?.();
       try {
         const { invoke } = await import('@tauri-apps/api/core');
-        const { save } = await import('@tauri-apps/plugin-dialog');
+        const { open, save } = await import('@tauri-apps/plugin-dialog');
         const baseName = entry.path.split(/[/\\]/).pop()?.replace(/\.[^.]+$/, '') || 'albedo';
-        const outputPath = await save({
-          defaultPath: `${baseName}-fixed.png`,
-          filters: [{ name: 'PNG', extensions: ['png'] }],
-        });
-        if (!outputPath) {
+        const outputPath = wholeMaterial
+          ? await open({ directory: true, title: 'Output folder for the fixed material' })
+          : await save({
+              defaultPath: `${baseName}-fixed.png`,
+              filters: [{ name: 'PNG', extensions: ['png'] }],
+            });
+        if (!outputPath || Array.isArray(outputPath)) {
           setFixingPath(null);
           return;
         }
-        const result = await invoke<{
-          output_path: string;
-          original_edge_difference: number;
-          fixed_edge_difference: number;
-          improved: boolean;
-        }>('fix_tileability_texture', {
-          path: entry.path,
+        const result = await invoke<FixTileabilityResult>('fix_tileability_texture', {
+          path: wholeMaterial ? entry.path.replace(/[/\\][^/\\]+$/, '') : entry.path,
           outputPath,
           blendWidth: 4,
+          referenceSlot: wholeMaterial ? entry.slot : undefined,
         });
+        if (result.material) {
+          for (const s of result.material.slots) {
+            onLog?.(
+              'info',
+              `${s.slot}: seam ${s.original_seam_score.toFixed(2)} → ${s.fixed_seam_score.toFixed(2)} (${s.output_path.split(/[/\\]/).pop()})`
+            );
+          }
+        }
         onLog?.(
           'success',
-          `Fixed: edge diff ${result.original_edge_difference.toFixed(1)} → ${result.fixed_edge_difference.toFixed(1)} (improved: ${result.improved})`
+          `Fixed: seam ${result.original_seam_score.toFixed(2)} → ${result.fixed_seam_score.toFixed(2)}, edge diff ${result.original_edge_difference.toFixed(1)} → ${result.fixed_edge_difference.toFixed(1)} (improved: ${result.improved})`
         );
         onTileabilityPreview?.(currentTextureUrls, result.output_path);
       } catch (e) {
@@ -146,6 +307,35 @@ export function AdvancedAnalysisPanel({
     [isTauri, currentTextureUrls, onLog, onTileabilityPreview, onClearTileabilityPreview]
   );
 
+  const runSearch = useCallback(async () => {
+    if (!isTauri) return;
+    try {
+      const { invoke } = await import('@tauri-apps/api/core');
+      const { open } = await import('@tauri-apps/plugin-dialog');
+      const queryPath = await open({
+        title: 'Example image',
+        filters: [{ name: 'Images', extensions: ['png', 'jpg', 'jpeg', 'tga', 'exr'] }],
+      });
+      if (!queryPath || Array.isArray(queryPath)) return;
+      const libraryRoot = await open({ directory: true, title: 'Material library to search' });
+      if (!libraryRoot || Array.isArray(libraryRoot)) return;
+      setSearching(true);
+      setSearchHits(null);
+      onLog?.('info', `Searching ${libraryRoot} for materials like ${queryPath.split(/[/\\]/).pop()}...`);
+      const hits = await invoke<SearchHit[]>('search_similar_materials', {
+        queryPath,
+        libraryRoot,
+        topK: 10,
+      });
+      setSearchHits(hits);
+      onLog?.('success', `Found ${hits.length} similar material(s)`);
+    } catch (e) {
+      onLog?.('error', `Search failed: ${String(e)}`);
+    } finally {
+      setSearching(false);
+    }
+  }, [isTauri, onLog]);
+
   if (!isTauri) return null;
 
   return (
@@ -160,11 +350,33 @@ export function AdvancedAnalysisPanel({
         >
           {loading ? 'Analyzing…' : 'Run Analysis'}
         </button>
+        <button type="button" className="btn-fix" onClick={runSearch} disabled={searching}>
+          {searching ? 'Searching…' : 'Find Similar…'}
+        </button>
         {materialPaths.length === 0 && (
           <span className="advanced-hint">Add material folders first.</span>
         )}
       </div>
 
+      {searchHits && (
+        <div className="advanced-subsection">
+          <h4>Similar materials</h4>
+          {searchHits.length === 0 ? (
+            <p className="advanced-empty">No similar materials found.</p>
+          ) : (
+            <ul>
+              {searchHits.map((h) => (
+                <li key={h.folder} title={h.folder}>
+                  {h.slot && <span className="slot-badge">{h.slot}</span>}
+                  <span className="similarity">{(h.similarity * 100).toFixed(0)}%</span>
+                  <span className="paths">{h.material_name ?? h.folder.split(/[/\\]/).pop()}</span>
+                </li>
+              ))}
+            </ul>
+          )}
+        </div>
+      )}
+
       {report && (
         <>
           <div className="advanced-section-tabs">
@@ -189,11 +401,34 @@ export function AdvancedAnalysisPanel({
             >
               Tileability ({report.tileability_analysis.filter((e) => e.needs_fix).length})
             </button>
+            <button
+              type="button"
+              className={activeSection === 'repetition' ? 'active' : ''}
+              onClick={() => setActiveSection('repetition')}
+            >
+              Repetition ({report.repetition_analysis.filter((e) => e.visible).length})
+            </button>
+            <button
+              type="button"
+              className={activeSection === 'colors' ? 'active' : ''}
+              onClick={() => setActiveSection('colors')}
+            >
+              Colors ({report.color_distributions.length})
+            </button>
           </div>
 
           <div className="advanced-section-content">
             {activeSection === 'duplicates' && (
               <div className="advanced-duplicates">
+                {report.exact_duplicates.duplicate_count > 0 && (
+                  <p className="advanced-summary">
+                    {report.exact_duplicates.duplicate_count} exact duplicate
+                    {report.exact_duplicates.duplicate_count === 1 ? '' : 's'} (
+                    {report.exact_duplicates.byte_identical_count} identical files,{' '}
+                    {report.exact_duplicates.pixel_identical_count} identical pixels). Deduplicating saves{' '}
+                    {report.exact_duplicates.disk_saved} on disk and {report.exact_duplicates.vram_saved} of VRAM.
+                  </p>
+                )}
                 {report.duplicates.duplicate_pairs.length > 0 && (
                   <div className="advanced-subsection">
                     <h4>Exact duplicates</h4>
@@ -204,6 +439,7 @@ export function AdvancedAnalysisPanel({
                           <span className="similarity">{(p.similarity * 100).toFixed(0)}%</span>
                           <span className="paths">
                             {p.path_a.split(/[/\\]/).pop()} ↔ {p.path_b.split(/[/\\]/).pop()}
+                            {describeTransform(p)}
                           </span>
                         </li>
                       ))}
@@ -220,6 +456,7 @@ export function AdvancedAnalysisPanel({
                           <span className="similarity">{(p.similarity * 100).toFixed(0)}%</span>
                           <span className="paths">
                             {p.path_a.split(/[/\\]/).pop()} ↔ {p.path_b.split(/[/\\]/).pop()}
+                            {describeTransform(p)}
                           </span>
                         </li>
                       ))}
@@ -245,6 +482,21 @@ export function AdvancedAnalysisPanel({
                     </ul>
                   </div>
                 )}
+                {report.cross_material.value_outliers.length > 0 && (
+                  <div className="advanced-subsection">
+                    <h4>Value outliers</h4>
+                    <ul>
+                      {report.cross_material.value_outliers.map((o, i) => (
+                        <li key={i}>
+                          <span className="slot-badge">{o.class}</span>
+                          {o.material}: {o.metric.replace(/_/g, ' ')} {o.value.toFixed(2)} vs {o.class_mean.toFixed(2)} (
+                          {o.z_score > 0 ? '+' : ''}
+                          {o.z_score.toFixed(1)}σ)
+                        </li>
+                      ))}
+                    </ul>
+                  </div>
+                )}
                 {report.cross_material.resolution_inconsistent && (
                   <div className="advanced-subsection">
                     <h4>Resolution distribution</h4>
@@ -284,7 +536,10 @@ export function AdvancedAnalysisPanel({
                         <li key={i} className="tileability-entry">
                           <div className="tileability-info">
                             <span className="slot-badge">{e.slot}</span>
-                            <span className="edge-diff">edge diff: {e.edge_difference.toFixed(1)}</span>
+                            <span className="edge-diff">
+                              seam: {e.score.toFixed(2)} (H {e.horizontal_seam.toFixed(2)} / V{' '}
+                              {e.vertical_seam.toFixed(2)})
+                            </span>
                             <span className="path-short">{e.path.split(/[/\\]/).pop()}</span>
                           </div>
                           <button
@@ -295,6 +550,15 @@ export function AdvancedAnalysisPanel({
                           >
                             {fixingPath === e.path ? 'Fixing…' : 'Fix Tileability'}
                           </button>
+                          <button
+                            type="button"
+                            className="btn-fix"
+                            onClick={() => handleFixTileability(e, true)}
+                            disabled={fixingPath === e.path}
+                            title="Fix every map of this material with the same seam cuts"
+                          >
+                            Fix Material
+                          </button>
                         </li>
                       ))}
                   </ul>
@@ -310,7 +574,7 @@ export function AdvancedAnalysisPanel({
                         .map((e, i) => (
                           <li key={i}>
                             <span className="slot-badge">{e.slot}</span>
-                            edge diff: {e.edge_difference.toFixed(1)} — {e.path.split(/[/\\]/).pop()}
+                            seam: {e.score.toFixed(2)} — {e.path.split(/[/\\]/).pop()}
                           </li>
                         ))}
                     </ul>
@@ -318,6 +582,73 @@ export function AdvancedAnalysisPanel({
                 )}
               </div>
             )}
+
+            {activeSection === 'repetition' && (
+              <div className="advanced-tileability">
+                {report.repetition_analysis.filter((e) => e.visible).length > 0 ? (
+                  <ul>
+                    {report.repetition_analysis
+                      .filter((e) => e.visible)
+                      .map((e, i) => (
+                        <li key={i} className="tileability-entry">
+                          <div className="tileability-info">
+                            <span className="slot-badge">{e.slot}</span>
+                            <span className="edge-diff">repetition: {e.repetition_score.toFixed(2)}</span>
+                            <span className="path-short">{e.path.split(/[/\\]/).pop()}</span>
+                          </div>
+                          {e.hotspots.length > 0 && (
+                            <div className="hotspots">
+                              Hotspots:{' '}
+                              {e.hotspots
+                                .map((h) => `(${h.x}, ${h.y}) r${h.radius}`)
+                                .join(', ')}
+                            </div>
+                          )}
+                        </li>
+                      ))}
+                  </ul>
+                ) : (
+                  <p className="advanced-empty">No visible tiling repetition detected.</p>
+                )}
+              </div>
+            )}
+
+            {activeSection === 'colors' && (
+              <div className="advanced-tileability">
+                {report.color_distributions.length > 0 ? (
+                  <ul>
+                    {report.color_distributions.map((d, i) => (
+                      <li key={i} className="tileability-entry">
+                        <div className="tileability-info">
+                          <span className="slot-badge">{d.slot}</span>
+                          <span className="edge-diff">
+                            lum p1/p50/p99: {d.luminance.percentiles.p1}/{d.luminance.percentiles.p50}/
+                            {d.luminance.percentiles.p99}
+                          </span>
+                          <span className="path-short">{d.path.split(/[/\\]/).pop()}</span>
+                        </div>
+                        <div className="color-swatches">
+                          {d.dominant_colors.map((c, j) => (
+                            <span
+                              key={j}
+                              title={`${c.hex} (${(c.share * 100).toFixed(0)}%)`}
+                              style={{ background: c.hex, flexGrow: c.share }}
+                            />
+                          ))}
+                        </div>
+                        <div className="hotspots">
+                          Chroma mean {d.gamut.chroma_mean.toFixed(1)} · gray{' '}
+                          {(d.gamut.grayscale_ratio * 100).toFixed(0)}% · clipped{' '}
+                          {(d.gamut.clipped_ratio * 100).toFixed(1)}%
+                        </div>
+                      </li>
+                    ))}
+                  </ul>
+                ) : (
+                  <p className="advanced-empty">No textures analyzed.</p>
+                )}
+              </div>
+            )}
           </div>
         </>
       )}
@@ -16,8 +16,17 @@ export type ExportPresetId = '4k' | 'unreal' | 'unity' | 'mobile' | string;
 interface CustomPreset {
   id: string;
   name: string;
-  target_resolution: string;
+  base?: string | null;
+  target_resolution?: string;
   include_lod?: boolean;
+  lod_levels?: string[] | null;
+  packing?: 'orm' | 'rma' | 'unity_mask' | 'separate' | null;
+  format?: 'png' | 'tga' | 'jpg' | null;
+  source_normal_convention?: 'opengl' | 'directx' | null;
+  normal_convention?: 'opengl' | 'directx' | null;
+  compression?: 'pc' | 'console' | 'mobile' | null;
+  naming?: string | null;
+  lod_folder?: string | null;
 }
 
 interface TexturePanelProps {
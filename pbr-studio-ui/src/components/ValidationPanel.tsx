@@ -11,11 +11,20 @@ interface OptimizationSuggestion {
   details?: string;
 }
 
+interface PlatformVramEstimate {
+  platform: 'pc' | 'console' | 'mobile';
+  bytes: number;
+  formatted: string;
+  packed_orm: boolean;
+  compression_ratio: number;
+}
+
 interface VramEstimate {
   bytes: number;
   formatted: string;
   include_mipmaps?: boolean;
   packed_orm: boolean;
+  platforms?: PlatformVramEstimate[];
 }
 
 interface AiInsights {
@@ -187,6 +196,16 @@ export function ValidationPanel({ report, compareReport, compareMode, compareNam
             <div className="score-status">
               {report.vram_estimate.packed_orm ? 'With packed ORM' : 'Unpacked'}
             </div>
+            {report.vram_estimate.platforms && report.vram_estimate.platforms.length > 0 && (
+              <div className="vram-platforms">
+                {report.vram_estimate.platforms.map((p) => (
+                  <div key={p.platform} className="vram-platform">
+                    <span className="vram-platform-name">{p.platform}</span>
+                    <span>{p.formatted}</span>
+                  </div>
+                ))}
+              </div>
+            )}
           </div>
         )}
 